@@ -0,0 +1,620 @@
+//! Buffering fallback for row-level and pull encoding.
+//!
+//! [`BufferedEncoder`] gives every encoder a working
+//! [`push_rows()`](Encoder::push_rows) / [`finish()`](Encoder::finish) and
+//! [`encode_from()`](Encoder::encode_from), so callers can always use the
+//! streaming API:
+//!
+//! ```rust,ignore
+//! let caps = config.capabilities();
+//! let mut enc = BufferedEncoder::for_capabilities(config.job().encoder()?, caps, limits);
+//! for strip in strips {
+//!     enc.push_rows(strip)?;
+//! }
+//! let output = enc.finish()?;
+//! ```
+//!
+//! Encoders without native support get their rows accumulated into a
+//! [`PixelBuffer`] and encoded in one [`encode()`](Encoder::encode) call at
+//! the end. The buffered size is checked against
+//! [`ResourceLimits::check_memory()`] before every allocation; a size that
+//! overflows `usize` is reported as [`LimitExceeded::Memory`].
+//!
+//! Works with a concrete [`Encoder`] or a `Box<dyn DynEncoder>`.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::encode::{DynEncoder, EncodeCapabilities, EncodeOutput, Encoder};
+use crate::traits::BoxedError;
+use crate::{LimitExceeded, ResourceLimits, UnsupportedOperation};
+use zenpixels::{PixelBuffer, PixelDescriptor, PixelSlice, PixelSliceMut};
+
+/// Strip height used for pulling when the encoder has no preference.
+const DEFAULT_PULL_ROWS: u32 = 16;
+
+/// Error from the row buffer behind [`BufferedEncoder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RowBufferError {
+    /// Buffering the rows would exceed the memory limit.
+    LimitExceeded(LimitExceeded),
+    /// Rows differ in width or pixel format from the first strip.
+    LayoutChanged,
+    /// `finish()` or `encode_from()` ended without any rows.
+    NoRows,
+}
+
+impl core::fmt::Display for RowBufferError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::LimitExceeded(e) => write!(f, "row buffer: {e}"),
+            Self::LayoutChanged => f.write_str("row buffer: strip width or format changed"),
+            Self::NoRows => f.write_str("row buffer: no rows to encode"),
+        }
+    }
+}
+
+impl core::error::Error for RowBufferError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::LimitExceeded(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<LimitExceeded> for RowBufferError {
+    fn from(e: LimitExceeded) -> Self {
+        Self::LimitExceeded(e)
+    }
+}
+
+/// Error from a [`BufferedEncoder`] wrapping a concrete [`Encoder`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BufferedEncodeError<E> {
+    /// The wrapped encoder failed.
+    Encoder(E),
+    /// Buffering the rows failed.
+    Buffer(RowBufferError),
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for BufferedEncodeError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Encoder(e) => e.fmt(f),
+            Self::Buffer(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for BufferedEncodeError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Encoder(e) => Some(e),
+            Self::Buffer(e) => Some(e),
+        }
+    }
+}
+
+/// Pull geometry for [`BufferedEncoder::encode_from()`](Encoder::encode_from).
+#[derive(Clone, Copy, Debug)]
+struct PullLayout {
+    width: u32,
+    height: u32,
+    descriptor: PixelDescriptor,
+}
+
+/// Contiguous rows accumulated until the image is complete.
+struct RowBuffer {
+    limits: ResourceLimits,
+    data: Vec<u8>,
+    width: u32,
+    descriptor: Option<PixelDescriptor>,
+    stride: usize,
+    rows: u32,
+}
+
+impl RowBuffer {
+    fn new(limits: ResourceLimits) -> Self {
+        Self {
+            limits,
+            data: Vec::new(),
+            width: 0,
+            descriptor: None,
+            stride: 0,
+            rows: 0,
+        }
+    }
+
+    /// A size that doesn't fit in memory at all, reported against the
+    /// memory limit (or the address space when there is none).
+    fn overflow(&self) -> RowBufferError {
+        let max = self.limits.max_memory_bytes.unwrap_or(usize::MAX as u64);
+        RowBufferError::LimitExceeded(LimitExceeded::Memory {
+            actual: u64::MAX,
+            max,
+        })
+    }
+
+    /// Buffer length after `rows` more rows past `start`, checked against
+    /// the memory limit.
+    fn end_after(&self, start: usize, rows: u32) -> Result<usize, RowBufferError> {
+        let end = (rows as usize)
+            .checked_mul(self.stride)
+            .and_then(|len| len.checked_add(start))
+            .ok_or_else(|| self.overflow())?;
+        self.limits.check_memory(end as u64)?;
+        Ok(end)
+    }
+
+    /// Fix the layout on first use; reject later changes.
+    fn set_layout(
+        &mut self,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<(), RowBufferError> {
+        match self.descriptor {
+            None => {
+                self.stride = (width as usize)
+                    .checked_mul(descriptor.bytes_per_pixel())
+                    .ok_or_else(|| self.overflow())?;
+                self.width = width;
+                self.descriptor = Some(descriptor);
+                Ok(())
+            }
+            Some(d) if d == descriptor && self.width == width => Ok(()),
+            Some(_) => Err(RowBufferError::LayoutChanged),
+        }
+    }
+
+    /// Check the limit, then grow the buffer by `rows` rows.
+    fn grow(&mut self, rows: u32) -> Result<usize, RowBufferError> {
+        let start = self.data.len();
+        let end = self.end_after(start, rows)?;
+        self.data.resize(end, 0);
+        Ok(start)
+    }
+
+    fn push(&mut self, rows: PixelSlice<'_>) -> Result<(), RowBufferError> {
+        self.set_layout(rows.width(), rows.descriptor())?;
+        let start = self.grow(rows.rows())?;
+        let stride = self.stride;
+        for y in 0..rows.rows() {
+            let at = start + y as usize * stride;
+            self.data[at..at + stride].copy_from_slice(rows.row(y));
+        }
+        self.rows += rows.rows();
+        Ok(())
+    }
+
+    /// Call `source` for strips of `strip_rows` until it returns 0 or the
+    /// layout's height is reached.
+    fn pull(
+        &mut self,
+        layout: PullLayout,
+        strip_rows: u32,
+        source: &mut dyn FnMut(u32, PixelSliceMut<'_>) -> usize,
+    ) -> Result<(), RowBufferError> {
+        self.set_layout(layout.width, layout.descriptor)?;
+        let stride = self.stride;
+        // The full image is needed anyway; fail before pulling anything.
+        let total = self.end_after(0, layout.height)?;
+        self.data.reserve_exact(total);
+        while self.rows < layout.height {
+            let want = strip_rows.min(layout.height - self.rows);
+            let start = self.grow(want)?;
+            let strip = PixelSliceMut::new(
+                &mut self.data[start..],
+                layout.width,
+                want,
+                stride,
+                layout.descriptor,
+            )
+            .expect("strip sized from its own layout");
+            let got = source(self.rows, strip).min(want as usize) as u32;
+            self.data.truncate(start + got as usize * stride);
+            if got == 0 {
+                break;
+            }
+            self.rows += got;
+        }
+        Ok(())
+    }
+
+    fn into_buffer(self) -> Result<PixelBuffer, RowBufferError> {
+        match self.descriptor {
+            Some(descriptor) if self.rows > 0 => Ok(PixelBuffer::from_vec(
+                self.data, self.width, self.rows, descriptor,
+            )
+            .expect("rows stored at tight stride")),
+            _ => Err(RowBufferError::NoRows),
+        }
+    }
+}
+
+/// Wrapper that gives any encoder row-level and pull encoding.
+///
+/// `E` is an [`Encoder`] or a `Box<dyn DynEncoder>`; the wrapper implements
+/// [`Encoder`] or [`DynEncoder`] respectively.
+///
+/// - [`new()`](Self::new) always buffers.
+/// - [`for_capabilities()`](Self::for_capabilities) passes `push_rows` and
+///   `encode_from` through to the encoder when its capabilities report
+///   native support, and buffers otherwise.
+///
+/// Buffered `encode_from` needs the image geometry up front; set it with
+/// [`with_pull_layout()`](Self::with_pull_layout). Without it, buffered
+/// `encode_from` returns [`UnsupportedOperation::PullEncode`].
+pub struct BufferedEncoder<E> {
+    encoder: E,
+    buffer: RowBuffer,
+    native_rows: bool,
+    native_pull: bool,
+    pull_layout: Option<PullLayout>,
+}
+
+impl<E> BufferedEncoder<E> {
+    /// Wrap `encoder`, buffering every row-level and pull encode.
+    pub fn new(encoder: E, limits: ResourceLimits) -> Self {
+        Self {
+            encoder,
+            buffer: RowBuffer::new(limits),
+            native_rows: false,
+            native_pull: false,
+            pull_layout: None,
+        }
+    }
+
+    /// Wrap `encoder`, buffering only the operations `caps` lacks.
+    pub fn for_capabilities(encoder: E, caps: &EncodeCapabilities, limits: ResourceLimits) -> Self {
+        Self {
+            native_rows: caps.push_rows(),
+            native_pull: caps.encode_from(),
+            ..Self::new(encoder, limits)
+        }
+    }
+
+    /// Image geometry for buffered `encode_from`.
+    pub fn with_pull_layout(
+        mut self,
+        width: u32,
+        height: u32,
+        descriptor: PixelDescriptor,
+    ) -> Self {
+        self.pull_layout = Some(PullLayout {
+            width,
+            height,
+            descriptor,
+        });
+        self
+    }
+
+    /// The wrapped encoder.
+    pub fn encoder(&self) -> &E {
+        &self.encoder
+    }
+
+    /// Rows buffered so far.
+    pub fn buffered_rows(&self) -> u32 {
+        self.buffer.rows
+    }
+}
+
+fn pull_rows(preferred: u32) -> u32 {
+    if preferred == 0 {
+        DEFAULT_PULL_ROWS
+    } else {
+        preferred
+    }
+}
+
+impl<E: Encoder> Encoder for BufferedEncoder<E> {
+    type Error = BufferedEncodeError<E::Error>;
+
+    fn reject(op: UnsupportedOperation) -> Self::Error {
+        BufferedEncodeError::Encoder(E::reject(op))
+    }
+
+    fn preferred_strip_height(&self) -> u32 {
+        self.encoder.preferred_strip_height()
+    }
+
+    fn encode(self, pixels: PixelSlice<'_>) -> Result<EncodeOutput, Self::Error> {
+        self.encoder
+            .encode(pixels)
+            .map_err(BufferedEncodeError::Encoder)
+    }
+
+    fn encode_srgba8(
+        self,
+        data: &mut [u8],
+        make_opaque: bool,
+        width: u32,
+        height: u32,
+        stride_pixels: u32,
+    ) -> Result<EncodeOutput, Self::Error> {
+        self.encoder
+            .encode_srgba8(data, make_opaque, width, height, stride_pixels)
+            .map_err(BufferedEncodeError::Encoder)
+    }
+
+    fn push_rows(&mut self, rows: PixelSlice<'_>) -> Result<(), Self::Error> {
+        if self.native_rows {
+            return self
+                .encoder
+                .push_rows(rows)
+                .map_err(BufferedEncodeError::Encoder);
+        }
+        self.buffer.push(rows).map_err(BufferedEncodeError::Buffer)
+    }
+
+    fn finish(self) -> Result<EncodeOutput, Self::Error> {
+        if self.native_rows {
+            return self.encoder.finish().map_err(BufferedEncodeError::Encoder);
+        }
+        self.finish_buffered()
+    }
+
+    fn encode_from(
+        mut self,
+        source: &mut dyn FnMut(u32, PixelSliceMut<'_>) -> usize,
+    ) -> Result<EncodeOutput, Self::Error> {
+        if self.native_pull {
+            return self
+                .encoder
+                .encode_from(source)
+                .map_err(BufferedEncodeError::Encoder);
+        }
+        let Some(layout) = self.pull_layout else {
+            return Err(Self::reject(UnsupportedOperation::PullEncode));
+        };
+        let strip_rows = pull_rows(self.encoder.preferred_strip_height());
+        self.buffer
+            .pull(layout, strip_rows, source)
+            .map_err(BufferedEncodeError::Buffer)?;
+        self.finish_buffered()
+    }
+}
+
+impl<E: Encoder> BufferedEncoder<E> {
+    fn finish_buffered(self) -> Result<EncodeOutput, BufferedEncodeError<E::Error>> {
+        let pixels = self
+            .buffer
+            .into_buffer()
+            .map_err(BufferedEncodeError::Buffer)?;
+        self.encoder
+            .encode(pixels.as_slice())
+            .map_err(BufferedEncodeError::Encoder)
+    }
+}
+
+impl BufferedEncoder<Box<dyn DynEncoder>> {
+    fn finish_buffered_dyn(self) -> Result<EncodeOutput, BoxedError> {
+        let pixels = self.buffer.into_buffer()?;
+        self.encoder.encode(pixels.as_slice())
+    }
+}
+
+impl DynEncoder for BufferedEncoder<Box<dyn DynEncoder>> {
+    fn preferred_strip_height(&self) -> u32 {
+        self.encoder.preferred_strip_height()
+    }
+
+    fn encode(self: Box<Self>, pixels: PixelSlice<'_>) -> Result<EncodeOutput, BoxedError> {
+        self.encoder.encode(pixels)
+    }
+
+    fn encode_srgba8(
+        self: Box<Self>,
+        data: &mut [u8],
+        make_opaque: bool,
+        width: u32,
+        height: u32,
+        stride_pixels: u32,
+    ) -> Result<EncodeOutput, BoxedError> {
+        self.encoder
+            .encode_srgba8(data, make_opaque, width, height, stride_pixels)
+    }
+
+    fn push_rows(&mut self, rows: PixelSlice<'_>) -> Result<(), BoxedError> {
+        if self.native_rows {
+            return self.encoder.push_rows(rows);
+        }
+        Ok(self.buffer.push(rows)?)
+    }
+
+    fn finish(self: Box<Self>) -> Result<EncodeOutput, BoxedError> {
+        if self.native_rows {
+            return self.encoder.finish();
+        }
+        self.finish_buffered_dyn()
+    }
+
+    fn encode_from(
+        mut self: Box<Self>,
+        source: &mut dyn FnMut(u32, PixelSliceMut<'_>) -> usize,
+    ) -> Result<EncodeOutput, BoxedError> {
+        if self.native_pull {
+            return self.encoder.encode_from(source);
+        }
+        let Some(layout) = self.pull_layout else {
+            return Err(Box::new(UnsupportedOperation::PullEncode));
+        };
+        let strip_rows = pull_rows(self.encoder.preferred_strip_height());
+        self.buffer.pull(layout, strip_rows, source)?;
+        self.finish_buffered_dyn()
+    }
+}
+
+impl<E> core::fmt::Debug for BufferedEncoder<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BufferedEncoder")
+            .field("native_rows", &self.native_rows)
+            .field("native_pull", &self.native_pull)
+            .field("buffered_rows", &self.buffer.rows)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CodecErrorExt, ImageFormat};
+    use alloc::vec;
+
+    #[derive(Debug)]
+    struct EncodeFailed;
+
+    impl core::fmt::Display for EncodeFailed {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str("encode failed")
+        }
+    }
+
+    impl core::error::Error for EncodeFailed {}
+
+    /// Supports only `encode()`; output is the tightly packed pixel bytes.
+    struct WholeImageEncoder;
+
+    impl Encoder for WholeImageEncoder {
+        type Error = EncodeFailed;
+
+        fn reject(_op: UnsupportedOperation) -> EncodeFailed {
+            EncodeFailed
+        }
+
+        fn encode(self, pixels: PixelSlice<'_>) -> Result<EncodeOutput, EncodeFailed> {
+            let mut data = Vec::new();
+            for y in 0..pixels.rows() {
+                data.extend_from_slice(pixels.row(y));
+            }
+            Ok(EncodeOutput::new(data, ImageFormat::Pnm).with_extras(pixels.rows()))
+        }
+    }
+
+    const GRAY: PixelDescriptor = PixelDescriptor::GRAY8_SRGB;
+
+    fn strip(first_row: u8, rows: u32) -> PixelBuffer {
+        let data = (0..rows as u8).flat_map(|r| [first_row + r; 2]).collect();
+        PixelBuffer::from_vec(data, 2, rows, GRAY).unwrap()
+    }
+
+    fn expected_rows(n: u8) -> Vec<u8> {
+        (0..n).flat_map(|r| [r, r]).collect()
+    }
+
+    #[test]
+    fn push_rows_buffers_until_finish() {
+        let mut enc = BufferedEncoder::new(WholeImageEncoder, ResourceLimits::none());
+        enc.push_rows(strip(0, 3).as_slice()).unwrap();
+        enc.push_rows(strip(3, 2).as_slice()).unwrap();
+        assert_eq!(enc.buffered_rows(), 5);
+        let out = enc.finish().unwrap();
+        assert_eq!(out.data(), expected_rows(5));
+        assert_eq!(out.extras::<u32>(), Some(&5));
+    }
+
+    #[test]
+    fn push_rows_checks_memory_limit() {
+        let limits = ResourceLimits::none().with_max_memory(8);
+        let mut enc = BufferedEncoder::new(WholeImageEncoder, limits);
+        enc.push_rows(strip(0, 4).as_slice()).unwrap();
+        let err = enc.push_rows(strip(4, 1).as_slice()).unwrap_err();
+        assert!(matches!(
+            err.limit_exceeded(),
+            Some(LimitExceeded::Memory { actual: 10, max: 8 })
+        ));
+    }
+
+    #[test]
+    fn layout_change_rejected() {
+        let mut enc = BufferedEncoder::new(WholeImageEncoder, ResourceLimits::none());
+        enc.push_rows(strip(0, 1).as_slice()).unwrap();
+        let rgb = PixelBuffer::from_vec(vec![0; 6], 2, 1, PixelDescriptor::RGB8_SRGB).unwrap();
+        let err = enc.push_rows(rgb.as_slice()).unwrap_err();
+        assert!(matches!(
+            err,
+            BufferedEncodeError::Buffer(RowBufferError::LayoutChanged)
+        ));
+    }
+
+    #[test]
+    fn finish_without_rows() {
+        let enc = BufferedEncoder::new(WholeImageEncoder, ResourceLimits::none());
+        let err = enc.finish().unwrap_err();
+        assert!(matches!(
+            err,
+            BufferedEncodeError::Buffer(RowBufferError::NoRows)
+        ));
+    }
+
+    #[test]
+    fn encode_from_pulls_strips() {
+        let enc = BufferedEncoder::new(WholeImageEncoder, ResourceLimits::none())
+            .with_pull_layout(2, 40, GRAY);
+        let mut calls = Vec::new();
+        let out = enc
+            .encode_from(&mut |y, mut buf| {
+                calls.push((y, buf.rows()));
+                for r in 0..buf.rows() {
+                    buf.row_mut(r).fill((y + r) as u8);
+                }
+                buf.rows() as usize
+            })
+            .unwrap();
+        assert_eq!(calls, vec![(0, 16), (16, 16), (32, 8)]);
+        assert_eq!(out.data(), expected_rows(40));
+    }
+
+    #[test]
+    fn encode_from_stops_at_zero() {
+        let enc = BufferedEncoder::new(WholeImageEncoder, ResourceLimits::none())
+            .with_pull_layout(2, 100, GRAY);
+        let out = enc
+            .encode_from(&mut |y, mut buf| {
+                if y >= 3 {
+                    return 0;
+                }
+                buf.row_mut(0).fill(y as u8);
+                1
+            })
+            .unwrap();
+        assert_eq!(out.data(), expected_rows(3));
+    }
+
+    #[test]
+    fn encode_from_checks_memory_before_pulling() {
+        let limits = ResourceLimits::none().with_max_memory(100);
+        let enc = BufferedEncoder::new(WholeImageEncoder, limits).with_pull_layout(10, 20, GRAY);
+        let err = enc.encode_from(&mut |_, _| unreachable!()).unwrap_err();
+        assert!(err.limit_exceeded().is_some());
+    }
+
+    #[test]
+    fn encode_from_reports_size_overflow_as_memory_limit() {
+        let enc = BufferedEncoder::new(WholeImageEncoder, ResourceLimits::none()).with_pull_layout(
+            u32::MAX,
+            u32::MAX,
+            PixelDescriptor::RGBA8_SRGB,
+        );
+        let err = enc.encode_from(&mut |_, _| unreachable!()).unwrap_err();
+        assert!(matches!(
+            err.limit_exceeded(),
+            Some(LimitExceeded::Memory {
+                actual: u64::MAX,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn encode_from_without_layout_is_unsupported() {
+        let enc = BufferedEncoder::new(WholeImageEncoder, ResourceLimits::none());
+        assert!(matches!(
+            enc.encode_from(&mut |_, _| 0),
+            Err(BufferedEncodeError::Encoder(EncodeFailed))
+        ));
+    }
+}
@@ -560,8 +560,9 @@ impl DecodeCapabilities {
     }
     /// Whether this decoder supports multi-image containers (TIFF, HEIF, ICO).
     ///
-    /// True for codecs with independently-addressable images.
-    /// False for single-image and animation-only codecs.
+    /// True for codecs with independently-addressable images, i.e. when
+    /// [`DecodeJob::multi_page_decoder()`](crate::decode::DecodeJob::multi_page_decoder)
+    /// succeeds. False for single-image and animation-only codecs.
     pub const fn multi_image(&self) -> bool {
         self.multi_image
     }
@@ -574,6 +575,9 @@ impl DecodeCapabilities {
         self.decode_into
     }
     /// Whether `StreamingDecode` / `streaming_decoder()` is implemented.
+    ///
+    /// Includes streaming decoders built from a push decoder with
+    /// [`PushStreamingDecoder`](crate::helpers::PushStreamingDecoder).
     pub const fn streaming(&self) -> bool {
         self.streaming
     }
@@ -0,0 +1,415 @@
+//! [`DecodeRowSink`] adapter that feeds decoded strips to an encoder.
+//!
+//! [`EncoderRowSink`] connects a push decoder to
+//! [`Encoder::push_rows()`] so a transcode holds one strip of pixels
+//! instead of the whole image:
+//!
+//! ```rust,ignore
+//! let encoder = enc_config.job().encoder()?;
+//! let mut sink = EncoderRowSink::new(encoder);
+//! dec_config.job().push_decoder(Cow::Borrowed(&data), &mut sink, &[])?;
+//! let output = sink.finish_encode()?;
+//! ```
+//!
+//! Strips are coalesced to the encoder's
+//! [`preferred_strip_height()`](Encoder::preferred_strip_height): a decoder
+//! producing one row at a time into a JPEG encoder wanting 16 rows pushes
+//! every 16 rows. The strip buffer is allocated once and reused.
+//!
+//! Works with a concrete [`Encoder`] or a `Box<dyn DynEncoder>`. Encoder
+//! errors surface from the decoder as [`SinkError`]s that box the original
+//! error, so [`find_cause()`](crate::find_cause) still reaches it.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::encode::{DynEncoder, EncodeOutput, Encoder};
+use crate::sink::{DecodeRowSink, SinkError, StripHeights};
+use crate::traits::BoxedError;
+use zenpixels::{PixelDescriptor, PixelSlice, PixelSliceMut};
+
+/// Reusable strip buffer shared by the [`EncoderRowSink`] impls.
+struct StripBuffer {
+    buf: Vec<u8>,
+    /// Target strip height (encoder's preferred height, at least 1).
+    strip_rows: u32,
+    width: u32,
+    stride: usize,
+    descriptor: Option<PixelDescriptor>,
+    /// Rows fully written by the decoder and not yet pushed.
+    filled: u32,
+    /// Rows in the region most recently handed to the decoder.
+    lent: u32,
+}
+
+impl StripBuffer {
+    fn new(strip_rows: u32) -> Self {
+        Self {
+            buf: Vec::new(),
+            strip_rows: strip_rows.max(1),
+            width: 0,
+            stride: 0,
+            descriptor: None,
+            filled: 0,
+            lent: 0,
+        }
+    }
+
+    fn reserve(&mut self, width: u32, descriptor: PixelDescriptor) {
+        let stride = width as usize * descriptor.bytes_per_pixel();
+        self.buf.reserve(stride * self.strip_rows as usize);
+    }
+
+    /// The decoder has finished writing the last lent region.
+    fn commit(&mut self) {
+        self.filled += self.lent;
+        self.lent = 0;
+    }
+
+    /// Whether filled rows must be pushed before lending `height` more rows.
+    fn must_flush_before(&self, height: u32, width: u32, descriptor: PixelDescriptor) -> bool {
+        self.filled > 0
+            && (self.filled + height > self.strip_rows
+                || width != self.width
+                || Some(descriptor) != self.descriptor)
+    }
+
+    fn lend(
+        &mut self,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        let stride = width as usize * descriptor.bytes_per_pixel();
+        let start = self.filled as usize * stride;
+        let end = start + height as usize * stride;
+        if self.buf.len() < end {
+            self.buf.resize(end, 0);
+        }
+        self.width = width;
+        self.stride = stride;
+        self.descriptor = Some(descriptor);
+        self.lent = height;
+        Ok(PixelSliceMut::new(
+            &mut self.buf[start..end],
+            width,
+            height,
+            stride,
+            descriptor,
+        )?)
+    }
+
+    /// Committed rows as a slice, clearing them from the buffer.
+    fn take_filled(&mut self) -> Option<PixelSlice<'_>> {
+        let (rows, descriptor) = (self.filled, self.descriptor?);
+        if rows == 0 {
+            return None;
+        }
+        self.filled = 0;
+        let len = rows as usize * self.stride;
+        // Rows were lent from this buffer with this exact geometry.
+        Some(
+            PixelSlice::new(&self.buf[..len], self.width, rows, self.stride, descriptor)
+                .expect("committed rows match lent geometry"),
+        )
+    }
+}
+
+/// [`DecodeRowSink`] that forwards decoded rows to an encoder's `push_rows`.
+///
+/// `E` is an [`Encoder`] or a `Box<dyn DynEncoder>`. Call
+/// [`finish_encode()`](EncoderRowSink::finish_encode) after the decode
+/// returns to get the encoded output.
+pub struct EncoderRowSink<E> {
+    encoder: E,
+    strips: StripBuffer,
+}
+
+impl<E> EncoderRowSink<E> {
+    /// The wrapped encoder.
+    pub fn encoder(&self) -> &E {
+        &self.encoder
+    }
+
+    /// Rows the sink accumulates before each `push_rows` call.
+    pub fn strip_height(&self) -> u32 {
+        self.strips.strip_rows
+    }
+}
+
+impl<E: Encoder> EncoderRowSink<E> {
+    /// Wrap `encoder`, coalescing strips to its preferred strip height.
+    pub fn new(encoder: E) -> Self {
+        let strips = StripBuffer::new(encoder.preferred_strip_height());
+        Self { encoder, strips }
+    }
+
+    fn flush(&mut self) -> Result<(), E::Error> {
+        match self.strips.take_filled() {
+            Some(rows) => self.encoder.push_rows(rows),
+            None => Ok(()),
+        }
+    }
+
+    /// Push any remaining rows and finish the encode.
+    pub fn finish_encode(mut self) -> Result<EncodeOutput, E::Error> {
+        self.strips.commit();
+        self.flush()?;
+        self.encoder.finish()
+    }
+}
+
+impl EncoderRowSink<Box<dyn DynEncoder>> {
+    /// Wrap a dyn encoder, coalescing strips to its preferred strip height.
+    pub fn new_dyn(encoder: Box<dyn DynEncoder>) -> Self {
+        let strips = StripBuffer::new(encoder.preferred_strip_height());
+        Self { encoder, strips }
+    }
+
+    fn flush_dyn(&mut self) -> Result<(), BoxedError> {
+        match self.strips.take_filled() {
+            Some(rows) => self.encoder.push_rows(rows),
+            None => Ok(()),
+        }
+    }
+
+    /// Push any remaining rows and finish the encode.
+    pub fn finish_encode_dyn(mut self) -> Result<EncodeOutput, BoxedError> {
+        self.strips.commit();
+        self.flush_dyn()?;
+        self.encoder.finish()
+    }
+}
+
+impl<E: Encoder> DecodeRowSink for EncoderRowSink<E> {
+    fn begin(
+        &mut self,
+        width: u32,
+        _height: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<(), SinkError> {
+        self.strips.reserve(width, descriptor);
+        Ok(())
+    }
+
+    fn provide_next_buffer(
+        &mut self,
+        _y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        self.strips.commit();
+        if self.strips.must_flush_before(height, width, descriptor) {
+            self.flush().map_err(|e| Box::new(e) as SinkError)?;
+        }
+        self.strips.lend(height, width, descriptor)
+    }
+
+    fn strip_heights(&self) -> StripHeights {
+        StripHeights::ANY.with_preferred(self.strips.strip_rows)
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.strips.commit();
+        self.flush().map_err(|e| Box::new(e) as SinkError)
+    }
+}
+
+impl DecodeRowSink for EncoderRowSink<Box<dyn DynEncoder>> {
+    fn begin(
+        &mut self,
+        width: u32,
+        _height: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<(), SinkError> {
+        self.strips.reserve(width, descriptor);
+        Ok(())
+    }
+
+    fn provide_next_buffer(
+        &mut self,
+        _y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        self.strips.commit();
+        if self.strips.must_flush_before(height, width, descriptor) {
+            self.flush_dyn()?;
+        }
+        self.strips.lend(height, width, descriptor)
+    }
+
+    fn strip_heights(&self) -> StripHeights {
+        StripHeights::ANY.with_preferred(self.strips.strip_rows)
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.strips.commit();
+        self.flush_dyn()
+    }
+}
+
+impl<E> core::fmt::Debug for EncoderRowSink<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EncoderRowSink")
+            .field("strip_height", &self.strips.strip_rows)
+            .field("pending_rows", &self.strips.filled)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ImageFormat, find_cause};
+    use alloc::vec;
+
+    #[derive(Debug)]
+    struct PushFailed;
+
+    impl core::fmt::Display for PushFailed {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str("push failed")
+        }
+    }
+
+    impl core::error::Error for PushFailed {}
+
+    /// Records the height of each `push_rows` call and the pushed bytes.
+    struct CollectEncoder {
+        strip_height: u32,
+        pushes: Vec<u32>,
+        data: Vec<u8>,
+        fail: bool,
+    }
+
+    impl CollectEncoder {
+        fn new(strip_height: u32) -> Self {
+            Self {
+                strip_height,
+                pushes: Vec::new(),
+                data: Vec::new(),
+                fail: false,
+            }
+        }
+    }
+
+    impl Encoder for CollectEncoder {
+        type Error = PushFailed;
+
+        fn reject(_op: crate::UnsupportedOperation) -> PushFailed {
+            PushFailed
+        }
+
+        fn preferred_strip_height(&self) -> u32 {
+            self.strip_height
+        }
+
+        fn encode(self, _pixels: PixelSlice<'_>) -> Result<EncodeOutput, PushFailed> {
+            unreachable!()
+        }
+
+        fn push_rows(&mut self, rows: PixelSlice<'_>) -> Result<(), PushFailed> {
+            if self.fail {
+                return Err(PushFailed);
+            }
+            self.pushes.push(rows.rows());
+            for y in 0..rows.rows() {
+                self.data.extend_from_slice(rows.row(y));
+            }
+            Ok(())
+        }
+
+        fn finish(self) -> Result<EncodeOutput, PushFailed> {
+            Ok(EncodeOutput::new(self.data, ImageFormat::Pnm).with_extras(self.pushes))
+        }
+    }
+
+    const GRAY: PixelDescriptor = PixelDescriptor::GRAY8_SRGB;
+
+    /// Drive `sink` like a push decoder: `strips` heights, 2 pixels wide,
+    /// each row filled with its row index.
+    fn drive(sink: &mut dyn DecodeRowSink, strips: &[u32]) -> Result<(), SinkError> {
+        let total = strips.iter().sum();
+        sink.begin(2, total, GRAY)?;
+        let mut y = 0;
+        for &h in strips {
+            let mut buf = sink.provide_next_buffer(y, h, 2, GRAY)?;
+            for r in 0..h {
+                buf.row_mut(r).fill((y + r) as u8);
+            }
+            y += h;
+        }
+        sink.finish()
+    }
+
+    fn expected_rows(n: u8) -> Vec<u8> {
+        (0..n).flat_map(|r| [r, r]).collect()
+    }
+
+    #[test]
+    fn coalesces_small_strips() {
+        let mut sink = EncoderRowSink::new(CollectEncoder::new(4));
+        drive(&mut sink, &[1; 10]).unwrap();
+        let out = sink.finish_encode().unwrap();
+        assert_eq!(out.extras::<Vec<u32>>(), Some(&vec![4, 4, 2]));
+        assert_eq!(out.data(), expected_rows(10));
+    }
+
+    #[test]
+    fn large_strips_pass_through() {
+        let mut sink = EncoderRowSink::new(CollectEncoder::new(2));
+        drive(&mut sink, &[5, 3]).unwrap();
+        let out = sink.finish_encode().unwrap();
+        assert_eq!(out.extras::<Vec<u32>>(), Some(&vec![5, 3]));
+        assert_eq!(out.data(), expected_rows(8));
+    }
+
+    #[test]
+    fn advertises_strip_height_to_coalescing_decoders() {
+        use crate::helpers::StripCoalescer;
+
+        let mut sink = EncoderRowSink::new(CollectEncoder::new(4));
+        assert_eq!(sink.strip_heights().preferred(), 4);
+
+        // A one-shot decoder's full image arrives in encoder-sized strips.
+        let image: Vec<u8> = expected_rows(10);
+        let pixels = PixelSlice::new(&image, 2, 10, 2, GRAY).unwrap();
+        let mut strips = StripCoalescer::begin(&mut sink, 2, 10, GRAY).unwrap();
+        strips.push(&mut sink, 0, &pixels).unwrap();
+        strips.finish(&mut sink).unwrap();
+
+        let out = sink.finish_encode().unwrap();
+        assert_eq!(out.extras::<Vec<u32>>(), Some(&vec![4, 4, 2]));
+        assert_eq!(out.data(), expected_rows(10));
+    }
+
+    #[test]
+    fn zero_strip_height_treated_as_one() {
+        let sink = EncoderRowSink::new(CollectEncoder::new(0));
+        assert_eq!(sink.strip_height(), 1);
+    }
+
+    #[test]
+    fn finish_encode_flushes_without_sink_finish() {
+        let mut sink = EncoderRowSink::new(CollectEncoder::new(8));
+        sink.begin(2, 3, GRAY).unwrap();
+        let mut buf = sink.provide_next_buffer(0, 3, 2, GRAY).unwrap();
+        for r in 0..3 {
+            buf.row_mut(r).fill(r as u8);
+        }
+        let out = sink.finish_encode().unwrap();
+        assert_eq!(out.data(), expected_rows(3));
+    }
+
+    #[test]
+    fn encoder_error_reachable_from_sink_error() {
+        let mut enc = CollectEncoder::new(1);
+        enc.fail = true;
+        let mut sink = EncoderRowSink::new(enc);
+        let err = drive(&mut sink, &[1, 1]).unwrap_err();
+        assert!(find_cause::<PushFailed>(&*err).is_some());
+    }
+}
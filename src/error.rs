@@ -7,9 +7,191 @@
 //!
 //! Works with `thiserror` `#[from]` variants, `whereat::At<E>` wrappers,
 //! and any error type that properly implements `source()`.
+//!
+//! # Classification
+//!
+//! [`CodecErrorExt::kind()`] sorts any codec error into a shared
+//! [`CodecErrorKind`] so callers can react — or pick an HTTP status —
+//! without downcasting to each codec's error type. `UnsupportedOperation`,
+//! `LimitExceeded`, `UnexpectedEof`, and escalated `DecodeWarning` causes are
+//! recognized as-is. For everything else a codec puts a marker in its cause
+//! chain: a [`CodecErrorKind`] as the `source()` of a variant without a
+//! cause, or a [`ClassifiedError`] wrapping the cause of a variant that has
+//! one.
+//!
+//! ```rust,ignore
+//! impl core::error::Error for MyError {
+//!     fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+//!         match self {
+//!             Self::UnexpectedEof => Some(&CodecErrorKind::Truncated),
+//!             Self::BadHuffmanTable => Some(&CodecErrorKind::Corrupt),
+//!             Self::Stopped(reason) => Some(CodecErrorKind::for_stop(*reason)),
+//!             Self::Limit(e) => Some(e),
+//!             Self::Sink(e) => Some(e), // ClassifiedError { kind: Sink, .. }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! zencodec's own helpers mark the errors they hand to codecs: sink
+//! failures passed to `wrap_sink_error` by the `helpers::copy_*_to_sink()`
+//! family, and read failures returned by the default `*_from_source`
+//! methods.
+
+use alloc::boxed::Box;
 
+use enough::StopReason;
+
+use crate::decode::{DecodeWarning, UnexpectedEof};
 use crate::{LimitExceeded, UnsupportedOperation};
 
+/// Shared classification of codec errors.
+///
+/// Returned by [`CodecErrorExt::kind()`]. Also an error type itself, so a
+/// codec can return `Some(&CodecErrorKind::Truncated)` from `source()` to
+/// mark a variant that has no underlying cause.
+///
+/// ```
+/// use zencodec::{CodecErrorExt, CodecErrorKind};
+///
+/// fn http_status(err: &(dyn core::error::Error + 'static)) -> u16 {
+///     match err.kind() {
+///         Some(CodecErrorKind::Truncated | CodecErrorKind::Corrupt
+///             | CodecErrorKind::InvalidArgument) => 400,
+///         Some(CodecErrorKind::LimitExceeded) => 413,
+///         Some(CodecErrorKind::Unsupported) => 415,
+///         Some(CodecErrorKind::Cancelled | CodecErrorKind::TimedOut) => 499,
+///         _ => 500,
+///     }
+/// }
+///
+/// assert_eq!(http_status(&CodecErrorKind::Truncated), 400);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CodecErrorKind {
+    /// The image uses a feature or operation the codec does not implement.
+    Unsupported,
+    /// A [`ResourceLimits`](crate::ResourceLimits) bound was exceeded.
+    LimitExceeded,
+    /// The input ended before the image was complete.
+    Truncated,
+    /// The input is malformed.
+    Corrupt,
+    /// Reading input or writing output failed.
+    Io,
+    /// The operation was cancelled through its stop token.
+    Cancelled,
+    /// The operation's deadline passed.
+    TimedOut,
+    /// A [`DecodeRowSink`](crate::decode::DecodeRowSink) reported an error.
+    Sink,
+    /// The caller passed an argument the codec rejects (bad dimensions,
+    /// buffer size, or option value).
+    InvalidArgument,
+}
+
+impl CodecErrorKind {
+    /// Marker for a [`StopReason`], for use as a `source()`.
+    pub fn for_stop(reason: StopReason) -> &'static Self {
+        match reason {
+            StopReason::TimedOut => &Self::TimedOut,
+            _ => &Self::Cancelled,
+        }
+    }
+
+    /// The [`StopReason`] this kind stands for, if it is a stop.
+    pub const fn stop_reason(self) -> Option<StopReason> {
+        match self {
+            Self::Cancelled => Some(StopReason::Cancelled),
+            Self::TimedOut => Some(StopReason::TimedOut),
+            _ => None,
+        }
+    }
+
+    /// Whether the input, rather than the codec or environment, is at fault.
+    pub const fn is_input_error(self) -> bool {
+        matches!(self, Self::Truncated | Self::Corrupt)
+    }
+}
+
+impl core::fmt::Display for CodecErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Unsupported => "unsupported operation",
+            Self::LimitExceeded => "limit exceeded",
+            Self::Truncated => "truncated input",
+            Self::Corrupt => "corrupt input",
+            Self::Io => "I/O error",
+            Self::Cancelled => "operation cancelled",
+            Self::TimedOut => "operation timed out",
+            Self::Sink => "sink failure",
+            Self::InvalidArgument => "invalid argument",
+        })
+    }
+}
+
+impl core::error::Error for CodecErrorKind {}
+
+/// An error tagged with a [`CodecErrorKind`].
+///
+/// Displays as the wrapped error and returns it from `source()`, so the
+/// tag adds a link to the cause chain without changing messages or hiding
+/// the original error from [`find_cause`].
+pub struct ClassifiedError {
+    kind: CodecErrorKind,
+    error: Box<dyn core::error::Error + Send + Sync>,
+}
+
+impl ClassifiedError {
+    /// Tag `error` with `kind`.
+    pub fn new(
+        kind: CodecErrorKind,
+        error: impl Into<Box<dyn core::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self {
+            kind,
+            error: error.into(),
+        }
+    }
+
+    /// The tag.
+    pub fn kind(&self) -> CodecErrorKind {
+        self.kind
+    }
+
+    /// The wrapped error.
+    pub fn get_ref(&self) -> &(dyn core::error::Error + Send + Sync + 'static) {
+        self.error.as_ref()
+    }
+
+    /// Unwrap the error.
+    pub fn into_inner(self) -> Box<dyn core::error::Error + Send + Sync> {
+        self.error
+    }
+}
+
+impl core::fmt::Debug for ClassifiedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ClassifiedError")
+            .field("kind", &self.kind)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl core::fmt::Display for ClassifiedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl core::error::Error for ClassifiedError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
 /// Extension trait for inspecting codec errors.
 ///
 /// Blanket-implemented for all `core::error::Error + 'static` types.
@@ -42,6 +224,17 @@ pub trait CodecErrorExt {
 
     /// Find a cause of arbitrary type `T` in this error's cause chain.
     fn find_cause<T: core::error::Error + 'static>(&self) -> Option<&T>;
+
+    /// Classify this error by the deepest classified link in its cause
+    /// chain, or `None` if nothing in the chain is classified.
+    ///
+    /// The deepest link is the root cause: a sink failure caused by an
+    /// encoder hitting a limit is [`LimitExceeded`](CodecErrorKind::LimitExceeded).
+    fn kind(&self) -> Option<CodecErrorKind>;
+
+    /// The [`StopReason`] if this error is a cancellation or timeout
+    /// anywhere in its cause chain.
+    fn stop_reason(&self) -> Option<StopReason>;
 }
 
 impl<E: core::error::Error + 'static> CodecErrorExt for E {
@@ -56,6 +249,14 @@ impl<E: core::error::Error + 'static> CodecErrorExt for E {
     fn find_cause<T: core::error::Error + 'static>(&self) -> Option<&T> {
         find_cause::<T>(self)
     }
+
+    fn kind(&self) -> Option<CodecErrorKind> {
+        error_kind(self)
+    }
+
+    fn stop_reason(&self) -> Option<StopReason> {
+        stop_reason(self)
+    }
 }
 
 // Manual impl for trait objects — the blanket impl requires Sized.
@@ -71,6 +272,14 @@ impl CodecErrorExt for dyn core::error::Error + Send + Sync + 'static {
     fn find_cause<T: core::error::Error + 'static>(&self) -> Option<&T> {
         find_cause::<T>(self)
     }
+
+    fn kind(&self) -> Option<CodecErrorKind> {
+        error_kind(self)
+    }
+
+    fn stop_reason(&self) -> Option<StopReason> {
+        stop_reason(self)
+    }
 }
 
 impl CodecErrorExt for dyn core::error::Error + Send + 'static {
@@ -85,6 +294,14 @@ impl CodecErrorExt for dyn core::error::Error + Send + 'static {
     fn find_cause<T: core::error::Error + 'static>(&self) -> Option<&T> {
         find_cause::<T>(self)
     }
+
+    fn kind(&self) -> Option<CodecErrorKind> {
+        error_kind(self)
+    }
+
+    fn stop_reason(&self) -> Option<StopReason> {
+        stop_reason(self)
+    }
 }
 
 impl CodecErrorExt for dyn core::error::Error + 'static {
@@ -99,6 +316,14 @@ impl CodecErrorExt for dyn core::error::Error + 'static {
     fn find_cause<T: core::error::Error + 'static>(&self) -> Option<&T> {
         find_cause::<T>(self)
     }
+
+    fn kind(&self) -> Option<CodecErrorKind> {
+        error_kind(self)
+    }
+
+    fn stop_reason(&self) -> Option<StopReason> {
+        stop_reason(self)
+    }
 }
 
 /// Walk an error's [`source()`](core::error::Error::source) chain to find
@@ -120,11 +345,73 @@ pub fn find_cause<'a, T: core::error::Error + 'static>(
     }
 }
 
+/// Tag `err` as a [`CodecErrorKind::Sink`] failure unless it already is.
+pub(crate) fn sink_failure(
+    err: Box<dyn core::error::Error + Send + Sync>,
+) -> Box<dyn core::error::Error + Send + Sync> {
+    tag(CodecErrorKind::Sink, err)
+}
+
+/// Tag `err` as a [`CodecErrorKind::Io`] failure unless it already is.
+pub(crate) fn io_failure(
+    err: Box<dyn core::error::Error + Send + Sync>,
+) -> Box<dyn core::error::Error + Send + Sync> {
+    tag(CodecErrorKind::Io, err)
+}
+
+fn tag(
+    kind: CodecErrorKind,
+    err: Box<dyn core::error::Error + Send + Sync>,
+) -> Box<dyn core::error::Error + Send + Sync> {
+    match err.downcast_ref::<ClassifiedError>() {
+        Some(tagged) if tagged.kind == kind => err,
+        _ => Box::new(ClassifiedError::new(kind, err)),
+    }
+}
+
+/// Classification of a single link, ignoring its sources.
+fn link_kind(err: &(dyn core::error::Error + 'static)) -> Option<CodecErrorKind> {
+    if let Some(kind) = err.downcast_ref::<CodecErrorKind>() {
+        Some(*kind)
+    } else if let Some(tagged) = err.downcast_ref::<ClassifiedError>() {
+        Some(tagged.kind)
+    } else if err.is::<UnsupportedOperation>() {
+        Some(CodecErrorKind::Unsupported)
+    } else if err.is::<LimitExceeded>() {
+        Some(CodecErrorKind::LimitExceeded)
+    } else if err.is::<UnexpectedEof>() {
+        Some(CodecErrorKind::Truncated)
+    } else {
+        err.downcast_ref::<DecodeWarning>()
+            .map(|warning| warning.code().error_kind())
+    }
+}
+
+fn error_kind(mut err: &(dyn core::error::Error + 'static)) -> Option<CodecErrorKind> {
+    let mut kind = None;
+    loop {
+        kind = link_kind(err).or(kind);
+        match err.source() {
+            Some(next) => err = next,
+            None => return kind,
+        }
+    }
+}
+
+fn stop_reason(mut err: &(dyn core::error::Error + 'static)) -> Option<StopReason> {
+    loop {
+        if let Some(reason) = link_kind(err).and_then(CodecErrorKind::stop_reason) {
+            return Some(reason);
+        }
+        err = err.source()?;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use alloc::boxed::Box;
-    use alloc::string::String;
+    use alloc::string::{String, ToString};
     use core::fmt;
 
     // A simple codec error with source() chain via manual impl
@@ -218,4 +505,126 @@ mod tests {
         let found = find_cause::<LimitExceeded>(&err);
         assert_eq!(found, Some(&err));
     }
+
+    #[derive(Debug)]
+    enum MarkedError {
+        Eof,
+        Stopped(StopReason),
+        Sink(ClassifiedError),
+        Limit(LimitExceeded),
+    }
+
+    impl fmt::Display for MarkedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Eof => f.write_str("unexpected end of file"),
+                Self::Stopped(r) => write!(f, "stopped: {r}"),
+                Self::Sink(e) => write!(f, "sink: {e}"),
+                Self::Limit(e) => write!(f, "limit: {e}"),
+            }
+        }
+    }
+
+    impl core::error::Error for MarkedError {
+        fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+            match self {
+                Self::Eof => Some(&CodecErrorKind::Truncated),
+                Self::Stopped(r) => Some(CodecErrorKind::for_stop(*r)),
+                Self::Sink(e) => Some(e),
+                Self::Limit(e) => Some(e),
+            }
+        }
+    }
+
+    #[test]
+    fn kind_from_marker_cause() {
+        assert_eq!(MarkedError::Eof.kind(), Some(CodecErrorKind::Truncated));
+        assert_eq!(MarkedError::Eof.stop_reason(), None);
+        assert!(CodecErrorKind::Truncated.is_input_error());
+        assert!(!CodecErrorKind::Sink.is_input_error());
+    }
+
+    #[test]
+    fn kind_from_recognized_causes() {
+        let limit = LimitExceeded::Width {
+            actual: 5000,
+            max: 4096,
+        };
+        assert_eq!(
+            MarkedError::Limit(limit.clone()).kind(),
+            Some(CodecErrorKind::LimitExceeded)
+        );
+        assert_eq!(limit.kind(), Some(CodecErrorKind::LimitExceeded));
+        let err = TestCodecError::Unsupported(UnsupportedOperation::DecodeInto);
+        assert_eq!(err.kind(), Some(CodecErrorKind::Unsupported));
+        assert_eq!(TestCodecError::Other("x".into()).kind(), None);
+        let eof = UnexpectedEof {
+            filled: 3,
+            requested: 8,
+        };
+        assert_eq!(eof.kind(), Some(CodecErrorKind::Truncated));
+    }
+
+    #[test]
+    fn stop_reason_from_marker() {
+        for reason in [StopReason::Cancelled, StopReason::TimedOut] {
+            let err = MarkedError::Stopped(reason);
+            assert_eq!(err.stop_reason(), Some(reason));
+            assert_eq!(
+                err.kind().and_then(CodecErrorKind::stop_reason),
+                Some(reason)
+            );
+        }
+        assert_eq!(
+            MarkedError::Stopped(StopReason::TimedOut).kind(),
+            Some(CodecErrorKind::TimedOut)
+        );
+    }
+
+    #[test]
+    fn classified_error_is_transparent() {
+        let tagged = ClassifiedError::new(CodecErrorKind::Sink, "disk full");
+        assert_eq!(tagged.kind(), CodecErrorKind::Sink);
+        assert_eq!(tagged.to_string(), "disk full");
+        assert_eq!(tagged.get_ref().to_string(), "disk full");
+
+        let err = MarkedError::Sink(tagged);
+        assert_eq!(err.to_string(), "sink: disk full");
+        assert_eq!(err.kind(), Some(CodecErrorKind::Sink));
+
+        let boxed: Box<dyn core::error::Error + Send + Sync> = Box::new(err);
+        assert_eq!(boxed.kind(), Some(CodecErrorKind::Sink));
+    }
+
+    #[test]
+    fn deepest_classification_wins() {
+        // An encoder behind a sink hit a limit; the limit is the root cause.
+        let limit = LimitExceeded::Pixels { actual: 2, max: 1 };
+        let err = MarkedError::Sink(ClassifiedError::new(
+            CodecErrorKind::Sink,
+            TestCodecError::Limit(limit),
+        ));
+        assert_eq!(err.kind(), Some(CodecErrorKind::LimitExceeded));
+
+        // A stop anywhere in the chain is reported.
+        let err = MarkedError::Sink(ClassifiedError::new(
+            CodecErrorKind::Sink,
+            MarkedError::Stopped(StopReason::Cancelled),
+        ));
+        assert_eq!(err.stop_reason(), Some(StopReason::Cancelled));
+    }
+
+    #[test]
+    fn tagging_does_not_double_wrap() {
+        let err = sink_failure(sink_failure("rejected".into()));
+        let tagged = err.downcast_ref::<ClassifiedError>().unwrap();
+        assert!(!tagged.get_ref().is::<ClassifiedError>());
+
+        let err = io_failure(err);
+        assert_eq!(err.kind(), Some(CodecErrorKind::Sink));
+        assert_eq!(
+            err.downcast_ref::<ClassifiedError>().unwrap().kind(),
+            CodecErrorKind::Io
+        );
+    }
 }
@@ -0,0 +1,774 @@
+//! Fallback decoder chains.
+//!
+//! [`FallbackDecoderConfig`] wraps an ordered list of [`DynDecoderConfig`]s
+//! for the same format and presents them as a single decoder config. Each
+//! operation runs on the first backend; when it fails with an error class
+//! the [`RetryPolicy`] allows, the next backend is tried with the same
+//! input and job settings.
+//!
+//! ```rust,ignore
+//! let jpeg = FallbackDecoderConfig::new("zenjpeg", ZenJpegDecoderConfig::new())
+//!     .with_fallback("mozjpeg", MozJpegDecoderConfig::new())?;
+//!
+//! let output = jpeg.dyn_job().into_decoder(Cow::Borrowed(&data), &[])?.decode()?;
+//! if let Some(by) = output.extras::<DecodedBy>() {
+//!     log::info!("decoded by {} after {} rejection(s)", by.name, by.rejected.len());
+//! }
+//! ```
+//!
+//! The composite implements [`DynDecoderConfig`], so it can be registered
+//! in a [`CodecRegistry`](crate::CodecRegistry) like any single codec.
+//!
+//! # What is retried
+//!
+//! Failures are classified by the error's
+//! [`kind()`](crate::CodecErrorExt::kind): [`FailureKind::Unsupported`],
+//! [`FailureKind::LimitExceeded`], [`FailureKind::Cancelled`] (also when the
+//! job's stop token has fired), [`FailureKind::Corrupt`] (corrupt or
+//! truncated input, or an error with no [`CodecErrorKind`] in its chain), and
+//! [`FailureKind::Other`] (I/O, sink, and invalid-argument failures). The
+//! default policy retries unsupported features and corrupt data; limits and
+//! cancellation apply to the whole chain and are returned immediately, and
+//! [`FailureKind::Other`] is never retried.
+//!
+//! Borrowed input is shared by every backend. Owned input is lent to each
+//! attempt and moved into the last one, so it is never copied.
+//!
+//! Retries happen before any pixels reach the caller. Streaming, animation,
+//! and multi-page decoders fall back only while being created, and
+//! [`push_decode()`](DynDecodeJob::push_decode) stops falling back once the
+//! sink has received [`begin()`](DecodeRowSink::begin).
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::cell::Cell;
+
+use crate::decode::{
+    DecodeCapabilities, DecodeOutput, DecodePolicy, DecodeRowSink, DecodeSource, DynDecodeJob,
+    DynDecoder, DynDecoderConfig, DynStreamingDecoder, OutputInfo, SinkError, StripHeights,
+};
+use crate::decode::{DynAnimationFrameDecoder, DynMultiPageDecoder, ProbeProgress};
+use crate::traits::BoxedError;
+use crate::{
+    CodecErrorExt, CodecErrorKind, ImageFormat, ImageInfo, OrientationHint, ResourceLimits,
+    StopToken,
+};
+use enough::Stop;
+use zenpixels::{PixelDescriptor, PixelSliceMut};
+
+/// Classification of a failed decode attempt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FailureKind {
+    /// The error chain contains an [`UnsupportedOperation`](crate::UnsupportedOperation)
+    /// — the file uses a feature this backend does not implement.
+    Unsupported,
+    /// Malformed or truncated input, or an error the codec did not
+    /// classify (no [`CodecErrorKind`] in the chain).
+    Corrupt,
+    /// The error chain contains a [`LimitExceeded`](crate::LimitExceeded).
+    LimitExceeded,
+    /// The job's stop token reported a stop, or the error chain marks a
+    /// cancellation or timeout.
+    Cancelled,
+    /// An I/O, sink, or invalid-argument failure. These would fail the same
+    /// way on every backend, so they are never retried.
+    Other,
+}
+
+/// Which [`FailureKind`]s move a [`FallbackDecoderConfig`] on to its next
+/// backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    unsupported: bool,
+    corrupt: bool,
+    limit_exceeded: bool,
+    cancelled: bool,
+}
+
+impl RetryPolicy {
+    /// Retry on unsupported features and corrupt data.
+    pub const fn new() -> Self {
+        Self {
+            unsupported: true,
+            corrupt: true,
+            limit_exceeded: false,
+            cancelled: false,
+        }
+    }
+
+    /// Never retry; the first backend's error is returned.
+    pub const fn none() -> Self {
+        Self {
+            unsupported: false,
+            corrupt: false,
+            limit_exceeded: false,
+            cancelled: false,
+        }
+    }
+
+    /// Retry when a backend reports an unsupported feature.
+    pub const fn with_unsupported(mut self, v: bool) -> Self {
+        self.unsupported = v;
+        self
+    }
+
+    /// Retry when a backend rejects the input as corrupt.
+    pub const fn with_corrupt(mut self, v: bool) -> Self {
+        self.corrupt = v;
+        self
+    }
+
+    /// Retry when a backend exceeds a resource limit.
+    ///
+    /// Useful when backends differ in peak memory for the same image.
+    pub const fn with_limit_exceeded(mut self, v: bool) -> Self {
+        self.limit_exceeded = v;
+        self
+    }
+
+    /// Retry after cancellation. Rarely wanted: the same stop token is
+    /// passed to every backend.
+    pub const fn with_cancelled(mut self, v: bool) -> Self {
+        self.cancelled = v;
+        self
+    }
+
+    /// Whether a failure of `kind` moves on to the next backend.
+    pub const fn retries(&self, kind: FailureKind) -> bool {
+        match kind {
+            FailureKind::Unsupported => self.unsupported,
+            FailureKind::Corrupt => self.corrupt,
+            FailureKind::LimitExceeded => self.limit_exceeded,
+            FailureKind::Cancelled => self.cancelled,
+            FailureKind::Other => false,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Which backend of a [`FallbackDecoderConfig`] produced a [`DecodeOutput`].
+///
+/// Attached to the output's extensions; read it with
+/// [`DecodeOutput::extras::<DecodedBy>()`](DecodeOutput::extras).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DecodedBy {
+    /// Position of the backend in the chain (0 = primary).
+    pub index: usize,
+    /// Name the backend was registered under.
+    pub name: &'static str,
+    /// Backends tried before this one, with the failure that skipped them.
+    pub rejected: Vec<(&'static str, FailureKind)>,
+}
+
+/// Error building a [`FallbackDecoderConfig`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FallbackConfigError {
+    /// The named backend decodes none of the primary backend's formats.
+    NoSharedFormat {
+        /// Name the backend was added under.
+        name: &'static str,
+    },
+}
+
+impl core::fmt::Display for FallbackConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NoSharedFormat { name } => write!(
+                f,
+                "fallback decoder {name:?} shares no format with the primary decoder"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for FallbackConfigError {}
+
+struct Backend {
+    name: &'static str,
+    config: Box<dyn DynDecoderConfig>,
+}
+
+/// Decoder config that tries an ordered list of backends for one format.
+///
+/// [`formats()`](DynDecoderConfig::formats),
+/// [`supported_descriptors()`](DynDecoderConfig::supported_descriptors), and
+/// [`capabilities()`](DynDecoderConfig::capabilities) report the primary
+/// backend. [`RetryPolicy`] decides which failures fall through.
+pub struct FallbackDecoderConfig {
+    backends: Vec<Backend>,
+    retry: RetryPolicy,
+}
+
+impl FallbackDecoderConfig {
+    /// Chain with `primary` as the first backend.
+    pub fn new(name: &'static str, primary: impl DynDecoderConfig + 'static) -> Self {
+        Self::from_boxed(name, Box::new(primary))
+    }
+
+    /// Chain with an already-boxed primary backend.
+    pub fn from_boxed(name: &'static str, primary: Box<dyn DynDecoderConfig>) -> Self {
+        Self {
+            backends: alloc::vec![Backend {
+                name,
+                config: primary,
+            }],
+            retry: RetryPolicy::new(),
+        }
+    }
+
+    /// Append a backend, tried after all previously added ones.
+    ///
+    /// # Errors
+    ///
+    /// [`FallbackConfigError::NoSharedFormat`] if `config` decodes none of
+    /// the primary backend's formats.
+    pub fn with_fallback(
+        self,
+        name: &'static str,
+        config: impl DynDecoderConfig + 'static,
+    ) -> Result<Self, FallbackConfigError> {
+        self.with_boxed_fallback(name, Box::new(config))
+    }
+
+    /// Append an already-boxed backend.
+    ///
+    /// # Errors
+    ///
+    /// [`FallbackConfigError::NoSharedFormat`] if `config` decodes none of
+    /// the primary backend's formats.
+    pub fn with_boxed_fallback(
+        mut self,
+        name: &'static str,
+        config: Box<dyn DynDecoderConfig>,
+    ) -> Result<Self, FallbackConfigError> {
+        let primary = self.backends[0].config.formats();
+        if !config.formats().iter().any(|f| primary.contains(f)) {
+            return Err(FallbackConfigError::NoSharedFormat { name });
+        }
+        self.backends.push(Backend { name, config });
+        Ok(self)
+    }
+
+    /// Set which failure classes move on to the next backend.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// The current retry policy.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// Backend names in the order they are tried.
+    pub fn backend_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.backends.iter().map(|b| b.name)
+    }
+}
+
+impl core::fmt::Debug for FallbackDecoderConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FallbackDecoderConfig")
+            .field("backends", &self.backend_names().collect::<Vec<_>>())
+            .field("retry", &self.retry)
+            .finish()
+    }
+}
+
+impl DynDecoderConfig for FallbackDecoderConfig {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn formats(&self) -> &'static [ImageFormat] {
+        self.backends[0].config.formats()
+    }
+
+    fn supported_descriptors(&self) -> &'static [PixelDescriptor] {
+        self.backends[0].config.supported_descriptors()
+    }
+
+    fn capabilities(&self) -> &'static DecodeCapabilities {
+        self.backends[0].config.capabilities()
+    }
+
+    fn dyn_job(&self) -> Box<dyn DynDecodeJob<'_> + '_> {
+        Box::new(FallbackDecodeJob {
+            backends: &self.backends,
+            retry: self.retry,
+            settings: JobSettings::default(),
+        })
+    }
+}
+
+// ===========================================================================
+// Job
+// ===========================================================================
+
+/// Settings recorded on the composite job and replayed on each backend job.
+#[derive(Clone, Default)]
+struct JobSettings {
+    stop: Option<StopToken>,
+    limits: Option<ResourceLimits>,
+    policy: Option<DecodePolicy>,
+    crop: Option<(u32, u32, u32, u32)>,
+    orientation: Option<OrientationHint>,
+    start_frame: Option<u32>,
+    extract_gain_map: Option<bool>,
+}
+
+#[derive(Clone)]
+struct FallbackDecodeJob<'a> {
+    backends: &'a [Backend],
+    retry: RetryPolicy,
+    settings: JobSettings,
+}
+
+impl<'a> FallbackDecodeJob<'a> {
+    fn backend_job<'j>(&self, backend: &'j Backend) -> Box<dyn DynDecodeJob<'j> + 'j> {
+        let s = &self.settings;
+        let mut job = backend.config.dyn_job();
+        if let Some(stop) = &s.stop {
+            job.set_stop(stop.clone());
+        }
+        if let Some(limits) = s.limits {
+            job.set_limits(limits);
+        }
+        if let Some(policy) = s.policy {
+            job.set_policy(policy);
+        }
+        if let Some((x, y, w, h)) = s.crop {
+            job.set_crop_hint(x, y, w, h);
+        }
+        if let Some(hint) = s.orientation {
+            job.set_orientation(hint);
+        }
+        if let Some(index) = s.start_frame {
+            job.set_start_frame_index(index);
+        }
+        if let Some(extract) = s.extract_gain_map {
+            job.set_extract_gain_map(extract);
+        }
+        job
+    }
+
+    fn classify(&self, err: &(dyn core::error::Error + Send + Sync + 'static)) -> FailureKind {
+        if self.settings.stop.as_ref().is_some_and(|s| s.should_stop())
+            || err.stop_reason().is_some()
+        {
+            return FailureKind::Cancelled;
+        }
+        // Unclassified errors keep the historical "assume the file is bad"
+        // treatment so that codecs without kinds still fall back.
+        match err.kind() {
+            Some(CodecErrorKind::LimitExceeded) => FailureKind::LimitExceeded,
+            Some(CodecErrorKind::Unsupported) => FailureKind::Unsupported,
+            Some(CodecErrorKind::Corrupt | CodecErrorKind::Truncated) | None => {
+                FailureKind::Corrupt
+            }
+            Some(CodecErrorKind::Cancelled | CodecErrorKind::TimedOut) => FailureKind::Cancelled,
+            Some(_) => FailureKind::Other,
+        }
+    }
+
+    /// Run `attempt` on each backend in turn until one succeeds or a failure
+    /// is not retryable. `attempt` gets the backend index and whether it is
+    /// the last backend. `committed` is set by `attempt` when its failure
+    /// must not be retried regardless of class.
+    fn run<T>(
+        &self,
+        committed: &Cell<bool>,
+        mut attempt: impl FnMut(usize, bool) -> Result<T, BoxedError>,
+    ) -> Result<(T, DecodedBy), BoxedError> {
+        let last = self.backends.len() - 1;
+        let mut rejected = Vec::new();
+        for (index, backend) in self.backends.iter().enumerate() {
+            match attempt(index, index == last) {
+                Ok(value) => {
+                    let by = DecodedBy {
+                        index,
+                        name: backend.name,
+                        rejected,
+                    };
+                    return Ok((value, by));
+                }
+                Err(err) => {
+                    let kind = self.classify(&*err);
+                    if index == last || committed.get() || !self.retry.retries(kind) {
+                        return Err(err);
+                    }
+                    rejected.push((backend.name, kind));
+                }
+            }
+        }
+        unreachable!("a fallback chain always has a primary backend")
+    }
+
+    /// Run an executor whose result doesn't keep the input.
+    ///
+    /// Every backend but the last gets a loan of `data`; the last gets
+    /// `data` itself, so owned input is never copied.
+    fn run_lending<T>(
+        &self,
+        data: Cow<'_, [u8]>,
+        committed: &Cell<bool>,
+        mut op: impl for<'x> FnMut(
+            Box<dyn DynDecodeJob<'x> + 'x>,
+            Cow<'x, [u8]>,
+        ) -> Result<T, BoxedError>,
+    ) -> Result<(T, DecodedBy), BoxedError> {
+        let mut data = Some(data);
+        self.run(committed, |index, last| {
+            let job = self.backend_job(&self.backends[index]);
+            if last {
+                op(job, data.take().expect("the last backend runs once"))
+            } else {
+                op(
+                    job,
+                    Cow::Borrowed(data.as_deref().expect("input is kept for later backends")),
+                )
+            }
+        })
+    }
+
+    /// Run an executor that keeps the input (streaming, multi-page).
+    ///
+    /// Borrowed input is shared by every attempt. Owned input is lent to
+    /// every backend but the last; a decoder built on a loan can't be
+    /// returned, so the backend that accepted it is rebuilt around the owned
+    /// buffer. Creating these decoders parses headers only, which is cheaper
+    /// than copying the input for each backend.
+    fn run_keeping<T>(
+        &self,
+        data: Cow<'a, [u8]>,
+        create: impl Fn(Box<dyn DynDecodeJob<'a> + 'a>, Cow<'a, [u8]>) -> Result<T, BoxedError>,
+        check: impl for<'x> Fn(Box<dyn DynDecodeJob<'x> + 'x>, Cow<'x, [u8]>) -> Result<(), BoxedError>,
+    ) -> Result<T, BoxedError> {
+        let none = Cell::new(false);
+        let backends: &'a [Backend] = self.backends;
+        let buf = match data {
+            Cow::Borrowed(slice) => {
+                return self
+                    .run(&none, |index, _| {
+                        create(self.backend_job(&backends[index]), Cow::Borrowed(slice))
+                    })
+                    .map(|(value, _)| value);
+            }
+            Cow::Owned(buf) => buf,
+        };
+        let mut buf = Some(buf);
+        let (value, by) = self.run(&none, |index, last| {
+            if last {
+                let buf = buf.take().expect("the last backend runs once");
+                create(self.backend_job(&backends[index]), Cow::Owned(buf)).map(Some)
+            } else {
+                let loan = buf.as_deref().expect("input is kept for later backends");
+                check(self.backend_job(&backends[index]), Cow::Borrowed(loan)).map(|()| None)
+            }
+        })?;
+        match value {
+            Some(value) => Ok(value),
+            None => {
+                let buf = buf
+                    .take()
+                    .expect("only the last backend consumes the input");
+                create(self.backend_job(&backends[by.index]), Cow::Owned(buf))
+            }
+        }
+    }
+
+    fn buffer(&self, source: DecodeSource<'a>) -> Result<Cow<'a, [u8]>, BoxedError> {
+        source.into_bytes(&self.settings.limits.unwrap_or_else(ResourceLimits::none))
+    }
+}
+
+impl<'a> DynDecodeJob<'a> for FallbackDecodeJob<'a> {
+    fn set_stop(&mut self, stop: StopToken) {
+        self.settings.stop = Some(stop);
+    }
+
+    fn set_limits(&mut self, limits: ResourceLimits) {
+        self.settings.limits = Some(limits);
+    }
+
+    fn set_policy(&mut self, policy: DecodePolicy) {
+        self.settings.policy = Some(policy);
+    }
+
+    fn probe(&self, data: &[u8]) -> Result<ImageInfo, BoxedError> {
+        let none = Cell::new(false);
+        self.run_lending(Cow::Borrowed(data), &none, |job, data| job.probe(&data))
+            .map(|(info, _)| info)
+    }
+
+    fn probe_full(&self, data: &[u8]) -> Result<ImageInfo, BoxedError> {
+        let none = Cell::new(false);
+        self.run_lending(Cow::Borrowed(data), &none, |job, data| {
+            job.probe_full(&data)
+        })
+        .map(|(info, _)| info)
+    }
+
+    fn probe_incremental(&self, data: &[u8]) -> Result<ProbeProgress, BoxedError> {
+        let none = Cell::new(false);
+        self.run_lending(Cow::Borrowed(data), &none, |job, data| {
+            job.probe_incremental(&data)
+        })
+        .map(|(progress, _)| progress)
+    }
+
+    fn set_crop_hint(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        self.settings.crop = Some((x, y, width, height));
+    }
+
+    fn set_orientation(&mut self, hint: OrientationHint) {
+        self.settings.orientation = Some(hint);
+    }
+
+    fn set_start_frame_index(&mut self, index: u32) {
+        self.settings.start_frame = Some(index);
+    }
+
+    fn set_extract_gain_map(&mut self, extract: bool) {
+        self.settings.extract_gain_map = Some(extract);
+    }
+
+    /// Always `None`: backends differ, so there is no single extension type.
+    fn extensions(&self) -> Option<&dyn Any> {
+        None
+    }
+
+    /// Always `None`: backends differ, so there is no single extension type.
+    fn extensions_mut(&mut self) -> Option<&mut dyn Any> {
+        None
+    }
+
+    fn output_info(&self, data: &[u8]) -> Result<OutputInfo, BoxedError> {
+        let none = Cell::new(false);
+        self.run_lending(Cow::Borrowed(data), &none, |job, data| {
+            job.output_info(&data)
+        })
+        .map(|(info, _)| info)
+    }
+
+    fn into_decoder(
+        self: Box<Self>,
+        data: Cow<'a, [u8]>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynDecoder + 'a>, BoxedError> {
+        // Most codecs parse headers lazily, so fallback has to cover
+        // `decode()` as well — defer everything to the returned decoder.
+        Ok(Box::new(FallbackDecoder {
+            job: *self,
+            data,
+            preferred: preferred.to_vec(),
+        }))
+    }
+
+    fn push_decode(
+        self: Box<Self>,
+        data: Cow<'a, [u8]>,
+        sink: &mut dyn DecodeRowSink,
+        preferred: &[PixelDescriptor],
+    ) -> Result<OutputInfo, BoxedError> {
+        let started = Cell::new(false);
+        let mut sink = TrackingSink {
+            inner: sink,
+            started: &started,
+        };
+        self.run_lending(data, &started, |job, data| {
+            job.push_decode(data, &mut sink, preferred)
+        })
+        .map(|(info, _)| info)
+    }
+
+    fn into_streaming_decoder(
+        self: Box<Self>,
+        data: Cow<'a, [u8]>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynStreamingDecoder + 'a>, BoxedError> {
+        self.run_keeping(
+            data,
+            |job, data| job.into_streaming_decoder(data, preferred),
+            |job, data| job.into_streaming_decoder(data, preferred).map(drop),
+        )
+    }
+
+    fn into_animation_frame_decoder(
+        self: Box<Self>,
+        data: Cow<'a, [u8]>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynAnimationFrameDecoder>, BoxedError> {
+        // Frame decoders own their input, so a loan is enough.
+        let none = Cell::new(false);
+        self.run_lending(data, &none, |job, data| {
+            job.into_animation_frame_decoder(data, preferred)
+        })
+        .map(|(dec, _)| dec)
+    }
+
+    fn into_multi_page_decoder(
+        self: Box<Self>,
+        data: Cow<'a, [u8]>,
+    ) -> Result<Box<dyn DynMultiPageDecoder + 'a>, BoxedError> {
+        self.run_keeping(
+            data,
+            |job, data| job.into_multi_page_decoder(data),
+            |job, data| job.into_multi_page_decoder(data).map(drop),
+        )
+    }
+
+    // Sources are buffered up front: a sequential source cannot be replayed
+    // for a second backend.
+
+    fn into_decoder_from_source(
+        self: Box<Self>,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynDecoder + 'a>, BoxedError> {
+        let data = self.buffer(source)?;
+        self.into_decoder(data, preferred)
+    }
+
+    fn push_decode_from_source(
+        self: Box<Self>,
+        source: DecodeSource<'a>,
+        sink: &mut dyn DecodeRowSink,
+        preferred: &[PixelDescriptor],
+    ) -> Result<OutputInfo, BoxedError> {
+        let data = self.buffer(source)?;
+        self.push_decode(data, sink, preferred)
+    }
+
+    fn into_streaming_decoder_from_source(
+        self: Box<Self>,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynStreamingDecoder + 'a>, BoxedError> {
+        let data = self.buffer(source)?;
+        self.into_streaming_decoder(data, preferred)
+    }
+
+    fn into_animation_frame_decoder_from_source(
+        self: Box<Self>,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynAnimationFrameDecoder>, BoxedError> {
+        let data = self.buffer(source)?;
+        self.into_animation_frame_decoder(data, preferred)
+    }
+
+    fn into_multi_page_decoder_from_source(
+        self: Box<Self>,
+        source: DecodeSource<'a>,
+    ) -> Result<Box<dyn DynMultiPageDecoder + 'a>, BoxedError> {
+        let data = self.buffer(source)?;
+        self.into_multi_page_decoder(data)
+    }
+}
+
+// ===========================================================================
+// Decoder
+// ===========================================================================
+
+struct FallbackDecoder<'a> {
+    job: FallbackDecodeJob<'a>,
+    data: Cow<'a, [u8]>,
+    preferred: Vec<PixelDescriptor>,
+}
+
+impl DynDecoder for FallbackDecoder<'_> {
+    fn decode(self: Box<Self>) -> Result<DecodeOutput, BoxedError> {
+        let Self {
+            job,
+            data,
+            preferred,
+        } = *self;
+        let none = Cell::new(false);
+        let (mut output, by) = job.run_lending(data, &none, |job, data| {
+            job.into_decoder(data, &preferred)?.decode()
+        })?;
+        output.extensions_mut().insert(by);
+        Ok(output)
+    }
+}
+
+/// Marks the chain as committed once the caller's sink sees any call.
+struct TrackingSink<'s, 'c> {
+    inner: &'s mut dyn DecodeRowSink,
+    started: &'c Cell<bool>,
+}
+
+impl DecodeRowSink for TrackingSink<'_, '_> {
+    fn begin(
+        &mut self,
+        width: u32,
+        height: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<(), SinkError> {
+        self.started.set(true);
+        self.inner.begin(width, height, descriptor)
+    }
+
+    fn provide_next_buffer(
+        &mut self,
+        y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        self.started.set(true);
+        self.inner.provide_next_buffer(y, height, width, descriptor)
+    }
+
+    fn strip_heights(&self) -> StripHeights {
+        self.inner.strip_heights()
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.started.set(true);
+        self.inner.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_retry_policy() {
+        let p = RetryPolicy::default();
+        assert!(p.retries(FailureKind::Unsupported));
+        assert!(p.retries(FailureKind::Corrupt));
+        assert!(!p.retries(FailureKind::LimitExceeded));
+        assert!(!p.retries(FailureKind::Cancelled));
+        assert!(!p.retries(FailureKind::Other));
+    }
+
+    #[test]
+    fn retry_policy_builders() {
+        let p = RetryPolicy::none()
+            .with_limit_exceeded(true)
+            .with_cancelled(true);
+        assert!(!p.retries(FailureKind::Unsupported));
+        assert!(!p.retries(FailureKind::Corrupt));
+        assert!(p.retries(FailureKind::LimitExceeded));
+        assert!(p.retries(FailureKind::Cancelled));
+    }
+
+    #[test]
+    fn fallback_config_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FallbackDecoderConfig>();
+    }
+}
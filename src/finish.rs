@@ -0,0 +1,456 @@
+//! Finish orientation and crop hints a decoder ignored.
+//!
+//! [`DecodeJob::with_orientation()`](crate::decode::DecodeJob::with_orientation)
+//! and [`with_crop_hint()`](crate::decode::DecodeJob::with_crop_hint) are
+//! hints: a decoder may apply them, apply part (a block-aligned crop), or
+//! ignore them, and reports what it did in
+//! [`OutputInfo::orientation_applied`] and [`OutputInfo::crop_applied`].
+//! [`RemainingTransform`] compares the request with that report and works
+//! out what is left; [`FinishingDecoder`] wraps a decoder and applies it to
+//! the decoded pixels, so callers get exactly what they asked for from any
+//! codec.
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+
+use crate::decode::{Decode, DecodeJob, DynDecodeJob, DynDecoder};
+use crate::traits::BoxedError;
+use crate::{DecodeOutput, ImageInfo, Orientation, OrientationHint, OutputInfo};
+use zenpixels::{PixelBuffer, PixelDescriptor, PixelSlice, PixelSliceMut};
+
+/// The orientation and crop a decoder left undone.
+///
+/// Built from the request and the decoder's [`OutputInfo`] by
+/// [`new()`](Self::new). The crop is in the coordinates of the decoder's
+/// output and is applied before the orientation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RemainingTransform {
+    crop: Option<[u32; 4]>,
+    orientation: Orientation,
+    reported: Orientation,
+}
+
+impl RemainingTransform {
+    /// Work out what is left of `hint` and `crop` (`[x, y, width, height]`
+    /// in source coordinates) after a decoder reported `output`.
+    ///
+    /// `source` must come from a probe made before the hints were set, so
+    /// its `orientation` is the intrinsic one and `width`/`height` are the
+    /// stored dimensions. The crop is clamped to the image; one that misses
+    /// it entirely is ignored.
+    pub fn new(
+        hint: OrientationHint,
+        crop: Option<[u32; 4]>,
+        source: &ImageInfo,
+        output: &OutputInfo,
+    ) -> Self {
+        let intrinsic = source.orientation;
+        let (requested, reported) = match hint {
+            OrientationHint::Preserve => (Orientation::Identity, intrinsic),
+            OrientationHint::Correct => (intrinsic, Orientation::Identity),
+            OrientationHint::CorrectAndTransform(o) => (intrinsic.then(o), Orientation::Identity),
+            OrientationHint::ExactTransform(o) => (o, Orientation::Identity),
+        };
+        let applied = output.orientation_applied;
+
+        let full = [0, 0, source.width, source.height];
+        let decoded = output
+            .crop_applied
+            .and_then(|a| intersect(a, full))
+            .unwrap_or(full);
+        let crop = crop
+            .and_then(|r| intersect(r, decoded))
+            .map(|[x, y, w, h]| [x - decoded[0], y - decoded[1], w, h])
+            .filter(|rel| *rel != [0, 0, decoded[2], decoded[3]])
+            .map(|rel| orient_rect(rel, applied, decoded[2], decoded[3]));
+
+        Self {
+            crop,
+            orientation: applied.inverse().then(requested),
+            reported,
+        }
+    }
+
+    /// Crop left to apply, in the decoder's output coordinates.
+    pub fn crop(&self) -> Option<[u32; 4]> {
+        self.crop
+    }
+
+    /// Orientation left to apply after the crop.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Whether the decoder did everything that was asked.
+    pub fn is_identity(&self) -> bool {
+        self.crop.is_none() && self.orientation.is_identity()
+    }
+
+    /// Output dimensions for a decoder output of `width` × `height`.
+    pub fn output_dimensions(&self, width: u32, height: u32) -> (u32, u32) {
+        let (w, h) = match self.crop {
+            Some(rect) => clamp_rect(rect, width, height).map_or((0, 0), |r| (r[2], r[3])),
+            None => (width, height),
+        };
+        self.orientation.output_dimensions(w, h)
+    }
+
+    /// Crop and orient `pixels` into a new buffer.
+    ///
+    /// A crop reaching past `pixels` is clamped to it.
+    pub fn apply(&self, pixels: PixelSlice<'_>) -> PixelBuffer {
+        let src = match self.crop {
+            Some(rect) => {
+                let [x, y, w, h] =
+                    clamp_rect(rect, pixels.width(), pixels.rows()).unwrap_or([0; 4]);
+                pixels.crop_view(x, y, w, h)
+            }
+            None => pixels,
+        };
+        orient_pixels(&src, self.orientation)
+    }
+
+    /// Apply to a decode output, updating its [`ImageInfo`] dimensions and
+    /// orientation to match.
+    ///
+    /// Afterwards `info().orientation` is the intrinsic orientation for
+    /// [`OrientationHint::Preserve`] and
+    /// [`Identity`](Orientation::Identity) otherwise.
+    pub fn apply_to_output(&self, mut output: DecodeOutput) -> DecodeOutput {
+        if !self.is_identity() {
+            let pixels = self.apply(output.pixels());
+            let info = output.info_mut();
+            info.width = pixels.width();
+            info.height = pixels.height();
+            output.set_pixels(pixels);
+        }
+        output.info_mut().orientation = self.reported;
+        output
+    }
+}
+
+/// A decoder wrapper that finishes the orientation and crop the inner
+/// decoder left undone.
+///
+/// Wraps a [`Decode`] implementation or a boxed [`DynDecoder`]:
+///
+/// ```rust,ignore
+/// let decoder = FinishingDecoder::from_dyn_job(
+///     config.dyn_job(),
+///     Cow::Borrowed(&data),
+///     &[],
+///     OrientationHint::Correct,
+///     Some([0, 0, 256, 256]),
+/// )?;
+/// let output = Box::new(decoder).decode()?; // upright, exactly 256×256
+/// ```
+///
+/// Only the primary image is transformed. Gain maps and other extras are
+/// left as the decoder produced them.
+#[derive(Debug)]
+pub struct FinishingDecoder<D> {
+    inner: D,
+    remaining: RemainingTransform,
+}
+
+impl<D> FinishingDecoder<D> {
+    /// Wrap `inner`, applying `remaining` to what it decodes.
+    pub fn new(inner: D, remaining: RemainingTransform) -> Self {
+        Self { inner, remaining }
+    }
+
+    /// What will be applied after decoding.
+    pub fn remaining(&self) -> &RemainingTransform {
+        &self.remaining
+    }
+
+    /// Unwrap the inner decoder.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+impl<'a, D: Decode> FinishingDecoder<D> {
+    /// Probe `data`, pass `hint` and `crop` to `job`, and create its decoder
+    /// wrapped to finish whatever the decoder leaves undone.
+    pub fn from_job<J>(
+        job: J,
+        data: Cow<'a, [u8]>,
+        preferred: &[PixelDescriptor],
+        hint: OrientationHint,
+        crop: Option<[u32; 4]>,
+    ) -> Result<Self, J::Error>
+    where
+        J: DecodeJob<'a, Dec = D>,
+    {
+        let source = job.probe(&data)?;
+        let mut job = job.with_orientation(hint);
+        if let Some([x, y, w, h]) = crop {
+            job = job.with_crop_hint(x, y, w, h);
+        }
+        let output = job.output_info(&data)?;
+        let remaining = RemainingTransform::new(hint, crop, &source, &output);
+        Ok(Self::new(job.decoder(data, preferred)?, remaining))
+    }
+}
+
+impl<'a> FinishingDecoder<Box<dyn DynDecoder + 'a>> {
+    /// [`from_job()`](FinishingDecoder::from_job) for a type-erased job.
+    ///
+    /// The result implements [`DynDecoder`], so it can be boxed and handed
+    /// to code expecting any decoder.
+    pub fn from_dyn_job(
+        mut job: Box<dyn DynDecodeJob<'a> + 'a>,
+        data: Cow<'a, [u8]>,
+        preferred: &[PixelDescriptor],
+        hint: OrientationHint,
+        crop: Option<[u32; 4]>,
+    ) -> Result<Self, BoxedError> {
+        let source = job.probe(&data)?;
+        job.set_orientation(hint);
+        if let Some([x, y, w, h]) = crop {
+            job.set_crop_hint(x, y, w, h);
+        }
+        let output = job.output_info(&data)?;
+        let remaining = RemainingTransform::new(hint, crop, &source, &output);
+        Ok(Self::new(job.into_decoder(data, preferred)?, remaining))
+    }
+}
+
+impl<D: Decode> Decode for FinishingDecoder<D> {
+    type Error = D::Error;
+
+    fn decode(self) -> Result<DecodeOutput, D::Error> {
+        let output = self.inner.decode()?;
+        Ok(self.remaining.apply_to_output(output))
+    }
+}
+
+impl DynDecoder for FinishingDecoder<Box<dyn DynDecoder + '_>> {
+    fn decode(self: Box<Self>) -> Result<DecodeOutput, BoxedError> {
+        let output = self.inner.decode()?;
+        Ok(self.remaining.apply_to_output(output))
+    }
+}
+
+/// Copy `src` into a new buffer with `orientation` applied.
+pub(crate) fn orient_pixels(src: &PixelSlice<'_>, orientation: Orientation) -> PixelBuffer {
+    let (w, h) = (src.width(), src.rows());
+    let (dw, dh) = orientation.output_dimensions(w, h);
+    let mut dst = PixelBuffer::new(dw, dh, src.descriptor());
+    if let Some(ctx) = src.color_context() {
+        dst = dst.with_color_context(ctx.clone());
+    }
+    orient_into(src, 0, (w, h), orientation, &mut dst.as_slice_mut());
+    dst
+}
+
+/// Write the rows of `strip`, which start at row `y0` of a `size` image,
+/// into `dst` (the whole image with `orientation` applied).
+pub(crate) fn orient_into(
+    strip: &PixelSlice<'_>,
+    y0: u32,
+    size: (u32, u32),
+    orientation: Orientation,
+    dst: &mut PixelSliceMut<'_>,
+) {
+    let (w, h) = size;
+    let bpp = strip.descriptor().bytes_per_pixel();
+    let row_bytes = w as usize * bpp;
+    for sy in 0..strip.rows() {
+        let y = y0 + sy;
+        let row = &strip.row(sy)[..row_bytes];
+        if orientation.is_identity() {
+            dst.row_mut(y)[..row_bytes].copy_from_slice(row);
+            continue;
+        }
+        for (x, px) in (0..w).zip(row.chunks_exact(bpp)) {
+            let (dx, dy) = orientation.forward_map(x, y, w, h);
+            let at = dx as usize * bpp;
+            dst.row_mut(dy)[at..at + bpp].copy_from_slice(px);
+        }
+    }
+}
+
+/// Intersection of two `[x, y, width, height]` rects, `None` if empty.
+fn intersect(a: [u32; 4], b: [u32; 4]) -> Option<[u32; 4]> {
+    let x0 = a[0].max(b[0]);
+    let y0 = a[1].max(b[1]);
+    let x1 = a[0].saturating_add(a[2]).min(b[0].saturating_add(b[2]));
+    let y1 = a[1].saturating_add(a[3]).min(b[1].saturating_add(b[3]));
+    (x1 > x0 && y1 > y0).then(|| [x0, y0, x1 - x0, y1 - y0])
+}
+
+fn clamp_rect(rect: [u32; 4], width: u32, height: u32) -> Option<[u32; 4]> {
+    intersect(rect, [0, 0, width, height])
+}
+
+/// Map a non-empty rect in a `width` × `height` image through `orientation`.
+fn orient_rect(rect: [u32; 4], orientation: Orientation, width: u32, height: u32) -> [u32; 4] {
+    let [x, y, w, h] = rect;
+    let (ax, ay) = orientation.forward_map(x, y, width, height);
+    let (bx, by) = orientation.forward_map(x + w - 1, y + h - 1, width, height);
+    [
+        ax.min(bx),
+        ay.min(by),
+        ax.abs_diff(bx) + 1,
+        ay.abs_diff(by) + 1,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ImageFormat;
+    use alloc::vec::Vec;
+
+    /// 5×3 Gray8 image where each pixel holds its index.
+    fn source() -> PixelBuffer {
+        let data = (0..15).collect();
+        PixelBuffer::from_vec(data, 5, 3, PixelDescriptor::GRAY8_SRGB).unwrap()
+    }
+
+    fn bytes(pixels: &PixelBuffer) -> (u32, u32, Vec<u8>) {
+        (
+            pixels.width(),
+            pixels.height(),
+            pixels.copy_to_contiguous_bytes(),
+        )
+    }
+
+    fn info(orientation: Orientation) -> ImageInfo {
+        ImageInfo::new(5, 3, ImageFormat::Pnm).with_orientation(orientation)
+    }
+
+    /// What a decoder honoring `applied` and `crop_applied` would produce.
+    fn decoded(applied: Orientation, crop_applied: Option<[u32; 4]>) -> (PixelBuffer, OutputInfo) {
+        let src = source();
+        let [x, y, w, h] = crop_applied.unwrap_or([0, 0, 5, 3]);
+        let pixels = orient_pixels(&src.as_slice().crop_view(x, y, w, h), applied);
+        let mut out = OutputInfo::full_decode(pixels.width(), pixels.height(), src.descriptor())
+            .with_orientation_applied(applied);
+        out.crop_applied = crop_applied;
+        (pixels, out)
+    }
+
+    #[test]
+    fn orient_pixels_rotate90() {
+        let rotated = orient_pixels(&source().as_slice(), Orientation::Rotate90);
+        #[rustfmt::skip]
+        let expected = alloc::vec![
+            10, 5, 0,
+            11, 6, 1,
+            12, 7, 2,
+            13, 8, 3,
+            14, 9, 4,
+        ];
+        assert_eq!(bytes(&rotated), (3, 5, expected));
+    }
+
+    #[test]
+    fn finishes_any_partial_orientation() {
+        let crop = [1, 1, 3, 2];
+        let cropped = source().crop_copy(1, 1, 3, 2);
+        for intrinsic in Orientation::ALL {
+            for extra in Orientation::ALL {
+                let hint = OrientationHint::CorrectAndTransform(extra);
+                let expected = bytes(&orient_pixels(&cropped.as_slice(), intrinsic.then(extra)));
+                for applied in Orientation::ALL {
+                    for crop_applied in [None, Some([0, 0, 4, 3]), Some(crop)] {
+                        let (pixels, out) = decoded(applied, crop_applied);
+                        let remaining =
+                            RemainingTransform::new(hint, Some(crop), &info(intrinsic), &out);
+                        let finished = remaining.apply(pixels.as_slice());
+                        assert_eq!(
+                            bytes(&finished),
+                            expected,
+                            "{intrinsic:?} {extra:?} {applied:?} {crop_applied:?}"
+                        );
+                        assert_eq!(
+                            remaining.output_dimensions(pixels.width(), pixels.height()),
+                            (finished.width(), finished.height())
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn honored_hints_leave_nothing() {
+        for intrinsic in Orientation::ALL {
+            let (_, out) = decoded(intrinsic, Some([1, 1, 3, 2]));
+            let remaining = RemainingTransform::new(
+                OrientationHint::Correct,
+                Some([1, 1, 3, 2]),
+                &info(intrinsic),
+                &out,
+            );
+            assert!(remaining.is_identity(), "{intrinsic:?}");
+        }
+    }
+
+    #[test]
+    fn hint_variants() {
+        let (_, out) = decoded(Orientation::Identity, None);
+        let exif = info(Orientation::Rotate90);
+        let plan = |hint| RemainingTransform::new(hint, None, &exif, &out).orientation();
+        assert_eq!(plan(OrientationHint::Preserve), Orientation::Identity);
+        assert_eq!(plan(OrientationHint::Correct), Orientation::Rotate90);
+        assert_eq!(
+            plan(OrientationHint::ExactTransform(Orientation::FlipH)),
+            Orientation::FlipH
+        );
+    }
+
+    #[test]
+    fn crop_is_clamped_or_ignored() {
+        let (pixels, out) = decoded(Orientation::Identity, None);
+        let exif = info(Orientation::Identity);
+        let hint = OrientationHint::Preserve;
+        let clamped = RemainingTransform::new(hint, Some([3, 1, 100, 100]), &exif, &out);
+        assert_eq!(clamped.crop(), Some([3, 1, 2, 2]));
+        assert_eq!(bytes(&clamped.apply(pixels.as_slice())).2, [8, 9, 13, 14]);
+        let outside = RemainingTransform::new(hint, Some([10, 10, 2, 2]), &exif, &out);
+        assert!(outside.is_identity());
+        let whole = RemainingTransform::new(hint, Some([0, 0, 5, 3]), &exif, &out);
+        assert!(whole.is_identity());
+    }
+
+    struct Ignoring(DecodeOutput);
+
+    impl Decode for Ignoring {
+        type Error = core::convert::Infallible;
+
+        fn decode(self) -> Result<DecodeOutput, Self::Error> {
+            Ok(self.0)
+        }
+    }
+
+    impl DynDecoder for Ignoring {
+        fn decode(self: Box<Self>) -> Result<DecodeOutput, BoxedError> {
+            Ok(self.0)
+        }
+    }
+
+    #[test]
+    fn finishing_decoder_updates_info() {
+        let exif = info(Orientation::Rotate90);
+        let (_, out) = decoded(Orientation::Identity, None);
+        let remaining =
+            RemainingTransform::new(OrientationHint::Correct, Some([0, 0, 2, 3]), &exif, &out);
+        let inner = DecodeOutput::new(source(), exif.clone()).with_extras(7u32);
+
+        let output = Decode::decode(FinishingDecoder::new(Ignoring(inner), remaining)).unwrap();
+        assert_eq!((output.width(), output.height()), (3, 2));
+        assert_eq!(output.info().orientation, Orientation::Identity);
+        assert_eq!(output.extras::<u32>(), Some(&7));
+        let pixels = output.into_buffer().copy_to_contiguous_bytes();
+        assert_eq!(pixels, [10, 5, 0, 11, 6, 1]);
+
+        let inner = DecodeOutput::new(source(), exif.clone());
+        let dyn_inner: Box<dyn DynDecoder> = Box::new(Ignoring(inner));
+        let boxed: Box<dyn DynDecoder> = Box::new(FinishingDecoder::new(dyn_inner, remaining));
+        assert_eq!(boxed.decode().unwrap().width(), 3);
+    }
+}
@@ -19,9 +19,14 @@
 
 use alloc::boxed::Box;
 use alloc::sync::Arc;
+use alloc::vec;
 use alloc::vec::Vec;
 
 use crate::info::Cicp;
+use zenpixels::{
+    ChannelLayout, ChannelType, ColorPrimaries, LumaCoefficients, PixelBuffer, PixelDescriptor,
+    PixelSlice, TransferFunction,
+};
 
 // =========================================================================
 // Wire format
@@ -49,8 +54,8 @@ use crate::info::Cicp;
 /// payload". It's a structured bundle with its own header and trailing
 /// fields — see `libjxl/lib/include/jxl/gain_map.h:38-63` and
 /// `libjxl/lib/extras/gain_map.cc:83-153`. Zencodec produces what goes
-/// in the `gain_map_metadata` field only; the bundle header and
-/// trailing fields are libjxl's responsibility.
+/// in the `gain_map_metadata` field; [`JxlGainMapBundle`] builds and
+/// parses the whole bundle.
 ///
 /// # Primary JPEG "signal" marker
 ///
@@ -318,6 +323,19 @@ impl GainMapParams {
         }
     }
 
+    /// ISO 21496-1 weight for a display with `display_hdr_headroom` (log2).
+    ///
+    /// 0.0 reproduces the base rendition and 1.0 the alternate; values in
+    /// between scale the log2 gain. The same formula covers both
+    /// [`GainMapDirection`]s. Equal headrooms give 0.0.
+    pub fn weight(&self, display_hdr_headroom: f64) -> f64 {
+        let span = self.alternate_hdr_headroom - self.base_hdr_headroom;
+        if span == 0.0 {
+            return 0.0;
+        }
+        ((display_hdr_headroom - self.base_hdr_headroom) / span).clamp(0.0, 1.0)
+    }
+
     /// Base HDR headroom in linear domain: 2^base_hdr_headroom.
     pub fn linear_base_headroom(&self) -> f64 {
         2.0f64.powf(self.base_hdr_headroom)
@@ -576,18 +594,642 @@ impl GainMapSource {
 #[non_exhaustive]
 pub struct DecodedGainMap {
     /// Gain map image pixels.
-    pub pixels: zenpixels::PixelBuffer,
+    pub pixels: PixelBuffer,
     /// ISO 21496-1 gain map metadata.
     pub metadata: GainMapInfo,
 }
 
 impl DecodedGainMap {
     /// Create a new decoded gain map.
-    pub fn new(pixels: zenpixels::PixelBuffer, metadata: GainMapInfo) -> Self {
+    pub fn new(pixels: PixelBuffer, metadata: GainMapInfo) -> Self {
         Self { pixels, metadata }
     }
 }
 
+/// Gain map handed to an encoder via
+/// [`EncodeJob::with_gain_map()`](crate::encode::EncodeJob::with_gain_map).
+///
+/// Encoders compress [`Decoded`](Self::Decoded) pixels in their own
+/// format. A [`Source`](Self::Source) bitstream is embedded verbatim when
+/// its `format` is the encoder's own; otherwise the encoder may decode it
+/// or skip it. Either way,
+/// [`EncodeOutput::gain_map_embedded()`](crate::encode::EncodeOutput::gain_map_embedded)
+/// reports the outcome.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EncodeGainMap {
+    /// Decoded gain map pixels to re-encode.
+    Decoded(DecodedGainMap),
+    /// Encoded gain map bitstream to embed as-is.
+    Source(GainMapSource),
+}
+
+impl EncodeGainMap {
+    /// The gain map metadata, whichever form it takes.
+    pub fn info(&self) -> &GainMapInfo {
+        match self {
+            Self::Decoded(decoded) => &decoded.metadata,
+            Self::Source(source) => &source.metadata,
+        }
+    }
+}
+
+impl From<DecodedGainMap> for EncodeGainMap {
+    fn from(decoded: DecodedGainMap) -> Self {
+        Self::Decoded(decoded)
+    }
+}
+
+impl From<GainMapSource> for EncodeGainMap {
+    fn from(source: GainMapSource) -> Self {
+        Self::Source(source)
+    }
+}
+
+// =========================================================================
+// Gain map application
+// =========================================================================
+
+/// Errors from [`apply_gain_map`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum GainMapApplyError {
+    /// The base image is not linear-light f32 RGB or RGBA.
+    UnsupportedBase { descriptor: PixelDescriptor },
+    /// The gain map image is empty or not gray/RGB in u8, u16, or f32.
+    UnsupportedGainMap { descriptor: PixelDescriptor },
+    /// The gain map parameters failed [`GainMapParams::validate`].
+    InvalidParams(GainMapParseError),
+    /// `use_base_color_space` is false, but the base or alternate primaries
+    /// are unknown, so the base cannot be converted into the alternate
+    /// colour space.
+    UnknownPrimaries,
+}
+
+impl core::fmt::Display for GainMapApplyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedBase { descriptor } => write!(
+                f,
+                "gain map: base must be linear f32 RGB or RGBA, got {} ({:?})",
+                descriptor.format.name(),
+                descriptor.transfer
+            ),
+            Self::UnsupportedGainMap { descriptor } => write!(
+                f,
+                "gain map: unsupported gain map pixels {}",
+                descriptor.format.name()
+            ),
+            Self::InvalidParams(e) => write!(f, "gain map: invalid parameters: {e}"),
+            Self::UnknownPrimaries => {
+                write!(f, "gain map: alternate colour space primaries are unknown")
+            }
+        }
+    }
+}
+
+impl core::error::Error for GainMapApplyError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidParams(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Render the rendition of `base` adapted to `display_hdr_headroom` (log2).
+///
+/// `base` must be linear-light f32 RGB or RGBA; alpha is copied through.
+/// The gain map is sampled bilinearly, so it may be smaller than the base.
+/// A single-channel map drives all three colour channels; a three-channel
+/// map drives each channel with its own [`GainMapChannel`] parameters.
+///
+/// Per ISO 21496-1, for each colour channel:
+///
+/// ```text
+/// G   = gain_map_value ^ (1 / gamma)
+/// L   = lerp(min, max, G)                         (log2)
+/// out = (base + base_offset) · 2^(L · W) − alternate_offset
+/// ```
+///
+/// where `W` is [`GainMapParams::weight()`]. This handles both
+/// [`GainMapDirection`]s: an SDR base brightens toward an HDR alternate,
+/// an HDR base tone-maps down toward an SDR alternate.
+///
+/// When [`use_base_color_space`](GainMapParams::use_base_color_space) is
+/// false, the gain applies in the alternate rendition's colour space: the
+/// base is converted to the primaries in
+/// [`GainMapInfo::alternate_cicp`] first, and the result is tagged with
+/// them. Otherwise the result keeps the base's descriptor.
+pub fn apply_gain_map(
+    base: &PixelSlice<'_>,
+    gain_map: &DecodedGainMap,
+    display_hdr_headroom: f64,
+) -> Result<PixelBuffer, GainMapApplyError> {
+    let info = &gain_map.metadata;
+    let params = &info.params;
+    params
+        .validate()
+        .map_err(GainMapApplyError::InvalidParams)?;
+
+    let descriptor = base.descriptor();
+    let channels = match descriptor.layout() {
+        ChannelLayout::Rgb => 3,
+        ChannelLayout::Rgba => 4,
+        _ => return Err(GainMapApplyError::UnsupportedBase { descriptor }),
+    };
+    if descriptor.channel_type() != ChannelType::F32
+        || descriptor.transfer() != TransferFunction::Linear
+    {
+        return Err(GainMapApplyError::UnsupportedBase { descriptor });
+    }
+
+    let mut out_descriptor = descriptor;
+    let mut gamut = None;
+    if !params.use_base_color_space {
+        let alternate = info
+            .alternate_cicp
+            .and_then(|cicp| ColorPrimaries::from_cicp(cicp.color_primaries))
+            .ok_or(GainMapApplyError::UnknownPrimaries)?;
+        if alternate != descriptor.primaries {
+            gamut = Some(
+                descriptor
+                    .primaries
+                    .gamut_matrix_to(alternate)
+                    .ok_or(GainMapApplyError::UnknownPrimaries)?,
+            );
+        }
+        out_descriptor = descriptor.with_primaries(alternate);
+    }
+
+    let map = GainMapSamples::new(&gain_map.pixels)?;
+    let weight = params.weight(display_hdr_headroom) as f32;
+    let coeffs = params.channels.map(|ch| ChannelCoeffs {
+        min: ch.min as f32,
+        range: (ch.max - ch.min) as f32,
+        inv_gamma: (1.0 / ch.gamma) as f32,
+        base_offset: ch.base_offset as f32,
+        alternate_offset: ch.alternate_offset as f32,
+    });
+
+    let width = base.width();
+    let height = base.rows();
+    let columns: Vec<_> = (0..width)
+        .map(|x| bilinear_axis(x, width, map.width))
+        .collect();
+    let row_bytes = width as usize * channels * 4;
+    let mut data = Vec::with_capacity(row_bytes * height as usize);
+    let mut gains = [0.0f32; 3];
+
+    for y in 0..height {
+        let row_axis = bilinear_axis(y, height, map.height);
+        for (pixel, &col_axis) in base.row(y).chunks_exact(channels * 4).zip(&columns) {
+            let mut rgb = [0.0f32; 4];
+            for (value, bytes) in rgb.iter_mut().zip(pixel.chunks_exact(4)) {
+                *value = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            }
+            if let Some(m) = &gamut {
+                let [r, g, b, _] = rgb;
+                for (c, value) in rgb.iter_mut().take(3).enumerate() {
+                    *value = m[c][0] * r + m[c][1] * g + m[c][2] * b;
+                }
+            }
+            map.sample(row_axis, col_axis, &mut gains);
+            for c in 0..3 {
+                let k = &coeffs[c];
+                let mut g = gains[if map.channels == 1 { 0 } else { c }];
+                if k.inv_gamma != 1.0 {
+                    g = g.powf(k.inv_gamma);
+                }
+                let log_gain = k.min + k.range * g;
+                rgb[c] = (rgb[c] + k.base_offset) * (log_gain * weight).exp2() - k.alternate_offset;
+            }
+            for value in &rgb[..channels] {
+                data.extend_from_slice(&value.to_ne_bytes());
+            }
+        }
+    }
+
+    Ok(PixelBuffer::from_vec(data, width, height, out_descriptor)
+        .expect("apply_gain_map: buffer sized correctly"))
+}
+
+/// Per-channel ISO 21496-1 coefficients in f32.
+struct ChannelCoeffs {
+    min: f32,
+    range: f32,
+    inv_gamma: f32,
+    base_offset: f32,
+    alternate_offset: f32,
+}
+
+/// Gain map pixels normalized to `[0, 1]` f32, one or three per pixel.
+struct GainMapSamples {
+    values: Vec<f32>,
+    width: u32,
+    height: u32,
+    channels: usize,
+}
+
+impl GainMapSamples {
+    fn new(pixels: &PixelBuffer) -> Result<Self, GainMapApplyError> {
+        let descriptor = pixels.descriptor();
+        let unsupported = GainMapApplyError::UnsupportedGainMap { descriptor };
+        let offsets: &[usize] = match descriptor.layout() {
+            ChannelLayout::Gray | ChannelLayout::GrayAlpha => &[0],
+            ChannelLayout::Rgb | ChannelLayout::Rgba => &[0, 1, 2],
+            ChannelLayout::Bgra => &[2, 1, 0],
+            _ => return Err(unsupported),
+        };
+        let read: fn(&[u8]) -> f32 = match descriptor.channel_type() {
+            ChannelType::U8 => |b| b[0] as f32 / 255.0,
+            ChannelType::U16 => |b| u16::from_ne_bytes([b[0], b[1]]) as f32 / 65535.0,
+            ChannelType::F32 => |b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]),
+            _ => return Err(unsupported),
+        };
+        let (width, height) = (pixels.width(), pixels.height());
+        if width == 0 || height == 0 {
+            return Err(unsupported);
+        }
+
+        let bpc = descriptor.bytes_per_channel();
+        let bpp = descriptor.bytes_per_pixel();
+        let slice = pixels.as_slice();
+        let mut values = Vec::with_capacity(width as usize * height as usize * offsets.len());
+        for y in 0..height {
+            for pixel in slice.row(y).chunks_exact(bpp) {
+                values.extend(offsets.iter().map(|&c| read(&pixel[c * bpc..])));
+            }
+        }
+        Ok(Self {
+            values,
+            width,
+            height,
+            channels: offsets.len(),
+        })
+    }
+
+    /// Bilinearly interpolate the map at the given row and column taps.
+    fn sample(&self, row: (usize, usize, f32), col: (usize, usize, f32), out: &mut [f32; 3]) {
+        let (y0, y1, fy) = row;
+        let (x0, x1, fx) = col;
+        let w = self.width as usize;
+        let at = |x: usize, y: usize, c: usize| self.values[(y * w + x) * self.channels + c];
+        for (c, value) in out.iter_mut().enumerate().take(self.channels) {
+            let top = at(x0, y0, c) + (at(x1, y0, c) - at(x0, y0, c)) * fx;
+            let bottom = at(x0, y1, c) + (at(x1, y1, c) - at(x0, y1, c)) * fx;
+            *value = top + (bottom - top) * fy;
+        }
+    }
+}
+
+/// Source taps and fraction for destination index `i` when resampling
+/// `src` samples to `dst`, with pixel centres aligned.
+fn bilinear_axis(i: u32, dst: u32, src: u32) -> (usize, usize, f32) {
+    let last = src as usize - 1;
+    let pos = ((i as f32 + 0.5) * src as f32 / dst as f32 - 0.5).clamp(0.0, last as f32);
+    let i0 = pos as usize;
+    (i0, (i0 + 1).min(last), pos - i0 as f32)
+}
+
+// =========================================================================
+// Gain map generation
+// =========================================================================
+
+/// Options for [`generate_gain_map`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct GainMapGenerateOptions {
+    /// Downscale factor per axis. Each gain map pixel averages a
+    /// `scale`×`scale` block of the inputs. 1 = full resolution.
+    pub scale: u32,
+    /// Gamma written to every channel. Linear domain, must be > 0.
+    pub gamma: f64,
+    /// Offset added to base values. Linear domain.
+    pub base_offset: f64,
+    /// Offset added to alternate values. Linear domain.
+    pub alternate_offset: f64,
+    /// Three-channel map with per-channel parameters instead of a
+    /// luminance map.
+    pub multichannel: bool,
+    /// Which rendition becomes the base image.
+    pub direction: GainMapDirection,
+    /// Log2 headroom of the HDR rendition. `None` derives it from the
+    /// largest gain in the image.
+    pub hdr_headroom: Option<f64>,
+}
+
+impl Default for GainMapGenerateOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GainMapGenerateOptions {
+    /// Full-resolution luminance map for an SDR base, gamma 1.0 and the
+    /// ISO 21496-1 default offsets of 1/64.
+    pub const fn new() -> Self {
+        Self {
+            scale: 1,
+            gamma: 1.0,
+            base_offset: 1.0 / 64.0,
+            alternate_offset: 1.0 / 64.0,
+            multichannel: false,
+            direction: GainMapDirection::BaseIsSdr,
+            hdr_headroom: None,
+        }
+    }
+
+    /// Set the per-axis downscale factor.
+    pub const fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Set the gamma applied to gain map values.
+    pub const fn with_gamma(mut self, gamma: f64) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Set the base and alternate offsets.
+    pub const fn with_offsets(mut self, base_offset: f64, alternate_offset: f64) -> Self {
+        self.base_offset = base_offset;
+        self.alternate_offset = alternate_offset;
+        self
+    }
+
+    /// Produce a three-channel map instead of a luminance map.
+    pub const fn with_multichannel(mut self, multichannel: bool) -> Self {
+        self.multichannel = multichannel;
+        self
+    }
+
+    /// Choose which rendition becomes the base image.
+    pub const fn with_direction(mut self, direction: GainMapDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Set the HDR rendition's log2 headroom instead of deriving it.
+    pub const fn with_hdr_headroom(mut self, hdr_headroom: f64) -> Self {
+        self.hdr_headroom = Some(hdr_headroom);
+        self
+    }
+}
+
+/// Errors from [`generate_gain_map`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum GainMapGenerateError {
+    /// An input is not linear-light f32 RGB or RGBA.
+    UnsupportedInput { descriptor: PixelDescriptor },
+    /// The SDR and HDR inputs have different dimensions.
+    DimensionMismatch { sdr: (u32, u32), hdr: (u32, u32) },
+    /// The inputs have different primaries and one of them is unknown.
+    UnknownPrimaries,
+    /// The input is empty or [`GainMapGenerateOptions::scale`] is 0.
+    EmptyGainMap,
+    /// The resulting parameters failed [`GainMapParams::validate`]
+    /// (non-positive gamma or non-finite options).
+    InvalidParams(GainMapParseError),
+}
+
+impl core::fmt::Display for GainMapGenerateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedInput { descriptor } => write!(
+                f,
+                "gain map: input must be linear f32 RGB or RGBA, got {} ({:?})",
+                descriptor.format.name(),
+                descriptor.transfer
+            ),
+            Self::DimensionMismatch { sdr, hdr } => write!(
+                f,
+                "gain map: SDR is {}x{} but HDR is {}x{}",
+                sdr.0, sdr.1, hdr.0, hdr.1
+            ),
+            Self::UnknownPrimaries => write!(f, "gain map: input primaries are unknown"),
+            Self::EmptyGainMap => write!(f, "gain map: empty input or zero scale"),
+            Self::InvalidParams(e) => write!(f, "gain map: invalid parameters: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for GainMapGenerateError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidParams(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Compute a gain map from matching SDR and HDR renditions.
+///
+/// Both inputs must be linear-light f32 RGB or RGBA of the same size, with
+/// SDR diffuse white at 1.0; alpha is ignored. If their primaries differ,
+/// the alternate rendition is converted into the base's primaries, and the
+/// map is computed in the base colour space.
+///
+/// Per gain map pixel (a block average when `scale > 1`) and channel:
+///
+/// ```text
+/// L = log2((alternate + alternate_offset) / (base + base_offset))
+/// v = ((L − min) / (max − min)) ^ gamma
+/// ```
+///
+/// `min`/`max` are the extremes of `L`, rounded through
+/// [`Fraction::from_f64_cf`] so the pixels match the values a decoder will
+/// parse. Single-channel maps use the luminance of the base primaries.
+/// Values are stored as [`PixelDescriptor::GRAY8`] or
+/// [`PixelDescriptor::RGB8`].
+///
+/// The returned parameters are validated and ready for
+/// [`serialize_iso21496_fmt`]; [`apply_gain_map`] at the HDR headroom
+/// reconstructs the HDR rendition up to quantization.
+pub fn generate_gain_map(
+    sdr: &PixelSlice<'_>,
+    hdr: &PixelSlice<'_>,
+    options: &GainMapGenerateOptions,
+) -> Result<(PixelBuffer, GainMapParams), GainMapGenerateError> {
+    let sdr_channels = linear_rgb_channels(sdr)?;
+    let hdr_channels = linear_rgb_channels(hdr)?;
+    let (width, height) = (sdr.width(), sdr.rows());
+    if (hdr.width(), hdr.rows()) != (width, height) {
+        return Err(GainMapGenerateError::DimensionMismatch {
+            sdr: (width, height),
+            hdr: (hdr.width(), hdr.rows()),
+        });
+    }
+    if width == 0 || height == 0 || options.scale == 0 {
+        return Err(GainMapGenerateError::EmptyGainMap);
+    }
+
+    let base_is_hdr = options.direction == GainMapDirection::BaseIsHdr;
+    let (base, base_channels, alternate, alternate_channels) = if base_is_hdr {
+        (hdr, hdr_channels, sdr, sdr_channels)
+    } else {
+        (sdr, sdr_channels, hdr, hdr_channels)
+    };
+    let primaries = base.descriptor().primaries;
+    let gamut = match alternate.descriptor().primaries {
+        p if p == primaries => None,
+        p => Some(
+            p.gamut_matrix_to(primaries)
+                .ok_or(GainMapGenerateError::UnknownPrimaries)?,
+        ),
+    };
+    let luma = match primaries {
+        ColorPrimaries::Bt2020 => LumaCoefficients::Bt2020,
+        ColorPrimaries::DisplayP3 => LumaCoefficients::DisplayP3,
+        _ => LumaCoefficients::Bt709,
+    }
+    .coefficients();
+
+    let scale = options.scale;
+    let map_width = width.div_ceil(scale);
+    let map_height = height.div_ceil(scale);
+    let map_channels = if options.multichannel { 3 } else { 1 };
+    let base_offset = options.base_offset as f32;
+    let alternate_offset = options.alternate_offset as f32;
+
+    // Log2 gains per gain map pixel, and their per-channel range.
+    let mut gains = Vec::with_capacity(map_width as usize * map_height as usize * map_channels);
+    let mut low = [f32::INFINITY; 3];
+    let mut high = [f32::NEG_INFINITY; 3];
+    let mut sums = vec![[[0.0f32; 3]; 2]; map_width as usize];
+    for block_y in 0..map_height {
+        sums.fill([[0.0; 3]; 2]);
+        let y_end = ((block_y + 1) * scale).min(height);
+        for y in block_y * scale..y_end {
+            let base_row = base.row(y).chunks_exact(base_channels * 4);
+            let alternate_row = alternate.row(y).chunks_exact(alternate_channels * 4);
+            for (x, (b, a)) in base_row.zip(alternate_row).enumerate() {
+                let mut a = read_rgb(a);
+                if let Some(m) = &gamut {
+                    let [r, g, bl] = a;
+                    a = m.map(|row| row[0] * r + row[1] * g + row[2] * bl);
+                }
+                let sum = &mut sums[x / scale as usize];
+                for (c, (bv, av)) in read_rgb(b).into_iter().zip(a).enumerate() {
+                    sum[0][c] += bv.max(0.0);
+                    sum[1][c] += av.max(0.0);
+                }
+            }
+        }
+        let block_height = (y_end - block_y * scale) as f32;
+        for (block_x, [base_sum, alternate_sum]) in (0..map_width).zip(&sums) {
+            let block_width = (((block_x + 1) * scale).min(width) - block_x * scale) as f32;
+            // Both sums cover the same pixels, so scaling the offsets by the
+            // pixel count makes their ratio the ratio of the averages.
+            let count = block_width * block_height;
+            let (b, a) = if options.multichannel {
+                (*base_sum, *alternate_sum)
+            } else {
+                let y = |v: &[f32; 3]| [luma[0] * v[0] + luma[1] * v[1] + luma[2] * v[2]; 3];
+                (y(base_sum), y(alternate_sum))
+            };
+            for c in 0..map_channels {
+                let num = a[c] + alternate_offset * count;
+                let den = b[c] + base_offset * count;
+                let gain = if num > 0.0 && den > 0.0 {
+                    (num / den).log2()
+                } else {
+                    0.0
+                };
+                low[c] = low[c].min(gain);
+                high[c] = high[c].max(gain);
+                gains.push(gain);
+            }
+        }
+    }
+
+    let quantize = |v: f64| Fraction::from_f64_cf(v).to_f64();
+    let mut channels = [GainMapChannel {
+        min: 0.0,
+        max: 0.0,
+        gamma: UFraction::from_f64_cf(options.gamma).to_f64(),
+        base_offset: quantize(options.base_offset),
+        alternate_offset: quantize(options.alternate_offset),
+    }; 3];
+    for (c, channel) in channels.iter_mut().enumerate() {
+        let c = if options.multichannel { c } else { 0 };
+        channel.min = quantize(low[c] as f64);
+        channel.max = quantize(high[c] as f64).max(channel.min);
+    }
+    let (lowest, highest) = channels.iter().fold((0.0f64, 0.0f64), |(lo, hi), ch| {
+        (lo.min(ch.min), hi.max(ch.max))
+    });
+    let hdr_headroom = options
+        .hdr_headroom
+        .unwrap_or(if base_is_hdr { -lowest } else { highest });
+    let hdr_headroom = UFraction::from_f64_cf(hdr_headroom).to_f64();
+    let params = GainMapParams {
+        channels,
+        base_hdr_headroom: if base_is_hdr { hdr_headroom } else { 0.0 },
+        alternate_hdr_headroom: if base_is_hdr { 0.0 } else { hdr_headroom },
+        use_base_color_space: true,
+        backward_direction: base_is_hdr,
+    };
+    params
+        .validate()
+        .map_err(GainMapGenerateError::InvalidParams)?;
+
+    let encode = params.channels.map(|ch| {
+        let range = (ch.max - ch.min) as f32;
+        (ch.min as f32, range, ch.gamma as f32)
+    });
+    let data = gains
+        .iter()
+        .enumerate()
+        .map(|(i, &gain)| {
+            let (min, range, gamma) = encode[i % map_channels];
+            let mut v = if range > 0.0 {
+                ((gain - min) / range).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            if gamma != 1.0 {
+                v = v.powf(gamma);
+            }
+            (v * 255.0 + 0.5) as u8
+        })
+        .collect();
+    let descriptor = if options.multichannel {
+        PixelDescriptor::RGB8
+    } else {
+        PixelDescriptor::GRAY8
+    };
+    let pixels = PixelBuffer::from_vec(data, map_width, map_height, descriptor)
+        .expect("generate_gain_map: buffer sized correctly");
+    Ok((pixels, params))
+}
+
+/// Colour channel count of a linear-light f32 RGB or RGBA slice.
+fn linear_rgb_channels(pixels: &PixelSlice<'_>) -> Result<usize, GainMapGenerateError> {
+    let descriptor = pixels.descriptor();
+    let channels = match descriptor.layout() {
+        ChannelLayout::Rgb => 3,
+        ChannelLayout::Rgba => 4,
+        _ => return Err(GainMapGenerateError::UnsupportedInput { descriptor }),
+    };
+    if descriptor.channel_type() != ChannelType::F32
+        || descriptor.transfer() != TransferFunction::Linear
+    {
+        return Err(GainMapGenerateError::UnsupportedInput { descriptor });
+    }
+    Ok(channels)
+}
+
+/// First three native-endian f32 values of a pixel.
+fn read_rgb(pixel: &[u8]) -> [f32; 3] {
+    core::array::from_fn(|c| {
+        let b = &pixel[c * 4..c * 4 + 4];
+        f32::from_ne_bytes([b[0], b[1], b[2], b[3]])
+    })
+}
+
 // =========================================================================
 // ISO 21496-1 fractions
 // =========================================================================
@@ -797,6 +1439,11 @@ pub enum GainMapParseError {
     NonFiniteValue { field: &'static str },
     /// Input to [`parse_iso21496_with_urn`] did not begin with the ISO 21496-1 URN.
     UrnMismatch,
+    /// A required `hdrgm:` XMP property is absent.
+    MissingXmpField { field: &'static str },
+    /// An `hdrgm:` XMP property is not a valid number, boolean, or
+    /// per-channel list.
+    InvalidXmpValue { field: &'static str },
 }
 
 impl core::fmt::Display for GainMapParseError {
@@ -832,6 +1479,12 @@ impl core::fmt::Display for GainMapParseError {
                     "ISO 21496-1: input does not begin with the ISO 21496-1 URN"
                 )
             }
+            Self::MissingXmpField { field } => {
+                write!(f, "gain map XMP: missing hdrgm:{field}")
+            }
+            Self::InvalidXmpValue { field } => {
+                write!(f, "gain map XMP: invalid hdrgm:{field}")
+            }
         }
     }
 }
@@ -1007,6 +1660,22 @@ pub fn serialize_iso21496_fmt_into(
     }
 }
 
+/// Re-encode ISO 21496-1 metadata from one container flavor to another.
+///
+/// Parses `data` as `from` and serializes the result as `to`, e.g. a JPEG
+/// APP2 body ([`Iso21496Format::JpegApp2BodyWithUrn`]) into an AVIF `tmap`
+/// payload ([`Iso21496Format::AvifTmap`]) or the `gain_map_metadata` field
+/// of a [`JxlGainMapBundle`] ([`Iso21496Format::JxlJhgm`]). Compact
+/// common-denominator input is re-emitted in the full encoding.
+pub fn retarget_iso21496(
+    data: &[u8],
+    from: Iso21496Format,
+    to: Iso21496Format,
+) -> Result<Vec<u8>, GainMapParseError> {
+    let params = parse_iso21496_fmt(data, from)?;
+    Ok(serialize_iso21496_fmt(&params, to))
+}
+
 // -- Internal format-specific entry points --
 
 /// Parse ISO 21496-1 from AVIF `tmap` item payload (with version byte prefix).
@@ -1275,6 +1944,280 @@ fn serialize_iso21496_no_version_into(params: &GainMapParams, out: &mut Vec<u8>)
     write_payload(out, params);
 }
 
+// =========================================================================
+// JXL jhgm bundle
+// =========================================================================
+
+/// A color encoding too long for [`JxlGainMapBundle::with_color_encoding`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorEncodingTooLong {
+    /// Length of the rejected encoding in bytes.
+    pub len: usize,
+}
+
+impl core::fmt::Display for ColorEncodingTooLong {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "jhgm: color encoding is {} bytes, at most 255 allowed",
+            self.len
+        )
+    }
+}
+
+impl core::error::Error for ColorEncodingTooLong {}
+
+/// Version byte written at the start of a [`JxlGainMapBundle`].
+pub const JHGM_VERSION: u8 = 0;
+
+/// The complete JXL `jhgm` box payload (libjxl's `JxlGainMapBundle`).
+///
+/// Layout, all integers big-endian:
+///
+/// | Field | Size |
+/// |-------|------|
+/// | `jhgm_version` | u8 (0) |
+/// | `gain_map_metadata_size` | u16 |
+/// | `gain_map_metadata` | [`Iso21496Format::JxlJhgm`] payload |
+/// | `color_encoding_size` | u8 (0 = absent) |
+/// | `color_encoding` | JXL-encoded `ColorEncoding` of the alternate rendition |
+/// | `alt_icc_size` | u32 |
+/// | `alt_icc` | JXL-compressed ICC of the alternate rendition |
+/// | `gain_map` | bare JXL codestream, to the end of the box |
+///
+/// `color_encoding` and `alt_icc` use JXL's own bit-level encodings and are
+/// carried as opaque bytes; the ISOBMFF box header is the caller's.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct JxlGainMapBundle {
+    /// ISO 21496-1 gain map parameters.
+    pub params: GainMapParams,
+    /// JXL-encoded color encoding of the alternate rendition. Empty if
+    /// absent; at most 255 bytes, enforced by
+    /// [`with_color_encoding`](Self::with_color_encoding).
+    color_encoding: Vec<u8>,
+    /// JXL-compressed ICC profile of the alternate rendition. Empty if absent.
+    pub alternate_icc: Vec<u8>,
+    /// Bare JXL codestream of the gain map image.
+    pub gain_map: Vec<u8>,
+}
+
+impl JxlGainMapBundle {
+    /// Create a bundle without alternate color information.
+    pub fn new(params: GainMapParams, gain_map: Vec<u8>) -> Self {
+        Self {
+            params,
+            color_encoding: Vec::new(),
+            alternate_icc: Vec::new(),
+            gain_map,
+        }
+    }
+
+    /// Set the JXL-encoded color encoding.
+    ///
+    /// Fails if it exceeds the 255 bytes the `jhgm` size field can hold.
+    pub fn with_color_encoding(
+        mut self,
+        color_encoding: Vec<u8>,
+    ) -> Result<Self, ColorEncodingTooLong> {
+        if color_encoding.len() > usize::from(u8::MAX) {
+            return Err(ColorEncodingTooLong {
+                len: color_encoding.len(),
+            });
+        }
+        self.color_encoding = color_encoding;
+        Ok(self)
+    }
+
+    /// JXL-encoded color encoding of the alternate rendition. Empty if
+    /// absent.
+    pub fn color_encoding(&self) -> &[u8] {
+        &self.color_encoding
+    }
+
+    /// Set the JXL-compressed alternate ICC profile.
+    pub fn with_alternate_icc(mut self, alternate_icc: Vec<u8>) -> Self {
+        self.alternate_icc = alternate_icc;
+        self
+    }
+
+    /// Parse a `jhgm` box payload.
+    ///
+    /// Returns [`GainMapParseError::UnsupportedVersion`] for a version other
+    /// than [`JHGM_VERSION`] and [`GainMapParseError::TruncatedData`] when a
+    /// size field overruns the input.
+    pub fn parse(data: &[u8]) -> Result<Self, GainMapParseError> {
+        let mut offset = 0;
+        let version = read_u8(data, &mut offset)?;
+        if version != JHGM_VERSION {
+            return Err(GainMapParseError::UnsupportedVersion { version });
+        }
+        let metadata_size = read_u16_be(data, &mut offset)? as usize;
+        let metadata = read_bytes(data, &mut offset, metadata_size)?;
+        let params = parse_iso21496_fmt(metadata, Iso21496Format::JxlJhgm)?;
+        let color_encoding_size = read_u8(data, &mut offset)? as usize;
+        let color_encoding = read_bytes(data, &mut offset, color_encoding_size)?.to_vec();
+        let alt_icc_size = read_u32_be(data, &mut offset)? as usize;
+        let alternate_icc = read_bytes(data, &mut offset, alt_icc_size)?.to_vec();
+        Ok(Self {
+            params,
+            color_encoding,
+            alternate_icc,
+            gain_map: data[offset..].to_vec(),
+        })
+    }
+
+    /// Serialize to a `jhgm` box payload.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alternate_icc` exceeds `u32::MAX` bytes, which the format
+    /// cannot represent.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.serialize_into(&mut out);
+        out
+    }
+
+    /// Serialize, appending to `out`. See [`serialize`](Self::serialize).
+    pub fn serialize_into(&self, out: &mut Vec<u8>) {
+        let metadata = serialize_iso21496_fmt(&self.params, Iso21496Format::JxlJhgm);
+        // Bounded by `with_color_encoding` and `parse`.
+        let color_encoding_size = self.color_encoding.len() as u8;
+        let alt_icc_size =
+            u32::try_from(self.alternate_icc.len()).expect("jhgm alternate ICC over 4 GiB");
+        out.reserve(8 + metadata.len() + self.color_encoding.len() + self.alternate_icc.len());
+        out.push(JHGM_VERSION);
+        // At most 3 channels of fractions: always well under 64 KiB.
+        out.extend_from_slice(&(metadata.len() as u16).to_be_bytes());
+        out.extend_from_slice(&metadata);
+        out.push(color_encoding_size);
+        out.extend_from_slice(&self.color_encoding);
+        out.extend_from_slice(&alt_icc_size.to_be_bytes());
+        out.extend_from_slice(&self.alternate_icc);
+        out.extend_from_slice(&self.gain_map);
+    }
+
+    /// Describe the bundle as a [`GainMapInfo`].
+    ///
+    /// Dimensions and channel count live in the gain map codestream, so the
+    /// caller supplies them. The alternate color stays in its JXL encodings
+    /// and is not copied.
+    pub fn info(&self, width: u32, height: u32, channels: u8) -> GainMapInfo {
+        GainMapInfo::new(self.params.clone(), width, height, channels)
+    }
+}
+
+// =========================================================================
+// Apple HDR gain maps
+// =========================================================================
+
+/// Auxiliary image URN of Apple's HEIC HDR gain map.
+pub const APPLE_HDR_GAIN_MAP_URN: &str = "urn:com:apple:photo:2020:aux:hdrgainmap";
+
+/// Apple HDR gain map metadata from the maker note.
+///
+/// Apple's gain map is not ISO 21496-1: the maker-note `HDRHeadroom`
+/// (tag 33) and `HDRGain` (tag 48) values select a headroom `H` in stops,
+/// and a gain map value `g` (linear, after decoding the map's sRGB transfer)
+/// scales the SDR base by `1 + (2^H − 1) · g`. ISO 21496-1 interpolates the
+/// gain in log2 instead, so [`to_params`](Self::to_params) matches at
+/// `g = 0` and `g = 1`; use [`iso_map_value`](Self::iso_map_value) to
+/// re-encode map pixels for an exact match.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AppleHdrGain {
+    /// Maker-note tag 33 (`HDRHeadroom`).
+    pub headroom: f64,
+    /// Maker-note tag 48 (`HDRGain`).
+    pub gain: f64,
+}
+
+impl AppleHdrGain {
+    /// Create from the maker-note `HDRHeadroom` and `HDRGain` values.
+    pub const fn new(headroom: f64, gain: f64) -> Self {
+        Self { headroom, gain }
+    }
+
+    /// HDR headroom in stops (log2), per Apple's published formula.
+    /// Never negative.
+    pub fn stops(&self) -> f64 {
+        let stops = match (self.headroom < 1.0, self.gain <= 0.01) {
+            (true, true) => -20.0 * self.gain + 1.8,
+            (true, false) => -0.101 * self.gain + 1.601,
+            (false, true) => -70.0 * self.gain + 3.0,
+            (false, false) => -0.303 * self.gain + 2.303,
+        };
+        stops.max(0.0)
+    }
+
+    /// Maker-note values for a headroom of `stops`, clamped to Apple's
+    /// representable range of 0–3 stops.
+    ///
+    /// Always uses the `HDRHeadroom ≥ 1` branch, so [`stops()`](Self::stops)
+    /// returns `stops` to within 0.01.
+    pub fn from_stops(stops: f64) -> Self {
+        let stops = stops.clamp(0.0, 3.0);
+        let gain = if stops >= 2.3 {
+            (3.0 - stops) / 70.0
+        } else {
+            (2.303 - stops) / 0.303
+        };
+        Self::new(1.0, gain)
+    }
+
+    /// ISO 21496-1 parameters for an SDR base: gains from 0 to
+    /// [`stops()`](Self::stops), gamma 1, no offsets.
+    pub fn to_params(&self) -> GainMapParams {
+        let stops = self.stops();
+        let channel = GainMapChannel {
+            min: 0.0,
+            max: stops,
+            gamma: 1.0,
+            base_offset: 0.0,
+            alternate_offset: 0.0,
+        };
+        GainMapParams {
+            channels: [channel; 3],
+            base_hdr_headroom: 0.0,
+            alternate_hdr_headroom: stops,
+            use_base_color_space: true,
+            backward_direction: false,
+        }
+    }
+
+    /// Apple metadata for ISO 21496-1 parameters, from the alternate
+    /// headroom. `None` when the base is the HDR rendition.
+    pub fn from_params(params: &GainMapParams) -> Option<Self> {
+        if params.backward_direction || params.direction() == GainMapDirection::BaseIsHdr {
+            return None;
+        }
+        Some(Self::from_stops(
+            params.alternate_hdr_headroom - params.base_hdr_headroom,
+        ))
+    }
+
+    /// Convert a linear Apple map value to the normalized ISO 21496-1 value
+    /// for [`to_params`](Self::to_params): `log2(1 + (2^H − 1) · g) / H`.
+    pub fn iso_map_value(&self, apple: f32) -> f32 {
+        let stops = self.stops() as f32;
+        if stops <= 0.0 {
+            return 0.0;
+        }
+        let boost = stops.exp2() - 1.0;
+        ((1.0 + boost * apple.clamp(0.0, 1.0)).log2() / stops).clamp(0.0, 1.0)
+    }
+
+    /// Inverse of [`iso_map_value`](Self::iso_map_value).
+    pub fn apple_map_value(&self, iso: f32) -> f32 {
+        let stops = self.stops() as f32;
+        if stops <= 0.0 {
+            return 0.0;
+        }
+        let boost = stops.exp2() - 1.0;
+        (((iso.clamp(0.0, 1.0) * stops).exp2() - 1.0) / boost).clamp(0.0, 1.0)
+    }
+}
+
 // =========================================================================
 // Internal helpers
 // =========================================================================
@@ -1291,6 +2234,23 @@ fn read_u8(data: &[u8], offset: &mut usize) -> Result<u8, GainMapParseError> {
     Ok(v)
 }
 
+fn read_bytes<'a>(
+    data: &'a [u8],
+    offset: &mut usize,
+    len: usize,
+) -> Result<&'a [u8], GainMapParseError> {
+    let end = offset.saturating_add(len);
+    if end > data.len() {
+        return Err(GainMapParseError::TruncatedData {
+            expected: end,
+            actual: data.len(),
+        });
+    }
+    let bytes = &data[*offset..end];
+    *offset = end;
+    Ok(bytes)
+}
+
 fn read_u16_be(data: &[u8], offset: &mut usize) -> Result<u16, GainMapParseError> {
     if *offset + 2 > data.len() {
         return Err(GainMapParseError::TruncatedData {
@@ -2752,4 +3712,556 @@ mod tests {
         assert_eq!(Iso21496Format::JpegApp2BodyWithUrn as u8, 3);
         assert_ne!(Iso21496Format::JpegApp2, Iso21496Format::JxlJhgm);
     }
+
+    // --- gain map application ---
+
+    fn f32_buffer(values: &[f32], width: u32, height: u32, desc: PixelDescriptor) -> PixelBuffer {
+        let data = values.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        PixelBuffer::from_vec(data, width, height, desc).unwrap()
+    }
+
+    fn f32_values(buf: &PixelBuffer) -> Vec<f32> {
+        buf.copy_to_contiguous_bytes()
+            .chunks_exact(4)
+            .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    }
+
+    fn sdr_base_params(max: f64) -> GainMapParams {
+        let ch = GainMapChannel {
+            min: 0.0,
+            max,
+            gamma: 1.0,
+            base_offset: 0.0,
+            alternate_offset: 0.0,
+        };
+        GainMapParams {
+            channels: [ch; 3],
+            base_hdr_headroom: 0.0,
+            alternate_hdr_headroom: max,
+            ..Default::default()
+        }
+    }
+
+    fn gray8_map(values: &[u8], width: u32, height: u32, params: GainMapParams) -> DecodedGainMap {
+        let pixels =
+            PixelBuffer::from_vec(values.to_vec(), width, height, PixelDescriptor::GRAY8).unwrap();
+        DecodedGainMap::new(pixels, GainMapInfo::new(params, width, height, 1))
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn weight_sdr_base() {
+        let p = sdr_base_params(2.0);
+        assert_eq!(p.weight(-1.0), 0.0);
+        assert_eq!(p.weight(0.0), 0.0);
+        assert_eq!(p.weight(1.0), 0.5);
+        assert_eq!(p.weight(2.0), 1.0);
+        assert_eq!(p.weight(5.0), 1.0);
+    }
+
+    #[test]
+    fn weight_hdr_base() {
+        let p = GainMapParams {
+            base_hdr_headroom: 2.0,
+            alternate_hdr_headroom: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(p.direction(), GainMapDirection::BaseIsHdr);
+        assert_eq!(p.weight(3.0), 0.0);
+        assert_eq!(p.weight(2.0), 0.0);
+        assert_eq!(p.weight(1.0), 0.5);
+        assert_eq!(p.weight(0.0), 1.0);
+    }
+
+    #[test]
+    fn weight_equal_headrooms_is_zero() {
+        assert_eq!(GainMapParams::default().weight(3.0), 0.0);
+    }
+
+    #[test]
+    fn apply_full_weight_single_channel() {
+        let base = f32_buffer(&[0.25, 0.5, 0.1], 1, 1, PixelDescriptor::RGBF32_LINEAR);
+        let map = gray8_map(&[255], 1, 1, sdr_base_params(2.0));
+        let out = apply_gain_map(&base.as_slice(), &map, 2.0).unwrap();
+        assert_eq!(out.descriptor(), PixelDescriptor::RGBF32_LINEAR);
+        assert_close(&f32_values(&out), &[1.0, 2.0, 0.4]);
+    }
+
+    #[test]
+    fn apply_partial_weight_scales_log_gain() {
+        let base = f32_buffer(&[0.25, 0.25, 0.25], 1, 1, PixelDescriptor::RGBF32_LINEAR);
+        let map = gray8_map(&[255], 1, 1, sdr_base_params(2.0));
+        let out = apply_gain_map(&base.as_slice(), &map, 1.0).unwrap();
+        assert_close(&f32_values(&out), &[0.5, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn apply_zero_weight_reproduces_base() {
+        let mut params = sdr_base_params(2.0);
+        for ch in &mut params.channels {
+            ch.base_offset = 1.0 / 64.0;
+            ch.alternate_offset = 1.0 / 64.0;
+        }
+        let base = f32_buffer(&[0.3, 0.6, 0.9], 1, 1, PixelDescriptor::RGBF32_LINEAR);
+        let out = apply_gain_map(&base.as_slice(), &gray8_map(&[200], 1, 1, params), 0.0).unwrap();
+        assert_close(&f32_values(&out), &[0.3, 0.6, 0.9]);
+    }
+
+    #[test]
+    fn apply_offsets() {
+        let mut params = sdr_base_params(1.0);
+        for ch in &mut params.channels {
+            ch.base_offset = 0.5;
+            ch.alternate_offset = 0.25;
+        }
+        let base = f32_buffer(&[0.5, 0.0, 1.5], 1, 1, PixelDescriptor::RGBF32_LINEAR);
+        let out = apply_gain_map(&base.as_slice(), &gray8_map(&[255], 1, 1, params), 1.0).unwrap();
+        // (b + 0.5) * 2 - 0.25
+        assert_close(&f32_values(&out), &[1.75, 0.75, 3.75]);
+    }
+
+    #[test]
+    fn apply_hdr_base_tone_maps_down() {
+        let ch = GainMapChannel {
+            min: -2.0,
+            max: 0.0,
+            gamma: 1.0,
+            base_offset: 0.0,
+            alternate_offset: 0.0,
+        };
+        let params = GainMapParams {
+            channels: [ch; 3],
+            base_hdr_headroom: 2.0,
+            alternate_hdr_headroom: 0.0,
+            backward_direction: true,
+            ..Default::default()
+        };
+        let base = f32_buffer(&[4.0, 2.0, 1.0], 1, 1, PixelDescriptor::RGBF32_LINEAR);
+        let map = gray8_map(&[0], 1, 1, params);
+        let sdr = apply_gain_map(&base.as_slice(), &map, 0.0).unwrap();
+        assert_close(&f32_values(&sdr), &[1.0, 0.5, 0.25]);
+        let hdr = apply_gain_map(&base.as_slice(), &map, 2.0).unwrap();
+        assert_close(&f32_values(&hdr), &[4.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn apply_multichannel_uses_per_channel_params() {
+        let mut params = sdr_base_params(1.0);
+        params.channels[1].max = 2.0;
+        params.channels[2].min = -1.0;
+        params.channels[2].max = 0.0;
+        let pixels = PixelBuffer::from_vec(vec![255, 255, 0], 1, 1, PixelDescriptor::RGB8).unwrap();
+        let map = DecodedGainMap::new(pixels, GainMapInfo::new(params, 1, 1, 3));
+        let base = f32_buffer(&[1.0, 1.0, 1.0], 1, 1, PixelDescriptor::RGBF32_LINEAR);
+        let out = apply_gain_map(&base.as_slice(), &map, 2.0).unwrap();
+        assert_close(&f32_values(&out), &[2.0, 4.0, 0.5]);
+    }
+
+    #[test]
+    fn apply_gamma() {
+        let mut params = sdr_base_params(4.0);
+        for ch in &mut params.channels {
+            ch.gamma = 0.5;
+        }
+        let pixels = f32_buffer(&[0.5], 1, 1, PixelDescriptor::GRAYF32);
+        let map = DecodedGainMap::new(pixels, GainMapInfo::new(params, 1, 1, 1));
+        let base = f32_buffer(&[1.0, 1.0, 1.0], 1, 1, PixelDescriptor::RGBF32_LINEAR);
+        // 0.5^(1/0.5) = 0.25 → log2 gain 1.0
+        let out = apply_gain_map(&base.as_slice(), &map, 4.0).unwrap();
+        assert_close(&f32_values(&out), &[2.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn apply_upsamples_bilinearly() {
+        let map = gray8_map(&[0, 255], 2, 1, sdr_base_params(1.0));
+        let base = f32_buffer(&[1.0; 12], 4, 1, PixelDescriptor::RGBF32_LINEAR);
+        let out = f32_values(&apply_gain_map(&base.as_slice(), &map, 1.0).unwrap());
+        let red: Vec<f32> = out.chunks_exact(3).map(|p| p[0]).collect();
+        let expected = [0.0f32, 0.25, 0.75, 1.0].map(|g| g.exp2());
+        assert_close(&red, &expected);
+    }
+
+    #[test]
+    fn apply_passes_alpha_through() {
+        let base = f32_buffer(&[0.5, 0.5, 0.5, 0.3], 1, 1, PixelDescriptor::RGBAF32_LINEAR);
+        let out = apply_gain_map(
+            &base.as_slice(),
+            &gray8_map(&[255], 1, 1, sdr_base_params(1.0)),
+            1.0,
+        )
+        .unwrap();
+        assert_close(&f32_values(&out), &[1.0, 1.0, 1.0, 0.3]);
+    }
+
+    #[test]
+    fn apply_in_alternate_color_space() {
+        let mut params = sdr_base_params(1.0);
+        params.use_base_color_space = false;
+        let base = f32_buffer(&[1.0, 1.0, 1.0], 1, 1, PixelDescriptor::RGBF32_LINEAR);
+
+        let map = gray8_map(&[255], 1, 1, params.clone());
+        assert_eq!(
+            apply_gain_map(&base.as_slice(), &map, 1.0).unwrap_err(),
+            GainMapApplyError::UnknownPrimaries
+        );
+
+        let mut map = gray8_map(&[255], 1, 1, params);
+        map.metadata = map.metadata.with_alternate_cicp(Cicp::DISPLAY_P3);
+        let out = apply_gain_map(&base.as_slice(), &map, 1.0).unwrap();
+        assert_eq!(out.descriptor().primaries, ColorPrimaries::DisplayP3);
+        // White stays white across the gamut conversion.
+        assert_close(&f32_values(&out), &[2.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn apply_rejects_non_linear_base() {
+        let base = PixelBuffer::from_vec(vec![0; 3], 1, 1, PixelDescriptor::RGB8_SRGB).unwrap();
+        let map = gray8_map(&[255], 1, 1, sdr_base_params(1.0));
+        assert!(matches!(
+            apply_gain_map(&base.as_slice(), &map, 1.0),
+            Err(GainMapApplyError::UnsupportedBase { .. })
+        ));
+        let base = f32_buffer(&[0.5; 3], 1, 1, PixelDescriptor::RGBF32);
+        assert!(apply_gain_map(&base.as_slice(), &map, 1.0).is_err());
+    }
+
+    #[test]
+    fn apply_rejects_invalid_params() {
+        let mut params = sdr_base_params(1.0);
+        params.channels[0].gamma = 0.0;
+        let base = f32_buffer(&[0.5; 3], 1, 1, PixelDescriptor::RGBF32_LINEAR);
+        let err =
+            apply_gain_map(&base.as_slice(), &gray8_map(&[255], 1, 1, params), 1.0).unwrap_err();
+        assert!(matches!(err, GainMapApplyError::InvalidParams(_)));
+        assert!(core::error::Error::source(&err).is_some());
+    }
+
+    // --- gain map generation ---
+
+    fn rgb_image(pixels: &[[f32; 3]], width: u32, height: u32) -> PixelBuffer {
+        let values: Vec<f32> = pixels.iter().flatten().copied().collect();
+        f32_buffer(&values, width, height, PixelDescriptor::RGBF32_LINEAR)
+    }
+
+    fn test_pair() -> (PixelBuffer, PixelBuffer) {
+        let sdr = [
+            [0.2, 0.4, 0.6],
+            [0.8, 0.8, 0.8],
+            [0.1, 0.5, 0.9],
+            [1.0, 1.0, 1.0],
+        ];
+        let hdr = [
+            [0.4, 0.8, 1.2],
+            [3.2, 3.2, 3.2],
+            [0.1, 1.0, 3.6],
+            [1.0, 1.0, 1.0],
+        ];
+        (rgb_image(&sdr, 2, 2), rgb_image(&hdr, 2, 2))
+    }
+
+    #[test]
+    fn generate_then_apply_reconstructs_hdr() {
+        let (sdr, hdr) = test_pair();
+        let options = GainMapGenerateOptions::new().with_multichannel(true);
+        let (pixels, params) =
+            generate_gain_map(&sdr.as_slice(), &hdr.as_slice(), &options).unwrap();
+        assert_eq!(pixels.descriptor(), PixelDescriptor::RGB8);
+        assert_eq!((pixels.width(), pixels.height()), (2, 2));
+        assert!(!params.backward_direction);
+        assert_eq!(params.direction(), GainMapDirection::BaseIsSdr);
+        assert_eq!(params.base_hdr_headroom, 0.0);
+        assert!(params.alternate_hdr_headroom > 1.9);
+
+        let headroom = params.alternate_hdr_headroom;
+        let map = DecodedGainMap::new(pixels, GainMapInfo::new(params, 2, 2, 3));
+        let out = apply_gain_map(&sdr.as_slice(), &map, headroom).unwrap();
+        for (a, e) in f32_values(&out).iter().zip(f32_values(&hdr)) {
+            assert!((a - e).abs() / e.max(0.1) < 0.02, "{a} vs {e}");
+        }
+    }
+
+    #[test]
+    fn generate_hdr_base() {
+        let (sdr, hdr) = test_pair();
+        let options = GainMapGenerateOptions::new()
+            .with_multichannel(true)
+            .with_direction(GainMapDirection::BaseIsHdr);
+        let (pixels, params) =
+            generate_gain_map(&sdr.as_slice(), &hdr.as_slice(), &options).unwrap();
+        assert!(params.backward_direction);
+        assert_eq!(params.direction(), GainMapDirection::BaseIsHdr);
+        assert_eq!(params.alternate_hdr_headroom, 0.0);
+        assert!(params.channels.iter().all(|ch| ch.max <= 0.0));
+
+        let map = DecodedGainMap::new(pixels, GainMapInfo::new(params, 2, 2, 3));
+        let out = apply_gain_map(&hdr.as_slice(), &map, 0.0).unwrap();
+        for (a, e) in f32_values(&out).iter().zip(f32_values(&sdr)) {
+            assert!((a - e).abs() / e.max(0.1) < 0.02, "{a} vs {e}");
+        }
+    }
+
+    #[test]
+    fn generate_single_channel_uses_luminance() {
+        let sdr = rgb_image(&[[0.5, 0.5, 0.5]; 2], 2, 1);
+        let hdr = rgb_image(&[[0.5, 0.5, 0.5], [2.0, 2.0, 2.0]], 2, 1);
+        let options = GainMapGenerateOptions::new().with_offsets(0.0, 0.0);
+        let (pixels, params) =
+            generate_gain_map(&sdr.as_slice(), &hdr.as_slice(), &options).unwrap();
+        assert_eq!(pixels.descriptor(), PixelDescriptor::GRAY8);
+        assert!(params.is_single_channel());
+        assert_eq!(params.channels[0].min, 0.0);
+        assert!((params.channels[0].max - 2.0).abs() < 1e-6);
+        assert_eq!(pixels.copy_to_contiguous_bytes(), [0, 255]);
+    }
+
+    #[test]
+    fn generate_downscales_and_applies_gamma() {
+        let sdr = rgb_image(&[[1.0; 3]; 6], 3, 2);
+        let hdr = rgb_image(
+            &[[1.0; 3], [1.0; 3], [4.0; 3], [1.0; 3], [1.0; 3], [4.0; 3]],
+            3,
+            2,
+        );
+        let options = GainMapGenerateOptions::new()
+            .with_scale(2)
+            .with_gamma(2.0)
+            .with_offsets(0.0, 0.0);
+        let (pixels, params) =
+            generate_gain_map(&sdr.as_slice(), &hdr.as_slice(), &options).unwrap();
+        assert_eq!((pixels.width(), pixels.height()), (2, 1));
+        assert_eq!(params.channels[0].gamma, 2.0);
+        // Left block averages to gain 1 (log2 0), right edge block to 4 (log2 2).
+        assert_eq!(pixels.copy_to_contiguous_bytes(), [0, 255]);
+    }
+
+    #[test]
+    fn generate_params_survive_serialization() {
+        let (sdr, hdr) = test_pair();
+        let options = GainMapGenerateOptions::new()
+            .with_multichannel(true)
+            .with_gamma(1.5);
+        let (_, params) = generate_gain_map(&sdr.as_slice(), &hdr.as_slice(), &options).unwrap();
+        for format in [Iso21496Format::AvifTmap, Iso21496Format::JxlJhgm] {
+            let bytes = serialize_iso21496_fmt(&params, format);
+            assert_eq!(parse_iso21496_fmt(&bytes, format).unwrap(), params);
+        }
+    }
+
+    #[test]
+    fn generate_explicit_headroom() {
+        let (sdr, hdr) = test_pair();
+        let options = GainMapGenerateOptions::new().with_hdr_headroom(3.0);
+        let (_, params) = generate_gain_map(&sdr.as_slice(), &hdr.as_slice(), &options).unwrap();
+        assert_eq!(params.alternate_hdr_headroom, 3.0);
+    }
+
+    #[test]
+    fn generate_converts_alternate_primaries() {
+        let sdr = rgb_image(&[[1.0; 3]], 1, 1);
+        let hdr = rgb_image(&[[2.0; 3]], 1, 1);
+        let hdr = hdr.with_primaries(ColorPrimaries::Bt2020);
+        let options = GainMapGenerateOptions::new().with_offsets(0.0, 0.0);
+        let (_, params) = generate_gain_map(&sdr.as_slice(), &hdr.as_slice(), &options).unwrap();
+        // White maps to white, so the gain is exactly 2×.
+        assert!((params.channels[0].max - 1.0).abs() < 1e-3);
+        assert!(params.use_base_color_space);
+    }
+
+    #[test]
+    fn generate_rejects_bad_input() {
+        let (sdr, hdr) = test_pair();
+        let small = rgb_image(&[[1.0; 3]], 1, 1);
+        assert_eq!(
+            generate_gain_map(&sdr.as_slice(), &small.as_slice(), &Default::default()).unwrap_err(),
+            GainMapGenerateError::DimensionMismatch {
+                sdr: (2, 2),
+                hdr: (1, 1)
+            }
+        );
+        let srgb = PixelBuffer::from_vec(vec![0; 12], 2, 2, PixelDescriptor::RGB8_SRGB).unwrap();
+        assert!(matches!(
+            generate_gain_map(&srgb.as_slice(), &hdr.as_slice(), &Default::default()),
+            Err(GainMapGenerateError::UnsupportedInput { .. })
+        ));
+        let zero_scale = GainMapGenerateOptions::new().with_scale(0);
+        assert_eq!(
+            generate_gain_map(&sdr.as_slice(), &hdr.as_slice(), &zero_scale).unwrap_err(),
+            GainMapGenerateError::EmptyGainMap
+        );
+        let bad_gamma = GainMapGenerateOptions::new().with_gamma(0.0);
+        assert!(matches!(
+            generate_gain_map(&sdr.as_slice(), &hdr.as_slice(), &bad_gamma),
+            Err(GainMapGenerateError::InvalidParams(_))
+        ));
+    }
+
+    // --- retargeting, jhgm bundle, Apple ---
+
+    fn hdr_params() -> GainMapParams {
+        GainMapParams {
+            alternate_hdr_headroom: 2.0,
+            channels: [GainMapChannel {
+                max: 2.0,
+                ..Default::default()
+            }; 3],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn retarget_between_formats() {
+        let params = hdr_params();
+        let jpeg = serialize_iso21496_fmt(&params, Iso21496Format::JpegApp2BodyWithUrn);
+        let avif = retarget_iso21496(
+            &jpeg,
+            Iso21496Format::JpegApp2BodyWithUrn,
+            Iso21496Format::AvifTmap,
+        )
+        .unwrap();
+        assert_eq!(
+            avif,
+            serialize_iso21496_fmt(&params, Iso21496Format::AvifTmap)
+        );
+        let jxl =
+            retarget_iso21496(&avif, Iso21496Format::AvifTmap, Iso21496Format::JxlJhgm).unwrap();
+        assert_eq!(jpeg[ISO_21496_1_URN.len()..], jxl[..]);
+        assert!(
+            retarget_iso21496(&jxl, Iso21496Format::AvifTmap, Iso21496Format::JxlJhgm).is_err()
+        );
+    }
+
+    #[test]
+    fn jhgm_bundle_roundtrip() {
+        let bundle = JxlGainMapBundle::new(hdr_params(), vec![0xFF, 0x0A, 1, 2, 3])
+            .with_color_encoding(vec![9, 8])
+            .unwrap()
+            .with_alternate_icc(vec![7; 5]);
+        let bytes = bundle.serialize();
+        let metadata = serialize_iso21496_fmt(&bundle.params, Iso21496Format::JxlJhgm);
+        assert_eq!(bytes[0], JHGM_VERSION);
+        assert_eq!(
+            u16::from_be_bytes([bytes[1], bytes[2]]) as usize,
+            metadata.len()
+        );
+        assert_eq!(&bytes[3..3 + metadata.len()], &metadata[..]);
+        let rest = &bytes[3 + metadata.len()..];
+        assert_eq!(rest[..3], [2, 9, 8]);
+        assert_eq!(rest[3..7], 5u32.to_be_bytes());
+        assert_eq!(rest[12..], [0xFF, 0x0A, 1, 2, 3]);
+        assert_eq!(JxlGainMapBundle::parse(&bytes).unwrap(), bundle);
+
+        let info = bundle.info(64, 48, 1);
+        assert_eq!(info.params, bundle.params);
+        assert_eq!((info.width, info.height, info.channels), (64, 48, 1));
+    }
+
+    #[test]
+    fn jhgm_bundle_without_optional_fields() {
+        let bundle = JxlGainMapBundle::new(hdr_params(), vec![0xFF, 0x0A]);
+        let bytes = bundle.serialize();
+        let parsed = JxlGainMapBundle::parse(&bytes).unwrap();
+        assert!(parsed.color_encoding().is_empty());
+        assert!(parsed.alternate_icc.is_empty());
+        assert_eq!(parsed.gain_map, [0xFF, 0x0A]);
+    }
+
+    #[test]
+    fn jhgm_bundle_limits_color_encoding() {
+        let bundle = JxlGainMapBundle::new(hdr_params(), Vec::new());
+        let max = bundle.clone().with_color_encoding(vec![1; 255]).unwrap();
+        let parsed = JxlGainMapBundle::parse(&max.serialize()).unwrap();
+        assert_eq!(parsed.color_encoding(), &[1; 255][..]);
+        assert_eq!(
+            bundle.with_color_encoding(vec![1; 256]),
+            Err(ColorEncodingTooLong { len: 256 })
+        );
+    }
+
+    #[test]
+    fn jhgm_bundle_rejects_bad_input() {
+        let bytes = JxlGainMapBundle::new(hdr_params(), Vec::new())
+            .with_alternate_icc(vec![1; 4])
+            .serialize();
+        assert!(matches!(
+            JxlGainMapBundle::parse(&bytes[..bytes.len() - 1]),
+            Err(GainMapParseError::TruncatedData { .. })
+        ));
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] = 1;
+        assert_eq!(
+            JxlGainMapBundle::parse(&wrong_version),
+            Err(GainMapParseError::UnsupportedVersion { version: 1 })
+        );
+        let mut huge_metadata = bytes;
+        huge_metadata[1] = 0xFF;
+        assert!(JxlGainMapBundle::parse(&huge_metadata).is_err());
+        assert!(JxlGainMapBundle::parse(&[]).is_err());
+    }
+
+    #[test]
+    fn apple_stops_formula() {
+        let close = |a: f64, b: f64| assert!((a - b).abs() < 1e-9, "{a} vs {b}");
+        close(AppleHdrGain::new(0.5, 0.0).stops(), 1.8);
+        close(AppleHdrGain::new(0.5, 1.0).stops(), 1.5);
+        close(AppleHdrGain::new(1.2, 0.005).stops(), 2.65);
+        close(AppleHdrGain::new(1.2, 1.0).stops(), 2.0);
+        assert_eq!(AppleHdrGain::new(1.2, 100.0).stops(), 0.0);
+    }
+
+    #[test]
+    fn apple_from_stops_roundtrip() {
+        for stops in [0.0, 0.5, 1.0, 2.0, 2.29, 2.3, 2.7, 3.0] {
+            let apple = AppleHdrGain::from_stops(stops);
+            assert!((apple.stops() - stops).abs() < 0.01, "{stops}");
+        }
+        assert!((AppleHdrGain::from_stops(5.0).stops() - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn apple_params_conversion() {
+        let apple = AppleHdrGain::new(1.2, 1.0);
+        let params = apple.to_params();
+        params.validate().unwrap();
+        assert_eq!(params.direction(), GainMapDirection::BaseIsSdr);
+        assert!((params.alternate_hdr_headroom - 2.0).abs() < 1e-9);
+        let back = AppleHdrGain::from_params(&params).unwrap();
+        assert!((back.stops() - 2.0).abs() < 0.01);
+
+        let hdr_base = GainMapParams {
+            base_hdr_headroom: 2.0,
+            backward_direction: true,
+            ..Default::default()
+        };
+        assert_eq!(AppleHdrGain::from_params(&hdr_base), None);
+    }
+
+    #[test]
+    fn apple_map_values_match_apple_formula() {
+        let apple = AppleHdrGain::new(1.2, 1.0);
+        let boost = 2.0f32.powf(apple.stops() as f32) - 1.0;
+        assert_eq!(apple.iso_map_value(0.0), 0.0);
+        assert!((apple.iso_map_value(1.0) - 1.0).abs() < 1e-6);
+        for g in [0.1f32, 0.5, 0.9] {
+            let iso = apple.iso_map_value(g);
+            assert!((apple.apple_map_value(iso) - g).abs() < 1e-5);
+        }
+
+        // Reconstructing with the ISO params reproduces Apple's linear gain.
+        let iso = apple.iso_map_value(0.5);
+        let map_pixels = f32_buffer(&[iso], 1, 1, PixelDescriptor::GRAYF32);
+        let params = apple.to_params();
+        let headroom = params.alternate_hdr_headroom;
+        let map = DecodedGainMap::new(map_pixels, GainMapInfo::new(params, 1, 1, 1));
+        let base = f32_buffer(&[0.5; 3], 1, 1, PixelDescriptor::RGBF32_LINEAR);
+        let out = apply_gain_map(&base.as_slice(), &map, headroom).unwrap();
+        let expected = 0.5 * (1.0 + boost * 0.5);
+        assert_close(&f32_values(&out), &[expected; 3]);
+    }
 }
@@ -1,26 +1,38 @@
-//! Minimal EXIF orientation parser.
+//! Bounded EXIF / TIFF IFD reader.
 //!
-//! Parses the EXIF Orientation tag (0x0112 / TIFF tag 274) from TIFF-structured
-//! EXIF data. Handles both raw TIFF bytes and JPEG APP1 style (`Exif\0\0` prefix).
+//! Parses TIFF-structured EXIF data. Handles both raw TIFF bytes and JPEG
+//! APP1 style (`Exif\0\0` prefix).
+//!
+//! - [`parse_exif_orientation`] extracts only the Orientation tag (0x0112 /
+//!   TIFF tag 274) from IFD0, exiting early on the sorted tag order.
+//! - [`ExifReader`] walks IFD0 (primary image), IFD1 (thumbnail), and the
+//!   Exif, GPS, and Interoperability sub-IFDs, with typed accessors for
+//!   common tags (make, model, dates, resolution, thumbnail) and a raw
+//!   [`ExifEntry`] iterator for everything else.
 //!
 //! Spec references:
 //! - TIFF 6.0 specification (Adobe, 1992): IFD structure, byte order, tag 274
-//! - EXIF 2.32 (CIPA DC-008-Translation-2019): Orientation tag semantics
+//! - EXIF 2.32 (CIPA DC-008-Translation-2019): Orientation tag semantics,
+//!   Exif/GPS/Interoperability IFD pointers, IFD1 thumbnail
 //! - TIFF/EP (ISO 12234-2): Same orientation tag definition
 //!
 //! # Design
 //!
-//! This parser is intentionally minimal — it extracts only the orientation tag.
-//! For full EXIF parsing (make, model, GPS, dates), use `zencodecs::exif::parse_exif`.
-//!
 //! Safety properties:
 //! - Every byte read is bounds-checked (returns `None` on truncation)
-//! - IFD entry count capped at 1000 to prevent DoS from malformed data
-//! - No recursion, no heap allocation, `no_std` compatible
+//! - IFD entry count capped at [`MAX_IFD_ENTRIES`] to prevent DoS from
+//!   malformed data; an IFD over the cap is treated as absent
+//! - No recursion; sub-IFDs are reached by explicit pointer tags, never by
+//!   following chains
+//! - [`ExifReader`] and [`parse_exif_orientation`] do no heap allocation and
+//!   their values borrow from the input; [`rewrite_exif`] allocates the
+//!   rewritten block it returns
+//! - `no_std` compatible
 //! - Handles both big-endian (Motorola/MM) and little-endian (Intel/II)
-//! - Accepts TIFF SHORT (type 3) and LONG (type 4) for the orientation value
-//! - Validates orientation value is in 1..=8
-//! - Does NOT follow EXIF sub-IFD pointers (orientation is always in IFD0)
+//! - The orientation parser accepts TIFF SHORT (type 3) and LONG (type 4),
+//!   validates the value is in 1..=8, and only looks in IFD0
+
+use alloc::vec::Vec;
 
 use zenpixels::Orientation;
 
@@ -31,7 +43,9 @@ const TIFF_SHORT: u16 = 3;
 /// TIFF type LONG (unsigned 32-bit integer).
 const TIFF_LONG: u16 = 4;
 /// Maximum IFD entries to scan before giving up (DoS protection).
-const MAX_IFD_ENTRIES: u16 = 1000;
+///
+/// IFDs declaring more entries than this are treated as absent.
+pub const MAX_IFD_ENTRIES: u16 = 1000;
 /// Minimum TIFF header size: byte order (2) + magic (2) + IFD0 offset (4).
 const TIFF_HEADER_SIZE: usize = 8;
 
@@ -187,6 +201,799 @@ fn rd32(data: &[u8], offset: usize, big_endian: bool) -> Option<u32> {
     })
 }
 
+// ── IFD walker ─────────────────────────────────────────────────────────
+
+/// TIFF type BYTE (unsigned 8-bit integer).
+const TIFF_BYTE: u16 = 1;
+/// TIFF type ASCII (NUL-terminated 7-bit text).
+const TIFF_ASCII: u16 = 2;
+/// TIFF type RATIONAL (two LONGs: numerator, denominator).
+const TIFF_RATIONAL: u16 = 5;
+/// TIFF type SRATIONAL (two SLONGs: numerator, denominator).
+const TIFF_SRATIONAL: u16 = 10;
+/// Size of one IFD entry: tag(2) + type(2) + count(4) + value(4).
+const IFD_ENTRY_SIZE: usize = 12;
+
+/// Well-known EXIF / TIFF tag numbers.
+pub mod tag {
+    /// Image width (IFD0/IFD1).
+    pub const IMAGE_WIDTH: u16 = 0x0100;
+    /// Image height (IFD0/IFD1).
+    pub const IMAGE_HEIGHT: u16 = 0x0101;
+    /// Image description, ASCII.
+    pub const IMAGE_DESCRIPTION: u16 = 0x010E;
+    /// Camera manufacturer, ASCII.
+    pub const MAKE: u16 = 0x010F;
+    /// Camera model, ASCII.
+    pub const MODEL: u16 = 0x0110;
+    /// Orientation, SHORT 1..=8.
+    pub const ORIENTATION: u16 = 0x0112;
+    /// Horizontal resolution, RATIONAL.
+    pub const X_RESOLUTION: u16 = 0x011A;
+    /// Vertical resolution, RATIONAL.
+    pub const Y_RESOLUTION: u16 = 0x011B;
+    /// Resolution unit, SHORT (2 = inch, 3 = centimeter).
+    pub const RESOLUTION_UNIT: u16 = 0x0128;
+    /// Software, ASCII.
+    pub const SOFTWARE: u16 = 0x0131;
+    /// File modification date and time, ASCII `YYYY:MM:DD HH:MM:SS`.
+    pub const DATE_TIME: u16 = 0x0132;
+    /// Artist, ASCII.
+    pub const ARTIST: u16 = 0x013B;
+    /// Thumbnail JPEG offset (IFD1), LONG.
+    pub const JPEG_INTERCHANGE_FORMAT: u16 = 0x0201;
+    /// Thumbnail JPEG length (IFD1), LONG.
+    pub const JPEG_INTERCHANGE_FORMAT_LENGTH: u16 = 0x0202;
+    /// Copyright, ASCII.
+    pub const COPYRIGHT: u16 = 0x8298;
+    /// Pointer to the Exif IFD (IFD0), LONG.
+    pub const EXIF_IFD_POINTER: u16 = 0x8769;
+    /// Pointer to the GPS IFD (IFD0), LONG.
+    pub const GPS_IFD_POINTER: u16 = 0x8825;
+    /// Capture date and time (Exif IFD), ASCII `YYYY:MM:DD HH:MM:SS`.
+    pub const DATE_TIME_ORIGINAL: u16 = 0x9003;
+    /// Digitization date and time (Exif IFD), ASCII.
+    pub const DATE_TIME_DIGITIZED: u16 = 0x9004;
+    /// Timezone offset of `DATE_TIME_ORIGINAL` (Exif IFD), ASCII `+HH:MM`.
+    pub const OFFSET_TIME_ORIGINAL: u16 = 0x9011;
+    /// Pointer to the Interoperability IFD (Exif IFD), LONG.
+    pub const INTEROP_IFD_POINTER: u16 = 0xA005;
+    /// Valid image width (Exif IFD), SHORT or LONG.
+    pub const PIXEL_X_DIMENSION: u16 = 0xA002;
+    /// Valid image height (Exif IFD), SHORT or LONG.
+    pub const PIXEL_Y_DIMENSION: u16 = 0xA003;
+}
+
+/// Which image file directory to read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Ifd {
+    /// IFD0 — the primary image.
+    Primary,
+    /// IFD1 — the thumbnail, linked from IFD0's next-IFD offset.
+    Thumbnail,
+    /// Exif sub-IFD, pointed to by [`tag::EXIF_IFD_POINTER`] in IFD0.
+    Exif,
+    /// GPS sub-IFD, pointed to by [`tag::GPS_IFD_POINTER`] in IFD0.
+    Gps,
+    /// Interoperability sub-IFD, pointed to by
+    /// [`tag::INTEROP_IFD_POINTER`] in the Exif IFD.
+    Interop,
+}
+
+/// An unsigned TIFF RATIONAL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    /// Numerator.
+    pub num: u32,
+    /// Denominator.
+    pub den: u32,
+}
+
+impl Rational {
+    /// The value as `f64`, or `None` for a zero denominator.
+    pub fn to_f64(self) -> Option<f64> {
+        if self.den == 0 {
+            None
+        } else {
+            Some(self.num as f64 / self.den as f64)
+        }
+    }
+}
+
+/// A signed TIFF SRATIONAL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SRational {
+    /// Numerator.
+    pub num: i32,
+    /// Denominator.
+    pub den: i32,
+}
+
+impl SRational {
+    /// The value as `f64`, or `None` for a zero denominator.
+    pub fn to_f64(self) -> Option<f64> {
+        if self.den == 0 {
+            None
+        } else {
+            Some(self.num as f64 / self.den as f64)
+        }
+    }
+}
+
+/// Byte size of one element of a TIFF field type, or `None` if unknown.
+fn type_size(field_type: u16) -> Option<usize> {
+    Some(match field_type {
+        1 | 2 | 6 | 7 => 1,   // BYTE, ASCII, SBYTE, UNDEFINED
+        3 | 8 => 2,           // SHORT, SSHORT
+        4 | 9 | 11 | 13 => 4, // LONG, SLONG, FLOAT, IFD
+        5 | 10 | 12 => 8,     // RATIONAL, SRATIONAL, DOUBLE
+        _ => return None,
+    })
+}
+
+/// One raw IFD entry, borrowing its value from the EXIF data.
+#[derive(Clone, Copy, Debug)]
+pub struct ExifEntry<'a> {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    /// The value bytes, or `None` if the type is unknown or the value lies
+    /// outside the data.
+    value: Option<&'a [u8]>,
+    big_endian: bool,
+    /// Position of the entry in the TIFF data.
+    offset: usize,
+    /// Position of the value in the TIFF data (meaningful when `value` is
+    /// `Some`).
+    value_start: usize,
+}
+
+impl<'a> ExifEntry<'a> {
+    /// Tag number (see [`tag`]).
+    pub fn tag(&self) -> u16 {
+        self.tag
+    }
+
+    /// TIFF field type (1 = BYTE, 2 = ASCII, 3 = SHORT, 4 = LONG,
+    /// 5 = RATIONAL, 7 = UNDEFINED, 10 = SRATIONAL, ...).
+    pub fn field_type(&self) -> u16 {
+        self.field_type
+    }
+
+    /// Number of values of [`field_type()`](Self::field_type).
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Whether the data is big-endian (values from
+    /// [`value_bytes()`](Self::value_bytes) are in this byte order).
+    pub fn is_big_endian(&self) -> bool {
+        self.big_endian
+    }
+
+    /// The raw value bytes, whether stored inline or at an offset.
+    ///
+    /// `None` for unknown field types or values that point outside the data.
+    pub fn value_bytes(&self) -> Option<&'a [u8]> {
+        self.value
+    }
+
+    /// The `index`th value of a BYTE, SHORT, or LONG entry, widened to `u32`.
+    pub fn get_u32(&self, index: u32) -> Option<u32> {
+        let bytes = self.value?;
+        let i = index as usize;
+        match self.field_type {
+            TIFF_BYTE => bytes.get(i).map(|&b| b as u32),
+            TIFF_SHORT => rd16(bytes, i.checked_mul(2)?, self.big_endian).map(u32::from),
+            TIFF_LONG => rd32(bytes, i.checked_mul(4)?, self.big_endian),
+            _ => None,
+        }
+    }
+
+    /// The `index`th value of a RATIONAL entry.
+    pub fn get_rational(&self, index: u32) -> Option<Rational> {
+        if self.field_type != TIFF_RATIONAL {
+            return None;
+        }
+        let off = (index as usize).checked_mul(8)?;
+        let bytes = self.value?;
+        Some(Rational {
+            num: rd32(bytes, off, self.big_endian)?,
+            den: rd32(bytes, off + 4, self.big_endian)?,
+        })
+    }
+
+    /// The `index`th value of an SRATIONAL entry.
+    pub fn get_srational(&self, index: u32) -> Option<SRational> {
+        if self.field_type != TIFF_SRATIONAL {
+            return None;
+        }
+        let off = (index as usize).checked_mul(8)?;
+        let bytes = self.value?;
+        Some(SRational {
+            num: rd32(bytes, off, self.big_endian)? as i32,
+            den: rd32(bytes, off + 4, self.big_endian)? as i32,
+        })
+    }
+
+    /// The text of an ASCII entry, up to the first NUL.
+    ///
+    /// `None` if the entry is not ASCII or the text is not valid UTF-8.
+    pub fn as_str(&self) -> Option<&'a str> {
+        if self.field_type != TIFF_ASCII {
+            return None;
+        }
+        let bytes = self.value?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        core::str::from_utf8(&bytes[..end]).ok()
+    }
+}
+
+/// Allocation-free reader over TIFF-structured EXIF data.
+///
+/// Construct with [`ExifReader::new`], then use the typed accessors or
+/// iterate raw entries with [`entries()`](Self::entries). Lookups are
+/// linear scans of one IFD, capped at [`MAX_IFD_ENTRIES`].
+///
+/// ```
+/// use zencodec::helpers::exif::{ExifReader, Ifd, tag};
+///
+/// let tiff = [
+///     b'I', b'I', 42, 0, 8, 0, 0, 0, // header, IFD0 at 8
+///     1, 0,                          // 1 entry
+///     0x0F, 0x01, 2, 0, 4, 0, 0, 0, // Make, ASCII, count 4
+///     b'A', b'c', b'm', 0,           // "Acm\0" inline
+///     0, 0, 0, 0,                    // no IFD1
+/// ];
+/// let exif = ExifReader::new(&tiff).unwrap();
+/// assert_eq!(exif.make(), Some("Acm"));
+/// assert_eq!(exif.entries(Ifd::Primary).count(), 1);
+/// assert!(exif.find(Ifd::Gps, tag::GPS_IFD_POINTER).is_none());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ExifReader<'a> {
+    tiff: &'a [u8],
+    big_endian: bool,
+    ifd0: usize,
+}
+
+impl<'a> ExifReader<'a> {
+    /// Validate the TIFF header of `data` (raw TIFF or `Exif\0\0`-prefixed).
+    ///
+    /// Returns `None` if the byte order mark, magic number, or IFD0 offset
+    /// is invalid.
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let tiff = data.strip_prefix(b"Exif\0\0").unwrap_or(data);
+        if tiff.len() < TIFF_HEADER_SIZE {
+            return None;
+        }
+        let big_endian = match [tiff[0], tiff[1]] {
+            [b'M', b'M'] => true,
+            [b'I', b'I'] => false,
+            _ => return None,
+        };
+        if rd16(tiff, 2, big_endian)? != 42 {
+            return None;
+        }
+        let ifd0 = rd32(tiff, 4, big_endian)? as usize;
+        // The entry count must be readable.
+        rd16(tiff, ifd0, big_endian)?;
+        Some(Self {
+            tiff,
+            big_endian,
+            ifd0,
+        })
+    }
+
+    /// Whether the data is big-endian (`MM`).
+    pub fn is_big_endian(&self) -> bool {
+        self.big_endian
+    }
+
+    /// The TIFF data, without any `Exif\0\0` prefix. IFD offsets are
+    /// relative to its start.
+    pub fn tiff_data(&self) -> &'a [u8] {
+        self.tiff
+    }
+
+    /// Entry count of the IFD at `offset`, or `None` if unreadable or over
+    /// [`MAX_IFD_ENTRIES`].
+    fn entry_count(&self, offset: usize) -> Option<u16> {
+        let count = rd16(self.tiff, offset, self.big_endian)?;
+        (count <= MAX_IFD_ENTRIES).then_some(count)
+    }
+
+    /// Byte offset of `ifd` within [`tiff_data()`](Self::tiff_data), or
+    /// `None` if the IFD is absent or malformed.
+    pub fn ifd_offset(&self, ifd: Ifd) -> Option<usize> {
+        let pointer = |parent: Ifd, tag: u16| {
+            let offset = self.find(parent, tag)?.get_u32(0)? as usize;
+            self.entry_count(offset).map(|_| offset)
+        };
+        match ifd {
+            Ifd::Primary => self.entry_count(self.ifd0).map(|_| self.ifd0),
+            Ifd::Thumbnail => {
+                let count = self.entry_count(self.ifd0)? as usize;
+                let next_at = self.ifd0 + 2 + count * IFD_ENTRY_SIZE;
+                let next = rd32(self.tiff, next_at, self.big_endian)? as usize;
+                if next == 0 || next == self.ifd0 {
+                    return None;
+                }
+                self.entry_count(next).map(|_| next)
+            }
+            Ifd::Exif => pointer(Ifd::Primary, tag::EXIF_IFD_POINTER),
+            Ifd::Gps => pointer(Ifd::Primary, tag::GPS_IFD_POINTER),
+            Ifd::Interop => pointer(Ifd::Exif, tag::INTEROP_IFD_POINTER),
+        }
+    }
+
+    /// Iterate the entries of `ifd` in stored order.
+    ///
+    /// Empty if the IFD is absent or over [`MAX_IFD_ENTRIES`]; stops at the
+    /// first entry that would run past the end of the data.
+    pub fn entries(&self, ifd: Ifd) -> ExifEntries<'a> {
+        let (next, remaining) = match self.ifd_offset(ifd) {
+            Some(offset) => (offset + 2, self.entry_count(offset).unwrap_or(0)),
+            None => (0, 0),
+        };
+        ExifEntries {
+            reader: *self,
+            next,
+            remaining,
+        }
+    }
+
+    /// The first entry with `tag` in `ifd`.
+    pub fn find(&self, ifd: Ifd, tag: u16) -> Option<ExifEntry<'a>> {
+        self.entries(ifd).find(|e| e.tag == tag)
+    }
+
+    fn entry_at(&self, off: usize) -> Option<ExifEntry<'a>> {
+        let end = off.checked_add(IFD_ENTRY_SIZE)?;
+        if end > self.tiff.len() {
+            return None;
+        }
+        let be = self.big_endian;
+        let tag = rd16(self.tiff, off, be)?;
+        let field_type = rd16(self.tiff, off + 2, be)?;
+        let count = rd32(self.tiff, off + 4, be)?;
+        let mut value_start = off + 8;
+        let value = type_size(field_type)
+            .and_then(|size| size.checked_mul(count as usize))
+            .and_then(|len| {
+                // Per TIFF 6.0 §2: values of 4 bytes or less are inline.
+                if len > 4 {
+                    value_start = rd32(self.tiff, off + 8, be)? as usize;
+                }
+                self.tiff.get(value_start..value_start.checked_add(len)?)
+            });
+        Some(ExifEntry {
+            tag,
+            field_type,
+            count,
+            value,
+            big_endian: be,
+            offset: off,
+            value_start,
+        })
+    }
+
+    fn ascii(&self, ifd: Ifd, tag: u16) -> Option<&'a str> {
+        self.find(ifd, tag)?.as_str()
+    }
+
+    /// Orientation from IFD0, validated to 1..=8.
+    pub fn orientation(&self) -> Option<Orientation> {
+        let raw = self.find(Ifd::Primary, tag::ORIENTATION)?.get_u32(0)?;
+        if raw > 8 {
+            return None;
+        }
+        Orientation::from_exif(raw as u8)
+    }
+
+    /// Camera manufacturer (IFD0 Make).
+    pub fn make(&self) -> Option<&'a str> {
+        self.ascii(Ifd::Primary, tag::MAKE)
+    }
+
+    /// Camera model (IFD0 Model).
+    pub fn model(&self) -> Option<&'a str> {
+        self.ascii(Ifd::Primary, tag::MODEL)
+    }
+
+    /// Software that produced the image (IFD0 Software).
+    pub fn software(&self) -> Option<&'a str> {
+        self.ascii(Ifd::Primary, tag::SOFTWARE)
+    }
+
+    /// File modification time (IFD0 DateTime), `YYYY:MM:DD HH:MM:SS`.
+    pub fn date_time(&self) -> Option<&'a str> {
+        self.ascii(Ifd::Primary, tag::DATE_TIME)
+    }
+
+    /// Capture time (Exif IFD DateTimeOriginal), `YYYY:MM:DD HH:MM:SS`.
+    pub fn date_time_original(&self) -> Option<&'a str> {
+        self.ascii(Ifd::Exif, tag::DATE_TIME_ORIGINAL)
+    }
+
+    /// Timezone of the capture time (Exif IFD OffsetTimeOriginal), `+HH:MM`.
+    pub fn offset_time_original(&self) -> Option<&'a str> {
+        self.ascii(Ifd::Exif, tag::OFFSET_TIME_ORIGINAL)
+    }
+
+    /// Horizontal resolution (IFD0 XResolution), in [`resolution_unit()`](Self::resolution_unit)s.
+    pub fn x_resolution(&self) -> Option<Rational> {
+        self.find(Ifd::Primary, tag::X_RESOLUTION)?.get_rational(0)
+    }
+
+    /// Vertical resolution (IFD0 YResolution), in [`resolution_unit()`](Self::resolution_unit)s.
+    pub fn y_resolution(&self) -> Option<Rational> {
+        self.find(Ifd::Primary, tag::Y_RESOLUTION)?.get_rational(0)
+    }
+
+    /// Resolution unit (IFD0 ResolutionUnit): 1 = none, 2 = inch,
+    /// 3 = centimeter.
+    pub fn resolution_unit(&self) -> Option<u16> {
+        let unit = self.find(Ifd::Primary, tag::RESOLUTION_UNIT)?.get_u32(0)?;
+        u16::try_from(unit).ok()
+    }
+
+    /// Valid image dimensions (Exif IFD PixelXDimension / PixelYDimension).
+    pub fn pixel_dimensions(&self) -> Option<(u32, u32)> {
+        let w = self.find(Ifd::Exif, tag::PIXEL_X_DIMENSION)?.get_u32(0)?;
+        let h = self.find(Ifd::Exif, tag::PIXEL_Y_DIMENSION)?.get_u32(0)?;
+        Some((w, h))
+    }
+
+    /// Offset of the Exif IFD, if IFD0 points to a readable one.
+    pub fn exif_ifd_offset(&self) -> Option<usize> {
+        self.ifd_offset(Ifd::Exif)
+    }
+
+    /// Offset of the GPS IFD, if IFD0 points to a readable one.
+    pub fn gps_ifd_offset(&self) -> Option<usize> {
+        self.ifd_offset(Ifd::Gps)
+    }
+
+    /// Offset of IFD1 (thumbnail), if IFD0 links to a readable one.
+    pub fn thumbnail_ifd_offset(&self) -> Option<usize> {
+        self.ifd_offset(Ifd::Thumbnail)
+    }
+
+    /// The embedded JPEG thumbnail bytes from IFD1, if present and in bounds.
+    pub fn thumbnail_jpeg(&self) -> Option<&'a [u8]> {
+        let start = self
+            .find(Ifd::Thumbnail, tag::JPEG_INTERCHANGE_FORMAT)?
+            .get_u32(0)? as usize;
+        let len = self
+            .find(Ifd::Thumbnail, tag::JPEG_INTERCHANGE_FORMAT_LENGTH)?
+            .get_u32(0)? as usize;
+        self.tiff.get(start..start.checked_add(len)?)
+    }
+}
+
+/// Iterator over the raw entries of one IFD. See [`ExifReader::entries`].
+#[derive(Clone, Debug)]
+pub struct ExifEntries<'a> {
+    reader: ExifReader<'a>,
+    next: usize,
+    remaining: u16,
+}
+
+impl<'a> Iterator for ExifEntries<'a> {
+    type Item = ExifEntry<'a>;
+
+    fn next(&mut self) -> Option<ExifEntry<'a>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let Some(entry) = self.reader.entry_at(self.next) else {
+            // Truncated: stop for good.
+            self.remaining = 0;
+            return None;
+        };
+        self.remaining -= 1;
+        self.next += IFD_ENTRY_SIZE;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining as usize))
+    }
+}
+
+// ── Rewriter ───────────────────────────────────────────────────────────
+
+/// Exif IFD MakerNote tag (vendor-private, often contains serial numbers).
+const TAG_MAKER_NOTE: u16 = 0x927C;
+/// Serial number tags removed by [`ExifRewrite::strip_serial_numbers`]:
+/// BodySerialNumber, LensSerialNumber (Exif IFD), CameraSerialNumber (DNG).
+const SERIAL_TAGS: [u16; 3] = [0xA431, 0xA435, 0xC62F];
+
+/// Changes [`rewrite_exif`] makes to an EXIF blob.
+///
+/// All options default to off. Each `strip_*` option removes the entries
+/// from their IFD and zeroes the bytes they referenced, so the data does
+/// not linger in the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ExifRewrite {
+    /// Set IFD0 Orientation to 1 (use after pixels were physically
+    /// oriented, so viewers do not rotate twice).
+    pub reset_orientation: bool,
+    /// Remove the GPS IFD and its pointer.
+    pub strip_gps: bool,
+    /// Remove the Exif IFD MakerNote.
+    pub strip_maker_note: bool,
+    /// Remove body, lens, and camera serial numbers.
+    pub strip_serial_numbers: bool,
+    /// Remove IFD1 and the embedded thumbnail JPEG.
+    pub strip_thumbnail: bool,
+}
+
+impl ExifRewrite {
+    /// No changes.
+    pub const fn new() -> Self {
+        Self {
+            reset_orientation: false,
+            strip_gps: false,
+            strip_maker_note: false,
+            strip_serial_numbers: false,
+            strip_thumbnail: false,
+        }
+    }
+
+    /// Strip GPS, MakerNote, serial numbers, and the thumbnail; leave
+    /// orientation alone.
+    pub const fn scrub_private() -> Self {
+        Self {
+            reset_orientation: false,
+            strip_gps: true,
+            strip_maker_note: true,
+            strip_serial_numbers: true,
+            strip_thumbnail: true,
+        }
+    }
+
+    /// Set whether to reset orientation to 1.
+    pub const fn with_reset_orientation(mut self, v: bool) -> Self {
+        self.reset_orientation = v;
+        self
+    }
+
+    /// Set whether to remove the GPS IFD.
+    pub const fn with_strip_gps(mut self, v: bool) -> Self {
+        self.strip_gps = v;
+        self
+    }
+
+    /// Set whether to remove the MakerNote.
+    pub const fn with_strip_maker_note(mut self, v: bool) -> Self {
+        self.strip_maker_note = v;
+        self
+    }
+
+    /// Set whether to remove serial numbers.
+    pub const fn with_strip_serial_numbers(mut self, v: bool) -> Self {
+        self.strip_serial_numbers = v;
+        self
+    }
+
+    /// Set whether to remove IFD1 and the thumbnail.
+    pub const fn with_strip_thumbnail(mut self, v: bool) -> Self {
+        self.strip_thumbnail = v;
+        self
+    }
+
+    /// Whether no option is set.
+    pub const fn is_noop(&self) -> bool {
+        !(self.reset_orientation
+            || self.strip_gps
+            || self.strip_maker_note
+            || self.strip_serial_numbers
+            || self.strip_thumbnail)
+    }
+}
+
+/// Return a copy of `data` with the changes in `rewrite` applied.
+///
+/// Accepts the same input as [`ExifReader::new`] and keeps any
+/// `Exif\0\0` prefix. The blob is patched in place: IFDs and values keep
+/// their offsets, removed entries are compacted out of their IFD, and
+/// freed bytes are zeroed, so the output is the same length as the input.
+/// Returns `None` if the TIFF header is invalid.
+///
+/// ```
+/// use zencodec::helpers::exif::{ExifReader, ExifRewrite, rewrite_exif};
+/// use zenpixels::Orientation;
+///
+/// let tiff = [
+///     b'I', b'I', 42, 0, 8, 0, 0, 0, // header, IFD0 at 8
+///     1, 0,                          // 1 entry
+///     0x12, 0x01, 3, 0, 1, 0, 0, 0, // Orientation, SHORT, count 1
+///     6, 0, 0, 0,                    // Rotate90
+///     0, 0, 0, 0,                    // no IFD1
+/// ];
+/// let patched = rewrite_exif(&tiff, &ExifRewrite::new().with_reset_orientation(true)).unwrap();
+/// let exif = ExifReader::new(&patched).unwrap();
+/// assert_eq!(exif.orientation(), Some(Orientation::Identity));
+/// ```
+pub fn rewrite_exif(data: &[u8], rewrite: &ExifRewrite) -> Option<Vec<u8>> {
+    let prefix = data.len() - ExifReader::new(data)?.tiff.len();
+    let mut out = data.to_vec();
+    let tiff = &mut out[prefix..];
+
+    if rewrite.reset_orientation {
+        reset_orientation(tiff);
+    }
+    if rewrite.strip_maker_note {
+        remove_tags(tiff, Ifd::Exif, &[TAG_MAKER_NOTE]);
+    }
+    if rewrite.strip_serial_numbers {
+        remove_tags(tiff, Ifd::Primary, &SERIAL_TAGS);
+        remove_tags(tiff, Ifd::Exif, &SERIAL_TAGS);
+    }
+    if rewrite.strip_gps {
+        remove_sub_ifd(tiff, Ifd::Gps, Ifd::Primary, tag::GPS_IFD_POINTER);
+    }
+    if rewrite.strip_thumbnail {
+        remove_thumbnail(tiff);
+    }
+    Some(out)
+}
+
+fn wr16(data: &mut [u8], offset: usize, v: u16, big_endian: bool) {
+    let bytes = if big_endian {
+        v.to_be_bytes()
+    } else {
+        v.to_le_bytes()
+    };
+    data[offset..offset + 2].copy_from_slice(&bytes);
+}
+
+fn wr32(data: &mut [u8], offset: usize, v: u32, big_endian: bool) {
+    let bytes = if big_endian {
+        v.to_be_bytes()
+    } else {
+        v.to_le_bytes()
+    };
+    data[offset..offset + 4].copy_from_slice(&bytes);
+}
+
+/// Zero `len` bytes at `start`, clamped to the data.
+fn zero(data: &mut [u8], start: usize, len: usize) {
+    let end = start.saturating_add(len).min(data.len());
+    if let Some(bytes) = data.get_mut(start..end) {
+        bytes.fill(0);
+    }
+}
+
+fn reset_orientation(tiff: &mut [u8]) {
+    let Some(reader) = ExifReader::new(tiff) else {
+        return;
+    };
+    let be = reader.big_endian;
+    let Some(entry) = reader.find(Ifd::Primary, tag::ORIENTATION) else {
+        return;
+    };
+    if entry.value.is_none() {
+        return;
+    }
+    let (at, ty) = (entry.value_start, entry.field_type);
+    match ty {
+        TIFF_SHORT => wr16(tiff, at, 1, be),
+        TIFF_LONG => wr32(tiff, at, 1, be),
+        _ => {}
+    }
+}
+
+/// Zero an entry's out-of-line value and compact it out of its IFD.
+fn remove_entry(
+    tiff: &mut [u8],
+    ifd_offset: usize,
+    entry_offset: usize,
+    value: Option<(usize, usize)>,
+) {
+    let be = tiff[0] == b'M';
+    if let Some((start, len)) = value
+        && len > 4
+    {
+        zero(tiff, start, len);
+    }
+    let Some(count) = rd16(tiff, ifd_offset, be) else {
+        return;
+    };
+    // Entries after this one, plus the 4-byte next-IFD offset.
+    let ifd_end = ifd_offset + 2 + count as usize * IFD_ENTRY_SIZE + 4;
+    let ifd_end = ifd_end.min(tiff.len());
+    tiff.copy_within(entry_offset + IFD_ENTRY_SIZE..ifd_end, entry_offset);
+    zero(tiff, ifd_end - IFD_ENTRY_SIZE, IFD_ENTRY_SIZE);
+    wr16(tiff, ifd_offset, count - 1, be);
+}
+
+/// Remove every entry in `ifd` whose tag is in `tags`.
+fn remove_tags(tiff: &mut [u8], ifd: Ifd, tags: &[u16]) {
+    // Each removal shifts later entries, so look up afresh every time.
+    loop {
+        let Some(reader) = ExifReader::new(tiff) else {
+            return;
+        };
+        let Some(ifd_offset) = reader.ifd_offset(ifd) else {
+            return;
+        };
+        let Some(entry) = reader.entries(ifd).find(|e| tags.contains(&e.tag)) else {
+            return;
+        };
+        let value = entry.value.map(|v| (entry.value_start, v.len()));
+        let entry_offset = entry.offset;
+        remove_entry(tiff, ifd_offset, entry_offset, value);
+    }
+}
+
+/// Zero every out-of-line value of `ifd`, then the IFD itself.
+fn zero_ifd(tiff: &mut [u8], ifd: Ifd) {
+    let Some(reader) = ExifReader::new(tiff) else {
+        return;
+    };
+    let Some(offset) = reader.ifd_offset(ifd) else {
+        return;
+    };
+    // A sub-IFD pointing back at IFD0 is malformed; never wipe IFD0.
+    if offset == reader.ifd0 {
+        return;
+    }
+    let count = reader.entry_count(offset).unwrap_or(0) as usize;
+    // Re-read each entry: zeroing a shared value may alter later entries.
+    for i in 0..count {
+        let Some(reader) = ExifReader::new(tiff) else {
+            return;
+        };
+        let Some(entry) = reader.entry_at(offset + 2 + i * IFD_ENTRY_SIZE) else {
+            break;
+        };
+        if let Some(v) = entry.value
+            && v.len() > 4
+        {
+            let (start, len) = (entry.value_start, v.len());
+            zero(tiff, start, len);
+        }
+    }
+    zero(tiff, offset, 2 + count * IFD_ENTRY_SIZE + 4);
+}
+
+/// Zero a sub-IFD and remove its pointer entry from `parent`.
+fn remove_sub_ifd(tiff: &mut [u8], ifd: Ifd, parent: Ifd, pointer_tag: u16) {
+    zero_ifd(tiff, ifd);
+    remove_tags(tiff, parent, &[pointer_tag]);
+}
+
+fn remove_thumbnail(tiff: &mut [u8]) {
+    let Some(reader) = ExifReader::new(tiff) else {
+        return;
+    };
+    let be = reader.big_endian;
+    let thumb_field = |tag| reader.find(Ifd::Thumbnail, tag)?.get_u32(0);
+    let thumb = thumb_field(tag::JPEG_INTERCHANGE_FORMAT)
+        .zip(thumb_field(tag::JPEG_INTERCHANGE_FORMAT_LENGTH))
+        .map(|(start, len)| (start as usize, len as usize));
+    let Some(count) = reader.entry_count(reader.ifd0) else {
+        return;
+    };
+    let next_at = reader.ifd0 + 2 + count as usize * IFD_ENTRY_SIZE;
+    let has_ifd1 = reader.ifd_offset(Ifd::Thumbnail).is_some();
+
+    if let Some((start, len)) = thumb {
+        zero(tiff, start, len);
+    }
+    if has_ifd1 {
+        zero_ifd(tiff, Ifd::Thumbnail);
+    }
+    if next_at + 4 <= tiff.len() {
+        wr32(tiff, next_at, 0, be);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -457,4 +1264,433 @@ mod tests {
         let data = b"Exif\0\0GARBAGE".to_vec();
         assert_eq!(parse_exif_orientation(&data), None);
     }
+
+    // ── ExifReader ─────────────────────────────────────────────────────
+
+    /// Field for [`build_exif`]: (tag, type, count, value bytes in LE).
+    type Field<'a> = (u16, u16, u32, &'a [u8]);
+
+    /// Lay out little-endian IFDs back to back after the header: IFD0
+    /// (linked to IFD1 when given), then the Exif and GPS IFDs, with
+    /// pointer tags patched in. Out-of-line values follow each IFD.
+    fn build_exif(ifd0: &[Field], exif: &[Field], gps: &[Field], ifd1: &[Field]) -> Vec<u8> {
+        fn write_ifd(buf: &mut Vec<u8>, fields: &[Field], next: u32) -> usize {
+            let start = buf.len();
+            let data_start = start + 2 + fields.len() * 12 + 4;
+            let mut data = Vec::new();
+            buf.extend_from_slice(&(fields.len() as u16).to_le_bytes());
+            for &(tag, ty, count, value) in fields {
+                buf.extend_from_slice(&tag.to_le_bytes());
+                buf.extend_from_slice(&ty.to_le_bytes());
+                buf.extend_from_slice(&count.to_le_bytes());
+                if value.len() <= 4 {
+                    let mut inline = [0u8; 4];
+                    inline[..value.len()].copy_from_slice(value);
+                    buf.extend_from_slice(&inline);
+                } else {
+                    let at = (data_start + data.len()) as u32;
+                    buf.extend_from_slice(&at.to_le_bytes());
+                    data.extend_from_slice(value);
+                }
+            }
+            buf.extend_from_slice(&next.to_le_bytes());
+            buf.extend_from_slice(&data);
+            start
+        }
+        fn patch(buf: &mut [u8], ifd: usize, tag: u16, value: u32) {
+            let count = u16::from_le_bytes([buf[ifd], buf[ifd + 1]]) as usize;
+            for i in 0..count {
+                let off = ifd + 2 + i * 12;
+                if u16::from_le_bytes([buf[off], buf[off + 1]]) == tag {
+                    buf[off + 8..off + 12].copy_from_slice(&value.to_le_bytes());
+                }
+            }
+        }
+
+        let mut buf = b"II\x2a\x00\x08\x00\x00\x00".to_vec();
+        write_ifd(&mut buf, ifd0, 0);
+        if !exif.is_empty() {
+            let at = write_ifd(&mut buf, exif, 0);
+            patch(&mut buf, 8, tag::EXIF_IFD_POINTER, at as u32);
+        }
+        if !gps.is_empty() {
+            let at = write_ifd(&mut buf, gps, 0);
+            patch(&mut buf, 8, tag::GPS_IFD_POINTER, at as u32);
+        }
+        if !ifd1.is_empty() {
+            let at = write_ifd(&mut buf, ifd1, 0);
+            let next_at = 8 + 2 + ifd0.len() * 12;
+            buf[next_at..next_at + 4].copy_from_slice(&(at as u32).to_le_bytes());
+        }
+        buf
+    }
+
+    const LONG_PLACEHOLDER: &[u8] = &[0, 0, 0, 0];
+
+    fn camera_exif() -> Vec<u8> {
+        let x_res = [72u32.to_le_bytes(), 1u32.to_le_bytes()].concat();
+        build_exif(
+            &[
+                (tag::MAKE, TIFF_ASCII, 6, b"Canon\0"),
+                (tag::MODEL, TIFF_ASCII, 4, b"R5\0\0"),
+                (tag::ORIENTATION, TIFF_SHORT, 1, &6u16.to_le_bytes()),
+                (tag::X_RESOLUTION, TIFF_RATIONAL, 1, &x_res),
+                (tag::RESOLUTION_UNIT, TIFF_SHORT, 1, &2u16.to_le_bytes()),
+                (tag::DATE_TIME, TIFF_ASCII, 20, b"2024:05:06 07:08:09\0"),
+                (tag::EXIF_IFD_POINTER, TIFF_LONG, 1, LONG_PLACEHOLDER),
+                (tag::GPS_IFD_POINTER, TIFF_LONG, 1, LONG_PLACEHOLDER),
+            ],
+            &[
+                (
+                    tag::DATE_TIME_ORIGINAL,
+                    TIFF_ASCII,
+                    20,
+                    b"2024:05:06 07:08:00\0",
+                ),
+                (tag::OFFSET_TIME_ORIGINAL, TIFF_ASCII, 7, b"+02:00\0"),
+                (tag::PIXEL_X_DIMENSION, TIFF_LONG, 1, &6000u32.to_le_bytes()),
+                (
+                    tag::PIXEL_Y_DIMENSION,
+                    TIFF_SHORT,
+                    1,
+                    &4000u16.to_le_bytes(),
+                ),
+            ],
+            &[(0x0001, TIFF_ASCII, 2, b"N\0")],
+            &[
+                (
+                    tag::JPEG_INTERCHANGE_FORMAT,
+                    TIFF_LONG,
+                    1,
+                    &8u32.to_le_bytes(),
+                ),
+                (
+                    tag::JPEG_INTERCHANGE_FORMAT_LENGTH,
+                    TIFF_LONG,
+                    1,
+                    &4u32.to_le_bytes(),
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn reader_typed_accessors() {
+        let data = camera_exif();
+        let exif = ExifReader::new(&data).unwrap();
+        assert!(!exif.is_big_endian());
+        assert_eq!(exif.make(), Some("Canon"));
+        assert_eq!(exif.model(), Some("R5"));
+        assert_eq!(exif.orientation(), Some(Orientation::Rotate90));
+        assert_eq!(exif.x_resolution(), Some(Rational { num: 72, den: 1 }));
+        assert_eq!(exif.x_resolution().unwrap().to_f64(), Some(72.0));
+        assert_eq!(exif.y_resolution(), None);
+        assert_eq!(exif.resolution_unit(), Some(2));
+        assert_eq!(exif.date_time(), Some("2024:05:06 07:08:09"));
+        assert_eq!(exif.date_time_original(), Some("2024:05:06 07:08:00"));
+        assert_eq!(exif.offset_time_original(), Some("+02:00"));
+        assert_eq!(exif.pixel_dimensions(), Some((6000, 4000)));
+        assert_eq!(exif.software(), None);
+    }
+
+    #[test]
+    fn reader_sub_ifds_and_thumbnail() {
+        let data = camera_exif();
+        let exif = ExifReader::new(&data).unwrap();
+        assert!(exif.exif_ifd_offset().is_some());
+        assert!(exif.gps_ifd_offset().is_some());
+        assert!(exif.thumbnail_ifd_offset().is_some());
+        assert_eq!(exif.ifd_offset(Ifd::Interop), None);
+        let lat_ref = exif.find(Ifd::Gps, 0x0001).unwrap();
+        assert_eq!(lat_ref.as_str(), Some("N"));
+        // Thumbnail points at IFD0's first bytes in this synthetic file.
+        assert_eq!(exif.thumbnail_jpeg(), Some(&data[8..12]));
+    }
+
+    #[test]
+    fn reader_accepts_app1_prefix() {
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend_from_slice(&camera_exif());
+        let exif = ExifReader::new(&app1).unwrap();
+        assert_eq!(exif.model(), Some("R5"));
+        assert_eq!(exif.tiff_data(), &app1[6..]);
+    }
+
+    #[test]
+    fn reader_big_endian() {
+        let tiff = make_tiff(true, 8, TIFF_SHORT);
+        let exif = ExifReader::new(&tiff).unwrap();
+        assert!(exif.is_big_endian());
+        assert_eq!(exif.orientation(), Some(Orientation::Rotate270));
+        let entry = exif.entries(Ifd::Primary).next().unwrap();
+        assert_eq!(entry.tag(), tag::ORIENTATION);
+        assert_eq!(entry.field_type(), TIFF_SHORT);
+        assert_eq!(entry.count(), 1);
+        assert_eq!(entry.get_u32(0), Some(8));
+        assert_eq!(entry.get_u32(1), None);
+    }
+
+    #[test]
+    fn reader_entries_in_stored_order() {
+        let data = camera_exif();
+        let exif = ExifReader::new(&data).unwrap();
+        let tags: Vec<u16> = exif.entries(Ifd::Exif).map(|e| e.tag()).collect();
+        assert_eq!(
+            tags,
+            [
+                tag::DATE_TIME_ORIGINAL,
+                tag::OFFSET_TIME_ORIGINAL,
+                tag::PIXEL_X_DIMENSION,
+                tag::PIXEL_Y_DIMENSION
+            ]
+        );
+        assert_eq!(exif.entries(Ifd::Interop).count(), 0);
+    }
+
+    #[test]
+    fn reader_srational_and_type_mismatch() {
+        let value = [(-3i32).to_le_bytes(), 2i32.to_le_bytes()].concat();
+        let data = build_exif(&[(0x9204, TIFF_SRATIONAL, 1, &value)], &[], &[], &[]);
+        let exif = ExifReader::new(&data).unwrap();
+        let entry = exif.find(Ifd::Primary, 0x9204).unwrap();
+        assert_eq!(entry.get_srational(0), Some(SRational { num: -3, den: 2 }));
+        assert_eq!(entry.get_srational(0).unwrap().to_f64(), Some(-1.5));
+        assert_eq!(entry.get_rational(0), None);
+        assert_eq!(entry.get_u32(0), None);
+        assert_eq!(entry.as_str(), None);
+    }
+
+    #[test]
+    fn reader_rejects_bad_header() {
+        assert!(ExifReader::new(&[]).is_none());
+        assert!(ExifReader::new(b"Exif\0\0").is_none());
+        assert!(ExifReader::new(b"XX\x2a\x00\x08\x00\x00\x00\x00\x00").is_none());
+        let mut tiff = make_tiff(false, 1, TIFF_SHORT);
+        tiff[4] = 0xFF;
+        assert!(ExifReader::new(&tiff).is_none());
+    }
+
+    #[test]
+    fn reader_out_of_bounds_value_is_none() {
+        let mut data = build_exif(&[(tag::MAKE, TIFF_ASCII, 6, b"Canon\0")], &[], &[], &[]);
+        // Point the out-of-line value past the end.
+        data[8 + 2 + 8..8 + 2 + 12].copy_from_slice(&0xFFFF_FF00u32.to_le_bytes());
+        let exif = ExifReader::new(&data).unwrap();
+        let entry = exif.find(Ifd::Primary, tag::MAKE).unwrap();
+        assert_eq!(entry.value_bytes(), None);
+        assert_eq!(exif.make(), None);
+    }
+
+    #[test]
+    fn reader_huge_count_does_not_overflow() {
+        let mut data = build_exif(&[(tag::MAKE, TIFF_RATIONAL, 1, &[0; 8])], &[], &[], &[]);
+        data[8 + 2 + 4..8 + 2 + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+        let exif = ExifReader::new(&data).unwrap();
+        assert_eq!(
+            exif.find(Ifd::Primary, tag::MAKE).unwrap().value_bytes(),
+            None
+        );
+    }
+
+    #[test]
+    fn reader_truncated_ifd_stops_iteration() {
+        let data = camera_exif();
+        let exif = ExifReader::new(&data[..8 + 2 + 12 * 3]).unwrap();
+        assert_eq!(exif.entries(Ifd::Primary).count(), 3);
+        assert_eq!(exif.orientation(), Some(Orientation::Rotate90));
+        // Make's out-of-line value was cut off.
+        assert_eq!(exif.make(), None);
+        assert_eq!(exif.exif_ifd_offset(), None);
+        assert_eq!(exif.thumbnail_ifd_offset(), None);
+    }
+
+    #[test]
+    fn reader_excessive_entry_count_treated_as_absent() {
+        let mut tiff = make_tiff(false, 6, TIFF_SHORT);
+        tiff[8..10].copy_from_slice(&(MAX_IFD_ENTRIES + 1).to_le_bytes());
+        let exif = ExifReader::new(&tiff).unwrap();
+        assert_eq!(exif.entries(Ifd::Primary).count(), 0);
+        assert_eq!(exif.orientation(), None);
+    }
+
+    #[test]
+    fn reader_self_linked_ifd1_ignored() {
+        let mut tiff = make_tiff(false, 6, TIFF_SHORT);
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        let exif = ExifReader::new(&tiff).unwrap();
+        assert_eq!(exif.thumbnail_ifd_offset(), None);
+    }
+
+    #[test]
+    fn reader_orientation_matches_parser() {
+        for v in 0..=10 {
+            let tiff = make_tiff(false, v, TIFF_SHORT);
+            assert_eq!(
+                ExifReader::new(&tiff).unwrap().orientation(),
+                parse_exif_orientation(&tiff),
+                "value={v}"
+            );
+        }
+    }
+
+    // ── Rewriter ───────────────────────────────────────────────────────
+
+    /// [`camera_exif`] plus MakerNote/serial numbers and a real thumbnail
+    /// appended at the end.
+    fn private_exif() -> Vec<u8> {
+        let mut data = build_exif(
+            &[
+                (tag::MAKE, TIFF_ASCII, 6, b"Canon\0"),
+                (tag::ORIENTATION, TIFF_SHORT, 1, &6u16.to_le_bytes()),
+                (tag::EXIF_IFD_POINTER, TIFF_LONG, 1, LONG_PLACEHOLDER),
+                (tag::GPS_IFD_POINTER, TIFF_LONG, 1, LONG_PLACEHOLDER),
+            ],
+            &[
+                (
+                    tag::DATE_TIME_ORIGINAL,
+                    TIFF_ASCII,
+                    20,
+                    b"2024:05:06 07:08:00\0",
+                ),
+                (TAG_MAKER_NOTE, 7, 8, b"SECRETMN"),
+                (0xA431, TIFF_ASCII, 8, b"SN12345\0"),
+                (0xA435, TIFF_ASCII, 4, b"L99\0"),
+            ],
+            &[(0x0002, TIFF_RATIONAL, 1, b"LATITUDE")],
+            &[
+                (tag::JPEG_INTERCHANGE_FORMAT, TIFF_LONG, 1, LONG_PLACEHOLDER),
+                (
+                    tag::JPEG_INTERCHANGE_FORMAT_LENGTH,
+                    TIFF_LONG,
+                    1,
+                    &4u32.to_le_bytes(),
+                ),
+            ],
+        );
+        let thumb_at = data.len() as u32;
+        data.extend_from_slice(&[0xFF, 0xD8, 0xFF, 0xD9]);
+        let ifd1 = ExifReader::new(&data)
+            .unwrap()
+            .thumbnail_ifd_offset()
+            .unwrap();
+        data[ifd1 + 2 + 8..ifd1 + 2 + 12].copy_from_slice(&thumb_at.to_le_bytes());
+        data
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[test]
+    fn rewrite_noop_copies_input() {
+        let data = private_exif();
+        assert_eq!(rewrite_exif(&data, &ExifRewrite::new()).unwrap(), data);
+        assert!(ExifRewrite::new().is_noop());
+        assert!(!ExifRewrite::scrub_private().is_noop());
+    }
+
+    #[test]
+    fn rewrite_rejects_invalid() {
+        assert_eq!(
+            rewrite_exif(b"garbage", &ExifRewrite::scrub_private()),
+            None
+        );
+    }
+
+    #[test]
+    fn rewrite_resets_orientation() {
+        let data = private_exif();
+        let out = rewrite_exif(&data, &ExifRewrite::new().with_reset_orientation(true)).unwrap();
+        assert_eq!(out.len(), data.len());
+        let exif = ExifReader::new(&out).unwrap();
+        assert_eq!(exif.orientation(), Some(Orientation::Identity));
+        assert_eq!(exif.make(), Some("Canon"));
+    }
+
+    #[test]
+    fn rewrite_resets_orientation_big_endian_long_with_prefix() {
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend_from_slice(&make_tiff(true, 8, TIFF_LONG));
+        let out = rewrite_exif(&app1, &ExifRewrite::new().with_reset_orientation(true)).unwrap();
+        assert!(out.starts_with(b"Exif\0\0"));
+        assert_eq!(parse_exif_orientation(&out), Some(Orientation::Identity));
+    }
+
+    #[test]
+    fn rewrite_without_orientation_tag_is_unchanged() {
+        let data = build_exif(&[(tag::MAKE, TIFF_ASCII, 4, b"Acm\0")], &[], &[], &[]);
+        let out = rewrite_exif(&data, &ExifRewrite::new().with_reset_orientation(true)).unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn rewrite_strips_gps() {
+        let data = private_exif();
+        let out = rewrite_exif(&data, &ExifRewrite::new().with_strip_gps(true)).unwrap();
+        let exif = ExifReader::new(&out).unwrap();
+        assert_eq!(exif.gps_ifd_offset(), None);
+        assert!(exif.find(Ifd::Primary, tag::GPS_IFD_POINTER).is_none());
+        assert!(!contains(&out, b"LATITUDE"));
+        // Everything else survives the compaction of IFD0.
+        assert_eq!(exif.entries(Ifd::Primary).count(), 3);
+        assert_eq!(exif.orientation(), Some(Orientation::Rotate90));
+        assert_eq!(exif.date_time_original(), Some("2024:05:06 07:08:00"));
+        assert_eq!(exif.thumbnail_jpeg(), Some(&[0xFF, 0xD8, 0xFF, 0xD9][..]));
+    }
+
+    #[test]
+    fn rewrite_strips_maker_note_and_serials() {
+        let data = private_exif();
+        let rw = ExifRewrite::new()
+            .with_strip_maker_note(true)
+            .with_strip_serial_numbers(true);
+        let out = rewrite_exif(&data, &rw).unwrap();
+        let exif = ExifReader::new(&out).unwrap();
+        let tags: Vec<u16> = exif.entries(Ifd::Exif).map(|e| e.tag()).collect();
+        assert_eq!(tags, [tag::DATE_TIME_ORIGINAL]);
+        assert!(!contains(&out, b"SECRETMN"));
+        assert!(!contains(&out, b"SN12345"));
+        assert_eq!(exif.date_time_original(), Some("2024:05:06 07:08:00"));
+    }
+
+    #[test]
+    fn rewrite_strips_thumbnail() {
+        let data = private_exif();
+        let out = rewrite_exif(&data, &ExifRewrite::new().with_strip_thumbnail(true)).unwrap();
+        let exif = ExifReader::new(&out).unwrap();
+        assert_eq!(exif.thumbnail_ifd_offset(), None);
+        assert_eq!(exif.thumbnail_jpeg(), None);
+        assert!(!contains(&out, &[0xFF, 0xD8, 0xFF, 0xD9]));
+        assert_eq!(exif.make(), Some("Canon"));
+    }
+
+    #[test]
+    fn rewrite_scrub_private_keeps_capture_date() {
+        let data = private_exif();
+        let out = rewrite_exif(&data, &ExifRewrite::scrub_private()).unwrap();
+        let exif = ExifReader::new(&out).unwrap();
+        assert_eq!(exif.gps_ifd_offset(), None);
+        assert_eq!(exif.thumbnail_ifd_offset(), None);
+        assert_eq!(exif.entries(Ifd::Exif).count(), 1);
+        assert_eq!(exif.orientation(), Some(Orientation::Rotate90));
+        assert_eq!(exif.date_time_original(), Some("2024:05:06 07:08:00"));
+    }
+
+    #[test]
+    fn rewrite_gps_pointing_at_ifd0_keeps_ifd0() {
+        let mut data = build_exif(
+            &[
+                (tag::MAKE, TIFF_ASCII, 4, b"Acm\0"),
+                (tag::GPS_IFD_POINTER, TIFF_LONG, 1, &8u32.to_le_bytes()),
+            ],
+            &[],
+            &[],
+            &[],
+        );
+        data.extend_from_slice(&[0; 4]);
+        let out = rewrite_exif(&data, &ExifRewrite::new().with_strip_gps(true)).unwrap();
+        let exif = ExifReader::new(&out).unwrap();
+        assert_eq!(exif.make(), Some("Acm"));
+        assert!(exif.find(Ifd::Primary, tag::GPS_IFD_POINTER).is_none());
+    }
 }
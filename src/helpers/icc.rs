@@ -7,11 +7,24 @@
 //!
 //! The zencodec-specific entry point is [`descriptor_for_decoded_pixels`],
 //! which applies codec-output priority rules (corrected_to → CICP → ICC → sRGB
-//! default). The `identify_well_known_icc` / `icc_profile_is_srgb` /
+//! default).
+//!
+//! [`icc_from_cicp`] goes the other way: it builds a compact ICC v4 profile
+//! for encoders whose format can only signal color through ICC.
+//! [`canonical_cicp`] finds the CICP equivalent of a well-known profile so
+//! encoders can swap it for CICP or a compact canonical profile.
+//! [`IccReader`] inspects any profile — header fields, tag table, text,
+//! colorants, and TRCs — for profiles `identify_common` doesn't know.
+//! [`validate_icc`] and [`sanitize_icc`] check the structure of untrusted
+//! profiles before they are embedded. The `identify_well_known_icc` / `icc_profile_is_srgb` /
 //! `IccMatchTolerance` symbols remain as deprecated shims for 0.1.x callers —
 //! scheduled for removal in the next minor release.
 
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
 use crate::decode::SourceColor;
+use crate::info::MasteringDisplay;
 use zenpixels::{
     Cicp, ColorPrimaries, ColorProfileSource, PixelDescriptor, PixelFormat, TransferFunction,
 };
@@ -152,6 +165,1127 @@ fn resolve_profile_source(src: &ColorProfileSource<'_>) -> (ColorPrimaries, Tran
     }
 }
 
+// ── ICC synthesis from CICP ────────────────────────────────────────────────
+
+/// ICC profile version written by [`icc_from_cicp`] (v4.4, the first with
+/// the `cicp` tag).
+const ICC_VERSION: u32 = 0x0440_0000;
+
+/// ICC PCS illuminant (D50) as `s15Fixed16` XYZ.
+const D50_XYZ: [f64; 3] = [0.9642, 1.0, 0.8249];
+
+/// Entries in the sampled `curv` used for PQ and HLG.
+const CURVE_ENTRIES: usize = 1024;
+
+/// Build an ICC v4.4 display profile equivalent to `cicp`.
+///
+/// JPEG, WebP, GIF, and BMP signal color only through ICC, so encoders for
+/// them can embed this when the source carries CICP but no ICC profile (see
+/// [`Metadata::with_icc_fallback()`](crate::Metadata::with_icc_fallback)).
+///
+/// The profile is an RGB matrix/TRC profile with:
+///
+/// - `rXYZ`/`gXYZ`/`bXYZ` colorants Bradford-adapted to the D50 PCS, plus
+///   the matching `chad` tag;
+/// - a shared `para` TRC for BT.709-family (1, 6, 7, 14, 15), gamma 2.2 (4),
+///   gamma 2.8 (5), linear (8), and sRGB (13) transfers;
+/// - for PQ (16) and HLG (18), a sampled `curv` plus a `cicp` tag that
+///   carries the exact meaning to CICP-aware color managers. The PQ curve
+///   is normalized to `mastering`'s peak luminance (10 000 cd/m² without
+///   one); the HLG curve is the inverse OETF;
+/// - a `lumi` tag with the mastering peak when `mastering` is given.
+///
+/// `matrix_coefficients` and `full_range` are ignored: ICC describes the
+/// RGB pixels handed to the encoder. Returns `None` when the primaries or
+/// transfer code has no ICC equivalent here (unspecified, reserved, XYZ,
+/// logarithmic, ...). Output is deterministic, with a fixed creation date
+/// and a zero profile ID.
+pub fn icc_from_cicp(cicp: Cicp, mastering: Option<&MasteringDisplay>) -> Option<Vec<u8>> {
+    let (primaries, white) = cicp_chromaticities(cicp.color_primaries)?;
+    let trc = cicp_trc(cicp.transfer_characteristics, mastering)?;
+
+    let to_xyz = rgb_to_xyz(primaries, white)?;
+    let chad = bradford(xy_to_xyz(white), D50_XYZ)?;
+    let colorants = mat_mul(&chad, &to_xyz);
+
+    let mut tags: Vec<([u8; 4], Vec<u8>)> = Vec::new();
+    let description = alloc::format!(
+        "{} {}",
+        Cicp::color_primaries_name(cicp.color_primaries),
+        Cicp::transfer_characteristics_name(cicp.transfer_characteristics),
+    );
+    tags.push((*b"desc", mluc(&description)));
+    tags.push((*b"cprt", mluc("No copyright, use freely")));
+    tags.push((*b"wtpt", xyz_tag(D50_XYZ)));
+    tags.push((*b"chad", sf32_tag(&chad)));
+    for (sig, column) in [(*b"rXYZ", 0), (*b"gXYZ", 1), (*b"bXYZ", 2)] {
+        let xyz = [
+            colorants[0][column],
+            colorants[1][column],
+            colorants[2][column],
+        ];
+        tags.push((sig, xyz_tag(xyz)));
+    }
+    let trc_data = trc.encode();
+    for sig in [*b"rTRC", *b"gTRC", *b"bTRC"] {
+        tags.push((sig, trc_data.clone()));
+    }
+    if matches!(trc, Trc::Sampled(_)) {
+        let mut tag = Vec::with_capacity(12);
+        tag.extend_from_slice(b"cicp");
+        tag.extend_from_slice(&[0; 4]);
+        tag.extend_from_slice(&[cicp.color_primaries, cicp.transfer_characteristics, 0, 1]);
+        tags.push((*b"cicp", tag));
+    }
+    if let Some(peak) = mastering.map(|m| f64::from(m.max_luminance))
+        && peak > 0.0
+    {
+        tags.push((*b"lumi", xyz_tag(D50_XYZ.map(|v| v * peak))));
+    }
+
+    Some(assemble_profile(&tags))
+}
+
+/// Lay out the header, tag table, and 4-byte aligned tag data. Identical
+/// tag payloads (the three TRCs) share one copy.
+fn assemble_profile(tags: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let table_end = 128 + 4 + 12 * tags.len();
+    let mut data: Vec<u8> = Vec::new();
+    let mut entries: Vec<([u8; 4], u32, u32)> = Vec::with_capacity(tags.len());
+    for (i, (sig, payload)) in tags.iter().enumerate() {
+        let shared = tags[..i]
+            .iter()
+            .zip(&entries)
+            .find(|((_, earlier), _)| earlier == payload)
+            .map(|(_, &(_, offset, _))| offset);
+        let offset = match shared {
+            Some(offset) => offset,
+            None => {
+                let offset = (table_end + data.len()) as u32;
+                data.extend_from_slice(payload);
+                data.resize(data.len().next_multiple_of(4), 0);
+                offset
+            }
+        };
+        entries.push((*sig, offset, payload.len() as u32));
+    }
+
+    let size = table_end + data.len();
+    let mut icc = Vec::with_capacity(size);
+    icc.extend_from_slice(&(size as u32).to_be_bytes());
+    icc.extend_from_slice(&[0; 4]); // preferred CMM
+    icc.extend_from_slice(&ICC_VERSION.to_be_bytes());
+    icc.extend_from_slice(b"mntrRGB XYZ ");
+    // 2024-01-01 00:00:00
+    for field in [2024u16, 1, 1, 0, 0, 0] {
+        icc.extend_from_slice(&field.to_be_bytes());
+    }
+    icc.extend_from_slice(b"acsp");
+    icc.extend_from_slice(&[0; 24]); // platform, flags, manufacturer, model, attributes
+    icc.extend_from_slice(&0u32.to_be_bytes()); // perceptual intent
+    for v in D50_XYZ {
+        icc.extend_from_slice(&s15_fixed16(v).to_be_bytes());
+    }
+    icc.extend_from_slice(&[0; 4]); // creator
+    icc.extend_from_slice(&[0; 16]); // profile ID
+    icc.extend_from_slice(&[0; 28]); // reserved
+    icc.extend_from_slice(&(tags.len() as u32).to_be_bytes());
+    for (sig, offset, len) in entries {
+        icc.extend_from_slice(&sig);
+        icc.extend_from_slice(&offset.to_be_bytes());
+        icc.extend_from_slice(&len.to_be_bytes());
+    }
+    icc.extend_from_slice(&data);
+    icc
+}
+
+/// Tone response curve for one CICP transfer code.
+enum Trc {
+    /// `para` function type 0 (`Y = X^g`) or 3 (`Y = (aX + b)^g` above `d`,
+    /// `cX` below).
+    Parametric(&'static [f64]),
+    /// `curv` table, linear output in `0..=65535`.
+    Sampled(Vec<u16>),
+}
+
+impl Trc {
+    fn encode(&self) -> Vec<u8> {
+        let mut tag = Vec::new();
+        match self {
+            Self::Parametric(params) => {
+                tag.extend_from_slice(b"para");
+                tag.extend_from_slice(&[0; 4]);
+                let function: u16 = if params.len() == 1 { 0 } else { 3 };
+                tag.extend_from_slice(&function.to_be_bytes());
+                tag.extend_from_slice(&[0; 2]);
+                for &p in *params {
+                    tag.extend_from_slice(&s15_fixed16(p).to_be_bytes());
+                }
+            }
+            Self::Sampled(table) => {
+                tag.extend_from_slice(b"curv");
+                tag.extend_from_slice(&[0; 4]);
+                tag.extend_from_slice(&(table.len() as u32).to_be_bytes());
+                for v in table {
+                    tag.extend_from_slice(&v.to_be_bytes());
+                }
+            }
+        }
+        tag
+    }
+}
+
+/// BT.709 OETF inverted: `(g, a, b, c, d)` for `para` type 3.
+const BT709_PARAMS: [f64; 5] = [
+    1.0 / 0.45,
+    1.0 / 1.099_296_826_809_44,
+    0.099_296_826_809_44 / 1.099_296_826_809_44,
+    1.0 / 4.5,
+    0.081_242_858_298_635_3,
+];
+
+/// SMPTE 240M OETF inverted.
+const SMPTE240_PARAMS: [f64; 5] = [1.0 / 0.45, 1.0 / 1.1115, 0.1115 / 1.1115, 1.0 / 4.0, 0.0912];
+
+/// IEC 61966-2-1 sRGB EOTF.
+const SRGB_PARAMS: [f64; 5] = [2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.040_45];
+
+fn cicp_trc(transfer: u8, mastering: Option<&MasteringDisplay>) -> Option<Trc> {
+    Some(match transfer {
+        1 | 6 | 14 | 15 => Trc::Parametric(&BT709_PARAMS),
+        4 => Trc::Parametric(&[2.2]),
+        5 => Trc::Parametric(&[2.8]),
+        7 => Trc::Parametric(&SMPTE240_PARAMS),
+        8 => Trc::Parametric(&[1.0]),
+        13 => Trc::Parametric(&SRGB_PARAMS),
+        16 => {
+            let peak = mastering
+                .map(|m| f64::from(m.max_luminance))
+                .filter(|&peak| peak > 0.0)
+                .unwrap_or(10_000.0);
+            Trc::Sampled(sample_curve(|e| pq_eotf(e) * 10_000.0 / peak))
+        }
+        18 => Trc::Sampled(sample_curve(hlg_inverse_oetf)),
+        _ => return None,
+    })
+}
+
+fn sample_curve(f: impl Fn(f64) -> f64) -> Vec<u16> {
+    (0..CURVE_ENTRIES)
+        .map(|i| {
+            let e = i as f64 / (CURVE_ENTRIES - 1) as f64;
+            (f(e).clamp(0.0, 1.0) * 65535.0 + 0.5) as u16
+        })
+        .collect()
+}
+
+/// SMPTE ST 2084 EOTF, normalized to 10 000 cd/m².
+fn pq_eotf(e: f64) -> f64 {
+    const M1: f64 = 2610.0 / 16384.0;
+    const M2: f64 = 2523.0 / 4096.0 * 128.0;
+    const C1: f64 = 3424.0 / 4096.0;
+    const C2: f64 = 2413.0 / 4096.0 * 32.0;
+    const C3: f64 = 2392.0 / 4096.0 * 32.0;
+    let p = e.powf(1.0 / M2);
+    ((p - C1).max(0.0) / (C2 - C3 * p)).powf(1.0 / M1)
+}
+
+/// ARIB STD-B67 (HLG) inverse OETF, scene-linear `0..=1`.
+fn hlg_inverse_oetf(e: f64) -> f64 {
+    const A: f64 = 0.178_832_77;
+    const B: f64 = 1.0 - 4.0 * A;
+    const C: f64 = 0.559_910_73;
+    if e <= 0.5 {
+        e * e / 3.0
+    } else {
+        (((e - C) / A).exp() + B) / 12.0
+    }
+}
+
+/// xy chromaticities `([r, g, b], white)` for an ITU-T H.273 primaries code.
+fn cicp_chromaticities(code: u8) -> Option<([[f64; 2]; 3], [f64; 2])> {
+    const D65: [f64; 2] = [0.3127, 0.3290];
+    const ILLUMINANT_C: [f64; 2] = [0.310, 0.316];
+    const DCI: [f64; 2] = [0.314, 0.351];
+    const P3: [[f64; 2]; 3] = [[0.680, 0.320], [0.265, 0.690], [0.150, 0.060]];
+    Some(match code {
+        1 => ([[0.64, 0.33], [0.30, 0.60], [0.15, 0.06]], D65),
+        4 => ([[0.67, 0.33], [0.21, 0.71], [0.14, 0.08]], ILLUMINANT_C),
+        5 => ([[0.64, 0.33], [0.29, 0.60], [0.15, 0.06]], D65),
+        6 | 7 => ([[0.630, 0.340], [0.310, 0.595], [0.155, 0.070]], D65),
+        8 => (
+            [[0.681, 0.319], [0.243, 0.692], [0.145, 0.049]],
+            ILLUMINANT_C,
+        ),
+        9 => ([[0.708, 0.292], [0.170, 0.797], [0.131, 0.046]], D65),
+        11 => (P3, DCI),
+        12 => (P3, D65),
+        22 => ([[0.630, 0.340], [0.295, 0.605], [0.155, 0.077]], D65),
+        _ => return None,
+    })
+}
+
+type Mat3 = [[f64; 3]; 3];
+
+fn xy_to_xyz([x, y]: [f64; 2]) -> [f64; 3] {
+    [x / y, 1.0, (1.0 - x - y) / y]
+}
+
+/// Linear RGB → XYZ matrix for the given primaries and white point.
+fn rgb_to_xyz(primaries: [[f64; 2]; 3], white: [f64; 2]) -> Option<Mat3> {
+    let [r, g, b] = primaries.map(xy_to_xyz);
+    let m = [[r[0], g[0], b[0]], [r[1], g[1], b[1]], [r[2], g[2], b[2]]];
+    let s = mat_vec(&mat_inverse(&m)?, xy_to_xyz(white));
+    Some(m.map(|row| [row[0] * s[0], row[1] * s[1], row[2] * s[2]]))
+}
+
+/// Bradford chromatic adaptation from white `src` to white `dst` (XYZ).
+fn bradford(src: [f64; 3], dst: [f64; 3]) -> Option<Mat3> {
+    const M: Mat3 = [
+        [0.8951, 0.2664, -0.1614],
+        [-0.7502, 1.7135, 0.0367],
+        [0.0389, -0.0685, 1.0296],
+    ];
+    let s = mat_vec(&M, src);
+    let d = mat_vec(&M, dst);
+    let scale = [
+        [d[0] / s[0], 0.0, 0.0],
+        [0.0, d[1] / s[1], 0.0],
+        [0.0, 0.0, d[2] / s[2]],
+    ];
+    Some(mat_mul(&mat_inverse(&M)?, &mat_mul(&scale, &M)))
+}
+
+fn mat_mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    core::array::from_fn(|i| core::array::from_fn(|j| (0..3).map(|k| a[i][k] * b[k][j]).sum()))
+}
+
+fn mat_vec(m: &Mat3, v: [f64; 3]) -> [f64; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+fn mat_inverse(m: &Mat3) -> Option<Mat3> {
+    let cofactor =
+        |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let adj = [
+        [
+            cofactor(1, 2, 1, 2),
+            -cofactor(0, 2, 1, 2),
+            cofactor(0, 1, 1, 2),
+        ],
+        [
+            -cofactor(1, 2, 0, 2),
+            cofactor(0, 2, 0, 2),
+            -cofactor(0, 1, 0, 2),
+        ],
+        [
+            cofactor(1, 2, 0, 1),
+            -cofactor(0, 2, 0, 1),
+            cofactor(0, 1, 0, 1),
+        ],
+    ];
+    let det = m[0][0] * adj[0][0] + m[0][1] * adj[1][0] + m[0][2] * adj[2][0];
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    Some(adj.map(|row| row.map(|v| v / det)))
+}
+
+fn s15_fixed16(v: f64) -> i32 {
+    (v * 65536.0).round() as i32
+}
+
+fn xyz_tag(xyz: [f64; 3]) -> Vec<u8> {
+    let mut tag = Vec::with_capacity(20);
+    tag.extend_from_slice(b"XYZ ");
+    tag.extend_from_slice(&[0; 4]);
+    for v in xyz {
+        tag.extend_from_slice(&s15_fixed16(v).to_be_bytes());
+    }
+    tag
+}
+
+fn sf32_tag(m: &Mat3) -> Vec<u8> {
+    let mut tag = Vec::with_capacity(44);
+    tag.extend_from_slice(b"sf32");
+    tag.extend_from_slice(&[0; 4]);
+    for v in m.iter().flatten() {
+        tag.extend_from_slice(&s15_fixed16(*v).to_be_bytes());
+    }
+    tag
+}
+
+/// `multiLocalizedUnicodeType` with a single `en-US` record.
+fn mluc(text: &str) -> Vec<u8> {
+    let utf16: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+    let mut tag = Vec::with_capacity(28 + utf16.len());
+    tag.extend_from_slice(b"mluc");
+    tag.extend_from_slice(&[0; 4]);
+    tag.extend_from_slice(&1u32.to_be_bytes()); // records
+    tag.extend_from_slice(&12u32.to_be_bytes()); // record size
+    tag.extend_from_slice(b"enUS");
+    tag.extend_from_slice(&(utf16.len() as u32).to_be_bytes());
+    tag.extend_from_slice(&28u32.to_be_bytes());
+    tag.extend_from_slice(&utf16);
+    tag
+}
+
+// ── Canonical profiles ─────────────────────────────────────────────────────
+
+/// CICP equivalent of a well-known RGB profile, when swapping it in is lossless.
+///
+/// Returns `Some` when [`zenpixels::icc::identify_common`] recognizes `icc`
+/// as safe for matrix/TRC substitution and both its primaries and transfer
+/// have CICP codes. Formats that signal CICP can carry the result (matrix 0,
+/// full range) instead of the profile; for the others, [`icc_from_cicp`]
+/// builds a compact stand-in, typically a fraction of the size of the vendor
+/// profiles it replaces. Grayscale profiles and spaces without CICP codes
+/// (Adobe RGB) return `None`.
+///
+/// See [`Metadata::canonicalize_icc()`](crate::Metadata::canonicalize_icc).
+pub fn canonical_cicp(icc: &[u8]) -> Option<Cicp> {
+    let id = zenpixels::icc::identify_common(icc)?;
+    if id.valid_use != zenpixels::icc::IdentificationUse::MatrixTrcSubstitution {
+        return None;
+    }
+    if IccReader::new(icc)?.color_space() != IccColorSpace::Rgb {
+        return None;
+    }
+    id.to_cicp()
+}
+
+// ── Profile reader ─────────────────────────────────────────────────────────
+
+/// ICC header size in bytes.
+const HEADER_SIZE: usize = 128;
+/// Maximum tag count accepted by [`IccReader::new`] (DoS protection).
+///
+/// Profiles declaring more tags than this are rejected.
+pub const MAX_TAG_COUNT: u32 = 1000;
+
+/// ICC data color space or PCS signature (header bytes 16 and 20).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum IccColorSpace {
+    /// `RGB `.
+    Rgb,
+    /// `GRAY`.
+    Gray,
+    /// `CMYK`.
+    Cmyk,
+    /// `CMY `.
+    Cmy,
+    /// `Lab ` (CIELAB, also a PCS).
+    Lab,
+    /// `XYZ ` (CIEXYZ, also a PCS).
+    Xyz,
+    /// `YCbr`.
+    YCbCr,
+    /// Any other signature.
+    Other([u8; 4]),
+}
+
+impl IccColorSpace {
+    fn from_signature(sig: [u8; 4]) -> Self {
+        match &sig {
+            b"RGB " => Self::Rgb,
+            b"GRAY" => Self::Gray,
+            b"CMYK" => Self::Cmyk,
+            b"CMY " => Self::Cmy,
+            b"Lab " => Self::Lab,
+            b"XYZ " => Self::Xyz,
+            b"YCbr" => Self::YCbCr,
+            _ => Self::Other(sig),
+        }
+    }
+}
+
+/// ICC profile/device class (header byte 12).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum IccProfileClass {
+    /// `scnr` — input device (camera, scanner).
+    Input,
+    /// `mntr` — display device.
+    Display,
+    /// `prtr` — output device (printer).
+    Output,
+    /// `link` — device link.
+    DeviceLink,
+    /// `spac` — color space conversion.
+    ColorSpace,
+    /// `abst` — abstract.
+    Abstract,
+    /// `nmcl` — named color.
+    NamedColor,
+    /// Any other signature.
+    Other([u8; 4]),
+}
+
+impl IccProfileClass {
+    fn from_signature(sig: [u8; 4]) -> Self {
+        match &sig {
+            b"scnr" => Self::Input,
+            b"mntr" => Self::Display,
+            b"prtr" => Self::Output,
+            b"link" => Self::DeviceLink,
+            b"spac" => Self::ColorSpace,
+            b"abst" => Self::Abstract,
+            b"nmcl" => Self::NamedColor,
+            _ => Self::Other(sig),
+        }
+    }
+}
+
+/// Text from a `desc`, `mluc`, or `text` tag, borrowing from the profile.
+///
+/// v2 `textDescriptionType` and `textType` hold ASCII; v4
+/// `multiLocalizedUnicodeType` holds UTF-16BE. Both end at the first NUL.
+/// Format with `Display` or walk [`chars()`](Self::chars).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IccText<'a> {
+    bytes: &'a [u8],
+    utf16: bool,
+}
+
+impl<'a> IccText<'a> {
+    /// Whether the text is UTF-16BE (`mluc`) rather than ASCII.
+    pub fn is_utf16(&self) -> bool {
+        self.utf16
+    }
+
+    /// Decode the text. Unpaired surrogates become U+FFFD; ASCII tags are
+    /// read as Latin-1.
+    pub fn chars(&self) -> impl Iterator<Item = char> + 'a {
+        let (latin1, utf16): (&[u8], &[u8]) = if self.utf16 {
+            (&[], self.bytes)
+        } else {
+            (self.bytes, &[])
+        };
+        let units = utf16
+            .chunks_exact(2)
+            .map(|unit| u16::from_be_bytes([unit[0], unit[1]]));
+        latin1
+            .iter()
+            .map(|&b| char::from(b))
+            .chain(char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)))
+    }
+}
+
+impl core::fmt::Display for IccText<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use core::fmt::Write;
+        self.chars().try_for_each(|c| f.write_char(c))
+    }
+}
+
+/// A tone response curve (`curv` or `para` tag).
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum IccCurve<'a> {
+    /// `curv` with no entries: `Y = X`.
+    Identity,
+    /// `curv` with one `u8Fixed8` entry: `Y = X^gamma`.
+    Gamma(f64),
+    /// `curv` table of big-endian `u16` entries, `0..=65535` over
+    /// `X` in `0..=1`, linearly interpolated.
+    Table(&'a [u8]),
+    /// `para` function type 0–4 with its `g, a, b, c, d, e, f` parameters
+    /// (unused ones are 0).
+    Parametric {
+        /// ICC function type (0: `X^g`, 3: sRGB-style with linear toe, ...).
+        function: u16,
+        /// `[g, a, b, c, d, e, f]`.
+        params: [f64; 7],
+    },
+}
+
+impl IccCurve<'_> {
+    /// Evaluate the curve at `x` in `0..=1`.
+    pub fn eval(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        match *self {
+            Self::Identity => x,
+            Self::Gamma(gamma) => x.powf(gamma),
+            Self::Table(table) => {
+                let entry = |i: usize| {
+                    f64::from(u16::from_be_bytes([table[2 * i], table[2 * i + 1]])) / 65535.0
+                };
+                match table.len() / 2 {
+                    0 => x,
+                    1 => entry(0),
+                    entries => {
+                        let pos = x * (entries - 1) as f64;
+                        let i = (pos as usize).min(entries - 2);
+                        entry(i) + (entry(i + 1) - entry(i)) * (pos - i as f64)
+                    }
+                }
+            }
+            Self::Parametric { function, params } => {
+                let [g, a, b, c, d, e, f] = params;
+                let power = |x: f64| {
+                    let base = a * x + b;
+                    if base > 0.0 { base.powf(g) } else { 0.0 }
+                };
+                match function {
+                    0 => x.powf(g),
+                    1 if x >= -b / a => power(x),
+                    1 => 0.0,
+                    2 if x >= -b / a => power(x) + c,
+                    2 => c,
+                    3 if x >= d => power(x),
+                    3 => c * x,
+                    4 if x >= d => power(x) + e,
+                    _ => c * x + f,
+                }
+            }
+        }
+    }
+
+    /// The exponent of a pure power curve (`Identity`, `Gamma`, or `para`
+    /// type 0).
+    pub fn gamma(&self) -> Option<f64> {
+        match *self {
+            Self::Identity => Some(1.0),
+            Self::Gamma(gamma) => Some(gamma),
+            Self::Parametric {
+                function: 0,
+                params,
+            } => Some(params[0]),
+            _ => None,
+        }
+    }
+}
+
+/// One tag table entry. See [`IccReader::tags`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IccTag<'a> {
+    /// Tag signature, e.g. `*b"rTRC"`.
+    pub signature: [u8; 4],
+    /// The tag data, or `None` if it lies outside the profile.
+    pub data: Option<&'a [u8]>,
+}
+
+impl IccTag<'_> {
+    /// The type signature of the tag data (its first four bytes).
+    pub fn type_signature(&self) -> Option<[u8; 4]> {
+        self.data?.get(..4)?.try_into().ok()
+    }
+}
+
+/// Allocation-free reader over an ICC profile's header and tag table.
+///
+/// Reports what [`zenpixels::icc::identify_common`] can't for unknown
+/// profiles: description, class, color space, PCS, version, whether the
+/// profile is matrix/TRC or LUT based, colorants and primaries, and TRCs.
+/// Every read is bounds-checked; malformed tags read as absent.
+///
+/// ```
+/// use zencodec::Cicp;
+/// use zencodec::helpers::icc::{IccColorSpace, IccReader, icc_from_cicp};
+///
+/// let icc = icc_from_cicp(Cicp::DISPLAY_P3, None).unwrap();
+/// let reader = IccReader::new(&icc).unwrap();
+/// assert_eq!(reader.color_space(), IccColorSpace::Rgb);
+/// assert!(reader.is_matrix_trc());
+/// let (primaries, _white) = reader.primaries_xy().unwrap();
+/// assert!((primaries[0][0] - 0.680).abs() < 1e-3);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct IccReader<'a> {
+    data: &'a [u8],
+    tag_count: u32,
+}
+
+impl<'a> IccReader<'a> {
+    /// Validate the header and tag table of `data`.
+    ///
+    /// Returns `None` if the `acsp` signature is missing, the declared size
+    /// is below the header or beyond `data`, or the tag table is truncated
+    /// or lists more than [`MAX_TAG_COUNT`] tags. Bytes past the declared
+    /// size are ignored.
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let size = be_u32(data, 0)? as usize;
+        if size < HEADER_SIZE + 4 || size > data.len() || data.get(36..40)? != b"acsp" {
+            return None;
+        }
+        let data = &data[..size];
+        let tag_count = be_u32(data, HEADER_SIZE)?;
+        if tag_count > MAX_TAG_COUNT || HEADER_SIZE + 4 + 12 * tag_count as usize > size {
+            return None;
+        }
+        Some(Self { data, tag_count })
+    }
+
+    /// The profile bytes, up to the declared size.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Profile version as `(major, minor, bugfix)`, e.g. `(4, 4, 0)`.
+    pub fn version(&self) -> (u8, u8, u8) {
+        (self.data[8], self.data[9] >> 4, self.data[9] & 0x0F)
+    }
+
+    /// Profile/device class.
+    pub fn class(&self) -> IccProfileClass {
+        IccProfileClass::from_signature(self.signature_at(12))
+    }
+
+    /// Color space of the device data.
+    pub fn color_space(&self) -> IccColorSpace {
+        IccColorSpace::from_signature(self.signature_at(16))
+    }
+
+    /// Profile connection space ([`Xyz`](IccColorSpace::Xyz) or
+    /// [`Lab`](IccColorSpace::Lab) for all but device links).
+    pub fn pcs(&self) -> IccColorSpace {
+        IccColorSpace::from_signature(self.signature_at(20))
+    }
+
+    /// Rendering intent from the header (0 perceptual, 1 relative
+    /// colorimetric, 2 saturation, 3 absolute colorimetric).
+    pub fn rendering_intent(&self) -> u32 {
+        be_u32(self.data, 64).unwrap_or(0)
+    }
+
+    /// Iterate the tag table.
+    pub fn tags(&self) -> impl Iterator<Item = IccTag<'a>> + 'a {
+        let data = self.data;
+        (0..self.tag_count as usize).map(move |i| {
+            let entry = HEADER_SIZE + 4 + 12 * i;
+            let signature = data[entry..entry + 4].try_into().unwrap_or([0; 4]);
+            let offset = be_u32(data, entry + 4).unwrap_or(0) as usize;
+            let len = be_u32(data, entry + 8).unwrap_or(0) as usize;
+            let data = offset
+                .checked_add(len)
+                .and_then(|end| data.get(offset..end))
+                .filter(|_| offset >= HEADER_SIZE);
+            IccTag { signature, data }
+        })
+    }
+
+    /// Data of the first tag with `signature`, if present and in bounds.
+    pub fn find(&self, signature: &[u8; 4]) -> Option<&'a [u8]> {
+        self.tags().find(|t| &t.signature == signature)?.data
+    }
+
+    /// Whether a tag with `signature` is listed.
+    pub fn has_tag(&self, signature: &[u8; 4]) -> bool {
+        self.tags().any(|t| &t.signature == signature)
+    }
+
+    /// Profile description (`desc`).
+    pub fn description(&self) -> Option<IccText<'a>> {
+        self.text(b"desc")
+    }
+
+    /// Copyright notice (`cprt`).
+    pub fn copyright(&self) -> Option<IccText<'a>> {
+        self.text(b"cprt")
+    }
+
+    /// Text of a `desc`, `mluc`, or `text` typed tag. For `mluc`, the
+    /// first English record wins, else the first record.
+    pub fn text(&self, signature: &[u8; 4]) -> Option<IccText<'a>> {
+        let tag = self.find(signature)?;
+        match tag.get(..4)? {
+            b"desc" => {
+                let len = be_u32(tag, 8)? as usize;
+                let ascii = tag.get(12..12usize.checked_add(len)?)?;
+                Some(IccText {
+                    bytes: until_nul(ascii, 1),
+                    utf16: false,
+                })
+            }
+            b"text" => Some(IccText {
+                bytes: until_nul(tag.get(8..)?, 1),
+                utf16: false,
+            }),
+            b"mluc" => {
+                let count = be_u32(tag, 8)? as usize;
+                let record_size = be_u32(tag, 12)? as usize;
+                if record_size < 12 {
+                    return None;
+                }
+                let record = |i: usize| {
+                    let at = 16usize.checked_add(i.checked_mul(record_size)?)?;
+                    let lang = tag.get(at..at + 2)?;
+                    let len = be_u32(tag, at + 4)? as usize;
+                    let offset = be_u32(tag, at + 8)? as usize;
+                    let text = tag.get(offset..offset.checked_add(len)?)?;
+                    Some((lang, text))
+                };
+                let records = (0..count).map_while(record);
+                let text = records
+                    .clone()
+                    .find(|(lang, _)| *lang == b"en")
+                    .or_else(|| records.clone().next())?
+                    .1;
+                Some(IccText {
+                    bytes: until_nul(text, 2),
+                    utf16: true,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The value of an `XYZ ` typed tag (first entry).
+    pub fn xyz(&self, signature: &[u8; 4]) -> Option<[f64; 3]> {
+        let tag = self.find(signature)?;
+        if tag.get(..4)? != b"XYZ " {
+            return None;
+        }
+        Some([s15(tag, 8)?, s15(tag, 12)?, s15(tag, 16)?])
+    }
+
+    /// The `rXYZ`/`gXYZ`/`bXYZ` colorants as matrix columns (PCS-relative,
+    /// normally D50-adapted).
+    pub fn colorants(&self) -> Option<[[f64; 3]; 3]> {
+        let [r, g, b] = [self.xyz(b"rXYZ")?, self.xyz(b"gXYZ")?, self.xyz(b"bXYZ")?];
+        Some([[r[0], g[0], b[0]], [r[1], g[1], b[1]], [r[2], g[2], b[2]]])
+    }
+
+    /// The chromatic adaptation matrix (`chad`), row-major.
+    pub fn chromatic_adaptation(&self) -> Option<[[f64; 3]; 3]> {
+        let tag = self.find(b"chad")?;
+        if tag.get(..4)? != b"sf32" {
+            return None;
+        }
+        let mut m = [[0.0; 3]; 3];
+        for (i, v) in m.iter_mut().flatten().enumerate() {
+            *v = s15(tag, 8 + 4 * i)?;
+        }
+        Some(m)
+    }
+
+    /// CIE xy chromaticities of the RGB primaries and the white point, as
+    /// `([r, g, b], white)`.
+    ///
+    /// The D50 colorants are mapped back to the device white through the
+    /// inverse of `chad`. Profiles without `chad` (most v2) are assumed to
+    /// have Bradford-adapted colorants when `wtpt` differs from D50.
+    pub fn primaries_xy(&self) -> Option<([[f64; 2]; 3], [f64; 2])> {
+        let colorants = self.colorants()?;
+        let to_device = match self.chromatic_adaptation() {
+            Some(chad) => mat_inverse(&chad)?,
+            None => match self.xyz(b"wtpt") {
+                Some(white) if (0..3).any(|c| (white[c] - D50_XYZ[c]).abs() > 1e-3) => {
+                    bradford(D50_XYZ, white)?
+                }
+                _ => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            },
+        };
+        let device = mat_mul(&to_device, &colorants);
+        let column = |c: usize| xyz_to_xy([device[0][c], device[1][c], device[2][c]]);
+        let white = xyz_to_xy(mat_vec(&to_device, D50_XYZ))?;
+        Some(([column(0)?, column(1)?, column(2)?], white))
+    }
+
+    /// A `curv` or `para` typed tag.
+    pub fn curve(&self, signature: &[u8; 4]) -> Option<IccCurve<'a>> {
+        let tag = self.find(signature)?;
+        match tag.get(..4)? {
+            b"curv" => {
+                let count = be_u32(tag, 8)? as usize;
+                match count {
+                    0 => Some(IccCurve::Identity),
+                    1 => Some(IccCurve::Gamma(f64::from(be_u16(tag, 12)?) / 256.0)),
+                    _ => Some(IccCurve::Table(
+                        tag.get(12..12usize.checked_add(count.checked_mul(2)?)?)?,
+                    )),
+                }
+            }
+            b"para" => {
+                let function = be_u16(tag, 8)?;
+                let count = *[1, 3, 4, 5, 7].get(usize::from(function))?;
+                let mut params = [0.0; 7];
+                for (i, p) in params.iter_mut().take(count).enumerate() {
+                    *p = s15(tag, 12 + 4 * i)?;
+                }
+                Some(IccCurve::Parametric { function, params })
+            }
+            _ => None,
+        }
+    }
+
+    /// The `rTRC`, `gTRC`, `bTRC` curves.
+    pub fn rgb_trc(&self) -> Option<[IccCurve<'a>; 3]> {
+        Some([
+            self.curve(b"rTRC")?,
+            self.curve(b"gTRC")?,
+            self.curve(b"bTRC")?,
+        ])
+    }
+
+    /// The `kTRC` curve of a grayscale profile.
+    pub fn gray_trc(&self) -> Option<IccCurve<'a>> {
+        self.curve(b"kTRC")
+    }
+
+    /// Whether the profile can be used as matrix/TRC (RGB colorants and
+    /// TRCs) or gray TRC.
+    pub fn is_matrix_trc(&self) -> bool {
+        match self.color_space() {
+            IccColorSpace::Rgb => self.colorants().is_some() && self.rgb_trc().is_some(),
+            IccColorSpace::Gray => self.gray_trc().is_some(),
+            _ => false,
+        }
+    }
+
+    /// Whether the profile carries any `A2Bx`, `B2Ax`, `D2Bx`, or `B2Dx`
+    /// LUT tag. CMMs prefer these over matrix/TRC when both exist.
+    pub fn is_lut_based(&self) -> bool {
+        self.tags().any(|t| {
+            matches!(&t.signature[..3], b"A2B" | b"B2A" | b"D2B" | b"B2D")
+                && t.signature[3].is_ascii_digit()
+        })
+    }
+
+    /// The `cicp` tag (ICC v4.4+).
+    pub fn cicp(&self) -> Option<Cicp> {
+        let tag = self.find(b"cicp")?;
+        if tag.get(..4)? != b"cicp" {
+            return None;
+        }
+        let v = tag.get(8..12)?;
+        Some(Cicp::new(v[0], v[1], v[2], v[3] != 0))
+    }
+
+    fn signature_at(&self, at: usize) -> [u8; 4] {
+        self.data[at..at + 4].try_into().unwrap_or([0; 4])
+    }
+}
+
+fn be_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn s15(data: &[u8], at: usize) -> Option<f64> {
+    Some(f64::from(be_u32(data, at)? as i32) / 65536.0)
+}
+
+/// Cut `bytes` at the first all-zero code unit of `unit` bytes.
+fn until_nul(bytes: &[u8], unit: usize) -> &[u8] {
+    let end = bytes
+        .chunks_exact(unit)
+        .position(|c| c.iter().all(|&b| b == 0))
+        .map_or(bytes.len() - bytes.len() % unit, |i| i * unit);
+    &bytes[..end]
+}
+
+fn xyz_to_xy([x, y, z]: [f64; 3]) -> Option<[f64; 2]> {
+    let sum = x + y + z;
+    (sum.abs() > 1e-12).then(|| [x / sum, y / sum])
+}
+
+// ── Validation ─────────────────────────────────────────────────────────────
+
+/// Why an ICC profile failed [`validate_icc`] or [`sanitize_icc`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IccValidationError {
+    /// Shorter than the 128-byte header plus tag count.
+    TooShort {
+        /// Length of the data.
+        len: usize,
+    },
+    /// The `acsp` signature at offset 36 is missing.
+    MissingSignature,
+    /// The tag count exceeds [`MAX_TAG_COUNT`].
+    TooManyTags {
+        /// Declared tag count.
+        count: u32,
+    },
+    /// The tag table runs past the end of the data.
+    TagTableTruncated {
+        /// Declared tag count.
+        count: u32,
+    },
+    /// A tag's data starts inside the header or tag table.
+    TagInHeader {
+        /// Tag signature.
+        signature: [u8; 4],
+    },
+    /// A tag's data runs past the end of the data.
+    TagOutOfBounds {
+        /// Tag signature.
+        signature: [u8; 4],
+    },
+    /// Two tags partially overlap. Tags sharing one offset are allowed.
+    OverlappingTags {
+        /// The tag with the lower offset.
+        first: [u8; 4],
+        /// The tag starting inside `first`.
+        second: [u8; 4],
+    },
+    /// The size field in the header does not match the data length.
+    /// [`sanitize_icc`] repairs this.
+    SizeMismatch {
+        /// Size declared in the header.
+        declared: u32,
+        /// Actual length of the data.
+        actual: usize,
+    },
+}
+
+impl core::fmt::Display for IccValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooShort { len } => write!(f, "ICC profile too short ({len} bytes)"),
+            Self::MissingSignature => f.write_str("ICC profile missing 'acsp' signature"),
+            Self::TooManyTags { count } => {
+                write!(f, "ICC profile declares {count} tags (max {MAX_TAG_COUNT})")
+            }
+            Self::TagTableTruncated { count } => {
+                write!(f, "ICC tag table with {count} tags is truncated")
+            }
+            Self::TagInHeader { signature } => write!(
+                f,
+                "ICC tag '{}' points into the header or tag table",
+                signature.escape_ascii()
+            ),
+            Self::TagOutOfBounds { signature } => write!(
+                f,
+                "ICC tag '{}' extends past the end of the profile",
+                signature.escape_ascii()
+            ),
+            Self::OverlappingTags { first, second } => write!(
+                f,
+                "ICC tags '{}' and '{}' overlap",
+                first.escape_ascii(),
+                second.escape_ascii()
+            ),
+            Self::SizeMismatch { declared, actual } => write!(
+                f,
+                "ICC profile declares {declared} bytes but is {actual} bytes long"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for IccValidationError {}
+
+/// Check that `data` is a structurally sound ICC profile.
+///
+/// Verifies the header length, `acsp` signature, tag count (at most
+/// [`MAX_TAG_COUNT`]), that the tag table and every tag lie within the data
+/// and after the table, that no two tags partially overlap (tags may share
+/// one offset), and that the header size field equals `data.len()`.
+///
+/// Only structure is checked, not tag contents: an untrusted profile that
+/// passes can still be meaningless, but it can't point a parser outside
+/// itself.
+pub fn validate_icc(data: &[u8]) -> Result<(), IccValidationError> {
+    check_structure(data)?;
+    let declared = be_u32(data, 0).unwrap_or(0);
+    if declared as usize != data.len() {
+        return Err(IccValidationError::SizeMismatch {
+            declared,
+            actual: data.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Validate `data` like [`validate_icc`], repairing what can be repaired.
+///
+/// A header size that disagrees with the data is fixed: trailing bytes past
+/// a plausible declared size are trimmed, and otherwise the profile is cut
+/// to the end of its last tag, zero-padded to a multiple of 4, with the
+/// size field rewritten. A repaired profile has its profile ID (header bytes
+/// 84..100) zeroed, since the MD5 it held no longer matches. Returns the
+/// input unchanged (borrowed) when it was already valid, and an error for
+/// structural damage.
+pub fn sanitize_icc(data: &[u8]) -> Result<Cow<'_, [u8]>, IccValidationError> {
+    let required = check_structure(data)?;
+    let declared = be_u32(data, 0).unwrap_or(0) as usize;
+    let mut repaired = if (required..=data.len()).contains(&declared) {
+        if declared == data.len() {
+            return Ok(Cow::Borrowed(data));
+        }
+        data[..declared].to_vec()
+    } else {
+        // Profiles are a whole number of 4-byte words; pad with zeros.
+        let len = required.next_multiple_of(4);
+        let mut repaired = Vec::with_capacity(len);
+        repaired.extend_from_slice(&data[..required]);
+        repaired.resize(len, 0);
+        repaired[..4].copy_from_slice(&(len as u32).to_be_bytes());
+        repaired
+    };
+    repaired[84..100].fill(0);
+    Ok(Cow::Owned(repaired))
+}
+
+/// Structural checks shared by [`validate_icc`] and [`sanitize_icc`].
+/// Returns the minimum length covering the tag table and all tag data.
+fn check_structure(data: &[u8]) -> Result<usize, IccValidationError> {
+    if data.len() < HEADER_SIZE + 4 {
+        return Err(IccValidationError::TooShort { len: data.len() });
+    }
+    if &data[36..40] != b"acsp" {
+        return Err(IccValidationError::MissingSignature);
+    }
+    let count = be_u32(data, HEADER_SIZE).unwrap_or(0);
+    if count > MAX_TAG_COUNT {
+        return Err(IccValidationError::TooManyTags { count });
+    }
+    let table_end = HEADER_SIZE + 4 + 12 * count as usize;
+    if table_end > data.len() {
+        return Err(IccValidationError::TagTableTruncated { count });
+    }
+
+    let mut spans: Vec<([u8; 4], usize, usize)> = Vec::with_capacity(count as usize);
+    let mut required = table_end;
+    for entry in data[HEADER_SIZE + 4..table_end].chunks_exact(12) {
+        let signature: [u8; 4] = entry[..4].try_into().unwrap_or([0; 4]);
+        let offset = be_u32(entry, 4).unwrap_or(0) as usize;
+        let end = offset.saturating_add(be_u32(entry, 8).unwrap_or(0) as usize);
+        if offset < table_end {
+            return Err(IccValidationError::TagInHeader { signature });
+        }
+        if end > data.len() {
+            return Err(IccValidationError::TagOutOfBounds { signature });
+        }
+        required = required.max(end);
+        spans.push((signature, offset, end));
+    }
+
+    // Walk by offset, tracking the furthest-reaching span that starts
+    // earlier; a tag starting before it ends overlaps it.
+    spans.sort_unstable_by_key(|&(_, offset, end)| (offset, end));
+    let mut reach: Option<([u8; 4], usize)> = None;
+    let mut group: Option<([u8; 4], usize, usize)> = None;
+    for (signature, offset, end) in spans {
+        if let Some((sig, group_offset, group_end)) = group
+            && group_offset != offset
+        {
+            if reach.is_none_or(|(_, reach_end)| group_end > reach_end) {
+                reach = Some((sig, group_end));
+            }
+            group = None;
+        }
+        if let Some((first, reach_end)) = reach
+            && offset < reach_end
+        {
+            return Err(IccValidationError::OverlappingTags {
+                first,
+                second: signature,
+            });
+        }
+        if group.is_none_or(|(_, _, group_end)| end > group_end) {
+            group = Some((signature, offset, end));
+        }
+    }
+    Ok(required)
+}
+
 // ── Deprecated shims (scheduled for removal in next minor release) ─────────
 
 /// Maximum u16 TRC error tolerance for ICC profile identification.
@@ -206,6 +1340,7 @@ pub fn icc_profile_is_srgb(icc_bytes: &[u8]) -> bool {
 #[allow(deprecated)]
 mod tests {
     use super::*;
+    use alloc::string::ToString;
     use alloc::sync::Arc;
     use zenpixels::{AlphaMode, Cicp, ColorPrimaries, SignalRange, TransferFunction};
 
@@ -464,6 +1599,466 @@ mod tests {
         assert_eq!(desc.transfer, TransferFunction::Srgb);
     }
 
+    // ── ICC synthesis from CICP ────────────────────────────────────────
+
+    fn reader(icc: &[u8]) -> IccReader<'_> {
+        IccReader::new(icc).expect("valid profile")
+    }
+
+    #[test]
+    fn icc_from_cicp_srgb_header_and_colorants() {
+        let icc = icc_from_cicp(Cicp::SRGB, None).unwrap();
+        assert_eq!(&icc[36..40], b"acsp");
+        assert_eq!(icc.len() % 4, 0);
+        let r = reader(&icc);
+        assert_eq!(r.data().len(), icc.len());
+        assert_eq!(r.version(), (4, 4, 0));
+        assert_eq!(r.class(), IccProfileClass::Display);
+        assert_eq!(r.color_space(), IccColorSpace::Rgb);
+        assert_eq!(r.pcs(), IccColorSpace::Xyz);
+
+        // Published D50-adapted sRGB colorants, as columns.
+        let expected = [
+            [0.4361, 0.3851, 0.1431],
+            [0.2225, 0.7169, 0.0606],
+            [0.0139, 0.0971, 0.7141],
+        ];
+        let colorants = r.colorants().unwrap();
+        for (got, want) in colorants.iter().zip(expected) {
+            for c in 0..3 {
+                assert!((got[c] - want[c]).abs() < 1e-3, "{colorants:?}");
+            }
+        }
+        for (row, d50) in colorants.iter().zip(D50_XYZ) {
+            assert!((row.iter().sum::<f64>() - d50).abs() < 1e-3);
+        }
+
+        let [trc, _, _] = r.rgb_trc().unwrap();
+        let IccCurve::Parametric { function, params } = trc else {
+            panic!("expected para, got {trc:?}");
+        };
+        assert_eq!(function, 3);
+        assert!((params[0] - 2.4).abs() < 1e-4);
+        assert!(r.cicp().is_none());
+        assert!(!r.has_tag(b"lumi"));
+    }
+
+    #[test]
+    fn icc_from_cicp_shares_trc_data() {
+        let icc = icc_from_cicp(Cicp::DISPLAY_P3, None).unwrap();
+        let r = reader(&icc);
+        let offset = |sig: &[u8; 4]| r.find(sig).unwrap().as_ptr();
+        assert_eq!(offset(b"rTRC"), offset(b"gTRC"));
+        assert_eq!(offset(b"gTRC"), offset(b"bTRC"));
+        assert_ne!(offset(b"rXYZ"), offset(b"gXYZ"));
+    }
+
+    #[test]
+    fn icc_from_cicp_pq_has_cicp_tag_and_curve() {
+        let icc = icc_from_cicp(Cicp::BT2100_PQ, None).unwrap();
+        let cicp = zenpixels::icc::extract_cicp(&icc).unwrap();
+        assert_eq!(
+            (
+                cicp.color_primaries,
+                cicp.transfer_characteristics,
+                cicp.matrix_coefficients,
+                cicp.full_range
+            ),
+            (9, 16, 0, true)
+        );
+        let r = reader(&icc);
+        assert_eq!(r.cicp(), Some(cicp));
+        let Some(IccCurve::Table(table)) = r.curve(b"gTRC") else {
+            panic!("expected curv table");
+        };
+        assert_eq!(table.len(), 2 * CURVE_ENTRIES);
+        assert_eq!(&table[table.len() - 2..], &[0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn icc_from_cicp_pq_normalized_to_mastering_peak() {
+        let mastering = MasteringDisplay::DISPLAY_P3_1000;
+        let icc = icc_from_cicp(Cicp::BT2100_PQ, Some(&mastering)).unwrap();
+        let r = reader(&icc);
+        assert!((r.xyz(b"lumi").unwrap()[1] - 1000.0).abs() < 1e-3);
+
+        // PQ 1000 cd/m² is ≈ 0.7518; the curve clips at the mastering peak.
+        let trc = r.curve(b"rTRC").unwrap();
+        assert!(trc.eval(0.74) < 1.0);
+        assert_eq!(trc.eval(0.76), 1.0);
+        // 100 cd/m² (PQ ≈ 0.5081) is a tenth of the peak.
+        assert!((trc.eval(0.5081) - 0.1).abs() < 2e-3);
+    }
+
+    #[test]
+    fn icc_from_cicp_hlg_and_dci_white() {
+        let hlg = icc_from_cicp(Cicp::BT2100_HLG, None).unwrap();
+        assert_eq!(
+            zenpixels::icc::extract_cicp(&hlg).map(|c| c.transfer_characteristics),
+            Some(18)
+        );
+        // DCI-P3 white is adapted to D50 like any other, and recovered.
+        let dci = icc_from_cicp(Cicp::new(11, 13, 0, true), None).unwrap();
+        let (primaries, white) = reader(&dci).primaries_xy().unwrap();
+        assert!((white[0] - 0.314).abs() < 1e-3 && (white[1] - 0.351).abs() < 1e-3);
+        assert!((primaries[1][1] - 0.690).abs() < 1e-3);
+    }
+
+    #[test]
+    fn icc_from_cicp_rejects_unrepresentable() {
+        assert!(icc_from_cicp(Cicp::new(2, 13, 0, true), None).is_none());
+        assert!(icc_from_cicp(Cicp::new(10, 13, 0, true), None).is_none());
+        assert!(icc_from_cicp(Cicp::new(1, 2, 0, true), None).is_none());
+        assert!(icc_from_cicp(Cicp::new(1, 9, 0, true), None).is_none());
+    }
+
+    #[test]
+    fn icc_from_cicp_is_deterministic() {
+        assert_eq!(
+            icc_from_cicp(Cicp::BT2100_PQ, None),
+            icc_from_cicp(Cicp::BT2100_PQ, None)
+        );
+    }
+
+    // ── Profile reader ─────────────────────────────────────────────────
+
+    /// Minimal profile with the given header signatures and tags.
+    fn build_profile(
+        version: [u8; 2],
+        class: &[u8; 4],
+        space: &[u8; 4],
+        tags: &[(&[u8; 4], &[u8])],
+    ) -> alloc::vec::Vec<u8> {
+        let owned: alloc::vec::Vec<([u8; 4], alloc::vec::Vec<u8>)> = tags
+            .iter()
+            .map(|(sig, data)| (**sig, data.to_vec()))
+            .collect();
+        let mut icc = assemble_profile(&owned);
+        icc[8..10].copy_from_slice(&version);
+        icc[12..16].copy_from_slice(class);
+        icc[16..20].copy_from_slice(space);
+        icc
+    }
+
+    fn v2_desc(text: &str) -> alloc::vec::Vec<u8> {
+        let mut tag = b"desc\0\0\0\0".to_vec();
+        tag.extend_from_slice(&(text.len() as u32 + 1).to_be_bytes());
+        tag.extend_from_slice(text.as_bytes());
+        tag.extend_from_slice(&[0; 1 + 4 + 4 + 2 + 1 + 67]);
+        tag
+    }
+
+    #[test]
+    fn reader_v2_gray_profile() {
+        let desc = v2_desc("Dot Gain 20%");
+        let ktrc = b"curv\0\0\0\0\0\0\0\x01\x02\x33"; // gamma 2.2 (u8Fixed8)
+        let icc = build_profile(
+            [2, 0x10],
+            b"prtr",
+            b"GRAY",
+            &[
+                (b"desc", &desc),
+                (b"kTRC", ktrc),
+                (b"cprt", b"text\0\0\0\0Acme\0"),
+            ],
+        );
+        let r = reader(&icc);
+        assert_eq!(r.version(), (2, 1, 0));
+        assert_eq!(r.class(), IccProfileClass::Output);
+        assert_eq!(r.color_space(), IccColorSpace::Gray);
+        let description = r.description().unwrap();
+        assert!(!description.is_utf16());
+        assert_eq!(description.to_string(), "Dot Gain 20%");
+        assert_eq!(r.copyright().unwrap().to_string(), "Acme");
+        let gamma = r.gray_trc().unwrap().gamma().unwrap();
+        assert!((gamma - 2.2).abs() < 0.01);
+        assert!(r.is_matrix_trc());
+        assert!(!r.is_lut_based());
+        assert!(r.colorants().is_none());
+    }
+
+    #[test]
+    fn reader_mluc_prefers_english() {
+        let mut mluc = b"mluc\0\0\0\0\0\0\0\x02\0\0\0\x0c".to_vec();
+        let de = "Farbprofil".encode_utf16().flat_map(u16::to_be_bytes);
+        let en = "Profile\0".encode_utf16().flat_map(u16::to_be_bytes);
+        let (de, en): (alloc::vec::Vec<u8>, alloc::vec::Vec<u8>) = (de.collect(), en.collect());
+        let base = 16 + 2 * 12;
+        for (lang, text, offset) in [(b"deDE", &de, base), (b"enUS", &en, base + de.len())] {
+            mluc.extend_from_slice(lang);
+            mluc.extend_from_slice(&(text.len() as u32).to_be_bytes());
+            mluc.extend_from_slice(&(offset as u32).to_be_bytes());
+        }
+        mluc.extend_from_slice(&de);
+        mluc.extend_from_slice(&en);
+        let icc = build_profile([4, 0x30], b"scnr", b"CMYK", &[(b"desc", &mluc)]);
+        let r = reader(&icc);
+        assert_eq!(r.class(), IccProfileClass::Input);
+        assert_eq!(r.color_space(), IccColorSpace::Cmyk);
+        let description = r.description().unwrap();
+        assert!(description.is_utf16());
+        assert_eq!(description.to_string(), "Profile");
+        assert!(!r.is_matrix_trc());
+    }
+
+    #[test]
+    fn reader_lut_profile() {
+        let icc = build_profile(
+            [4, 0x20],
+            b"spac",
+            b"Lab ",
+            &[(b"A2B0", b"mAB \0\0\0\0"), (b"B2A0", b"mBA \0\0\0\0")],
+        );
+        let r = reader(&icc);
+        assert_eq!(r.class(), IccProfileClass::ColorSpace);
+        assert_eq!(r.color_space(), IccColorSpace::Lab);
+        assert!(r.is_lut_based());
+        assert!(!r.is_matrix_trc());
+        let types: alloc::vec::Vec<_> = r.tags().map(|t| t.type_signature()).collect();
+        assert_eq!(types, [Some(*b"mAB "), Some(*b"mBA ")]);
+    }
+
+    #[test]
+    fn reader_parametric_curves() {
+        let srgb = IccCurve::Parametric {
+            function: 3,
+            params: [
+                2.4,
+                1.0 / 1.055,
+                0.055 / 1.055,
+                1.0 / 12.92,
+                0.04045,
+                0.0,
+                0.0,
+            ],
+        };
+        assert!((srgb.eval(0.5) - 0.214_041).abs() < 1e-5);
+        assert!((srgb.eval(0.02) - 0.02 / 12.92).abs() < 1e-9);
+        assert_eq!(srgb.gamma(), None);
+        let offset = IccCurve::Parametric {
+            function: 4,
+            params: [1.0, 1.0, 0.0, 0.5, 0.5, 0.25, 0.125],
+        };
+        assert_eq!(offset.eval(0.25), 0.25);
+        assert_eq!(offset.eval(0.75), 1.0);
+        assert_eq!(IccCurve::Identity.eval(0.3), 0.3);
+        assert_eq!(IccCurve::Table(&[]).eval(0.3), 0.3);
+        assert_eq!(IccCurve::Table(&[0x80, 0x00]).eval(0.9), 32768.0 / 65535.0);
+    }
+
+    #[test]
+    fn reader_v2_primaries_from_wtpt() {
+        // v2 style: D50-adapted sRGB colorants, D65 wtpt, no chad.
+        let icc = icc_from_cicp(Cicp::SRGB, None).unwrap();
+        let r = reader(&icc);
+        let colorants = r.colorants().unwrap();
+        let column = |c: usize| [colorants[0][c], colorants[1][c], colorants[2][c]];
+        let tags = [
+            (b"rXYZ", xyz_tag(column(0))),
+            (b"gXYZ", xyz_tag(column(1))),
+            (b"bXYZ", xyz_tag(column(2))),
+            (b"wtpt", xyz_tag([0.9505, 1.0, 1.089])),
+        ];
+        let tags: alloc::vec::Vec<(&[u8; 4], &[u8])> = tags
+            .iter()
+            .map(|(sig, data)| (*sig, data.as_slice()))
+            .collect();
+        let v2 = build_profile([2, 0x10], b"mntr", b"RGB ", &tags);
+        let (primaries, white) = reader(&v2).primaries_xy().unwrap();
+        for (got, want) in primaries
+            .iter()
+            .zip([[0.64, 0.33], [0.30, 0.60], [0.15, 0.06]])
+        {
+            assert!((got[0] - want[0]).abs() < 2e-3 && (got[1] - want[1]).abs() < 2e-3);
+        }
+        assert!((white[0] - 0.3127).abs() < 1e-3);
+    }
+
+    #[test]
+    fn reader_rejects_malformed() {
+        let icc = icc_from_cicp(Cicp::SRGB, None).unwrap();
+        assert!(IccReader::new(&[]).is_none());
+        assert!(IccReader::new(&icc[..100]).is_none());
+        assert!(IccReader::new(&icc[..icc.len() - 1]).is_none());
+
+        let mut no_magic = icc.clone();
+        no_magic[36] = b'x';
+        assert!(IccReader::new(&no_magic).is_none());
+
+        let mut too_many = icc.clone();
+        too_many[128..132].copy_from_slice(&(MAX_TAG_COUNT + 1).to_be_bytes());
+        assert!(IccReader::new(&too_many).is_none());
+
+        // Trailing bytes past the declared size are ignored.
+        let mut padded = icc.clone();
+        padded.extend_from_slice(&[0; 16]);
+        assert_eq!(reader(&padded).data().len(), icc.len());
+
+        // A tag pointing outside the profile is listed but has no data.
+        let mut bad_tag = icc.clone();
+        bad_tag[136..140].copy_from_slice(&u32::MAX.to_be_bytes());
+        let r = reader(&bad_tag);
+        assert!(r.has_tag(b"desc"));
+        assert!(r.find(b"desc").is_none());
+        assert!(r.description().is_none());
+    }
+
+    // ── Validation ─────────────────────────────────────────────────────
+
+    fn set_u32(data: &mut [u8], at: usize, v: u32) {
+        data[at..at + 4].copy_from_slice(&v.to_be_bytes());
+    }
+
+    #[test]
+    fn validate_accepts_synthesized_profiles() {
+        for cicp in [Cicp::SRGB, Cicp::DISPLAY_P3, Cicp::BT2100_PQ] {
+            let icc = icc_from_cicp(cicp, None).unwrap();
+            assert_eq!(validate_icc(&icc), Ok(()));
+            assert!(matches!(sanitize_icc(&icc), Ok(Cow::Borrowed(_))));
+        }
+    }
+
+    #[test]
+    fn validate_rejects_structural_damage() {
+        use IccValidationError as E;
+        let icc = icc_from_cicp(Cicp::SRGB, None).unwrap();
+        assert_eq!(validate_icc(&icc[..100]), Err(E::TooShort { len: 100 }));
+
+        let mut no_magic = icc.clone();
+        no_magic[36] = 0;
+        assert_eq!(validate_icc(&no_magic), Err(E::MissingSignature));
+
+        let mut many = icc.clone();
+        set_u32(&mut many, 128, 5000);
+        assert_eq!(sanitize_icc(&many), Err(E::TooManyTags { count: 5000 }));
+
+        let mut table = icc.clone();
+        set_u32(&mut table, 128, 100);
+        assert_eq!(
+            sanitize_icc(&table),
+            Err(E::TagTableTruncated { count: 100 })
+        );
+
+        let mut in_header = icc.clone();
+        set_u32(&mut in_header, 132 + 4, 64);
+        assert_eq!(
+            sanitize_icc(&in_header),
+            Err(E::TagInHeader {
+                signature: *b"desc"
+            })
+        );
+
+        let mut past_end = icc.clone();
+        set_u32(&mut past_end, 132 + 8, 1 << 20);
+        assert_eq!(
+            sanitize_icc(&past_end),
+            Err(E::TagOutOfBounds {
+                signature: *b"desc"
+            })
+        );
+
+        // Start cprt four bytes into desc.
+        let desc_offset = be_u32(&icc, 132 + 4).unwrap();
+        let mut overlap = icc.clone();
+        set_u32(&mut overlap, 132 + 12 + 4, desc_offset + 4);
+        assert_eq!(
+            sanitize_icc(&overlap),
+            Err(E::OverlappingTags {
+                first: *b"desc",
+                second: *b"cprt"
+            })
+        );
+    }
+
+    #[test]
+    fn validate_allows_shared_offsets() {
+        // The three TRC tags share one offset; a shorter view of it is fine too.
+        let icc = icc_from_cicp(Cicp::BT2100_PQ, None).unwrap();
+        let reader = reader(&icc);
+        let index = reader.tags().position(|t| &t.signature == b"gTRC").unwrap();
+        let mut shorter = icc.clone();
+        set_u32(&mut shorter, 132 + 12 * index + 8, 12);
+        assert_eq!(validate_icc(&shorter), Ok(()));
+    }
+
+    #[test]
+    fn sanitize_repairs_size_field() {
+        let icc = icc_from_cicp(Cicp::DISPLAY_P3, None).unwrap();
+        let mut padded = icc.clone();
+        padded.extend_from_slice(&[0xAB; 13]);
+        assert_eq!(
+            validate_icc(&padded),
+            Err(IccValidationError::SizeMismatch {
+                declared: icc.len() as u32,
+                actual: icc.len() + 13
+            })
+        );
+        assert_eq!(sanitize_icc(&padded).unwrap(), icc.as_slice());
+
+        // Wrong size field, no trailing data: rewritten to the real end.
+        for declared in [0, 200, icc.len() as u32 + 64] {
+            let mut wrong = icc.clone();
+            set_u32(&mut wrong, 0, declared);
+            let repaired = sanitize_icc(&wrong).unwrap();
+            assert_eq!(&*repaired, icc.as_slice(), "declared {declared}");
+        }
+
+        // Garbage after a wrong size field is trimmed to the last tag.
+        let mut both = padded.clone();
+        set_u32(&mut both, 0, 0);
+        let repaired = sanitize_icc(&both).unwrap();
+        assert_eq!(repaired.len(), icc.len());
+        assert_eq!(validate_icc(&repaired), Ok(()));
+
+        // A last tag ending off a word boundary is zero-padded.
+        let icc = icc_from_cicp(Cicp::BT2100_PQ, None).unwrap(); // `cicp` is last
+        let mut unaligned = icc.clone();
+        let last = 132 + 12 * (be_u32(&icc, 128).unwrap() as usize - 1);
+        let end = (be_u32(&icc, last + 4).unwrap() + be_u32(&icc, last + 8).unwrap()) as usize;
+        set_u32(
+            &mut unaligned,
+            last + 8,
+            be_u32(&icc, last + 8).unwrap() - 2,
+        );
+        set_u32(&mut unaligned, 0, 0);
+        unaligned[end - 2..end].fill(0xAB);
+        let repaired = sanitize_icc(&unaligned[..end]).unwrap();
+        assert_eq!(repaired.len(), end);
+        assert_eq!(&repaired[end - 2..], &[0, 0]);
+        assert_eq!(validate_icc(&repaired), Ok(()));
+    }
+
+    #[test]
+    fn validation_error_display() {
+        let err = IccValidationError::OverlappingTags {
+            first: *b"rXYZ",
+            second: *b"g\0YZ",
+        };
+        assert_eq!(err.to_string(), "ICC tags 'rXYZ' and 'g\\x00YZ' overlap");
+    }
+
+    // ── Canonical profiles ─────────────────────────────────────────────
+
+    #[test]
+    fn canonical_cicp_for_known_profiles() {
+        let srgb = moxcms::ColorProfile::new_srgb().encode().unwrap();
+        assert_eq!(canonical_cicp(&srgb), Some(Cicp::SRGB));
+        let p3 = moxcms::ColorProfile::new_display_p3().encode().unwrap();
+        assert_eq!(canonical_cicp(&p3), Some(Cicp::DISPLAY_P3));
+
+        let canonical = icc_from_cicp(Cicp::DISPLAY_P3, None).unwrap();
+        assert!(canonical.len() < p3.len());
+    }
+
+    #[test]
+    fn canonical_cicp_rejects_unknown_and_non_cicp() {
+        let adobe = moxcms::ColorProfile::new_adobe_rgb().encode().unwrap();
+        assert_eq!(canonical_cicp(&adobe), None);
+        let synthesized = icc_from_cicp(Cicp::SRGB, None).unwrap();
+        assert_eq!(canonical_cicp(&synthesized), None);
+        assert_eq!(canonical_cicp(&[]), None);
+    }
+
     // ── Deprecated shim sanity ─────────────────────────────────────────
 
     #[test]
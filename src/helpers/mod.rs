@@ -6,7 +6,11 @@
 //!
 //! # Submodules
 //!
-//! - [`icc`]: ICC profile identification and pixel descriptor derivation.
+//! - [`icc`](crate::helpers::icc): ICC profile identification, pixel descriptor derivation, and
+//!   ICC synthesis from CICP.
+//! - [`exif`](crate::helpers::exif): bounded, allocation-free EXIF / TIFF IFD reader.
+//! - [`streaming`](crate::helpers::streaming): push decoding on top of
+//!   `StreamingDecode`, and `StreamingDecode` on top of push decoding.
 
 use alloc::borrow::Cow;
 
@@ -14,17 +18,20 @@ use enough::Stop;
 use zenpixels::PixelDescriptor;
 
 use crate::cost::OutputInfo;
+use crate::error::sink_failure;
 use crate::sink::SinkError;
-use crate::traits::{AnimationFrameDecoder, Decode, DecodeJob};
+use crate::traits::{AnimationFrameDecoder, Decode, DecodeJob, MultiPageDecoder};
 
-mod exif;
-mod icc;
+pub mod exif;
+pub mod icc;
+pub mod streaming;
 pub use exif::parse_exif_orientation;
 pub use icc::descriptor_for_decoded_pixels_v2;
 #[allow(deprecated)]
 pub use icc::{
     IccMatchTolerance, descriptor_for_decoded_pixels, icc_profile_is_srgb, identify_well_known_icc,
 };
+pub use streaming::{PushStreamingDecoder, StripCoalescer, stream_decode_to_sink};
 
 /// Implement `push_decoder` by doing a full decode and copying rows to the sink.
 ///
@@ -41,6 +48,10 @@ pub use icc::{
 ///     zencodec::helpers::copy_decode_to_sink(self, data, sink, preferred, MyError::from_sink)
 /// }
 /// ```
+///
+/// Rows reach the sink in strips sized by its
+/// [`strip_heights()`](crate::DecodeRowSink::strip_heights) — one strip
+/// unless the sink asks for fewer rows.
 pub fn copy_decode_to_sink<'a, J>(
     job: J,
     data: Cow<'a, [u8]>,
@@ -58,17 +69,17 @@ where
     let w = ps.width();
     let h = ps.rows();
 
-    sink.begin(w, h, desc).map_err(wrap_sink_error)?;
-
-    let mut dst = sink
-        .provide_next_buffer(0, h, w, desc)
+    let mut strips = StripCoalescer::begin(sink, w, h, desc)
+        .map_err(sink_failure)
+        .map_err(wrap_sink_error)?;
+    strips
+        .push(sink, 0, &ps)
+        .map_err(sink_failure)
+        .map_err(wrap_sink_error)?;
+    strips
+        .finish(sink)
+        .map_err(sink_failure)
         .map_err(wrap_sink_error)?;
-    for row in 0..h {
-        dst.row_mut(row).copy_from_slice(ps.row(row));
-    }
-    drop(dst);
-
-    sink.finish().map_err(wrap_sink_error)?;
 
     let info = output.info();
     Ok(OutputInfo::full_decode(info.width, info.height, desc))
@@ -102,15 +113,62 @@ pub fn copy_frame_to_sink<D: AnimationFrameDecoder>(
     let w = ps.width();
     let h = ps.rows();
 
-    sink.begin(w, h, desc).map_err(D::wrap_sink_error)?;
-    let mut dst = sink
-        .provide_next_buffer(0, h, w, desc)
+    let mut strips = StripCoalescer::begin(sink, w, h, desc)
+        .map_err(sink_failure)
+        .map_err(D::wrap_sink_error)?;
+    strips
+        .push(sink, 0, ps)
+        .map_err(sink_failure)
+        .map_err(D::wrap_sink_error)?;
+    strips
+        .finish(sink)
+        .map_err(sink_failure)
         .map_err(D::wrap_sink_error)?;
-    for row in 0..h {
-        dst.row_mut(row).copy_from_slice(ps.row(row));
-    }
-    drop(dst);
-    sink.finish().map_err(D::wrap_sink_error)?;
 
     Ok(Some(OutputInfo::full_decode(w, h, desc)))
 }
+
+/// Implement `decode_page_to_sink` by decoding a page and copying rows.
+///
+/// This is the default for [`MultiPageDecoder::decode_page_to_sink`].
+/// Codecs that override that method for other reasons can still call this
+/// for pages without a native streaming path:
+///
+/// ```rust,ignore
+/// fn decode_page_to_sink(
+///     &mut self,
+///     index: u32,
+///     preferred: &[PixelDescriptor],
+///     stop: Option<&dyn Stop>,
+///     sink: &mut dyn DecodeRowSink,
+/// ) -> Result<OutputInfo, Self::Error> {
+///     zencodec::helpers::copy_page_to_sink(self, index, preferred, stop, sink)
+/// }
+/// ```
+pub fn copy_page_to_sink<D: MultiPageDecoder>(
+    decoder: &mut D,
+    index: u32,
+    preferred: &[PixelDescriptor],
+    stop: Option<&dyn Stop>,
+    sink: &mut dyn crate::DecodeRowSink,
+) -> Result<OutputInfo, D::Error> {
+    let output = decoder.decode_page(index, preferred, stop)?;
+    let ps = output.pixels();
+    let desc = ps.descriptor();
+    let w = ps.width();
+    let h = ps.rows();
+
+    let mut strips = StripCoalescer::begin(sink, w, h, desc)
+        .map_err(sink_failure)
+        .map_err(D::wrap_sink_error)?;
+    strips
+        .push(sink, 0, &ps)
+        .map_err(sink_failure)
+        .map_err(D::wrap_sink_error)?;
+    strips
+        .finish(sink)
+        .map_err(sink_failure)
+        .map_err(D::wrap_sink_error)?;
+
+    Ok(OutputInfo::full_decode(w, h, desc))
+}
@@ -0,0 +1,527 @@
+//! Bridges between pull-based [`StreamingDecode`] and push-based
+//! [`DecodeRowSink`] decoding.
+//!
+//! A codec can implement one streaming shape and derive the other:
+//!
+//! - [`stream_decode_to_sink()`] implements
+//!   [`DecodeJob::push_decoder`] by pulling batches from the codec's
+//!   streaming decoder and writing each one into the sink as it arrives.
+//! - [`PushStreamingDecoder`] implements [`StreamingDecode`] on top of
+//!   [`DecodeJob::push_decoder`], queueing the strips the push decoder
+//!   writes and handing them out from
+//!   [`next_batch()`](StreamingDecode::next_batch).
+//!
+//! Only the first direction streams. A push decoder runs to completion
+//! once started, and without threads there is no way to suspend it while
+//! the caller pulls, so [`PushStreamingDecoder`] decodes the whole image
+//! when it is created. Its strip queue is bounded by the output size
+//! announced to [`begin()`](DecodeRowSink::begin): strips outside it fail
+//! the decode instead of growing the queue.
+//!
+//! Both directions, and the one-shot `copy_*_to_sink()` helpers, size the
+//! strips they push with [`StripCoalescer`], which honors the sink's
+//! [`strip_heights()`](DecodeRowSink::strip_heights).
+//!
+//! [`DecodeCapabilities::streaming`](crate::decode::DecodeCapabilities::streaming)
+//! means `streaming_decoder()` works, so codecs using either adapter report
+//! it as `true`. A [`PushStreamingDecoder`] still holds the whole image,
+//! so it offers the batch API without the memory savings of a native
+//! streaming decoder.
+
+use alloc::borrow::Cow;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use zenpixels::{PixelDescriptor, PixelSlice, PixelSliceMut};
+
+use crate::ImageInfo;
+use crate::cost::OutputInfo;
+use crate::error::sink_failure;
+use crate::sink::{DecodeRowSink, SinkError, StripHeights};
+use crate::sink_adapters::{BufferSink, RowSinkError};
+use crate::traits::{DecodeJob, StreamingDecode};
+
+/// Implement `push_decoder` by pulling batches from `streaming_decoder`.
+///
+/// Codecs with a native [`StreamingDecode`] can use this to implement
+/// [`DecodeJob::push_decoder`] without buffering the image:
+///
+/// ```rust,ignore
+/// fn push_decoder(
+///     self,
+///     data: Cow<'a, [u8]>,
+///     sink: &mut dyn DecodeRowSink,
+///     preferred: &[PixelDescriptor],
+/// ) -> Result<OutputInfo, Self::Error> {
+///     zencodec::helpers::stream_decode_to_sink(self, data, sink, preferred, MyError::from_sink)
+/// }
+/// ```
+///
+/// Dimensions for [`begin()`](DecodeRowSink::begin) come from
+/// [`output_info()`](DecodeJob::output_info); the descriptor is the one
+/// the first batch carries, which is also reported in the returned
+/// [`OutputInfo::native_format`]. Batches are regrouped to the sink's
+/// [`strip_heights()`](DecodeRowSink::strip_heights).
+pub fn stream_decode_to_sink<'a, J>(
+    job: J,
+    data: Cow<'a, [u8]>,
+    sink: &mut dyn DecodeRowSink,
+    preferred: &[PixelDescriptor],
+    wrap_sink_error: fn(SinkError) -> J::Error,
+) -> Result<OutputInfo, J::Error>
+where
+    J: DecodeJob<'a>,
+{
+    let mut info = job.output_info(&data)?;
+    let mut dec = job.streaming_decoder(data, preferred)?;
+    let mut strips = None;
+
+    while let Some((y, strip)) = dec.next_batch()? {
+        let strips = match &mut strips {
+            Some(strips) => strips,
+            None => {
+                let desc = strip.descriptor();
+                info.native_format = desc;
+                info.has_alpha = desc.has_alpha();
+                let begun = StripCoalescer::begin(sink, info.width, info.height, desc)
+                    .map_err(sink_failure)
+                    .map_err(wrap_sink_error)?;
+                strips.insert(begun)
+            }
+        };
+        strips
+            .push(sink, y, &strip)
+            .map_err(sink_failure)
+            .map_err(wrap_sink_error)?;
+    }
+    let strips = match strips {
+        Some(strips) => strips,
+        None => StripCoalescer::begin(sink, info.width, info.height, info.native_format)
+            .map_err(sink_failure)
+            .map_err(wrap_sink_error)?,
+    };
+
+    strips
+        .finish(sink)
+        .map_err(sink_failure)
+        .map_err(wrap_sink_error)?;
+    Ok(info)
+}
+
+/// Writes a decoder's strips into a sink at the sink's preferred height.
+///
+/// Decoders produce strips at whatever height suits them — a full image
+/// for one-shot codecs, 8 or 16 rows for JPEG MCUs, one row for PNG. The
+/// coalescer asks the sink for its [`StripHeights`] after `begin()`,
+/// picks a strip height with [`StripHeights::strip_height()`] from the
+/// first strip it sees, and forwards rows in strips of exactly that height
+/// (the last one may be shorter). Rows that arrive directly in a full
+/// strip are copied once, into the sink's buffer; the rest are staged
+/// until a strip fills.
+///
+/// ```rust,ignore
+/// let mut strips = StripCoalescer::begin(sink, width, height, desc).map_err(wrap)?;
+/// while let Some((y, mcu_rows)) = next_mcu_row()? {
+///     strips.push(sink, y, &mcu_rows).map_err(wrap)?;
+/// }
+/// strips.finish(sink).map_err(wrap)?;
+/// ```
+///
+/// Strips must arrive top to bottom without gaps, in the width and format
+/// given to `begin()`; anything else fails with a [`RowSinkError`].
+#[derive(Debug)]
+pub struct StripCoalescer {
+    width: u32,
+    height: u32,
+    descriptor: PixelDescriptor,
+    heights: StripHeights,
+    /// Chosen strip height; 0 until the first strip arrives.
+    strip_rows: u32,
+    /// Rows received so far.
+    received: u32,
+    staged: Vec<u8>,
+    staged_rows: u32,
+}
+
+impl StripCoalescer {
+    /// Call `sink.begin()` and read its [`strip_heights()`](DecodeRowSink::strip_heights).
+    pub fn begin(
+        sink: &mut dyn DecodeRowSink,
+        width: u32,
+        height: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<Self, SinkError> {
+        sink.begin(width, height, descriptor)?;
+        Ok(Self {
+            width,
+            height,
+            descriptor,
+            heights: sink.strip_heights(),
+            strip_rows: 0,
+            received: 0,
+            staged: Vec::new(),
+            staged_rows: 0,
+        })
+    }
+
+    /// The sink's requested strip heights.
+    pub fn strip_heights(&self) -> StripHeights {
+        self.heights
+    }
+
+    /// Rows per strip sent to the sink, once the first strip has arrived.
+    pub fn strip_height(&self) -> Option<u32> {
+        (self.strip_rows > 0).then_some(self.strip_rows)
+    }
+
+    /// Forward decoded rows `y .. y + strip.rows()`.
+    pub fn push(
+        &mut self,
+        sink: &mut dyn DecodeRowSink,
+        y: u32,
+        strip: &PixelSlice<'_>,
+    ) -> Result<(), SinkError> {
+        let rows = strip.rows();
+        if strip.width() != self.width || strip.descriptor() != self.descriptor {
+            return Err(RowSinkError::LayoutChanged.into());
+        }
+        if y != self.received {
+            let expected = self.received;
+            return Err(RowSinkError::OutOfOrder { expected, y }.into());
+        }
+        if y.checked_add(rows).is_none_or(|end| end > self.height) {
+            return Err(RowSinkError::OutOfBounds { y, height: rows }.into());
+        }
+        if rows == 0 {
+            return Ok(());
+        }
+        if self.strip_rows == 0 {
+            self.strip_rows = self.heights.strip_height(rows);
+        }
+
+        let row_bytes = self.row_bytes();
+        let mut row = 0;
+        while row < rows {
+            let out_y = y + row - self.staged_rows;
+            if self.staged_rows == 0 && rows - row >= self.strip_rows {
+                let n = self.strip_rows;
+                self.emit(sink, out_y, n, |r| strip.row(row + r))?;
+                row += n;
+                continue;
+            }
+            let n = (self.strip_rows - self.staged_rows).min(rows - row);
+            for r in 0..n {
+                self.staged
+                    .extend_from_slice(&strip.row(row + r)[..row_bytes]);
+            }
+            self.staged_rows += n;
+            row += n;
+            if self.staged_rows == self.strip_rows {
+                self.flush(sink, out_y)?;
+            }
+        }
+        self.received += rows;
+        Ok(())
+    }
+
+    /// Forward any staged rows and call `sink.finish()`.
+    pub fn finish(mut self, sink: &mut dyn DecodeRowSink) -> Result<(), SinkError> {
+        let y = self.received - self.staged_rows;
+        self.flush(sink, y)?;
+        sink.finish()
+    }
+
+    fn row_bytes(&self) -> usize {
+        self.width as usize * self.descriptor.bytes_per_pixel()
+    }
+
+    fn flush(&mut self, sink: &mut dyn DecodeRowSink, y: u32) -> Result<(), SinkError> {
+        if self.staged_rows == 0 {
+            return Ok(());
+        }
+        let row_bytes = self.row_bytes();
+        let staged = core::mem::take(&mut self.staged);
+        let n = self.staged_rows;
+        self.emit(sink, y, n, |r| {
+            &staged[r as usize * row_bytes..][..row_bytes]
+        })?;
+        self.staged = staged;
+        self.staged.clear();
+        self.staged_rows = 0;
+        Ok(())
+    }
+
+    fn emit<'r>(
+        &self,
+        sink: &mut dyn DecodeRowSink,
+        y: u32,
+        rows: u32,
+        src: impl Fn(u32) -> &'r [u8],
+    ) -> Result<(), SinkError> {
+        let row_bytes = self.row_bytes();
+        let mut dst = sink.provide_next_buffer(y, rows, self.width, self.descriptor)?;
+        for r in 0..rows {
+            dst.row_mut(r)[..row_bytes].copy_from_slice(&src(r)[..row_bytes]);
+        }
+        Ok(())
+    }
+}
+
+/// [`StreamingDecode`] built on a codec's push decoder.
+///
+/// Codecs whose native path is [`DecodeJob::push_decoder`] can use this as
+/// their `StreamDec`:
+///
+/// ```rust,ignore
+/// type StreamDec = PushStreamingDecoder<MyError>;
+///
+/// fn streaming_decoder(
+///     self,
+///     data: Cow<'a, [u8]>,
+///     preferred: &[PixelDescriptor],
+/// ) -> Result<Self::StreamDec, Self::Error> {
+///     PushStreamingDecoder::new(self, data, preferred)
+/// }
+/// ```
+///
+/// `new()` probes the image, runs the push decoder into a strip queue,
+/// and returns; [`next_batch()`](StreamingDecode::next_batch) then yields
+/// the strips in the order the decoder wrote them. See the
+/// [module docs](self) for why the decode is not interleaved with pulls.
+pub struct PushStreamingDecoder<E> {
+    info: ImageInfo,
+    queue: StripQueue,
+    _error: PhantomData<fn() -> E>,
+}
+
+impl<E: core::error::Error + Send + Sync + 'static> PushStreamingDecoder<E> {
+    /// Decode `data` with `job.push_decoder()` and queue the strips.
+    ///
+    /// Errors from probing or decoding are returned as-is. Strips that
+    /// overrun the dimensions or change the format given to `begin()`
+    /// reach the codec as sink errors.
+    pub fn new<'a, J>(job: J, data: Cow<'a, [u8]>, preferred: &[PixelDescriptor]) -> Result<Self, E>
+    where
+        J: DecodeJob<'a, Error = E>,
+    {
+        let info = job.probe(&data)?;
+        let mut queue = StripQueue::default();
+        job.push_decoder(data, &mut queue, preferred)?;
+        Ok(Self {
+            info,
+            queue,
+            _error: PhantomData,
+        })
+    }
+
+    /// Strips not yet returned by `next_batch()`.
+    pub fn queued(&self) -> usize {
+        self.queue.strips.len()
+    }
+}
+
+impl<E: core::error::Error + Send + Sync + 'static> StreamingDecode for PushStreamingDecoder<E> {
+    type Error = E;
+
+    fn next_batch(&mut self) -> Result<Option<(u32, PixelSlice<'_>)>, E> {
+        let Some((y, height)) = self.queue.strips.pop_front() else {
+            return Ok(None);
+        };
+        let Some(buffer) = self.queue.image.buffer() else {
+            return Ok(None);
+        };
+        Ok(Some((y, buffer.rows(y, height))))
+    }
+
+    fn info(&self) -> &ImageInfo {
+        &self.info
+    }
+}
+
+impl<E> core::fmt::Debug for PushStreamingDecoder<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PushStreamingDecoder")
+            .field("info", &self.info)
+            .field("queued", &self.queue.strips.len())
+            .finish()
+    }
+}
+
+/// Sink that stores strips in one image buffer and remembers their order.
+#[derive(Default)]
+struct StripQueue {
+    image: BufferSink,
+    strips: VecDeque<(u32, u32)>,
+}
+
+impl DecodeRowSink for StripQueue {
+    fn begin(
+        &mut self,
+        width: u32,
+        height: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<(), SinkError> {
+        self.strips.clear();
+        self.image.begin(width, height, descriptor)
+    }
+
+    fn provide_next_buffer(
+        &mut self,
+        y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        let dst = self
+            .image
+            .provide_next_buffer(y, height, width, descriptor)?;
+        if height > 0 {
+            self.strips.push_back((y, height));
+        }
+        Ok(dst)
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.image.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use zenpixels::PixelBuffer;
+
+    const GRAY: PixelDescriptor = PixelDescriptor::GRAY8_SRGB;
+
+    /// Records strip positions and collects rows, asking for `heights`.
+    struct Recorder {
+        heights: StripHeights,
+        strips: Vec<(u32, u32)>,
+        image: BufferSink,
+        finished: bool,
+    }
+
+    impl Recorder {
+        fn new(heights: StripHeights) -> Self {
+            Self {
+                heights,
+                strips: Vec::new(),
+                image: BufferSink::new(),
+                finished: false,
+            }
+        }
+    }
+
+    impl DecodeRowSink for Recorder {
+        fn begin(
+            &mut self,
+            width: u32,
+            height: u32,
+            descriptor: PixelDescriptor,
+        ) -> Result<(), SinkError> {
+            self.image.begin(width, height, descriptor)
+        }
+
+        fn provide_next_buffer(
+            &mut self,
+            y: u32,
+            height: u32,
+            width: u32,
+            descriptor: PixelDescriptor,
+        ) -> Result<PixelSliceMut<'_>, SinkError> {
+            self.strips.push((y, height));
+            self.image.provide_next_buffer(y, height, width, descriptor)
+        }
+
+        fn strip_heights(&self) -> StripHeights {
+            self.heights
+        }
+
+        fn finish(&mut self) -> Result<(), SinkError> {
+            self.finished = true;
+            Ok(())
+        }
+    }
+
+    /// 3×`rows` Gray8 image where each row holds its index.
+    fn image(rows: u32) -> PixelBuffer {
+        let data = (0..rows).flat_map(|r| [r as u8; 3]).collect();
+        PixelBuffer::from_vec(data, 3, rows, GRAY).unwrap()
+    }
+
+    /// Push `image` in native strips of `native` rows through a coalescer.
+    fn coalesce(heights: StripHeights, rows: u32, native: u32) -> Recorder {
+        let src = image(rows);
+        let mut sink = Recorder::new(heights);
+        let mut strips = StripCoalescer::begin(&mut sink, 3, rows, GRAY).unwrap();
+        let mut y = 0;
+        while y < rows {
+            let n = native.min(rows - y);
+            strips.push(&mut sink, y, &src.rows(y, n)).unwrap();
+            y += n;
+        }
+        strips.finish(&mut sink).unwrap();
+        assert!(sink.finished);
+        let out = sink.image.buffer().unwrap();
+        assert_eq!(
+            out.copy_to_contiguous_bytes(),
+            src.copy_to_contiguous_bytes()
+        );
+        sink
+    }
+
+    #[test]
+    fn any_keeps_native_strips() {
+        let sink = coalesce(StripHeights::ANY, 20, 8);
+        assert_eq!(sink.strips, [(0, 8), (8, 8), (16, 4)]);
+    }
+
+    #[test]
+    fn coalesces_small_strips_to_preferred() {
+        // 8-row MCU strips into a sink that wants 64-row strips.
+        let heights = StripHeights::ANY.with_preferred(64).with_alignment(64);
+        let sink = coalesce(heights, 150, 8);
+        assert_eq!(sink.strips, [(0, 64), (64, 64), (128, 22)]);
+    }
+
+    #[test]
+    fn splits_large_strips_and_straddles() {
+        let heights = StripHeights::ANY.with_preferred(4);
+        let sink = coalesce(heights, 10, 10);
+        assert_eq!(sink.strips, [(0, 4), (4, 4), (8, 2)]);
+
+        // Native 3-row strips straddle 4-row output strips.
+        let sink = coalesce(heights, 10, 3);
+        assert_eq!(sink.strips, [(0, 4), (4, 4), (8, 2)]);
+    }
+
+    #[test]
+    fn max_bounds_native_strips() {
+        let sink = coalesce(StripHeights::ANY.with_max(5), 12, 12);
+        assert_eq!(sink.strips, [(0, 5), (5, 5), (10, 2)]);
+    }
+
+    #[test]
+    fn rejects_protocol_violations() {
+        let src = image(4);
+        let mut sink = Recorder::new(StripHeights::ANY);
+        let mut strips = StripCoalescer::begin(&mut sink, 3, 4, GRAY).unwrap();
+        assert_eq!(strips.strip_height(), None);
+        let kind = |e: SinkError| *e.downcast::<RowSinkError>().unwrap();
+
+        let err = strips.push(&mut sink, 1, &src.rows(1, 1)).unwrap_err();
+        assert_eq!(kind(err), RowSinkError::OutOfOrder { expected: 0, y: 1 });
+
+        strips.push(&mut sink, 0, &src.rows(0, 2)).unwrap();
+        assert_eq!(strips.strip_height(), Some(2));
+
+        let err = strips.push(&mut sink, 2, &image(5).rows(2, 3)).unwrap_err();
+        assert_eq!(kind(err), RowSinkError::OutOfBounds { y: 2, height: 3 });
+
+        let wide = PixelBuffer::from_vec(vec![0; 8], 4, 2, GRAY).unwrap();
+        let err = strips.push(&mut sink, 2, &wide.as_slice()).unwrap_err();
+        assert_eq!(kind(err), RowSinkError::LayoutChanged);
+    }
+}
@@ -9,6 +9,7 @@ use alloc::vec::Vec;
 use crate::detect::SourceEncodingDetails;
 use crate::gainmap::GainMapPresence;
 use crate::metadata::Metadata;
+use crate::warning::{DecodeWarning, DecodeWarningCode};
 use crate::{ImageFormat, Orientation};
 use zenpixels::{ColorAuthority, ColorPrimaries, TransferFunction};
 
@@ -24,7 +25,7 @@ pub use zenpixels::Cicp;
 /// Determines which decoder trait is appropriate:
 /// - `Single` → [`Decode`](crate::decode::Decode)
 /// - `Animation` → [`AnimationFrameDecoder`](crate::decode::AnimationFrameDecoder)
-/// - `Multi` → future `MultiPageDecoder` (or `Decode` for primary only)
+/// - `Multi` → [`MultiPageDecoder`](crate::decode::MultiPageDecoder) (or `Decode` for primary only)
 ///
 /// # Key invariant
 ///
@@ -60,7 +61,7 @@ pub enum ImageSequence {
     ///
     /// Pages may differ in dimensions, pixel format, color space, and
     /// metadata. `Decode` returns the primary image only. Other images
-    /// require a `MultiPageDecoder` (future) or the codec's native API.
+    /// require a [`MultiPageDecoder`](crate::decode::MultiPageDecoder) or the codec's native API.
     ///
     /// Examples: multi-page TIFF, HEIF collections, ICO sizes, DICOM slices,
     /// GeoTIFF spectral bands.
@@ -350,10 +351,7 @@ impl SourceColor {
                 } else if let Some(cicp) = self.cicp {
                     zenpixels::ColorContext::from_cicp(cicp)
                 } else {
-                    zenpixels::ColorContext {
-                        icc: None,
-                        cicp: None,
-                    }
+                    zenpixels::ColorContext::default()
                 }
             }
         }
@@ -410,8 +408,8 @@ impl EmbeddedMetadata {
 /// | `embedded_metadata` | The image | Container-level | Primary image only |
 ///
 /// For `Multi`, other images may have completely different dimensions, color
-/// spaces, and metadata. Per-image information requires a future
-/// `MultiPageDecoder` or the codec's native API.
+/// spaces, and metadata. Per-image information is available via
+/// [`MultiPageDecoder::page_info()`](crate::decode::MultiPageDecoder::page_info).
 #[derive(Clone)]
 #[non_exhaustive]
 pub struct ImageInfo {
@@ -480,12 +478,14 @@ pub struct ImageInfo {
     ///
     /// Skipped by `PartialEq` (trait objects aren't comparable).
     pub source_encoding: Option<Arc<dyn SourceEncodingDetails>>,
-    /// Non-fatal diagnostic messages from probing or decoding.
+    /// Non-fatal diagnostics from probing or decoding.
     ///
     /// Populated when the operation succeeded but encountered unusual
     /// conditions (e.g., metadata located beyond the fast-path probe cap,
-    /// permissive parsing recovered from structural issues).
-    pub warnings: Vec<alloc::string::String>,
+    /// permissive parsing recovered from structural issues). Codes the
+    /// job's [`DecodePolicy`](crate::decode::DecodePolicy) escalates are
+    /// errors instead and never appear here.
+    pub warnings: Vec<DecodeWarning>,
 }
 
 // ImageInfo contains Arc, Vec, trait objects — heavily pointer-dependent.
@@ -677,20 +677,22 @@ impl ImageInfo {
         self.source_encoding.as_deref()
     }
 
-    /// Add a single warning message.
-    pub fn with_warning(mut self, msg: alloc::string::String) -> Self {
-        self.warnings.push(msg);
+    /// Add a single warning.
+    ///
+    /// Plain strings become [`DecodeWarningCode::Other`] warnings.
+    pub fn with_warning(mut self, warning: impl Into<DecodeWarning>) -> Self {
+        self.warnings.push(warning.into());
         self
     }
 
     /// Replace warnings with the given list.
-    pub fn with_warnings(mut self, msgs: Vec<alloc::string::String>) -> Self {
-        self.warnings = msgs;
+    pub fn with_warnings(mut self, warnings: Vec<DecodeWarning>) -> Self {
+        self.warnings = warnings;
         self
     }
 
-    /// Non-fatal diagnostic messages.
-    pub fn warnings(&self) -> &[alloc::string::String] {
+    /// Non-fatal diagnostics.
+    pub fn warnings(&self) -> &[DecodeWarning] {
         &self.warnings
     }
 
@@ -699,6 +701,11 @@ impl ImageInfo {
         !self.warnings.is_empty()
     }
 
+    /// Whether a warning with `code` was recorded.
+    pub fn has_warning(&self, code: DecodeWarningCode) -> bool {
+        self.warnings.iter().any(|w| w.code() == code)
+    }
+
     /// Display width after applying EXIF orientation.
     ///
     /// For orientations 5-8 (90/270 rotation), this returns `height`.
@@ -884,6 +891,23 @@ mod tests {
         assert_ne!(a, c);
     }
 
+    #[test]
+    fn image_info_warnings() {
+        let info = ImageInfo::new(10, 20, ImageFormat::Png);
+        assert!(!info.has_warnings());
+
+        let info = info.with_warning("legacy message").with_warning(
+            DecodeWarning::new(DecodeWarningCode::IccIgnored, "bad tag").with_offset(33),
+        );
+        assert!(info.has_warnings());
+        assert_eq!(info.warnings().len(), 2);
+        assert_eq!(info.warnings()[0].code(), DecodeWarningCode::Other);
+        assert_eq!(info.warnings()[1].offset(), Some(33));
+        assert!(info.has_warning(DecodeWarningCode::IccIgnored));
+        assert!(!info.has_warning(DecodeWarningCode::Truncated));
+        assert_ne!(info, ImageInfo::new(10, 20, ImageFormat::Png));
+    }
+
     #[test]
     fn cicp_constants() {
         assert_eq!(Cicp::SRGB.color_primaries, 1);
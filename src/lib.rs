@@ -11,9 +11,11 @@
 //! # Shared types (root)
 //!
 //! - [`ImageFormat`] — format detection from magic bytes
+//! - [`CodecRegistry`] — format → decoder/encoder dispatch
+//! - [`transcode()`] — decode → encode with format negotiation and metadata carry-over
 //! - [`ImageInfo`] / [`Metadata`] / [`Orientation`] / [`OrientationHint`] — image metadata
 //! - [`ResourceLimits`] / [`ThreadingPolicy`] — resource limit and threading configuration
-//! - [`UnsupportedOperation`] / [`CodecErrorExt`] — standard unsupported operation reporting and error chain inspection
+//! - [`UnsupportedOperation`] / [`CodecErrorExt`] / [`CodecErrorKind`] — standard unsupported operation reporting, error chain inspection, and error classification
 //!
 //! # Re-exported crates
 //!
@@ -37,11 +39,15 @@ extern crate alloc;
 
 whereat::define_at_crate_info!();
 
+mod buffered_encoder;
 mod capabilities;
 mod cost;
 mod detect;
+mod encoder_sink;
 mod error;
 mod extensions;
+mod fallback;
+mod finish;
 mod format;
 /// Cross-codec gain map types (ISO 21496-1).
 pub mod gainmap;
@@ -56,8 +62,16 @@ mod negotiate;
 mod orientation;
 mod output;
 mod policy;
+mod probe;
+mod registry;
 mod sink;
+mod sink_adapters;
+mod source;
 mod traits;
+mod transcode;
+mod warning;
+/// Minimal XMP reader (hdrgm gain maps, orientation, dates) and hdrgm writer.
+pub mod xmp;
 
 // =========================================================================
 // Public root: shared types used by both encode and decode
@@ -79,11 +93,13 @@ pub use limits::{LimitExceeded, ResourceLimits, ThreadingPolicy};
 pub use metadata::Metadata;
 pub use orientation::{Orientation, OrientationHint};
 pub use output::{AnimationFrame, OwnedAnimationFrame};
+pub use registry::{CodecRegistry, RegistryError};
+pub use transcode::transcode;
 pub use zenpixels::ColorAuthority;
 
 pub use capabilities::UnsupportedOperation;
 pub use detect::SourceEncodingDetails;
-pub use error::{CodecErrorExt, find_cause};
+pub use error::{ClassifiedError, CodecErrorExt, CodecErrorKind, find_cause};
 pub use traits::Unsupported;
 
 // =========================================================================
@@ -143,9 +159,13 @@ pub mod encode {
 
     // Types
     pub use crate::capabilities::EncodeCapabilities;
+    pub use crate::metadata::IccSubstitution;
     pub use crate::negotiate::best_encode_format;
     pub use crate::output::EncodeOutput;
     pub use crate::policy::EncodePolicy;
+
+    // Buffering fallback for row-level and pull encoding
+    pub use crate::buffered_encoder::{BufferedEncodeError, BufferedEncoder, RowBufferError};
 }
 
 /// Decode traits, types, and configuration.
@@ -155,13 +175,15 @@ pub mod encode {
 /// ```text
 ///                                  ┌→ Dec (implements Decode)
 /// DecoderConfig → DecodeJob<'a> ──┤→ StreamDec (implements StreamingDecode)
-///                                  └→ AnimationFrameDec (implements AnimationFrameDecoder)
+///                                  ├→ AnimationFrameDec (implements AnimationFrameDecoder)
+///                                  └→ MultiPageDec (implements MultiPageDecoder)
 /// ```
 ///
 /// # Object-safe dyn dispatch
 ///
 /// ```text
 /// DynDecoderConfig → DynDecodeJob → DynDecoder / DynAnimationFrameDecoder / DynStreamingDecoder
+///                                   / DynMultiPageDecoder
 /// ```
 ///
 /// Codec implementors implement the generic traits. Dispatch callers
@@ -169,21 +191,37 @@ pub mod encode {
 pub mod decode {
     // Traits — config, job, execution
     pub use crate::traits::{
-        AnimationFrameDecoder, Decode, DecodeJob, DecoderConfig, StreamingDecode,
+        AnimationFrameDecoder, Decode, DecodeJob, DecoderConfig, MultiPageDecoder, StreamingDecode,
     };
 
     // Object-safe dyn dispatch
     pub use crate::traits::{
         BoxedError, DynAnimationFrameDecoder, DynDecodeJob, DynDecoder, DynDecoderConfig,
-        DynStreamingDecoder,
+        DynMultiPageDecoder, DynStreamingDecoder,
     };
 
     // Types
     pub use crate::capabilities::DecodeCapabilities;
     pub use crate::cost::OutputInfo;
+    pub use crate::encoder_sink::EncoderRowSink;
+    pub use crate::fallback::{
+        DecodedBy, FailureKind, FallbackConfigError, FallbackDecoderConfig, RetryPolicy,
+    };
+    pub use crate::finish::{FinishingDecoder, RemainingTransform};
     pub use crate::output::{AnimationFrame, DecodeOutput, OwnedAnimationFrame};
     pub use crate::policy::DecodePolicy;
-    pub use crate::sink::{DecodeRowSink, SinkError};
+    pub use crate::probe::ProbeProgress;
+    pub use crate::sink::{DecodeRowSink, SinkError, StripHeights};
+    pub use crate::sink_adapters::{
+        BufferSink, ConvertingSink, OrientingSink, RowSinkError, TeeSink,
+    };
+    pub use crate::source::{
+        ByteSource, DecodeSource, RandomAccessReader, RandomAccessSource, SourceError,
+        UnexpectedEof,
+    };
+    pub use crate::warning::{
+        DecodeWarning, DecodeWarningCode, DecodeWarningSet, DecodeWarningSeverity,
+    };
 
     pub use crate::negotiate::{is_format_available, negotiate_pixel_format};
 
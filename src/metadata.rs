@@ -3,10 +3,11 @@
 //! [`Metadata`] carries ICC, EXIF, XMP, CICP, HDR, and orientation data
 //! using `Arc<[u8]>` for byte buffers (cheap cloning via ref-count bump).
 
+use alloc::borrow::Cow;
 use alloc::sync::Arc;
 
-use crate::Orientation;
 use crate::info::{Cicp, ContentLightLevel, MasteringDisplay};
+use crate::{EncodeCapabilities, Orientation};
 use zenpixels::{ColorPrimaries, TransferFunction};
 
 /// Owned image metadata for encode/decode roundtrip.
@@ -105,6 +106,106 @@ impl Metadata {
         self
     }
 
+    /// Fill in an ICC profile synthesized from [`cicp`](Self::cicp) for an
+    /// encoder that can only signal color through ICC.
+    ///
+    /// Applies when `caps` reports [`icc()`](EncodeCapabilities::icc) but not
+    /// [`cicp()`](EncodeCapabilities::cicp), no ICC profile is set, and the
+    /// CICP is not plain sRGB (which ICC-only formats already assume).
+    /// The profile comes from
+    /// [`helpers::icc::icc_from_cicp()`](crate::helpers::icc::icc_from_cicp)
+    /// with [`mastering_display`](Self::mastering_display); CICP it can't
+    /// express leaves the metadata unchanged.
+    pub fn with_icc_fallback(mut self, caps: &EncodeCapabilities) -> Self {
+        if self.icc_profile.is_some() || !caps.icc() || caps.cicp() {
+            return self;
+        }
+        let Some(cicp) = self.cicp else {
+            return self;
+        };
+        if (cicp.color_primaries, cicp.transfer_characteristics) == (1, 13) {
+            return self;
+        }
+        if let Some(icc) = crate::helpers::icc::icc_from_cicp(cicp, self.mastering_display.as_ref())
+        {
+            self.icc_profile = Some(icc.into());
+        }
+        self
+    }
+
+    /// Repair the ICC profile with
+    /// [`helpers::icc::sanitize_icc()`](crate::helpers::icc::sanitize_icc),
+    /// or drop it if it is structurally broken.
+    ///
+    /// Encoders call this before embedding when
+    /// [`EncodePolicy::resolve_validate_icc()`](crate::EncodePolicy::resolve_validate_icc)
+    /// is true.
+    pub fn with_sanitized_icc(mut self) -> Self {
+        if let Some(icc) = self.icc_profile.take() {
+            self.icc_profile = match crate::helpers::icc::sanitize_icc(&icc) {
+                Ok(Cow::Borrowed(_)) => Some(icc),
+                Ok(Cow::Owned(repaired)) => Some(repaired.into()),
+                Err(_) => None,
+            };
+        }
+        self
+    }
+
+    /// Replace a well-known ICC profile with its compact equivalent for an
+    /// encoder with `caps`, returning what was done.
+    ///
+    /// Encoders call this before embedding when
+    /// [`EncodePolicy::resolve_canonicalize_icc()`](crate::EncodePolicy::resolve_canonicalize_icc)
+    /// is true, and report the result via
+    /// [`EncodeOutput::with_icc_substitution()`](crate::encode::EncodeOutput::with_icc_substitution).
+    /// Profiles [`helpers::icc::canonical_cicp()`](crate::helpers::icc::canonical_cicp)
+    /// recognizes are:
+    ///
+    /// - dropped in favor of [`cicp`](Self::cicp) when `caps` reports
+    ///   [`cicp()`](EncodeCapabilities::cicp). An existing CICP is kept; one
+    ///   whose primaries or transfer disagree with the profile leaves the
+    ///   metadata unchanged;
+    /// - otherwise, when `caps` reports [`icc()`](EncodeCapabilities::icc),
+    ///   replaced by the profile from
+    ///   [`helpers::icc::icc_from_cicp()`](crate::helpers::icc::icc_from_cicp)
+    ///   if that is smaller.
+    ///
+    /// Returns `None` and leaves the metadata unchanged otherwise.
+    pub fn canonicalize_icc(&mut self, caps: &EncodeCapabilities) -> Option<IccSubstitution> {
+        let icc = self.icc_profile.as_deref()?;
+        let cicp = crate::helpers::icc::canonical_cicp(icc)?;
+        let original_len = icc.len();
+        if caps.cicp() {
+            let cicp = match self.cicp {
+                None => cicp,
+                Some(existing)
+                    if (existing.color_primaries, existing.transfer_characteristics)
+                        == (cicp.color_primaries, cicp.transfer_characteristics) =>
+                {
+                    existing
+                }
+                Some(_) => return None,
+            };
+            self.cicp = Some(cicp);
+            self.icc_profile = None;
+            return Some(IccSubstitution::Cicp { cicp, original_len });
+        }
+        if !caps.icc() {
+            return None;
+        }
+        let canonical = crate::helpers::icc::icc_from_cicp(cicp, None)?;
+        if canonical.len() >= original_len {
+            return None;
+        }
+        let canonical_len = canonical.len();
+        self.icc_profile = Some(canonical.into());
+        Some(IccSubstitution::Canonical {
+            cicp,
+            original_len,
+            canonical_len,
+        })
+    }
+
     /// Whether any metadata is present.
     pub fn is_empty(&self) -> bool {
         self.icc_profile.is_none()
@@ -151,6 +252,52 @@ impl From<&crate::ImageInfo> for Metadata {
     }
 }
 
+/// How [`Metadata::canonicalize_icc()`] replaced a well-known ICC profile.
+///
+/// Encoders report it via
+/// [`EncodeOutput::icc_substitution()`](crate::encode::EncodeOutput::icc_substitution).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IccSubstitution {
+    /// The profile was dropped; the output signals `cicp` instead.
+    Cicp {
+        /// CICP written in place of the profile.
+        cicp: Cicp,
+        /// Size of the dropped profile in bytes.
+        original_len: usize,
+    },
+    /// The profile was replaced by a compact canonical one for `cicp`.
+    Canonical {
+        /// Color space the profiles describe.
+        cicp: Cicp,
+        /// Size of the replaced profile in bytes.
+        original_len: usize,
+        /// Size of the canonical profile in bytes.
+        canonical_len: usize,
+    },
+}
+
+impl IccSubstitution {
+    /// The color space the replaced profile was identified as.
+    pub fn cicp(&self) -> Cicp {
+        match *self {
+            Self::Cicp { cicp, .. } | Self::Canonical { cicp, .. } => cicp,
+        }
+    }
+
+    /// Bytes of ICC data no longer embedded.
+    pub fn bytes_saved(&self) -> usize {
+        match *self {
+            Self::Cicp { original_len, .. } => original_len,
+            Self::Canonical {
+                original_len,
+                canonical_len,
+                ..
+            } => original_len - canonical_len,
+        }
+    }
+}
+
 /// Parse the EXIF Orientation tag (0x0112) from a TIFF/EXIF blob.
 ///
 /// Handles both little-endian (`II*\0`) and big-endian (`MM\0*`) byte
@@ -381,4 +528,126 @@ mod tests {
         // Explicit FlipH must win over the EXIF blob's Rotate90.
         assert_eq!(meta.orientation, Orientation::FlipH);
     }
+
+    #[test]
+    fn icc_fallback_only_for_icc_only_encoders() {
+        let meta = Metadata::none().with_cicp(Cicp::BT2100_PQ);
+        let icc_only = EncodeCapabilities::new().with_icc(true);
+        let with_icc = meta.clone().with_icc_fallback(&icc_only);
+        let icc = with_icc.icc_profile.as_deref().expect("synthesized ICC");
+        assert_eq!(
+            zenpixels::icc::extract_cicp(icc).map(|c| c.transfer_characteristics),
+            Some(16)
+        );
+
+        let both = EncodeCapabilities::new().with_icc(true).with_cicp(true);
+        assert_eq!(meta.clone().with_icc_fallback(&both), meta);
+        assert_eq!(
+            meta.clone().with_icc_fallback(&EncodeCapabilities::new()),
+            meta
+        );
+    }
+
+    #[test]
+    fn icc_fallback_keeps_existing_icc_and_srgb() {
+        let caps = EncodeCapabilities::new().with_icc(true);
+        let existing = Metadata::none()
+            .with_cicp(Cicp::DISPLAY_P3)
+            .with_icc(alloc::vec![1, 2, 3]);
+        assert_eq!(existing.clone().with_icc_fallback(&caps), existing);
+
+        let srgb = Metadata::none().with_cicp(Cicp::SRGB);
+        assert!(srgb.with_icc_fallback(&caps).icc_profile.is_none());
+
+        let unspecified = Metadata::none().with_cicp(Cicp::new(2, 2, 0, true));
+        assert!(unspecified.with_icc_fallback(&caps).icc_profile.is_none());
+    }
+
+    #[test]
+    fn sanitized_icc_repairs_or_drops() {
+        let icc = crate::helpers::icc::icc_from_cicp(Cicp::DISPLAY_P3, None).unwrap();
+        let valid = Metadata::none().with_icc(icc.clone());
+        let kept = valid.clone().with_sanitized_icc();
+        assert!(Arc::ptr_eq(
+            kept.icc_profile.as_ref().unwrap(),
+            valid.icc_profile.as_ref().unwrap()
+        ));
+
+        // A stale profile ID is cleared along with the repair.
+        let mut padded = icc.clone();
+        padded[84..100].fill(0xAB);
+        padded.extend_from_slice(b"garbage");
+        let repaired = Metadata::none().with_icc(padded).with_sanitized_icc();
+        let repaired = repaired.icc_profile.as_deref().unwrap();
+        assert_eq!(repaired, icc.as_slice());
+        assert_eq!(&repaired[84..100], &[0; 16]);
+
+        let broken = Metadata::none()
+            .with_icc(icc[..200].to_vec())
+            .with_cicp(Cicp::DISPLAY_P3)
+            .with_sanitized_icc();
+        assert!(broken.icc_profile.is_none());
+        assert_eq!(broken.cicp, Some(Cicp::DISPLAY_P3));
+        assert_eq!(Metadata::none().with_sanitized_icc(), Metadata::none());
+    }
+
+    #[test]
+    fn canonicalize_icc_prefers_cicp() {
+        let p3 = moxcms::ColorProfile::new_display_p3().encode().unwrap();
+        let caps = EncodeCapabilities::new().with_icc(true).with_cicp(true);
+
+        let mut meta = Metadata::none().with_icc(p3.clone());
+        let sub = meta.canonicalize_icc(&caps).unwrap();
+        assert_eq!(
+            sub,
+            IccSubstitution::Cicp {
+                cicp: Cicp::DISPLAY_P3,
+                original_len: p3.len()
+            }
+        );
+        assert_eq!(sub.bytes_saved(), p3.len());
+        assert!(meta.icc_profile.is_none());
+        assert_eq!(meta.cicp, Some(Cicp::DISPLAY_P3));
+
+        let limited = Cicp::new(12, 13, 1, false);
+        let mut meta = Metadata::none().with_icc(p3.clone()).with_cicp(limited);
+        assert_eq!(meta.canonicalize_icc(&caps).unwrap().cicp(), limited);
+
+        let conflicting = Metadata::none().with_icc(p3).with_cicp(Cicp::BT2100_PQ);
+        let mut meta = conflicting.clone();
+        assert_eq!(meta.canonicalize_icc(&caps), None);
+        assert_eq!(meta, conflicting);
+    }
+
+    #[test]
+    fn canonicalize_icc_shrinks_for_icc_only() {
+        let srgb = moxcms::ColorProfile::new_srgb().encode().unwrap();
+        let caps = EncodeCapabilities::new().with_icc(true);
+
+        let mut meta = Metadata::none().with_icc(srgb.clone());
+        let Some(IccSubstitution::Canonical {
+            cicp,
+            original_len,
+            canonical_len,
+        }) = meta.canonicalize_icc(&caps)
+        else {
+            panic!("expected a canonical profile");
+        };
+        assert_eq!(cicp, Cicp::SRGB);
+        assert_eq!(original_len, srgb.len());
+        let icc = meta.icc_profile.as_deref().unwrap();
+        assert_eq!(icc.len(), canonical_len);
+        assert!(canonical_len < original_len);
+        assert!(meta.cicp.is_none());
+
+        // Already canonical, unknown, or nowhere to put it: untouched.
+        let before = meta.clone();
+        assert_eq!(meta.canonicalize_icc(&caps), None);
+        assert_eq!(meta, before);
+        let mut unknown = Metadata::none().with_icc(alloc::vec![0; 4096]);
+        assert_eq!(unknown.canonicalize_icc(&caps), None);
+        let mut no_color = Metadata::none().with_icc(srgb);
+        assert_eq!(no_color.canonicalize_icc(&EncodeCapabilities::new()), None);
+        assert!(no_color.icc_profile.is_some());
+    }
 }
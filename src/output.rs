@@ -6,6 +6,8 @@ use core::any::Any;
 
 use crate::detect::SourceEncodingDetails;
 use crate::extensions::Extensions;
+use crate::metadata::IccSubstitution;
+use crate::warning::DecodeWarning;
 use crate::{ImageFormat, ImageInfo, Metadata};
 use zenpixels::{PixelBuffer, PixelDescriptor, PixelSlice};
 
@@ -23,11 +25,13 @@ pub struct EncodeOutput {
     format: ImageFormat,
     mime_type: &'static str,
     extension: &'static str,
+    gain_map_embedded: bool,
+    icc_substitution: Option<IccSubstitution>,
     extensions: Extensions,
 }
 
 #[cfg(target_pointer_width = "64")]
-const _: () = assert!(core::mem::size_of::<EncodeOutput>() == 96);
+const _: () = assert!(core::mem::size_of::<EncodeOutput>() == 128);
 
 impl EncodeOutput {
     /// Create a new encode output.
@@ -42,10 +46,27 @@ impl EncodeOutput {
             mime_type: format.mime_type(),
             extension: format.extension(),
             format,
+            gain_map_embedded: false,
+            icc_substitution: None,
             extensions: Extensions::new(),
         }
     }
 
+    /// Record that a gain map set via
+    /// [`EncodeJob::with_gain_map()`](crate::encode::EncodeJob::with_gain_map)
+    /// was written into the output.
+    pub fn with_gain_map_embedded(mut self, embedded: bool) -> Self {
+        self.gain_map_embedded = embedded;
+        self
+    }
+
+    /// Record that the ICC profile was replaced per
+    /// [`Metadata::canonicalize_icc()`](crate::Metadata::canonicalize_icc).
+    pub fn with_icc_substitution(mut self, substitution: IccSubstitution) -> Self {
+        self.icc_substitution = Some(substitution);
+        self
+    }
+
     /// Override the MIME type for the encoded output.
     ///
     /// Use when the actual output differs from the base format's default,
@@ -105,6 +126,23 @@ impl EncodeOutput {
         self.extension
     }
 
+    /// Whether a gain map was embedded in the output.
+    ///
+    /// `false` when none was set, or when the encoder does not support gain
+    /// maps or could not use the one it was given.
+    pub fn gain_map_embedded(&self) -> bool {
+        self.gain_map_embedded
+    }
+
+    /// How the ICC profile was replaced, if it was.
+    ///
+    /// `None` when the encoder embedded the profile as given, or did not
+    /// canonicalize (see
+    /// [`EncodePolicy::canonicalize_icc`](crate::encode::EncodePolicy::canonicalize_icc)).
+    pub fn icc_substitution(&self) -> Option<IccSubstitution> {
+        self.icc_substitution
+    }
+
     /// Attach a typed extension value (e.g., encoding statistics, codec-specific metadata).
     ///
     /// Multiple independently-typed values can be stored. Inserting a value of a type
@@ -145,6 +183,8 @@ impl Clone for EncodeOutput {
             format: self.format,
             mime_type: self.mime_type,
             extension: self.extension,
+            gain_map_embedded: self.gain_map_embedded,
+            icc_substitution: self.icc_substitution,
             extensions: self.extensions.clone(),
         }
     }
@@ -157,6 +197,8 @@ impl core::fmt::Debug for EncodeOutput {
             .field("format", &self.format)
             .field("mime_type", &self.mime_type)
             .field("extension", &self.extension)
+            .field("gain_map_embedded", &self.gain_map_embedded)
+            .field("icc_substitution", &self.icc_substitution)
             .field("extensions", &self.extensions)
             .finish()
     }
@@ -168,6 +210,8 @@ impl PartialEq for EncodeOutput {
             && self.format == other.format
             && self.mime_type == other.mime_type
             && self.extension == other.extension
+            && self.gain_map_embedded == other.gain_map_embedded
+            && self.icc_substitution == other.icc_substitution
     }
 }
 
@@ -286,6 +330,32 @@ impl DecodeOutput {
         &self.info
     }
 
+    /// Record a warning found while decoding, after the info was built.
+    ///
+    /// Plain strings become [`DecodeWarningCode::Other`](crate::decode::DecodeWarningCode::Other)
+    /// warnings.
+    pub fn with_warning(mut self, warning: impl Into<DecodeWarning>) -> Self {
+        self.info.warnings.push(warning.into());
+        self
+    }
+
+    /// Warnings from probing and decoding.
+    ///
+    /// Shorthand for `self.info().warnings()`.
+    pub fn warnings(&self) -> &[DecodeWarning] {
+        &self.info.warnings
+    }
+
+    /// Replace the pixels, keeping info, source details, and extras.
+    pub(crate) fn set_pixels(&mut self, pixels: PixelBuffer) {
+        self.pixels = pixels;
+    }
+
+    /// Mutable image info, for fixing it up after [`set_pixels()`](Self::set_pixels).
+    pub(crate) fn info_mut(&mut self) -> &mut ImageInfo {
+        &mut self.info
+    }
+
     /// Image width.
     pub fn width(&self) -> u32 {
         self.pixels.width()
@@ -552,6 +622,31 @@ mod tests {
         assert_ne!(a, c);
     }
 
+    #[test]
+    fn encode_output_gain_map_embedded() {
+        let plain = EncodeOutput::new(vec![1], ImageFormat::Avif);
+        assert!(!plain.gain_map_embedded());
+        let embedded = plain.clone().with_gain_map_embedded(true);
+        assert!(embedded.gain_map_embedded());
+        assert!(embedded.clone().gain_map_embedded());
+        assert_ne!(plain, embedded);
+    }
+
+    #[test]
+    fn encode_output_icc_substitution() {
+        let plain = EncodeOutput::new(vec![1], ImageFormat::Jpeg);
+        assert_eq!(plain.icc_substitution(), None);
+        let sub = IccSubstitution::Canonical {
+            cicp: zenpixels::Cicp::SRGB,
+            original_len: 3144,
+            canonical_len: 544,
+        };
+        let reported = plain.clone().with_icc_substitution(sub);
+        assert_eq!(reported.clone().icc_substitution(), Some(sub));
+        assert_eq!(sub.bytes_saved(), 2600);
+        assert_ne!(plain, reported);
+    }
+
     #[test]
     fn decode_output() {
         let buf = make_rgb8_buffer(2, 2);
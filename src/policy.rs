@@ -12,6 +12,9 @@
 //!
 //! Individual flags can be overridden after constructing a named level.
 
+use crate::helpers::exif::ExifRewrite;
+use crate::warning::{DecodeWarning, DecodeWarningCode, DecodeWarningSet};
+
 /// Decode security policy.
 ///
 /// Controls what features a decoder is permitted to use when processing
@@ -34,6 +37,11 @@ pub struct DecodePolicy {
     /// Extract ICC color profiles. When `Some(false)`, the decoder
     /// skips ICC parsing and returns no profile in [`ImageInfo`](crate::ImageInfo).
     pub allow_icc: Option<bool>,
+    /// Validate extracted ICC profiles. When `Some(true)`, the decoder
+    /// repairs them with
+    /// [`helpers::icc::sanitize_icc`](crate::helpers::icc::sanitize_icc) and
+    /// drops ones that fail.
+    pub validate_icc: Option<bool>,
     /// Extract EXIF metadata.
     pub allow_exif: Option<bool>,
     /// Extract XMP metadata.
@@ -51,36 +59,46 @@ pub struct DecodePolicy {
     /// When `Some(true)`, reject non-conformant inputs that would
     /// otherwise be accepted with workarounds.
     pub strict: Option<bool>,
+    /// Warning codes to treat as errors. When `None`, `strict` decides:
+    /// [`DecodeWarningSet::STRICT`] if strict, otherwise nothing.
+    /// See [`escalate()`](Self::escalate).
+    pub escalate_warnings: Option<DecodeWarningSet>,
 }
 
-// All Option<bool>, no pointers — same size on all platforms.
-const _: () = assert!(core::mem::size_of::<DecodePolicy>() == 7);
+// Option<bool> flags plus an Option<u32> set (4-byte aligned on every
+// target), no pointers — same size on all platforms.
+const _: () = assert!(core::mem::size_of::<DecodePolicy>() == 16);
 
 impl DecodePolicy {
     /// No preferences — codec uses its own defaults.
     pub const fn none() -> Self {
         Self {
             allow_icc: None,
+            validate_icc: None,
             allow_exif: None,
             allow_xmp: None,
             allow_progressive: None,
             allow_animation: None,
             allow_truncated: None,
             strict: None,
+            escalate_warnings: None,
         }
     }
 
     /// Minimal attack surface: no metadata extraction, no progressive,
-    /// no animation, strict parsing.
+    /// no animation, strict parsing. ICC validation is on, for callers that
+    /// re-enable ICC.
     pub const fn strict() -> Self {
         Self {
             allow_icc: Some(false),
+            validate_icc: Some(true),
             allow_exif: Some(false),
             allow_xmp: Some(false),
             allow_progressive: Some(false),
             allow_animation: Some(false),
             allow_truncated: Some(false),
             strict: Some(true),
+            escalate_warnings: Some(DecodeWarningSet::STRICT),
         }
     }
 
@@ -88,12 +106,14 @@ impl DecodePolicy {
     pub const fn permissive() -> Self {
         Self {
             allow_icc: Some(true),
+            validate_icc: Some(false),
             allow_exif: Some(true),
             allow_xmp: Some(true),
             allow_progressive: Some(true),
             allow_animation: Some(true),
             allow_truncated: Some(true),
             strict: Some(false),
+            escalate_warnings: Some(DecodeWarningSet::EMPTY),
         }
     }
 
@@ -103,6 +123,12 @@ impl DecodePolicy {
         self
     }
 
+    /// Override ICC profile validation.
+    pub const fn with_validate_icc(mut self, v: bool) -> Self {
+        self.validate_icc = Some(v);
+        self
+    }
+
     /// Override EXIF extraction.
     pub const fn with_allow_exif(mut self, v: bool) -> Self {
         self.allow_exif = Some(v);
@@ -139,6 +165,12 @@ impl DecodePolicy {
         self
     }
 
+    /// Override which warning codes are treated as errors.
+    pub const fn with_escalate_warnings(mut self, v: DecodeWarningSet) -> Self {
+        self.escalate_warnings = Some(v);
+        self
+    }
+
     /// Resolve a flag: return the explicit value, or fall back to `default`.
     pub const fn resolve_icc(&self, default: bool) -> bool {
         match self.allow_icc {
@@ -147,6 +179,14 @@ impl DecodePolicy {
         }
     }
 
+    /// Resolve ICC validation flag.
+    pub const fn resolve_validate_icc(&self, default: bool) -> bool {
+        match self.validate_icc {
+            Some(v) => v,
+            None => default,
+        }
+    }
+
     /// Resolve EXIF flag.
     pub const fn resolve_exif(&self, default: bool) -> bool {
         match self.allow_exif {
@@ -194,6 +234,38 @@ impl DecodePolicy {
             None => default,
         }
     }
+
+    /// Resolve the escalated warning set.
+    pub const fn resolve_escalate_warnings(&self, default: DecodeWarningSet) -> DecodeWarningSet {
+        match self.escalate_warnings {
+            Some(v) => v,
+            None => default,
+        }
+    }
+
+    /// Whether warnings with `code` should fail the decode.
+    ///
+    /// Uses `escalate_warnings` if set; otherwise [`DecodeWarningSet::STRICT`]
+    /// when `strict` is `Some(true)`, and nothing when it isn't.
+    pub const fn escalates(&self, code: DecodeWarningCode) -> bool {
+        let fallback = if self.resolve_strict(false) {
+            DecodeWarningSet::STRICT
+        } else {
+            DecodeWarningSet::EMPTY
+        };
+        self.resolve_escalate_warnings(fallback).contains(code)
+    }
+
+    /// Route a warning through the policy: `Err` if its code
+    /// [escalates](Self::escalates), `Ok` to record it on
+    /// [`ImageInfo`](crate::ImageInfo) and carry on.
+    pub fn escalate(&self, warning: DecodeWarning) -> Result<DecodeWarning, DecodeWarning> {
+        if self.escalates(warning.code()) {
+            Err(warning)
+        } else {
+            Ok(warning)
+        }
+    }
 }
 
 /// Encode metadata policy.
@@ -215,27 +287,59 @@ impl DecodePolicy {
 ///     .with_embed_icc(true)
 ///     .with_embed_exif(false)
 ///     .with_embed_xmp(false);
+///
+/// // Keep EXIF, but without location, and mark pixels as upright
+/// let policy = EncodePolicy::none()
+///     .with_embed_exif(true)
+///     .with_strip_gps(true)
+///     .with_reset_orientation(true);
 /// ```
+///
+/// Codecs apply `reset_orientation` and `strip_gps` to the EXIF they embed
+/// via [`exif_rewrite()`](Self::exif_rewrite) and
+/// [`helpers::exif::rewrite_exif`](crate::helpers::exif::rewrite_exif);
+/// [`transcode()`](crate::transcode()) applies them before handing source
+/// EXIF to the encoder.
+/// With `validate_icc`, they pass the metadata through
+/// [`Metadata::with_sanitized_icc()`](crate::Metadata::with_sanitized_icc)
+/// before embedding. With `canonicalize_icc`, they call
+/// [`Metadata::canonicalize_icc()`](crate::Metadata::canonicalize_icc) and
+/// report the result via
+/// [`EncodeOutput::with_icc_substitution()`](crate::encode::EncodeOutput::with_icc_substitution).
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct EncodePolicy {
     /// Embed ICC color profiles in the output.
     pub embed_icc: Option<bool>,
+    /// Repair ICC profiles before embedding, and drop ones that can't be
+    /// repaired.
+    pub validate_icc: Option<bool>,
+    /// Replace well-known ICC profiles with a compact canonical profile, or
+    /// with CICP where the format signals it.
+    pub canonicalize_icc: Option<bool>,
     /// Embed EXIF metadata in the output.
     pub embed_exif: Option<bool>,
     /// Embed XMP metadata in the output.
     pub embed_xmp: Option<bool>,
+    /// Set the embedded EXIF Orientation to 1 (pixels already upright).
+    pub reset_orientation: Option<bool>,
+    /// Remove the GPS IFD from embedded EXIF.
+    pub strip_gps: Option<bool>,
 }
 
-const _: () = assert!(core::mem::size_of::<EncodePolicy>() == 3);
+const _: () = assert!(core::mem::size_of::<EncodePolicy>() == 7);
 
 impl EncodePolicy {
     /// No preferences — codec uses its own defaults.
     pub const fn none() -> Self {
         Self {
             embed_icc: None,
+            validate_icc: None,
+            canonicalize_icc: None,
             embed_exif: None,
             embed_xmp: None,
+            reset_orientation: None,
+            strip_gps: None,
         }
     }
 
@@ -243,8 +347,12 @@ impl EncodePolicy {
     pub const fn strip_all() -> Self {
         Self {
             embed_icc: Some(false),
+            validate_icc: None,
+            canonicalize_icc: None,
             embed_exif: Some(false),
             embed_xmp: Some(false),
+            reset_orientation: None,
+            strip_gps: None,
         }
     }
 
@@ -252,8 +360,12 @@ impl EncodePolicy {
     pub const fn preserve_all() -> Self {
         Self {
             embed_icc: Some(true),
+            validate_icc: None,
+            canonicalize_icc: Some(false),
             embed_exif: Some(true),
             embed_xmp: Some(true),
+            reset_orientation: None,
+            strip_gps: Some(false),
         }
     }
 
@@ -263,6 +375,18 @@ impl EncodePolicy {
         self
     }
 
+    /// Override ICC profile validation.
+    pub const fn with_validate_icc(mut self, v: bool) -> Self {
+        self.validate_icc = Some(v);
+        self
+    }
+
+    /// Override ICC canonicalization.
+    pub const fn with_canonicalize_icc(mut self, v: bool) -> Self {
+        self.canonicalize_icc = Some(v);
+        self
+    }
+
     /// Override EXIF embedding.
     pub const fn with_embed_exif(mut self, v: bool) -> Self {
         self.embed_exif = Some(v);
@@ -275,6 +399,18 @@ impl EncodePolicy {
         self
     }
 
+    /// Override EXIF orientation reset.
+    pub const fn with_reset_orientation(mut self, v: bool) -> Self {
+        self.reset_orientation = Some(v);
+        self
+    }
+
+    /// Override GPS stripping.
+    pub const fn with_strip_gps(mut self, v: bool) -> Self {
+        self.strip_gps = Some(v);
+        self
+    }
+
     /// Resolve ICC embedding flag.
     pub const fn resolve_icc(&self, default: bool) -> bool {
         match self.embed_icc {
@@ -283,6 +419,22 @@ impl EncodePolicy {
         }
     }
 
+    /// Resolve ICC validation flag.
+    pub const fn resolve_validate_icc(&self, default: bool) -> bool {
+        match self.validate_icc {
+            Some(v) => v,
+            None => default,
+        }
+    }
+
+    /// Resolve ICC canonicalization flag.
+    pub const fn resolve_canonicalize_icc(&self, default: bool) -> bool {
+        match self.canonicalize_icc {
+            Some(v) => v,
+            None => default,
+        }
+    }
+
     /// Resolve EXIF embedding flag.
     pub const fn resolve_exif(&self, default: bool) -> bool {
         match self.embed_exif {
@@ -298,6 +450,29 @@ impl EncodePolicy {
             None => default,
         }
     }
+
+    /// Resolve EXIF orientation reset flag.
+    pub const fn resolve_reset_orientation(&self, default: bool) -> bool {
+        match self.reset_orientation {
+            Some(v) => v,
+            None => default,
+        }
+    }
+
+    /// Resolve GPS stripping flag.
+    pub const fn resolve_strip_gps(&self, default: bool) -> bool {
+        match self.strip_gps {
+            Some(v) => v,
+            None => default,
+        }
+    }
+
+    /// EXIF changes this policy asks for, with unset options off.
+    pub const fn exif_rewrite(&self) -> ExifRewrite {
+        ExifRewrite::new()
+            .with_reset_orientation(self.resolve_reset_orientation(false))
+            .with_strip_gps(self.resolve_strip_gps(false))
+    }
 }
 
 #[cfg(test)]
@@ -308,12 +483,14 @@ mod tests {
     fn decode_none_is_all_none() {
         let p = DecodePolicy::none();
         assert_eq!(p.allow_icc, None);
+        assert_eq!(p.validate_icc, None);
         assert_eq!(p.allow_exif, None);
         assert_eq!(p.allow_xmp, None);
         assert_eq!(p.allow_progressive, None);
         assert_eq!(p.allow_animation, None);
         assert_eq!(p.allow_truncated, None);
         assert_eq!(p.strict, None);
+        assert_eq!(p.escalate_warnings, None);
     }
 
     #[test]
@@ -350,6 +527,39 @@ mod tests {
         assert!(!p.resolve_icc(true)); // explicit false overrides default true
     }
 
+    #[test]
+    fn decode_warning_escalation() {
+        let crc = || DecodeWarning::new(DecodeWarningCode::ChecksumMismatch, "bad crc");
+        let icc = || DecodeWarning::new(DecodeWarningCode::IccIgnored, "bad icc");
+
+        // Default: nothing escalates.
+        let p = DecodePolicy::none();
+        assert!(p.escalate(crc()).is_ok());
+        assert!(p.escalate(icc()).is_ok());
+
+        // strict escalates data problems, not metadata ones.
+        let p = DecodePolicy::strict();
+        assert!(p.escalate(crc()).is_err());
+        assert!(p.escalate(icc()).is_ok());
+        assert!(
+            DecodePolicy::none()
+                .with_strict(true)
+                .escalates(DecodeWarningCode::Truncated)
+        );
+
+        // An explicit set wins over strict.
+        let p = DecodePolicy::strict()
+            .with_escalate_warnings(DecodeWarningSet::EMPTY.with(DecodeWarningCode::IccIgnored));
+        assert!(p.escalate(crc()).is_ok());
+        assert_eq!(
+            p.escalate(icc()).unwrap_err().code(),
+            DecodeWarningCode::IccIgnored
+        );
+
+        let p = DecodePolicy::permissive();
+        assert!(!p.escalates(DecodeWarningCode::Truncated));
+    }
+
     #[test]
     fn encode_none_is_all_none() {
         let p = EncodePolicy::none();
@@ -391,6 +601,38 @@ mod tests {
         assert!(!p.resolve_icc(true));
     }
 
+    #[test]
+    fn encode_exif_rewrite_options() {
+        assert!(EncodePolicy::none().exif_rewrite().is_noop());
+        // No EXIF is embedded, so there is nothing to rewrite.
+        assert!(EncodePolicy::strip_all().exif_rewrite().is_noop());
+        let rw = EncodePolicy::preserve_all()
+            .with_reset_orientation(true)
+            .exif_rewrite();
+        assert!(rw.reset_orientation);
+        assert!(!rw.strip_gps);
+    }
+
+    #[test]
+    fn validate_icc_flags() {
+        assert!(DecodePolicy::strict().resolve_validate_icc(false));
+        assert!(!DecodePolicy::permissive().resolve_validate_icc(true));
+        assert!(DecodePolicy::none().resolve_validate_icc(true));
+
+        let p = EncodePolicy::preserve_all().with_validate_icc(true);
+        assert!(p.resolve_validate_icc(false));
+        assert_eq!(EncodePolicy::strip_all().validate_icc, None);
+        assert!(!EncodePolicy::none().resolve_validate_icc(false));
+    }
+
+    #[test]
+    fn canonicalize_icc_flag() {
+        assert!(!EncodePolicy::preserve_all().resolve_canonicalize_icc(true));
+        assert!(EncodePolicy::none().resolve_canonicalize_icc(true));
+        let p = EncodePolicy::strip_all().with_canonicalize_icc(true);
+        assert!(p.resolve_canonicalize_icc(false));
+    }
+
     #[test]
     fn static_construction() {
         static _DECODE: DecodePolicy = DecodePolicy::strict().with_allow_icc(true);
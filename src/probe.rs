@@ -0,0 +1,121 @@
+//! Incremental probing for partial input.
+//!
+//! [`DecodeJob::probe_incremental()`](crate::decode::DecodeJob::probe_incremental)
+//! returns a [`ProbeProgress`] instead of failing on truncated input, so
+//! callers fetching headers over HTTP range requests can request exactly the
+//! bytes the codec needs next.
+//!
+//! ```rust,ignore
+//! let mut have = fetch(0..4096)?;
+//! let info = loop {
+//!     match job.probe_incremental(&have)? {
+//!         ProbeProgress::Complete(info) => break info,
+//!         ProbeProgress::NeedMoreData { at_least, .. } => {
+//!             have.extend(fetch(have.len() as u64..at_least)?);
+//!         }
+//!     }
+//! };
+//! ```
+
+use crate::ImageInfo;
+
+// `Complete` is the terminal case of every probe; boxing it would add an
+// allocation per probe to shrink a short-lived value.
+/// Outcome of an incremental probe.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ProbeProgress {
+    /// The input is too short to finish parsing headers.
+    NeedMoreData {
+        /// Minimum total input length (bytes from the start of the file)
+        /// for the next call to make progress.
+        ///
+        /// Always greater than the length of the data just probed. The next
+        /// call may still return `NeedMoreData` — e.g. once a JPEG APP1
+        /// segment is skipped, the codec learns where SOF starts.
+        at_least: u64,
+        /// File offset of the structure the probe is waiting on, when it is
+        /// not simply the bytes following the current input.
+        ///
+        /// For example, a TIFF IFD or a JPEG SOF after a large APP1 segment.
+        /// Callers can use it to prefetch that range; the next call still
+        /// takes a prefix covering `at_least` bytes.
+        hint_offset: Option<u64>,
+    },
+    /// Headers fully parsed.
+    Complete(ImageInfo),
+}
+
+impl ProbeProgress {
+    /// Shorthand for [`NeedMoreData`](ProbeProgress::NeedMoreData) without
+    /// an offset hint.
+    pub fn need(at_least: u64) -> Self {
+        Self::NeedMoreData {
+            at_least,
+            hint_offset: None,
+        }
+    }
+
+    /// Whether probing finished.
+    pub fn is_complete(&self) -> bool {
+        matches!(self, Self::Complete(_))
+    }
+
+    /// The probed metadata, if complete.
+    pub fn info(&self) -> Option<&ImageInfo> {
+        match self {
+            Self::Complete(info) => Some(info),
+            Self::NeedMoreData { .. } => None,
+        }
+    }
+
+    /// Consume and return the probed metadata, if complete.
+    pub fn into_info(self) -> Option<ImageInfo> {
+        match self {
+            Self::Complete(info) => Some(info),
+            Self::NeedMoreData { .. } => None,
+        }
+    }
+
+    /// Additional bytes required beyond `have` bytes of input.
+    ///
+    /// Returns 0 for [`Complete`](ProbeProgress::Complete).
+    pub fn additional_bytes(&self, have: u64) -> u64 {
+        match self {
+            Self::Complete(_) => 0,
+            Self::NeedMoreData { at_least, .. } => at_least.saturating_sub(have),
+        }
+    }
+}
+
+impl From<ImageInfo> for ProbeProgress {
+    fn from(info: ImageInfo) -> Self {
+        Self::Complete(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ImageFormat;
+
+    #[test]
+    fn need_and_additional_bytes() {
+        let p = ProbeProgress::need(100);
+        assert!(!p.is_complete());
+        assert!(p.info().is_none());
+        assert_eq!(p.additional_bytes(40), 60);
+        assert_eq!(p.additional_bytes(200), 0);
+    }
+
+    #[test]
+    fn complete_accessors() {
+        let info = ImageInfo::new(8, 4, ImageFormat::Png);
+        let p = ProbeProgress::from(info.clone());
+        assert!(p.is_complete());
+        assert_eq!(p.info(), Some(&info));
+        assert_eq!(p.additional_bytes(0), 0);
+        assert_eq!(p.into_info(), Some(info));
+    }
+}
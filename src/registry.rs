@@ -0,0 +1,300 @@
+//! Format → codec dispatch.
+//!
+//! [`CodecRegistry`] connects [`ImageFormat`]s to boxed
+//! [`DynDecoderConfig`]s and [`DynEncoderConfig`]s. Applications build one
+//! at startup, codec crates register into it, and callers dispatch by
+//! format (or by magic bytes via [`decode_any()`](CodecRegistry::decode_any))
+//! instead of writing their own `match` over formats.
+//!
+//! ```rust,ignore
+//! let mut codecs = CodecRegistry::new();
+//! codecs
+//!     .register_decoder(Box::new(JpegDecoderConfig::new()), 100)
+//!     .register_decoder(Box::new(FallbackJpegDecoderConfig::new()), 0)
+//!     .register_encoder(Box::new(JpegEncoderConfig::new()), 0);
+//!
+//! let output = codecs.decode_any(&bytes, ResourceLimits::none().with_max_pixels(100_000_000))?;
+//! let lossless_webp = codecs
+//!     .encoders_where(ImageFormat::WebP, |caps| caps.lossless())
+//!     .next();
+//! ```
+//!
+//! # Priorities and fallbacks
+//!
+//! Each registration carries an `i32` priority. Lookups yield configs in
+//! descending priority order; equal priorities keep registration order.
+//! [`decode_any()`](CodecRegistry::decode_any) tries the highest-priority
+//! decoder first and falls back to the next one only when the error
+//! classifies as [`CodecErrorKind::Unsupported`] (the same test
+//! [`FallbackDecoderConfig`](crate::decode::FallbackDecoderConfig) uses) —
+//! corrupt data, exceeded limits, and cancellation are returned immediately.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::decode::{DecodeOutput, DynDecoderConfig};
+use crate::encode::{DynEncoderConfig, EncodeCapabilities};
+use crate::traits::BoxedError;
+use crate::{
+    CodecErrorExt, CodecErrorKind, ImageFormat, ImageFormatRegistry, ResourceLimits,
+    UnsupportedOperation,
+};
+use zenpixels::PixelDescriptor;
+
+/// Error returned by [`CodecRegistry`] lookups and dispatch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RegistryError {
+    /// The format registry did not recognize the input's magic bytes.
+    UnknownFormat,
+    /// No decoder is registered for this format.
+    NoDecoder(ImageFormat),
+    /// No encoder is registered for this format (or none matched the filter).
+    NoEncoder(ImageFormat),
+}
+
+impl core::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownFormat => f.write_str("unrecognized image format"),
+            Self::NoDecoder(fmt) => write!(f, "no decoder registered for {fmt:?}"),
+            Self::NoEncoder(fmt) => write!(f, "no encoder registered for {fmt:?}"),
+        }
+    }
+}
+
+impl core::error::Error for RegistryError {}
+
+struct Registered<C: ?Sized> {
+    priority: i32,
+    config: Box<C>,
+}
+
+/// Registry of decoder and encoder configs, keyed by [`ImageFormat`].
+///
+/// Decoders are registered under every format in
+/// [`DynDecoderConfig::formats()`]; encoders under
+/// [`DynEncoderConfig::format()`]. `Send + Sync`, so a built registry can be
+/// shared across threads.
+pub struct CodecRegistry {
+    formats: ImageFormatRegistry,
+    decoders: Vec<Registered<dyn DynDecoderConfig>>,
+    encoders: Vec<Registered<dyn DynEncoderConfig>>,
+}
+
+impl CodecRegistry {
+    /// Empty registry using [`ImageFormatRegistry::common()`] for detection.
+    pub fn new() -> Self {
+        Self {
+            formats: ImageFormatRegistry::common(),
+            decoders: Vec::new(),
+            encoders: Vec::new(),
+        }
+    }
+
+    /// Use a custom format registry for magic-byte detection.
+    pub fn with_format_registry(mut self, formats: ImageFormatRegistry) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    /// The format registry used for detection.
+    pub fn format_registry(&self) -> &ImageFormatRegistry {
+        &self.formats
+    }
+
+    // --- Registration ---
+
+    /// Register a decoder config for all of its formats.
+    ///
+    /// Higher `priority` is tried first.
+    pub fn register_decoder(
+        &mut self,
+        config: Box<dyn DynDecoderConfig>,
+        priority: i32,
+    ) -> &mut Self {
+        self.decoders.push(Registered { priority, config });
+        // Stable sort: equal priorities keep registration order.
+        self.decoders
+            .sort_by_key(|r| core::cmp::Reverse(r.priority));
+        self
+    }
+
+    /// Register an encoder config for its format.
+    ///
+    /// Higher `priority` is returned first.
+    pub fn register_encoder(
+        &mut self,
+        config: Box<dyn DynEncoderConfig>,
+        priority: i32,
+    ) -> &mut Self {
+        self.encoders.push(Registered { priority, config });
+        self.encoders
+            .sort_by_key(|r| core::cmp::Reverse(r.priority));
+        self
+    }
+
+    /// Builder form of [`register_decoder()`](CodecRegistry::register_decoder).
+    pub fn with_decoder(mut self, config: impl DynDecoderConfig + 'static, priority: i32) -> Self {
+        self.register_decoder(Box::new(config), priority);
+        self
+    }
+
+    /// Builder form of [`register_encoder()`](CodecRegistry::register_encoder).
+    pub fn with_encoder(mut self, config: impl DynEncoderConfig + 'static, priority: i32) -> Self {
+        self.register_encoder(Box::new(config), priority);
+        self
+    }
+
+    // --- Lookup ---
+
+    /// Detect the format of `data` with the format registry.
+    pub fn detect(&self, data: &[u8]) -> Option<ImageFormat> {
+        self.formats.detect(data)
+    }
+
+    /// Decoders for `format`, highest priority first.
+    pub fn decoders_for(&self, format: ImageFormat) -> impl Iterator<Item = &dyn DynDecoderConfig> {
+        self.decoders
+            .iter()
+            .filter(move |r| r.config.formats().contains(&format))
+            .map(|r| &*r.config)
+    }
+
+    /// Highest-priority decoder for `format`.
+    pub fn decoder_for(&self, format: ImageFormat) -> Option<&dyn DynDecoderConfig> {
+        self.decoders_for(format).next()
+    }
+
+    /// Encoders for `format`, highest priority first.
+    pub fn encoders_for(&self, format: ImageFormat) -> impl Iterator<Item = &dyn DynEncoderConfig> {
+        self.encoders_where(format, |_| true)
+    }
+
+    /// Highest-priority encoder for `format`.
+    pub fn encoder_for(&self, format: ImageFormat) -> Option<&dyn DynEncoderConfig> {
+        self.encoders_for(format).next()
+    }
+
+    /// Encoders for `format` whose [`EncodeCapabilities`] satisfy `filter`,
+    /// highest priority first.
+    pub fn encoders_where<F>(
+        &self,
+        format: ImageFormat,
+        filter: F,
+    ) -> impl Iterator<Item = &dyn DynEncoderConfig>
+    where
+        F: Fn(&EncodeCapabilities) -> bool,
+    {
+        self.encoders
+            .iter()
+            .filter(move |r| r.config.format() == format && filter(r.config.capabilities()))
+            .map(|r| &*r.config)
+    }
+
+    /// Encoders for `format` that support every operation in `required`,
+    /// highest priority first.
+    ///
+    /// ```rust,ignore
+    /// let streaming = registry.encoders_supporting(
+    ///     ImageFormat::Png,
+    ///     &[UnsupportedOperation::RowLevelEncode],
+    /// );
+    /// ```
+    pub fn encoders_supporting<'r>(
+        &'r self,
+        format: ImageFormat,
+        required: &'r [UnsupportedOperation],
+    ) -> impl Iterator<Item = &'r dyn DynEncoderConfig> {
+        self.encoders_where(format, move |caps| {
+            required.iter().all(|&op| caps.supports(op))
+        })
+    }
+
+    /// Formats with at least one registered decoder, in registry order.
+    pub fn decodable_formats(&self) -> Vec<ImageFormat> {
+        let mut out = Vec::new();
+        for r in &self.decoders {
+            for &f in r.config.formats() {
+                if !out.contains(&f) {
+                    out.push(f);
+                }
+            }
+        }
+        out
+    }
+
+    /// Formats with at least one registered encoder, in registry order.
+    pub fn encodable_formats(&self) -> Vec<ImageFormat> {
+        let mut out = Vec::new();
+        for r in &self.encoders {
+            let f = r.config.format();
+            if !out.contains(&f) {
+                out.push(f);
+            }
+        }
+        out
+    }
+
+    // --- Dispatch ---
+
+    /// Detect the format of `data` and decode it with the registered
+    /// decoders under `limits`.
+    ///
+    /// Equivalent to [`decode_any_with()`](CodecRegistry::decode_any_with)
+    /// with native output format. Input is usually untrusted, so the limits
+    /// are not optional; pass [`ResourceLimits::none()`] to opt out.
+    pub fn decode_any(
+        &self,
+        data: &[u8],
+        limits: ResourceLimits,
+    ) -> Result<DecodeOutput, BoxedError> {
+        self.decode_any_with(data, limits, &[])
+    }
+
+    /// Detect the format of `data` and decode it, applying `limits` and
+    /// `preferred` output formats.
+    ///
+    /// Decoders are tried in priority order. A decoder whose error
+    /// classifies as [`CodecErrorKind::Unsupported`] (an
+    /// [`UnsupportedOperation`] in its chain, or an explicit kind) is skipped
+    /// in favor of the next one; any other error is returned as-is. If every
+    /// decoder reports an unsupported operation, the last error is returned.
+    pub fn decode_any_with(
+        &self,
+        data: &[u8],
+        limits: ResourceLimits,
+        preferred: &[PixelDescriptor],
+    ) -> Result<DecodeOutput, BoxedError> {
+        let format = self.detect(data).ok_or(RegistryError::UnknownFormat)?;
+        let mut last_err: Option<BoxedError> = None;
+        for config in self.decoders_for(format) {
+            let mut job = config.dyn_job();
+            job.set_limits(limits);
+            let result = job
+                .into_decoder(alloc::borrow::Cow::Borrowed(data), preferred)
+                .and_then(|dec| dec.decode());
+            match result {
+                Ok(output) => return Ok(output),
+                Err(e) if e.kind() == Some(CodecErrorKind::Unsupported) => last_err = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| Box::new(RegistryError::NoDecoder(format))))
+    }
+}
+
+impl Default for CodecRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for CodecRegistry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CodecRegistry")
+            .field("decodable_formats", &self.decodable_formats())
+            .field("encodable_formats", &self.encodable_formats())
+            .finish_non_exhaustive()
+    }
+}
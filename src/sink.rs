@@ -27,6 +27,16 @@
 //!
 //! `begin()` and `finish()` have default no-op implementations. Minimal
 //! sinks only need to implement `provide_next_buffer()`.
+//!
+//! # Strip heights
+//!
+//! After `begin()`, a decoder may ask [`strip_heights()`](DecodeRowSink::strip_heights)
+//! what strips the sink wants — a preferred height, bounds, and an
+//! alignment — and size its `provide_next_buffer()` calls to match,
+//! coalescing or splitting its native strips (8-row JPEG MCUs, single PNG
+//! rows) as needed. [`helpers::StripCoalescer`](crate::helpers::StripCoalescer)
+//! does this for decoders that produce strips of their own. The default is
+//! [`StripHeights::ANY`]; decoders that ignore the hint remain correct.
 
 use alloc::boxed::Box;
 
@@ -118,6 +128,17 @@ pub trait DecodeRowSink {
         descriptor: PixelDescriptor,
     ) -> Result<PixelSliceMut<'_>, SinkError>;
 
+    /// Strip heights this sink wants from `provide_next_buffer()`.
+    ///
+    /// Decoders call this after [`begin()`](DecodeRowSink::begin), so the
+    /// answer may depend on the announced dimensions and format. It is a
+    /// request, not a guarantee: sinks must still accept any strip height.
+    ///
+    /// Default: [`StripHeights::ANY`].
+    fn strip_heights(&self) -> StripHeights {
+        StripHeights::ANY
+    }
+
     /// Called once after the last strip has been fully written.
     ///
     /// The last buffer from `provide_next_buffer()` has been completely
@@ -130,6 +151,193 @@ pub trait DecodeRowSink {
     }
 }
 
+/// Strip sizes a [`DecodeRowSink`] asks decoders to produce.
+///
+/// Built with const `with_*` methods from [`StripHeights::ANY`]. All
+/// constraints except `preferred` apply to every strip but the last, which
+/// holds whatever rows remain.
+///
+/// ```
+/// use zencodec::decode::StripHeights;
+///
+/// // An AV1 encoder behind the sink wants 64-row superblock rows.
+/// let want = StripHeights::ANY.with_preferred(64).with_alignment(64);
+/// assert_eq!(want.strip_height(8), 64);   // JPEG MCU rows coalesced
+/// assert_eq!(want.strip_height(100), 64);
+/// assert_eq!(StripHeights::ANY.strip_height(8), 8);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StripHeights {
+    preferred: u32,
+    min: u32,
+    max: u32,
+    alignment: u32,
+}
+
+impl StripHeights {
+    /// No preference: any strip height is welcome.
+    pub const ANY: Self = Self {
+        preferred: 0,
+        min: 1,
+        max: u32::MAX,
+        alignment: 1,
+    };
+
+    /// Rows per strip the sink handles best (0 = no preference).
+    pub const fn with_preferred(mut self, rows: u32) -> Self {
+        self.preferred = rows;
+        self
+    }
+
+    /// Fewest rows per strip (clamped to at least 1).
+    pub const fn with_min(mut self, rows: u32) -> Self {
+        self.min = if rows == 0 { 1 } else { rows };
+        self
+    }
+
+    /// Most rows per strip (clamped to at least 1).
+    pub const fn with_max(mut self, rows: u32) -> Self {
+        self.max = if rows == 0 { 1 } else { rows };
+        self
+    }
+
+    /// Strip heights, and so strip start rows, are multiples of this
+    /// (clamped to at least 1).
+    pub const fn with_alignment(mut self, rows: u32) -> Self {
+        self.alignment = if rows == 0 { 1 } else { rows };
+        self
+    }
+
+    /// Preferred rows per strip, or 0 for none.
+    pub const fn preferred(&self) -> u32 {
+        self.preferred
+    }
+
+    /// Fewest rows per strip.
+    pub const fn min(&self) -> u32 {
+        self.min
+    }
+
+    /// Most rows per strip.
+    pub const fn max(&self) -> u32 {
+        self.max
+    }
+
+    /// Row multiple for strip heights.
+    pub const fn alignment(&self) -> u32 {
+        self.alignment
+    }
+
+    /// Whether any constraint is set.
+    pub const fn is_any(&self) -> bool {
+        self.preferred == 0 && self.min == 1 && self.max == u32::MAX && self.alignment == 1
+    }
+
+    /// Strip height for a decoder whose natural strip is `native` rows.
+    ///
+    /// Starts from `preferred` (or `native` without one), clamps to
+    /// `min..=max`, then rounds down to a multiple of `alignment` — or up,
+    /// when rounding down would reach zero. Alignment wins over `max` when
+    /// the two conflict. Never returns 0.
+    pub const fn strip_height(&self, native: u32) -> u32 {
+        let mut rows = if self.preferred > 0 {
+            self.preferred
+        } else {
+            native
+        };
+        if rows < self.min {
+            rows = self.min;
+        }
+        if rows > self.max {
+            rows = self.max;
+        }
+        rows -= rows % self.alignment;
+        if rows == 0 { self.alignment } else { rows }
+    }
+
+    /// Combine two sinks' requests, as when one decode feeds both.
+    ///
+    /// Bounds intersect (an empty intersection keeps `self`'s bounds), the
+    /// alignments combine to their least common multiple, and `self`'s
+    /// preferred height wins when both set one.
+    pub const fn merge(self, other: Self) -> Self {
+        let min = if self.min > other.min {
+            self.min
+        } else {
+            other.min
+        };
+        let max = if self.max < other.max {
+            self.max
+        } else {
+            other.max
+        };
+        let (min, max) = if min <= max {
+            (min, max)
+        } else {
+            (self.min, self.max)
+        };
+        Self {
+            preferred: if self.preferred > 0 {
+                self.preferred
+            } else {
+                other.preferred
+            },
+            min,
+            max,
+            alignment: lcm(self.alignment, other.alignment),
+        }
+    }
+}
+
+impl Default for StripHeights {
+    fn default() -> Self {
+        Self::ANY
+    }
+}
+
+const fn lcm(a: u32, b: u32) -> u32 {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        let t = x % y;
+        x = y;
+        y = t;
+    }
+    // Saturate rather than overflow on pathological alignments.
+    match (a / x).checked_mul(b) {
+        Some(v) => v,
+        None => u32::MAX,
+    }
+}
+
+impl<S: DecodeRowSink + ?Sized> DecodeRowSink for &mut S {
+    fn begin(
+        &mut self,
+        width: u32,
+        height: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<(), SinkError> {
+        (**self).begin(width, height, descriptor)
+    }
+
+    fn provide_next_buffer(
+        &mut self,
+        y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        (**self).provide_next_buffer(y, height, width, descriptor)
+    }
+
+    fn strip_heights(&self) -> StripHeights {
+        (**self).strip_heights()
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        (**self).finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -459,4 +667,81 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().to_string(), "flush failed");
     }
+
+    #[test]
+    fn strip_heights_pick() {
+        let any = StripHeights::ANY;
+        assert!(any.is_any());
+        assert_eq!(any, StripHeights::default());
+        assert_eq!(any.strip_height(8), 8);
+        assert_eq!(any.strip_height(0), 1);
+
+        let bounded = any.with_min(16).with_max(32);
+        assert_eq!(bounded.strip_height(1), 16);
+        assert_eq!(bounded.strip_height(100), 32);
+        assert_eq!(bounded.strip_height(20), 20);
+
+        let aligned = any.with_preferred(20).with_alignment(8);
+        assert_eq!(aligned.strip_height(1), 16);
+        assert_eq!(any.with_preferred(3).with_alignment(8).strip_height(1), 8);
+        // Alignment wins over a smaller max.
+        assert_eq!(any.with_max(4).with_alignment(8).strip_height(1), 8);
+
+        let zeros = any.with_min(0).with_max(0).with_alignment(0);
+        assert_eq!((zeros.min(), zeros.max(), zeros.alignment()), (1, 1, 1));
+    }
+
+    #[test]
+    fn strip_heights_merge() {
+        let a = StripHeights::ANY
+            .with_preferred(16)
+            .with_alignment(8)
+            .with_max(64);
+        let b = StripHeights::ANY
+            .with_preferred(64)
+            .with_alignment(6)
+            .with_min(4);
+        let m = a.merge(b);
+        assert_eq!(m.preferred(), 16);
+        assert_eq!((m.min(), m.max(), m.alignment()), (4, 64, 24));
+        assert_eq!(m.strip_height(1), 24);
+
+        assert_eq!(StripHeights::ANY.merge(b).preferred(), 64);
+        assert_eq!(
+            StripHeights::ANY.merge(StripHeights::ANY),
+            StripHeights::ANY
+        );
+
+        // Disjoint bounds keep the first sink's.
+        let low = StripHeights::ANY.with_max(8);
+        let high = StripHeights::ANY.with_min(16);
+        let m = low.merge(high);
+        assert_eq!((m.min(), m.max()), (1, 8));
+
+        assert_eq!(lcm(u32::MAX, u32::MAX - 1), u32::MAX);
+    }
+
+    #[test]
+    fn mut_ref_forwards_strip_heights() {
+        struct Wants16;
+        impl DecodeRowSink for Wants16 {
+            fn provide_next_buffer(
+                &mut self,
+                _y: u32,
+                _height: u32,
+                _width: u32,
+                _descriptor: PixelDescriptor,
+            ) -> Result<PixelSliceMut<'_>, SinkError> {
+                unreachable!("not called in this test")
+            }
+
+            fn strip_heights(&self) -> StripHeights {
+                StripHeights::ANY.with_preferred(16)
+            }
+        }
+
+        let mut sink = Wants16;
+        let by_ref: &mut dyn DecodeRowSink = &mut &mut sink;
+        assert_eq!(by_ref.strip_heights().preferred(), 16);
+    }
 }
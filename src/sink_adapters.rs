@@ -0,0 +1,822 @@
+//! Ready-made [`DecodeRowSink`]s.
+//!
+//! - [`BufferSink`] assembles strips into a [`PixelBuffer`] sized by
+//!   [`begin()`](DecodeRowSink::begin).
+//! - [`TeeSink`] fans every strip out to two sinks.
+//! - [`OrientingSink`] applies an [`Orientation`] on the way to another sink.
+//! - [`ConvertingSink`] accepts the decoder's native format and hands
+//!   another sink the caller's preferred one.
+//!
+//! They nest, and work with any push decoder, including
+//! [`helpers::copy_decode_to_sink()`](crate::helpers::copy_decode_to_sink):
+//!
+//! ```rust,ignore
+//! let mut upright = BufferSink::new();
+//! let mut sink = OrientingSink::new(&mut upright, info.orientation);
+//! job.push_decoder(Cow::Borrowed(&data), &mut sink, &[])?;
+//! let pixels = upright.into_buffer().unwrap();
+//! ```
+//!
+//! A decoder writes each strip after `provide_next_buffer()` returns, so
+//! the adapters that transform pixels stage the strip in their own buffer
+//! and pass it on at the next call or at `finish()`.
+
+use alloc::vec::Vec;
+
+use crate::Orientation;
+use crate::finish::orient_into;
+use crate::sink::{DecodeRowSink, SinkError, StripHeights};
+use zenpixels::{PixelBuffer, PixelDescriptor, PixelSlice, PixelSliceMut};
+
+/// Error from the sinks in this module and
+/// [`StripCoalescer`](crate::helpers::StripCoalescer) when a decoder breaks
+/// the [`DecodeRowSink`] protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RowSinkError {
+    /// A strip arrived before `begin()`.
+    NotBegun,
+    /// A strip's width or pixel format differs from `begin()`.
+    LayoutChanged,
+    /// A strip extends past the height given to `begin()`.
+    OutOfBounds {
+        /// First row of the strip.
+        y: u32,
+        /// Rows in the strip.
+        height: u32,
+    },
+    /// A strip did not start where the previous one ended.
+    OutOfOrder {
+        /// Row the next strip should start at.
+        expected: u32,
+        /// Row the strip started at.
+        y: u32,
+    },
+    /// The image buffer could not be allocated.
+    Allocation,
+}
+
+impl core::fmt::Display for RowSinkError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotBegun => f.write_str("row sink: strip before begin()"),
+            Self::LayoutChanged => f.write_str("row sink: strip width or format changed"),
+            Self::OutOfBounds { y, height } => {
+                write!(f, "row sink: rows {y}..{} past the image", y + height)
+            }
+            Self::OutOfOrder { expected, y } => {
+                write!(f, "row sink: strip at row {y}, expected row {expected}")
+            }
+            Self::Allocation => f.write_str("row sink: image buffer allocation failed"),
+        }
+    }
+}
+
+impl core::error::Error for RowSinkError {}
+
+/// Image geometry announced by `begin()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Frame {
+    width: u32,
+    height: u32,
+    descriptor: PixelDescriptor,
+}
+
+impl Frame {
+    fn check(
+        frame: Option<Frame>,
+        y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<Frame, RowSinkError> {
+        let frame = frame.ok_or(RowSinkError::NotBegun)?;
+        if width != frame.width || descriptor != frame.descriptor {
+            return Err(RowSinkError::LayoutChanged);
+        }
+        if y.checked_add(height).is_none_or(|end| end > frame.height) {
+            return Err(RowSinkError::OutOfBounds { y, height });
+        }
+        Ok(frame)
+    }
+}
+
+/// A strip lent to the decoder and not yet passed on.
+#[derive(Default)]
+struct Staging {
+    buf: Vec<u8>,
+    lent: Option<(u32, u32, u32, PixelDescriptor)>,
+}
+
+impl Staging {
+    fn lend(
+        &mut self,
+        y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        let len = (width as usize)
+            .checked_mul(descriptor.bytes_per_pixel())
+            .and_then(|stride| stride.checked_mul(height as usize))
+            .ok_or(RowSinkError::Allocation)?;
+        if self.buf.len() < len {
+            self.buf
+                .try_reserve(len - self.buf.len())
+                .map_err(|_| RowSinkError::Allocation)?;
+            self.buf.resize(len, 0);
+        }
+        let stride = width as usize * descriptor.bytes_per_pixel();
+        self.lent = Some((y, height, width, descriptor));
+        Ok(PixelSliceMut::new(
+            &mut self.buf[..len],
+            width,
+            height,
+            stride,
+            descriptor,
+        )?)
+    }
+
+    /// The strip the decoder has finished writing, with its first row.
+    fn take(&mut self) -> Option<(u32, PixelSlice<'_>)> {
+        let (y, height, width, descriptor) = self.lent.take()?;
+        let stride = width as usize * descriptor.bytes_per_pixel();
+        let len = height as usize * stride;
+        // Same geometry the strip was lent with.
+        let strip = PixelSlice::new(&self.buf[..len], width, height, stride, descriptor)
+            .expect("staged strip matches lent geometry");
+        Some((y, strip))
+    }
+}
+
+/// Copy `src` into the next buffer of `sink`, rows `y..`.
+fn forward(sink: &mut dyn DecodeRowSink, y: u32, src: &PixelSlice<'_>) -> Result<(), SinkError> {
+    let row_bytes = src.width() as usize * src.descriptor().bytes_per_pixel();
+    let mut dst = sink.provide_next_buffer(y, src.rows(), src.width(), src.descriptor())?;
+    for row in 0..src.rows() {
+        dst.row_mut(row)[..row_bytes].copy_from_slice(&src.row(row)[..row_bytes]);
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// BufferSink
+// ---------------------------------------------------------------------------
+
+/// [`DecodeRowSink`] that assembles the decoded image into a [`PixelBuffer`].
+///
+/// The buffer is allocated in [`begin()`](DecodeRowSink::begin) with the
+/// announced dimensions and format, and strips are written straight into
+/// it. Strips that don't fit fail with a [`RowSinkError`].
+#[derive(Debug, Default)]
+pub struct BufferSink {
+    buffer: Option<PixelBuffer>,
+    finished: bool,
+}
+
+impl BufferSink {
+    /// An empty sink, waiting for `begin()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The image so far, once `begin()` has been called.
+    pub fn buffer(&self) -> Option<&PixelBuffer> {
+        self.buffer.as_ref()
+    }
+
+    /// Take the image, once `begin()` has been called.
+    pub fn into_buffer(self) -> Option<PixelBuffer> {
+        self.buffer
+    }
+
+    /// Whether `finish()` has been called.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn frame(&self) -> Option<Frame> {
+        self.buffer.as_ref().map(|b| Frame {
+            width: b.width(),
+            height: b.height(),
+            descriptor: b.descriptor(),
+        })
+    }
+}
+
+impl DecodeRowSink for BufferSink {
+    fn begin(
+        &mut self,
+        width: u32,
+        height: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<(), SinkError> {
+        let buffer = PixelBuffer::try_new(width, height, descriptor)
+            .map_err(|_| RowSinkError::Allocation)?;
+        self.buffer = Some(buffer);
+        self.finished = false;
+        Ok(())
+    }
+
+    fn provide_next_buffer(
+        &mut self,
+        y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        Frame::check(self.frame(), y, height, width, descriptor)?;
+        let buffer = self.buffer.as_mut().ok_or(RowSinkError::NotBegun)?;
+        Ok(buffer.rows_mut(y, height))
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.finished = true;
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// TeeSink
+// ---------------------------------------------------------------------------
+
+/// [`DecodeRowSink`] that passes every strip to two sinks.
+///
+/// `begin()` and `finish()` reach both, `first` before `second`; the first
+/// error stops the decode.
+#[derive(Default)]
+pub struct TeeSink<A, B> {
+    first: A,
+    second: B,
+    staging: Staging,
+}
+
+impl<A: DecodeRowSink, B: DecodeRowSink> TeeSink<A, B> {
+    /// Fan out to `first` and `second`.
+    pub fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            staging: Staging::default(),
+        }
+    }
+
+    /// The first sink.
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// The second sink.
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    /// Unwrap both sinks.
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+
+    fn flush(&mut self) -> Result<(), SinkError> {
+        if let Some((y, strip)) = self.staging.take() {
+            forward(&mut self.first, y, &strip)?;
+            forward(&mut self.second, y, &strip)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A: DecodeRowSink, B: DecodeRowSink> DecodeRowSink for TeeSink<A, B> {
+    fn begin(
+        &mut self,
+        width: u32,
+        height: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<(), SinkError> {
+        self.first.begin(width, height, descriptor)?;
+        self.second.begin(width, height, descriptor)
+    }
+
+    fn provide_next_buffer(
+        &mut self,
+        y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        self.flush()?;
+        self.staging.lend(y, height, width, descriptor)
+    }
+
+    fn strip_heights(&self) -> StripHeights {
+        self.first
+            .strip_heights()
+            .merge(self.second.strip_heights())
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.flush()?;
+        self.first.finish()?;
+        self.second.finish()
+    }
+}
+
+impl<A, B> core::fmt::Debug for TeeSink<A, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TeeSink").finish_non_exhaustive()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// OrientingSink
+// ---------------------------------------------------------------------------
+
+/// [`DecodeRowSink`] that applies an [`Orientation`] before passing rows on.
+///
+/// The inner sink's `begin()` gets the oriented dimensions.
+/// [`Identity`](Orientation::Identity) and [`FlipH`](Orientation::FlipH)
+/// keep rows in order, so strips stream through. The other orientations
+/// move pixels across rows: each strip is written into an oriented image
+/// buffer as it arrives and the inner sink receives the whole image as one
+/// strip at `finish()`.
+pub struct OrientingSink<S> {
+    inner: S,
+    orientation: Orientation,
+    frame: Option<Frame>,
+    staging: Staging,
+    oriented: Option<PixelBuffer>,
+}
+
+impl<S: DecodeRowSink> OrientingSink<S> {
+    /// Apply `orientation` to rows before `inner` sees them.
+    pub fn new(inner: S, orientation: Orientation) -> Self {
+        Self {
+            inner,
+            orientation,
+            frame: None,
+            staging: Staging::default(),
+            oriented: None,
+        }
+    }
+
+    /// The orientation being applied.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Unwrap the inner sink.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn flush(&mut self) -> Result<(), SinkError> {
+        let Some((y, strip)) = self.staging.take() else {
+            return Ok(());
+        };
+        if let Some(oriented) = self.oriented.as_mut() {
+            let frame = self.frame.ok_or(RowSinkError::NotBegun)?;
+            let size = (frame.width, frame.height);
+            orient_into(
+                &strip,
+                y,
+                size,
+                self.orientation,
+                &mut oriented.as_slice_mut(),
+            );
+            return Ok(());
+        }
+        // FlipH: mirror each row in place.
+        let bpp = strip.descriptor().bytes_per_pixel();
+        let row_bytes = strip.width() as usize * bpp;
+        let mut dst =
+            self.inner
+                .provide_next_buffer(y, strip.rows(), strip.width(), strip.descriptor())?;
+        for row in 0..strip.rows() {
+            let src = strip.row(row)[..row_bytes].chunks_exact(bpp).rev();
+            let out = dst.row_mut(row)[..row_bytes].chunks_exact_mut(bpp);
+            for (d, s) in out.zip(src) {
+                d.copy_from_slice(s);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: DecodeRowSink> DecodeRowSink for OrientingSink<S> {
+    fn begin(
+        &mut self,
+        width: u32,
+        height: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<(), SinkError> {
+        let (w, h) = self.orientation.output_dimensions(width, height);
+        self.inner.begin(w, h, descriptor)?;
+        self.frame = Some(Frame {
+            width,
+            height,
+            descriptor,
+        });
+        self.oriented = if self.orientation.is_row_local() {
+            None
+        } else {
+            Some(PixelBuffer::try_new(w, h, descriptor).map_err(|_| RowSinkError::Allocation)?)
+        };
+        Ok(())
+    }
+
+    fn provide_next_buffer(
+        &mut self,
+        y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        Frame::check(self.frame, y, height, width, descriptor)?;
+        if self.orientation.is_identity() {
+            return self.inner.provide_next_buffer(y, height, width, descriptor);
+        }
+        self.flush()?;
+        self.staging.lend(y, height, width, descriptor)
+    }
+
+    /// The inner sink's request for row-local orientations; otherwise any
+    /// height, since the inner sink receives one strip at `finish()`.
+    fn strip_heights(&self) -> StripHeights {
+        if self.orientation.is_row_local() {
+            self.inner.strip_heights()
+        } else {
+            StripHeights::ANY
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.flush()?;
+        if let Some(oriented) = self.oriented.take() {
+            forward(&mut self.inner, 0, &oriented.as_slice())?;
+        }
+        self.inner.finish()
+    }
+}
+
+impl<S> core::fmt::Debug for OrientingSink<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OrientingSink")
+            .field("orientation", &self.orientation)
+            .finish_non_exhaustive()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ConvertingSink
+// ---------------------------------------------------------------------------
+
+/// [`DecodeRowSink`] that converts strips to a target [`PixelDescriptor`].
+///
+/// The decoder writes its native format; the inner sink's `begin()` and
+/// buffers use `target`. zencodec has no pixel conversion of its own, so
+/// `convert` does the work: it receives each decoded strip and the inner
+/// sink's buffer of the same size, and typically wraps a zenpixels
+/// row converter planned for the two descriptors. When the decoder
+/// already produces `target`, strips go straight to the inner sink and
+/// `convert` is never called.
+///
+/// ```rust,ignore
+/// let mut sink = ConvertingSink::new(&mut pixels, PixelDescriptor::RGBA8_SRGB,
+///     |src: &PixelSlice<'_>, dst: &mut PixelSliceMut<'_>| {
+///         converter.convert_rows(src, dst).map_err(|e| Box::new(e) as SinkError)
+///     });
+/// job.push_decoder(Cow::Borrowed(&data), &mut sink, &[PixelDescriptor::RGBA8_SRGB])?;
+/// ```
+pub struct ConvertingSink<S, F> {
+    inner: S,
+    target: PixelDescriptor,
+    convert: F,
+    frame: Option<Frame>,
+    staging: Staging,
+}
+
+impl<S, F> ConvertingSink<S, F>
+where
+    S: DecodeRowSink,
+    F: FnMut(&PixelSlice<'_>, &mut PixelSliceMut<'_>) -> Result<(), SinkError>,
+{
+    /// Convert strips to `target` with `convert` before `inner` sees them.
+    pub fn new(inner: S, target: PixelDescriptor, convert: F) -> Self {
+        Self {
+            inner,
+            target,
+            convert,
+            frame: None,
+            staging: Staging::default(),
+        }
+    }
+
+    /// The format the inner sink receives.
+    pub fn target(&self) -> PixelDescriptor {
+        self.target
+    }
+
+    /// Unwrap the inner sink.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn flush(&mut self) -> Result<(), SinkError> {
+        if let Some((y, strip)) = self.staging.take() {
+            let mut dst =
+                self.inner
+                    .provide_next_buffer(y, strip.rows(), strip.width(), self.target)?;
+            (self.convert)(&strip, &mut dst)?;
+        }
+        Ok(())
+    }
+}
+
+impl<S, F> DecodeRowSink for ConvertingSink<S, F>
+where
+    S: DecodeRowSink,
+    F: FnMut(&PixelSlice<'_>, &mut PixelSliceMut<'_>) -> Result<(), SinkError>,
+{
+    fn begin(
+        &mut self,
+        width: u32,
+        height: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<(), SinkError> {
+        self.inner.begin(width, height, self.target)?;
+        self.frame = Some(Frame {
+            width,
+            height,
+            descriptor,
+        });
+        Ok(())
+    }
+
+    fn provide_next_buffer(
+        &mut self,
+        y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        Frame::check(self.frame, y, height, width, descriptor)?;
+        if descriptor == self.target {
+            return self.inner.provide_next_buffer(y, height, width, descriptor);
+        }
+        self.flush()?;
+        self.staging.lend(y, height, width, descriptor)
+    }
+
+    fn strip_heights(&self) -> StripHeights {
+        self.inner.strip_heights()
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.flush()?;
+        self.inner.finish()
+    }
+}
+
+impl<S, F> core::fmt::Debug for ConvertingSink<S, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ConvertingSink")
+            .field("target", &self.target)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finish::orient_pixels;
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+
+    /// 5×7 RGB8 image where each channel holds a distinct byte.
+    fn source() -> PixelBuffer {
+        let data = (0..5 * 7 * 3).map(|i| i as u8).collect();
+        PixelBuffer::from_vec(data, 5, 7, PixelDescriptor::RGB8_SRGB).unwrap()
+    }
+
+    /// Drive `sink` like a push decoder, `strip` rows at a time.
+    fn push(src: &PixelBuffer, sink: &mut dyn DecodeRowSink, strip: u32) -> Result<(), SinkError> {
+        let ps = src.as_slice();
+        let (w, h) = (ps.width(), ps.rows());
+        sink.begin(w, h, ps.descriptor())?;
+        let mut y = 0;
+        while y < h {
+            let n = strip.min(h - y);
+            let mut dst = sink.provide_next_buffer(y, n, w, ps.descriptor())?;
+            for row in 0..n {
+                dst.row_mut(row).copy_from_slice(ps.row(y + row));
+            }
+            y += n;
+        }
+        sink.finish()
+    }
+
+    fn bytes(pixels: &PixelBuffer) -> (u32, u32, Vec<u8>) {
+        (
+            pixels.width(),
+            pixels.height(),
+            pixels.copy_to_contiguous_bytes(),
+        )
+    }
+
+    #[test]
+    fn buffer_sink_assembles_strips() {
+        let src = source();
+        for strip in [1, 3, 7, 16] {
+            let mut sink = BufferSink::new();
+            push(&src, &mut sink, strip).unwrap();
+            assert!(sink.is_finished());
+            assert_eq!(bytes(&sink.into_buffer().unwrap()), bytes(&src));
+        }
+    }
+
+    #[test]
+    fn buffer_sink_rejects_protocol_violations() {
+        let rgb = PixelDescriptor::RGB8_SRGB;
+        let kind = |e: SinkError| *e.downcast::<RowSinkError>().unwrap();
+
+        let mut sink = BufferSink::new();
+        let err = sink.provide_next_buffer(0, 1, 5, rgb).err().unwrap();
+        assert_eq!(kind(err), RowSinkError::NotBegun);
+
+        sink.begin(5, 7, rgb).unwrap();
+        let err = sink.provide_next_buffer(0, 1, 4, rgb).err().unwrap();
+        assert_eq!(kind(err), RowSinkError::LayoutChanged);
+        let err = sink
+            .provide_next_buffer(0, 1, 5, PixelDescriptor::RGBA8_SRGB)
+            .err()
+            .unwrap();
+        assert_eq!(kind(err), RowSinkError::LayoutChanged);
+        let err = sink.provide_next_buffer(6, 2, 5, rgb).err().unwrap();
+        assert_eq!(kind(err), RowSinkError::OutOfBounds { y: 6, height: 2 });
+        assert_eq!(
+            RowSinkError::OutOfBounds { y: 6, height: 2 }.to_string(),
+            "row sink: rows 6..8 past the image"
+        );
+        assert!(sink.provide_next_buffer(6, 1, 5, rgb).is_ok());
+    }
+
+    #[test]
+    fn staging_rejects_oversized_strips() {
+        let err = Staging::default()
+            .lend(0, u32::MAX, u32::MAX, PixelDescriptor::RGBA8_SRGB)
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<RowSinkError>(),
+            Some(RowSinkError::Allocation)
+        ));
+    }
+
+    #[test]
+    fn tee_sink_feeds_both() {
+        let src = source();
+        let (mut a, mut b) = (BufferSink::new(), BufferSink::new());
+        let mut tee = TeeSink::new(&mut a, &mut b);
+        push(&src, &mut tee, 2).unwrap();
+        drop(tee);
+        assert!(a.is_finished() && b.is_finished());
+        assert_eq!(bytes(a.buffer().unwrap()), bytes(&src));
+        assert_eq!(bytes(b.buffer().unwrap()), bytes(&src));
+    }
+
+    #[test]
+    fn orienting_sink_matches_orient_pixels() {
+        let src = source();
+        for orientation in Orientation::ALL {
+            let expected = orient_pixels(&src.as_slice(), orientation);
+            for strip in [1, 3, 7] {
+                let mut sink = OrientingSink::new(BufferSink::new(), orientation);
+                push(&src, &mut sink, strip).unwrap();
+                let out = sink.into_inner();
+                assert!(out.is_finished());
+                assert_eq!(
+                    bytes(&out.into_buffer().unwrap()),
+                    bytes(&expected),
+                    "{orientation:?} strip {strip}"
+                );
+            }
+        }
+    }
+
+    /// RGB8 → RGBA8 with opaque alpha.
+    fn add_alpha(src: &PixelSlice<'_>, dst: &mut PixelSliceMut<'_>) -> Result<(), SinkError> {
+        for y in 0..src.rows() {
+            let (s, d) = (src.row(y), dst.row_mut(y));
+            for (rgb, rgba) in s.chunks_exact(3).zip(d.chunks_exact_mut(4)) {
+                rgba[..3].copy_from_slice(rgb);
+                rgba[3] = 255;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn converting_sink_converts_to_target() {
+        let src = source();
+        let mut sink =
+            ConvertingSink::new(BufferSink::new(), PixelDescriptor::RGBA8_SRGB, add_alpha);
+        push(&src, &mut sink, 3).unwrap();
+        let out = sink.into_inner().into_buffer().unwrap();
+        assert_eq!(out.descriptor(), PixelDescriptor::RGBA8_SRGB);
+        let (w, h, data) = bytes(&out);
+        assert_eq!((w, h), (5, 7));
+        let expected: Vec<u8> = src
+            .copy_to_contiguous_bytes()
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect();
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn converting_sink_passes_matching_format_through() {
+        let src = source();
+        let mut calls = 0;
+        let mut sink = ConvertingSink::new(
+            BufferSink::new(),
+            PixelDescriptor::RGB8_SRGB,
+            |_: &PixelSlice<'_>, _: &mut PixelSliceMut<'_>| {
+                calls += 1;
+                Ok(())
+            },
+        );
+        push(&src, &mut sink, 2).unwrap();
+        let out = sink.into_inner().into_buffer().unwrap();
+        assert_eq!(bytes(&out), bytes(&src));
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn converter_errors_stop_the_decode() {
+        let mut sink = ConvertingSink::new(
+            BufferSink::new(),
+            PixelDescriptor::RGBA8_SRGB,
+            |_: &PixelSlice<'_>, _: &mut PixelSliceMut<'_>| -> Result<(), SinkError> {
+                Err(Box::new(RowSinkError::Allocation))
+            },
+        );
+        assert!(push(&source(), &mut sink, 7).is_err());
+    }
+
+    #[test]
+    fn adapters_compose() {
+        let src = source();
+        let mut plain = BufferSink::new();
+        let mut upright = BufferSink::new();
+        let oriented = OrientingSink::new(&mut upright, Orientation::Rotate270);
+        let converted = ConvertingSink::new(oriented, PixelDescriptor::RGBA8_SRGB, add_alpha);
+        let mut tee = TeeSink::new(&mut plain, converted);
+        push(&src, &mut tee, 2).unwrap();
+        drop(tee);
+        assert_eq!(bytes(plain.buffer().unwrap()), bytes(&src));
+        let out = upright.into_buffer().unwrap();
+        assert_eq!((out.width(), out.height()), (7, 5));
+        assert_eq!(out.descriptor(), PixelDescriptor::RGBA8_SRGB);
+    }
+
+    #[test]
+    fn adapters_forward_strip_heights() {
+        struct Wants(StripHeights);
+        impl DecodeRowSink for Wants {
+            fn provide_next_buffer(
+                &mut self,
+                _y: u32,
+                _height: u32,
+                _width: u32,
+                _descriptor: PixelDescriptor,
+            ) -> Result<PixelSliceMut<'_>, SinkError> {
+                unreachable!("not called in this test")
+            }
+
+            fn strip_heights(&self) -> StripHeights {
+                self.0
+            }
+        }
+
+        let h16 = StripHeights::ANY.with_preferred(16).with_alignment(16);
+        let a8 = StripHeights::ANY.with_alignment(8).with_max(64);
+        assert_eq!(BufferSink::new().strip_heights(), StripHeights::ANY);
+        assert_eq!(
+            TeeSink::new(Wants(h16), Wants(a8)).strip_heights(),
+            h16.merge(a8)
+        );
+        assert_eq!(
+            OrientingSink::new(Wants(h16), Orientation::FlipH).strip_heights(),
+            h16
+        );
+        assert_eq!(
+            OrientingSink::new(Wants(h16), Orientation::Rotate90).strip_heights(),
+            StripHeights::ANY
+        );
+        let converting = ConvertingSink::new(
+            Wants(h16),
+            PixelDescriptor::RGBA8_SRGB,
+            |_: &PixelSlice<'_>, _: &mut PixelSliceMut<'_>| Ok(()),
+        );
+        assert_eq!(converting.strip_heights(), h16);
+    }
+}
@@ -0,0 +1,595 @@
+//! Reader-based input sources for decode.
+//!
+//! [`DecodeJob`](crate::decode::DecodeJob) executors normally bind a
+//! `Cow<'a, [u8]>`. For inputs that don't fit comfortably in memory
+//! (multi-hundred-megabyte TIFFs, PDFs streamed from object storage), jobs
+//! can instead bind a [`DecodeSource`] wrapping one of two object-safe
+//! reader traits:
+//!
+//! - [`ByteSource`] — sequential reads (network streams, decompressors).
+//! - [`RandomAccessSource`] — positioned reads (files, HTTP range requests).
+//!
+//! Both traits are `no_std`-compatible and report failures as a boxed
+//! [`SourceError`], mirroring [`SinkError`](crate::decode::SinkError) on the
+//! output side.
+//!
+//! # Slices
+//!
+//! `&[u8]` implements [`ByteSource`] (reads advance the slice), and `[u8]`,
+//! `Vec<u8>`, and `Cow<'_, [u8]>` implement [`RandomAccessSource`]. Both
+//! traits expose [`as_slice()`](ByteSource::as_slice) so codecs can take a
+//! zero-copy fast path for in-memory input.
+//!
+//! # Codec support
+//!
+//! Every `*_from_source` method on `DecodeJob` has a default that buffers the
+//! source with [`DecodeSource::into_bytes()`] — bounded by
+//! [`DecodeJob::limits()`](crate::decode::DecodeJob::limits) — and delegates
+//! to the slice-based executor, so existing codecs accept sources without
+//! changes. Codecs that
+//! can decode incrementally (TIFF strips, PDF objects) override the methods
+//! to read on demand.
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::ResourceLimits;
+
+/// Boxed error type for source read failures.
+pub type SourceError = Box<dyn core::error::Error + Send + Sync>;
+
+/// Chunk size used when buffering a sequential source of unknown length.
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Error returned when a source ends before a requested range was filled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnexpectedEof {
+    /// Offset (from the start of the request) where the source ran out.
+    pub filled: usize,
+    /// Number of bytes requested.
+    pub requested: usize,
+}
+
+impl core::fmt::Display for UnexpectedEof {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "unexpected end of source: filled {} of {} bytes",
+            self.filled, self.requested
+        )
+    }
+}
+
+impl core::error::Error for UnexpectedEof {}
+
+// ===========================================================================
+// ByteSource
+// ===========================================================================
+
+/// Sequential byte input.
+///
+/// Semantics follow `std::io::Read`: [`read()`](ByteSource::read) fills a
+/// prefix of `buf` and returns the number of bytes written; `Ok(0)` means
+/// end of input (or an empty `buf`).
+///
+/// # Object safety
+///
+/// This trait is object-safe. [`DecodeSource::Sequential`] holds a
+/// `Box<dyn ByteSource + Send + 'a>`.
+pub trait ByteSource {
+    /// Read up to `buf.len()` bytes. Returns `Ok(0)` at end of input.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, SourceError>;
+
+    /// Remaining length in bytes, if known.
+    ///
+    /// Used to pre-size buffers. Not a guarantee — readers must still handle
+    /// short reads and early end of input.
+    fn size_hint(&self) -> Option<u64> {
+        None
+    }
+
+    /// The remaining bytes, if they are already in memory.
+    ///
+    /// Lets codecs skip copying for slice-backed sources.
+    fn as_slice(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Fill `buf` completely, or fail with [`UnexpectedEof`].
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), SourceError> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            let n = self.read(&mut buf[filled..])?;
+            if n == 0 {
+                return Err(Box::new(UnexpectedEof {
+                    filled,
+                    requested: buf.len(),
+                }));
+            }
+            filled += n;
+        }
+        Ok(())
+    }
+}
+
+impl ByteSource for &[u8] {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, SourceError> {
+        let n = buf.len().min(self.len());
+        let (head, tail) = self.split_at(n);
+        buf[..n].copy_from_slice(head);
+        *self = tail;
+        Ok(n)
+    }
+
+    fn size_hint(&self) -> Option<u64> {
+        Some(<[u8]>::len(self) as u64)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl<S: ByteSource + ?Sized> ByteSource for &mut S {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, SourceError> {
+        (**self).read(buf)
+    }
+
+    fn size_hint(&self) -> Option<u64> {
+        (**self).size_hint()
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        (**self).as_slice()
+    }
+}
+
+impl<S: ByteSource + ?Sized> ByteSource for Box<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, SourceError> {
+        (**self).read(buf)
+    }
+
+    fn size_hint(&self) -> Option<u64> {
+        (**self).size_hint()
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        (**self).as_slice()
+    }
+}
+
+// ===========================================================================
+// RandomAccessSource
+// ===========================================================================
+
+/// Seekable byte input with a known length.
+///
+/// Reads are positioned (`pread`-style) and take `&self`, so one source can
+/// serve several readers — e.g. a multi-page decoder reading IFDs out of
+/// order. Implementations backed by a cursor need interior mutability.
+///
+/// # Object safety
+///
+/// This trait is object-safe. [`DecodeSource::RandomAccess`] holds a
+/// `Box<dyn RandomAccessSource + Send + Sync + 'a>`.
+pub trait RandomAccessSource {
+    /// Total length of the source in bytes.
+    fn len(&self) -> u64;
+
+    /// Whether the source is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read up to `buf.len()` bytes starting at `offset`.
+    ///
+    /// Returns the number of bytes written; `Ok(0)` when `offset >= len()`.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, SourceError>;
+
+    /// The whole source, if it is already in memory.
+    fn as_slice(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Fill `buf` from `offset`, or fail with [`UnexpectedEof`].
+    fn read_exact_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), SourceError> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            let n = self.read_at(offset + filled as u64, &mut buf[filled..])?;
+            if n == 0 {
+                return Err(Box::new(UnexpectedEof {
+                    filled,
+                    requested: buf.len(),
+                }));
+            }
+            filled += n;
+        }
+        Ok(())
+    }
+}
+
+impl RandomAccessSource for [u8] {
+    fn len(&self) -> u64 {
+        <[u8]>::len(self) as u64
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, SourceError> {
+        let start = usize::try_from(offset)
+            .unwrap_or(usize::MAX)
+            .min(<[u8]>::len(self));
+        let n = buf.len().min(<[u8]>::len(self) - start);
+        buf[..n].copy_from_slice(&self[start..start + n]);
+        Ok(n)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl RandomAccessSource for Vec<u8> {
+    fn len(&self) -> u64 {
+        <[u8]>::len(self) as u64
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, SourceError> {
+        <[u8] as RandomAccessSource>::read_at(self, offset, buf)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl RandomAccessSource for Cow<'_, [u8]> {
+    fn len(&self) -> u64 {
+        <[u8]>::len(self) as u64
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, SourceError> {
+        <[u8] as RandomAccessSource>::read_at(self, offset, buf)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl<S: RandomAccessSource + ?Sized> RandomAccessSource for &S {
+    fn len(&self) -> u64 {
+        (**self).len()
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, SourceError> {
+        (**self).read_at(offset, buf)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        (**self).as_slice()
+    }
+}
+
+impl<S: RandomAccessSource + ?Sized> RandomAccessSource for Box<S> {
+    fn len(&self) -> u64 {
+        (**self).len()
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, SourceError> {
+        (**self).read_at(offset, buf)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        (**self).as_slice()
+    }
+}
+
+/// Sequential cursor over a [`RandomAccessSource`].
+///
+/// Lets a seekable source feed code that only needs sequential reads.
+#[derive(Debug)]
+pub struct RandomAccessReader<S> {
+    source: S,
+    pos: u64,
+}
+
+impl<S: RandomAccessSource> RandomAccessReader<S> {
+    /// Start reading `source` from offset 0.
+    pub fn new(source: S) -> Self {
+        Self { source, pos: 0 }
+    }
+
+    /// Current read position.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Move the read position. Positions past the end read as end of input.
+    pub fn seek(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+
+    /// Return the underlying source.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<S: RandomAccessSource> ByteSource for RandomAccessReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, SourceError> {
+        let n = self.source.read_at(self.pos, buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn size_hint(&self) -> Option<u64> {
+        Some(self.source.len().saturating_sub(self.pos))
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        let all = self.source.as_slice()?;
+        let start = usize::try_from(self.pos)
+            .unwrap_or(usize::MAX)
+            .min(all.len());
+        Some(&all[start..])
+    }
+}
+
+// ===========================================================================
+// DecodeSource
+// ===========================================================================
+
+/// Input bound to a decode executor.
+///
+/// Passed to the `*_from_source` methods on
+/// [`DecodeJob`](crate::decode::DecodeJob) and
+/// [`DynDecodeJob`](crate::decode::DynDecodeJob).
+#[non_exhaustive]
+pub enum DecodeSource<'a> {
+    /// In-memory bytes — same as the slice-based executors.
+    Bytes(Cow<'a, [u8]>),
+    /// Sequential reader.
+    Sequential(Box<dyn ByteSource + Send + 'a>),
+    /// Seekable reader with a known length.
+    RandomAccess(Box<dyn RandomAccessSource + Send + Sync + 'a>),
+}
+
+impl<'a> DecodeSource<'a> {
+    /// Wrap a sequential reader.
+    pub fn sequential(source: impl ByteSource + Send + 'a) -> Self {
+        Self::Sequential(Box::new(source))
+    }
+
+    /// Wrap a seekable reader.
+    pub fn random_access(source: impl RandomAccessSource + Send + Sync + 'a) -> Self {
+        Self::RandomAccess(Box::new(source))
+    }
+
+    /// Total length in bytes, if known without reading.
+    pub fn len_hint(&self) -> Option<u64> {
+        match self {
+            Self::Bytes(b) => Some(<[u8]>::len(b) as u64),
+            Self::Sequential(s) => s.size_hint(),
+            Self::RandomAccess(s) => Some(s.len()),
+        }
+    }
+
+    /// Whether positioned reads are available without buffering.
+    pub fn is_random_access(&self) -> bool {
+        !matches!(self, Self::Sequential(_))
+    }
+
+    /// Buffer the whole source into memory.
+    ///
+    /// `Bytes` is returned as-is. Readers are drained into an owned buffer;
+    /// in-memory readers are copied once via [`as_slice()`](ByteSource::as_slice).
+    ///
+    /// Enforces [`ResourceLimits::max_input_bytes`] before and during
+    /// buffering, returning a boxed [`LimitExceeded::InputSize`](crate::LimitExceeded::InputSize)
+    /// — reachable via [`CodecErrorExt::limit_exceeded()`](crate::CodecErrorExt::limit_exceeded).
+    pub fn into_bytes(self, limits: &ResourceLimits) -> Result<Cow<'a, [u8]>, SourceError> {
+        if let Some(len) = self.len_hint() {
+            limits.check_input_size(len)?;
+        }
+        match self {
+            Self::Bytes(b) => Ok(b),
+            Self::RandomAccess(s) => {
+                if let Some(slice) = s.as_slice() {
+                    return Ok(Cow::Owned(slice.to_vec()));
+                }
+                let len =
+                    usize::try_from(s.len()).map_err(|_| "source length exceeds address space")?;
+                let mut buf = alloc::vec![0u8; len];
+                s.read_exact_at(0, &mut buf)?;
+                Ok(Cow::Owned(buf))
+            }
+            Self::Sequential(mut s) => {
+                if let Some(slice) = s.as_slice() {
+                    return Ok(Cow::Owned(slice.to_vec()));
+                }
+                let cap = s
+                    .size_hint()
+                    .and_then(|n| usize::try_from(n).ok())
+                    .unwrap_or(0)
+                    .min(64 * READ_CHUNK);
+                let mut buf = Vec::with_capacity(cap);
+                let mut filled = 0;
+                loop {
+                    if filled == buf.len() {
+                        buf.resize(filled + READ_CHUNK, 0);
+                    }
+                    let n = s.read(&mut buf[filled..])?;
+                    if n == 0 {
+                        break;
+                    }
+                    filled += n;
+                    limits.check_input_size(filled as u64)?;
+                }
+                buf.truncate(filled);
+                Ok(Cow::Owned(buf))
+            }
+        }
+    }
+}
+
+impl<'a> From<Cow<'a, [u8]>> for DecodeSource<'a> {
+    fn from(data: Cow<'a, [u8]>) -> Self {
+        Self::Bytes(data)
+    }
+}
+
+impl<'a> From<&'a [u8]> for DecodeSource<'a> {
+    fn from(data: &'a [u8]) -> Self {
+        Self::Bytes(Cow::Borrowed(data))
+    }
+}
+
+impl From<Vec<u8>> for DecodeSource<'_> {
+    fn from(data: Vec<u8>) -> Self {
+        Self::Bytes(Cow::Owned(data))
+    }
+}
+
+impl core::fmt::Debug for DecodeSource<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Bytes(b) => f.debug_tuple("Bytes").field(&b.len()).finish(),
+            Self::Sequential(s) => f
+                .debug_struct("Sequential")
+                .field("size_hint", &s.size_hint())
+                .finish(),
+            Self::RandomAccess(s) => f
+                .debug_struct("RandomAccess")
+                .field("len", &s.len())
+                .finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CodecErrorExt;
+
+    /// Sequential source that yields at most 3 bytes per read and hides its slice.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl ByteSource for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, SourceError> {
+            let n = buf.len().min(3).min(self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    /// Random-access source without an in-memory view.
+    struct Opaque(Vec<u8>);
+
+    impl RandomAccessSource for Opaque {
+        fn len(&self) -> u64 {
+            self.0.len() as u64
+        }
+
+        fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, SourceError> {
+            let n = <[u8] as RandomAccessSource>::read_at(&self.0, offset, buf)?;
+            Ok(n.min(5))
+        }
+    }
+
+    #[test]
+    fn slice_byte_source_advances() {
+        let mut src: &[u8] = &[1, 2, 3, 4, 5];
+        let mut buf = [0u8; 3];
+        assert_eq!(src.read(&mut buf).unwrap(), 3);
+        assert_eq!(buf, [1, 2, 3]);
+        assert_eq!(src.size_hint(), Some(2));
+        assert_eq!(src.read(&mut buf).unwrap(), 2);
+        assert_eq!(src.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn read_exact_reports_eof() {
+        let mut src = Trickle(&[1, 2, 3, 4]);
+        let mut buf = [0u8; 8];
+        let err = src.read_exact(&mut buf).unwrap_err();
+        let eof = err.downcast_ref::<UnexpectedEof>().unwrap();
+        assert_eq!(eof.filled, 4);
+        assert_eq!(eof.requested, 8);
+    }
+
+    #[test]
+    fn slice_random_access_bounds() {
+        let data: &[u8] = &[10, 11, 12, 13];
+        let mut buf = [0u8; 3];
+        assert_eq!(data.read_at(2, &mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], &[12, 13]);
+        assert_eq!(data.read_at(4, &mut buf).unwrap(), 0);
+        assert_eq!(data.read_at(u64::MAX, &mut buf).unwrap(), 0);
+        assert!(data.read_exact_at(3, &mut buf).is_err());
+    }
+
+    #[test]
+    fn random_access_reader_is_sequential() {
+        let data = Opaque((0u8..20).collect());
+        let mut reader = RandomAccessReader::new(&data);
+        let mut buf = [0u8; 8];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(reader.position(), 8);
+        assert_eq!(reader.size_hint(), Some(12));
+        reader.seek(18);
+        assert_eq!(reader.read(&mut buf).unwrap(), 2);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn into_bytes_borrowed_is_zero_copy() {
+        let data = [1u8, 2, 3];
+        let src = DecodeSource::from(&data[..]);
+        assert!(src.is_random_access());
+        let bytes = src.into_bytes(&ResourceLimits::none()).unwrap();
+        assert!(matches!(bytes, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn into_bytes_drains_readers() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(200_000).collect();
+
+        let seq = DecodeSource::sequential(Trickle(&data));
+        assert!(!seq.is_random_access());
+        assert_eq!(seq.len_hint(), None);
+        assert_eq!(
+            &*seq.into_bytes(&ResourceLimits::none()).unwrap(),
+            &data[..]
+        );
+
+        let ra = DecodeSource::random_access(Opaque(data.clone()));
+        assert_eq!(ra.len_hint(), Some(200_000));
+        assert_eq!(&*ra.into_bytes(&ResourceLimits::none()).unwrap(), &data[..]);
+    }
+
+    #[test]
+    fn into_bytes_enforces_input_limit() {
+        let data = [0u8; 100];
+        let limits = ResourceLimits::none().with_max_input_bytes(50);
+
+        // Known length: rejected up front
+        let err = DecodeSource::from(&data[..])
+            .into_bytes(&limits)
+            .unwrap_err();
+        assert!(matches!(
+            err.limit_exceeded(),
+            Some(crate::LimitExceeded::InputSize {
+                actual: 100,
+                max: 50
+            })
+        ));
+
+        // Unknown length: rejected while buffering
+        let err = DecodeSource::sequential(Trickle(&data))
+            .into_bytes(&limits)
+            .unwrap_err();
+        assert!(err.limit_exceeded().is_some());
+    }
+}
@@ -1,10 +1,10 @@
-//! Decode execution traits: one-shot, streaming, and animation.
+//! Decode execution traits: one-shot, streaming, animation, and multi-page.
 
 use crate::output::{AnimationFrame, OwnedAnimationFrame};
 use crate::sink::SinkError;
 use crate::{DecodeOutput, ImageInfo, OutputInfo};
 use enough::Stop;
-use zenpixels::PixelSlice;
+use zenpixels::{PixelDescriptor, PixelSlice};
 
 /// Single-image decode. Returns owned pixels.
 ///
@@ -176,3 +176,95 @@ pub trait AnimationFrameDecoder: Sized {
         sink: &mut dyn crate::DecodeRowSink,
     ) -> Result<Option<OutputInfo>, Self::Error>;
 }
+
+/// Independent-page decode for multi-image containers.
+///
+/// ONLY for [`ImageSequence::Multi`](crate::ImageSequence::Multi). Each page
+/// has its own [`ImageInfo`] with potentially different dimensions, pixel
+/// format, color space, and metadata. No compositing — pages are independent
+/// documents, ICO sizes, HEIF items, or data planes.
+///
+/// Created by [`DecodeJob::multi_page_decoder()`](super::DecodeJob::multi_page_decoder)
+/// with input data already bound. Format preferences are passed per page,
+/// because each page may negotiate a different output format.
+///
+/// # Page index
+///
+/// Page indices are 0-based and count primary-level images only —
+/// thumbnails, masks, and pyramid levels are excluded (those are reported
+/// via [`Supplements`](crate::Supplements)). Index 0 is not necessarily the
+/// image that [`Decode`] returns: for ICO the primary is the largest entry,
+/// for HEIF it is the `pitm` item.
+///
+/// # Access pattern
+///
+/// When [`ImageSequence::random_access()`](crate::ImageSequence::random_access)
+/// is true, [`page_info()`](MultiPageDecoder::page_info) and
+/// [`decode_page()`](MultiPageDecoder::decode_page) accept any index in any
+/// order. When false, only the next sequential index is guaranteed to work;
+/// codecs return an error for out-of-order requests.
+///
+/// # Cooperative cancellation
+///
+/// Decode methods take an `Option<&dyn Stop>` token per call, mirroring
+/// [`AnimationFrameDecoder`]. Pass `None` when cancellation is not needed.
+pub trait MultiPageDecoder: Sized {
+    /// The codec-specific error type.
+    type Error: core::error::Error + Send + Sync + 'static;
+
+    /// Wrap a [`SinkError`] into this decoder's error type.
+    ///
+    /// Used by the default [`decode_page_to_sink()`](MultiPageDecoder::decode_page_to_sink).
+    fn wrap_sink_error(err: SinkError) -> Self::Error;
+
+    /// Container-level metadata, available after construction.
+    ///
+    /// Describes the primary image, as returned by
+    /// [`DecodeJob::probe()`](super::DecodeJob::probe). Use
+    /// [`page_info()`](MultiPageDecoder::page_info) for per-page details.
+    fn info(&self) -> &ImageInfo;
+
+    /// Number of pages, if known without walking the whole container.
+    fn page_count(&self) -> Option<u32> {
+        None
+    }
+
+    /// Metadata for a specific page, without decoding pixels.
+    ///
+    /// Returns an error if `index` is out of range, or if the container is
+    /// not random-access and `index` is not the next sequential page.
+    fn page_info(&mut self, index: u32) -> Result<ImageInfo, Self::Error>;
+
+    /// Decode a specific page to owned pixels.
+    ///
+    /// `preferred` is a ranked list of desired output formats for this page.
+    /// The decoder picks the first it can produce without lossy conversion.
+    /// Pass `&[]` for the page's native format.
+    ///
+    /// Pass `None` if cancellation is not needed.
+    fn decode_page(
+        &mut self,
+        index: u32,
+        preferred: &[PixelDescriptor],
+        stop: Option<&dyn Stop>,
+    ) -> Result<DecodeOutput, Self::Error>;
+
+    /// Decode a specific page directly into a caller-owned sink (push model).
+    ///
+    /// The sink's [`begin()`](crate::DecodeRowSink::begin) is called once
+    /// per page with that page's dimensions and format.
+    ///
+    /// Default implementation calls [`decode_page()`](MultiPageDecoder::decode_page)
+    /// and copies rows via
+    /// [`zencodec::helpers::copy_page_to_sink()`](crate::helpers::copy_page_to_sink).
+    /// Codecs with native row streaming may override.
+    fn decode_page_to_sink(
+        &mut self,
+        index: u32,
+        preferred: &[PixelDescriptor],
+        stop: Option<&dyn Stop>,
+        sink: &mut dyn crate::DecodeRowSink,
+    ) -> Result<OutputInfo, Self::Error> {
+        crate::helpers::copy_page_to_sink(self, index, preferred, stop, sink)
+    }
+}
@@ -3,16 +3,19 @@
 use alloc::borrow::Cow;
 use alloc::boxed::Box;
 
+use crate::error::io_failure;
 use crate::format::ImageFormat;
 use crate::orientation::OrientationHint;
-use crate::{DecodeCapabilities, ImageInfo, OutputInfo, ResourceLimits, StopToken};
+use crate::probe::ProbeProgress;
+use crate::source::DecodeSource;
+use crate::{DecodeCapabilities, ImageInfo, LimitExceeded, OutputInfo, ResourceLimits, StopToken};
 use zenpixels::PixelDescriptor;
 
 use super::BoxedError;
-use super::decoder::{AnimationFrameDecoder, Decode, StreamingDecode};
+use super::decoder::{AnimationFrameDecoder, Decode, MultiPageDecoder, StreamingDecode};
 use super::dyn_decoding::{
     AnimationFrameDecoderShim, DecoderShim, DynAnimationFrameDecoder, DynDecoder,
-    DynStreamingDecoder, StreamingDecoderShim,
+    DynMultiPageDecoder, DynStreamingDecoder, MultiPageDecoderShim, StreamingDecoderShim,
 };
 
 // ===========================================================================
@@ -102,6 +105,14 @@ pub trait DecodeJob<'a>: Sized {
     /// across thread boundaries (e.g., in pipeline `Source` implementations).
     type AnimationFrameDec: AnimationFrameDecoder<Error = Self::Error> + Send + 'static;
 
+    /// Multi-page decoder type.
+    ///
+    /// Implements [`MultiPageDecoder`] for independent-page containers
+    /// (multi-page TIFF, ICO, HEIF collections). Set to
+    /// [`Unsupported<Self::Error>`](crate::Unsupported) if the codec does
+    /// not support multi-page decode.
+    type MultiPageDec: MultiPageDecoder<Error = Self::Error> + Send;
+
     /// Set cooperative cancellation token.
     ///
     /// [`StopToken`](crate::StopToken) is `Clone + Send + Sync + 'static` —
@@ -125,6 +136,15 @@ pub trait DecodeJob<'a>: Sized {
         self
     }
 
+    /// Resource limits set via [`with_limits()`](DecodeJob::with_limits).
+    ///
+    /// The default `*_from_source` methods stop buffering with
+    /// [`LimitExceeded::InputSize`](crate::LimitExceeded::InputSize) once
+    /// [`max_input_bytes`](ResourceLimits::max_input_bytes) is reached.
+    /// Required so that no codec buffers an untrusted stream without the
+    /// caller's cap.
+    fn limits(&self) -> &ResourceLimits;
+
     // --- Probing (needs limits + stop context) ---
 
     /// Probe image metadata cheaply (header parse only).
@@ -144,6 +164,21 @@ pub trait DecodeJob<'a>: Sized {
         self.probe(data)
     }
 
+    /// Probe a possibly truncated prefix of the file.
+    ///
+    /// Returns [`ProbeProgress::NeedMoreData`] when `data` ends before the
+    /// headers do, reporting the minimum input length for the next attempt
+    /// (and, where known, the offset of the structure being waited on).
+    /// Errors are reserved for input that is invalid regardless of length.
+    ///
+    /// Default: calls [`probe()`](DecodeJob::probe) and wraps the result in
+    /// [`ProbeProgress::Complete`], so truncation surfaces as an error.
+    /// Codecs whose headers can sit far into the file (JPEG after large APP
+    /// segments, TIFF IFDs, ISOBMFF `meta` boxes) should override.
+    fn probe_incremental(&self, data: &[u8]) -> Result<ProbeProgress, Self::Error> {
+        self.probe(data).map(ProbeProgress::Complete)
+    }
+
     // --- Decode hints (optional, decoder may ignore) ---
 
     /// Hint: crop to this region in source coordinates.
@@ -207,10 +242,13 @@ pub trait DecodeJob<'a>: Sized {
     //
     // All executors bind `data` here so the DecodeJob is the single
     // place where input is provided. This keeps Decode/StreamingDecode/
-    // AnimationFrameDecoder free of data parameters, and prepares for future
-    // IO-read sources (the job can bind a reader instead of a slice).
+    // AnimationFrameDecoder free of data parameters, and lets the job bind a
+    // reader instead of a slice (see `DecodeSource`).
     //
     // Consistent parameter order: data, [sink], preferred.
+    //
+    // Each executor has a `*_from_source` twin taking a `DecodeSource`
+    // (slice, sequential reader, or seekable reader) instead of a slice.
 
     /// Create a one-shot decoder bound to `data`.
     ///
@@ -274,6 +312,96 @@ pub trait DecodeJob<'a>: Sized {
         preferred: &[PixelDescriptor],
     ) -> Result<Self::AnimationFrameDec, Self::Error>;
 
+    /// Create a multi-page decoder for independent-page containers.
+    ///
+    /// Returns an error if the codec does not support multi-page decode
+    /// (check [`DecodeCapabilities::multi_image()`](crate::DecodeCapabilities::multi_image)).
+    ///
+    /// Format preferences are passed per page to
+    /// [`MultiPageDecoder::decode_page()`], not here, because pages may
+    /// differ in pixel format.
+    fn multi_page_decoder(self, data: Cow<'a, [u8]>) -> Result<Self::MultiPageDec, Self::Error>;
+
+    // --- Executor creation from readers ---
+    //
+    // Defaults buffer the source via `DecodeSource::into_bytes()`, bounded
+    // by `limits()`, and delegate to the slice-based executors. Codecs that
+    // read on demand override these.
+    //
+    // Errors are boxed like the dyn API's: a read failure is not one of the
+    // codec's own errors. Codec errors are reachable via
+    // `CodecErrorExt::find_cause()`; read failures classify as
+    // `CodecErrorKind::Io`.
+
+    /// Create a one-shot decoder bound to a [`DecodeSource`].
+    ///
+    /// Default: buffers the source and calls [`decoder()`](DecodeJob::decoder).
+    fn decoder_from_source(
+        self,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Self::Dec, BoxedError> {
+        let data = buffer_source(source, self.limits())?;
+        self.decoder(data, preferred)
+            .map_err(|e| Box::new(e) as BoxedError)
+    }
+
+    /// Decode a [`DecodeSource`] into a caller-owned sink.
+    ///
+    /// Default: buffers the source and calls [`push_decoder()`](DecodeJob::push_decoder).
+    fn push_decoder_from_source(
+        self,
+        source: DecodeSource<'a>,
+        sink: &mut dyn crate::DecodeRowSink,
+        preferred: &[PixelDescriptor],
+    ) -> Result<OutputInfo, BoxedError> {
+        let data = buffer_source(source, self.limits())?;
+        self.push_decoder(data, sink, preferred)
+            .map_err(|e| Box::new(e) as BoxedError)
+    }
+
+    /// Create a streaming decoder bound to a [`DecodeSource`].
+    ///
+    /// Default: buffers the source and calls
+    /// [`streaming_decoder()`](DecodeJob::streaming_decoder).
+    fn streaming_decoder_from_source(
+        self,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Self::StreamDec, BoxedError> {
+        let data = buffer_source(source, self.limits())?;
+        self.streaming_decoder(data, preferred)
+            .map_err(|e| Box::new(e) as BoxedError)
+    }
+
+    /// Create a full-frame animation decoder bound to a [`DecodeSource`].
+    ///
+    /// Default: buffers the source and calls
+    /// [`animation_frame_decoder()`](DecodeJob::animation_frame_decoder).
+    fn animation_frame_decoder_from_source(
+        self,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Self::AnimationFrameDec, BoxedError> {
+        let data = buffer_source(source, self.limits())?;
+        self.animation_frame_decoder(data, preferred)
+            .map_err(|e| Box::new(e) as BoxedError)
+    }
+
+    /// Create a multi-page decoder bound to a [`DecodeSource`].
+    ///
+    /// Random-access sources are the natural fit for page-indexed
+    /// containers. Default: buffers the source and calls
+    /// [`multi_page_decoder()`](DecodeJob::multi_page_decoder).
+    fn multi_page_decoder_from_source(
+        self,
+        source: DecodeSource<'a>,
+    ) -> Result<Self::MultiPageDec, BoxedError> {
+        let data = buffer_source(source, self.limits())?;
+        self.multi_page_decoder(data)
+            .map_err(|e| Box::new(e) as BoxedError)
+    }
+
     // --- Type-erased convenience methods ---
 
     /// Create a type-erased one-shot decoder.
@@ -357,4 +485,44 @@ pub trait DecodeJob<'a>: Sized {
             .map_err(|e| Box::new(e) as BoxedError)?;
         Ok(Box::new(StreamingDecoderShim(dec)))
     }
+
+    /// Create a type-erased multi-page decoder.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut dec = config.job().dyn_multi_page_decoder(data)?;
+    ///
+    /// for i in 0..dec.page_count().unwrap_or(0) {
+    ///     let page = dec.decode_page(i, &[], None)?;
+    ///     // process page
+    /// }
+    /// ```
+    fn dyn_multi_page_decoder(
+        self,
+        data: Cow<'a, [u8]>,
+    ) -> Result<Box<dyn DynMultiPageDecoder + 'a>, BoxedError>
+    where
+        Self: 'a,
+    {
+        let dec = self
+            .multi_page_decoder(data)
+            .map_err(|e| Box::new(e) as BoxedError)?;
+        Ok(Box::new(MultiPageDecoderShim(dec)))
+    }
+}
+
+/// Buffer a source for the slice-based executors, bounded by the job's
+/// limits. Read failures are tagged as I/O.
+fn buffer_source<'a>(
+    source: DecodeSource<'a>,
+    limits: &ResourceLimits,
+) -> Result<Cow<'a, [u8]>, BoxedError> {
+    source.into_bytes(limits).map_err(|err| {
+        if err.is::<LimitExceeded>() {
+            err
+        } else {
+            io_failure(err)
+        }
+    })
 }
@@ -2,7 +2,7 @@
 //!
 //! Mirrors the generic decode hierarchy with dyn-safe traits:
 //!
-//!   DynDecoderConfig → DynDecodeJob → DynDecoder / DynAnimationFrameDecoder / DynStreamingDecoder
+//!   DynDecoderConfig → DynDecodeJob → DynDecoder / DynAnimationFrameDecoder / DynStreamingDecoder / DynMultiPageDecoder
 //!
 //! Each layer is a separate trait with blanket impls via private shim structs.
 //! Every method from the generic traits is exposed.
@@ -14,12 +14,14 @@ use core::any::Any;
 use crate::format::ImageFormat;
 use crate::orientation::OrientationHint;
 use crate::output::OwnedAnimationFrame;
+use crate::probe::ProbeProgress;
+use crate::source::DecodeSource;
 use crate::{DecodeCapabilities, DecodeOutput, ImageInfo, OutputInfo, ResourceLimits, StopToken};
 use enough::Stop;
 use zenpixels::{PixelDescriptor, PixelSlice};
 
 use super::BoxedError;
-use super::decoder::{AnimationFrameDecoder, Decode, StreamingDecode};
+use super::decoder::{AnimationFrameDecoder, Decode, MultiPageDecoder, StreamingDecode};
 use super::decoding::{DecodeJob, DecoderConfig};
 
 // ===========================================================================
@@ -186,6 +188,90 @@ impl<S: StreamingDecode + Send> DynStreamingDecoder for StreamingDecoderShim<S>
     }
 }
 
+// ===========================================================================
+// DynMultiPageDecoder
+// ===========================================================================
+
+/// Object-safe multi-page decoder.
+///
+/// Wraps [`MultiPageDecoder`] for dyn dispatch. Produced by
+/// [`DynDecodeJob::into_multi_page_decoder`].
+pub trait DynMultiPageDecoder: Send {
+    /// Container-level metadata, available after construction.
+    fn info(&self) -> &ImageInfo;
+
+    /// Number of pages, if known without walking the whole container.
+    fn page_count(&self) -> Option<u32>;
+
+    /// Metadata for a specific page, without decoding pixels.
+    fn page_info(&mut self, index: u32) -> Result<ImageInfo, BoxedError>;
+
+    /// Decode a specific page to owned pixels.
+    fn decode_page(
+        &mut self,
+        index: u32,
+        preferred: &[PixelDescriptor],
+        stop: Option<&dyn Stop>,
+    ) -> Result<DecodeOutput, BoxedError>;
+
+    /// Decode a specific page directly into a caller-owned sink.
+    fn decode_page_to_sink(
+        &mut self,
+        index: u32,
+        preferred: &[PixelDescriptor],
+        stop: Option<&dyn Stop>,
+        sink: &mut dyn crate::DecodeRowSink,
+    ) -> Result<OutputInfo, BoxedError>;
+}
+
+impl core::fmt::Debug for dyn DynMultiPageDecoder + '_ {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DynMultiPageDecoder")
+            .finish_non_exhaustive()
+    }
+}
+
+pub(super) struct MultiPageDecoderShim<M>(pub(super) M);
+
+impl<M: MultiPageDecoder + Send> DynMultiPageDecoder for MultiPageDecoderShim<M> {
+    fn info(&self) -> &ImageInfo {
+        self.0.info()
+    }
+
+    fn page_count(&self) -> Option<u32> {
+        self.0.page_count()
+    }
+
+    fn page_info(&mut self, index: u32) -> Result<ImageInfo, BoxedError> {
+        self.0
+            .page_info(index)
+            .map_err(|e| Box::new(e) as BoxedError)
+    }
+
+    fn decode_page(
+        &mut self,
+        index: u32,
+        preferred: &[PixelDescriptor],
+        stop: Option<&dyn Stop>,
+    ) -> Result<DecodeOutput, BoxedError> {
+        self.0
+            .decode_page(index, preferred, stop)
+            .map_err(|e| Box::new(e) as BoxedError)
+    }
+
+    fn decode_page_to_sink(
+        &mut self,
+        index: u32,
+        preferred: &[PixelDescriptor],
+        stop: Option<&dyn Stop>,
+        sink: &mut dyn crate::DecodeRowSink,
+    ) -> Result<OutputInfo, BoxedError> {
+        self.0
+            .decode_page_to_sink(index, preferred, stop, sink)
+            .map_err(|e| Box::new(e) as BoxedError)
+    }
+}
+
 // ===========================================================================
 // DynDecodeJob
 // ===========================================================================
@@ -211,6 +297,9 @@ pub trait DynDecodeJob<'a> {
     /// Probe image metadata with a full parse.
     fn probe_full(&self, data: &[u8]) -> Result<ImageInfo, BoxedError>;
 
+    /// Probe a possibly truncated prefix, reporting how much more input is needed.
+    fn probe_incremental(&self, data: &[u8]) -> Result<ProbeProgress, BoxedError>;
+
     /// Hint: crop to this region in source coordinates.
     fn set_crop_hint(&mut self, x: u32, y: u32, width: u32, height: u32);
 
@@ -267,6 +356,51 @@ pub trait DynDecodeJob<'a> {
         data: Cow<'a, [u8]>,
         preferred: &[PixelDescriptor],
     ) -> Result<Box<dyn DynAnimationFrameDecoder>, BoxedError>;
+
+    /// Create a multi-page decoder (consumes this job).
+    ///
+    /// Format preferences are passed per page to
+    /// [`DynMultiPageDecoder::decode_page`].
+    fn into_multi_page_decoder(
+        self: Box<Self>,
+        data: Cow<'a, [u8]>,
+    ) -> Result<Box<dyn DynMultiPageDecoder + 'a>, BoxedError>;
+
+    /// Create a one-shot decoder bound to a [`DecodeSource`] (consumes this job).
+    fn into_decoder_from_source(
+        self: Box<Self>,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynDecoder + 'a>, BoxedError>;
+
+    /// Decode a [`DecodeSource`] into a caller-owned sink (consumes this job).
+    fn push_decode_from_source(
+        self: Box<Self>,
+        source: DecodeSource<'a>,
+        sink: &mut dyn crate::DecodeRowSink,
+        preferred: &[PixelDescriptor],
+    ) -> Result<OutputInfo, BoxedError>;
+
+    /// Create a streaming decoder bound to a [`DecodeSource`] (consumes this job).
+    fn into_streaming_decoder_from_source(
+        self: Box<Self>,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynStreamingDecoder + 'a>, BoxedError>;
+
+    /// Create a full-frame animation decoder bound to a [`DecodeSource`]
+    /// (consumes this job).
+    fn into_animation_frame_decoder_from_source(
+        self: Box<Self>,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynAnimationFrameDecoder>, BoxedError>;
+
+    /// Create a multi-page decoder bound to a [`DecodeSource`] (consumes this job).
+    fn into_multi_page_decoder_from_source(
+        self: Box<Self>,
+        source: DecodeSource<'a>,
+    ) -> Result<Box<dyn DynMultiPageDecoder + 'a>, BoxedError>;
 }
 
 struct DecodeJobShim<J>(Option<J>);
@@ -294,6 +428,7 @@ where
     J: DecodeJob<'a> + 'a,
     J::StreamDec: Send,
     J::AnimationFrameDec: Send,
+    J::MultiPageDec: Send,
 {
     fn set_stop(&mut self, stop: StopToken) {
         if let Ok(job) = self.take() {
@@ -325,6 +460,12 @@ where
             .map_err(|e| Box::new(e) as BoxedError)
     }
 
+    fn probe_incremental(&self, data: &[u8]) -> Result<ProbeProgress, BoxedError> {
+        self.as_ref()?
+            .probe_incremental(data)
+            .map_err(|e| Box::new(e) as BoxedError)
+    }
+
     fn set_crop_hint(&mut self, x: u32, y: u32, width: u32, height: u32) {
         if let Ok(job) = self.take() {
             self.put(job.with_crop_hint(x, y, width, height));
@@ -409,6 +550,66 @@ where
             .map_err(|e| Box::new(e) as BoxedError)?;
         Ok(Box::new(AnimationFrameDecoderShim(dec)))
     }
+
+    fn into_multi_page_decoder(
+        mut self: Box<Self>,
+        data: Cow<'a, [u8]>,
+    ) -> Result<Box<dyn DynMultiPageDecoder + 'a>, BoxedError> {
+        let job = self.take()?;
+        let dec = job
+            .multi_page_decoder(data)
+            .map_err(|e| Box::new(e) as BoxedError)?;
+        Ok(Box::new(MultiPageDecoderShim(dec)))
+    }
+
+    fn into_decoder_from_source(
+        mut self: Box<Self>,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynDecoder + 'a>, BoxedError> {
+        let job = self.take()?;
+        let dec = job.decoder_from_source(source, preferred)?;
+        Ok(Box::new(DecoderShim(dec)))
+    }
+
+    fn push_decode_from_source(
+        mut self: Box<Self>,
+        source: DecodeSource<'a>,
+        sink: &mut dyn crate::DecodeRowSink,
+        preferred: &[PixelDescriptor],
+    ) -> Result<OutputInfo, BoxedError> {
+        let job = self.take()?;
+        job.push_decoder_from_source(source, sink, preferred)
+    }
+
+    fn into_streaming_decoder_from_source(
+        mut self: Box<Self>,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynStreamingDecoder + 'a>, BoxedError> {
+        let job = self.take()?;
+        let dec = job.streaming_decoder_from_source(source, preferred)?;
+        Ok(Box::new(StreamingDecoderShim(dec)))
+    }
+
+    fn into_animation_frame_decoder_from_source(
+        mut self: Box<Self>,
+        source: DecodeSource<'a>,
+        preferred: &[PixelDescriptor],
+    ) -> Result<Box<dyn DynAnimationFrameDecoder>, BoxedError> {
+        let job = self.take()?;
+        let dec = job.animation_frame_decoder_from_source(source, preferred)?;
+        Ok(Box::new(AnimationFrameDecoderShim(dec)))
+    }
+
+    fn into_multi_page_decoder_from_source(
+        mut self: Box<Self>,
+        source: DecodeSource<'a>,
+    ) -> Result<Box<dyn DynMultiPageDecoder + 'a>, BoxedError> {
+        let job = self.take()?;
+        let dec = job.multi_page_decoder_from_source(source)?;
+        Ok(Box::new(MultiPageDecoderShim(dec)))
+    }
 }
 
 // ===========================================================================
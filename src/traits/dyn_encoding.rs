@@ -23,6 +23,7 @@ use core::any::Any;
 
 use crate::StopToken;
 use crate::format::ImageFormat;
+use crate::gainmap::EncodeGainMap;
 use crate::{EncodeCapabilities, EncodeOutput, Metadata, ResourceLimits};
 use enough::Stop;
 use zenpixels::{PixelDescriptor, PixelSlice, PixelSliceMut};
@@ -225,6 +226,9 @@ pub trait DynEncodeJob {
     /// Set metadata (ICC, EXIF, XMP) to embed.
     fn set_metadata(&mut self, meta: Metadata);
 
+    /// Set a gain map to embed alongside the primary image.
+    fn set_gain_map(&mut self, _gain_map: EncodeGainMap) {}
+
     /// Set animation canvas dimensions.
     fn set_canvas_size(&mut self, width: u32, height: u32);
 
@@ -296,6 +300,12 @@ where
         }
     }
 
+    fn set_gain_map(&mut self, gain_map: EncodeGainMap) {
+        if let Ok(job) = self.take() {
+            self.put(job.with_gain_map(gain_map));
+        }
+    }
+
     fn set_canvas_size(&mut self, width: u32, height: u32) {
         if let Ok(job) = self.take() {
             self.put(job.with_canvas_size(width, height));
@@ -192,6 +192,21 @@ pub trait EncodeJob: Sized {
     /// The codec embeds what the format supports, silently skips the rest.
     fn with_metadata(self, meta: Metadata) -> Self;
 
+    /// Set a gain map to embed alongside the primary image.
+    ///
+    /// Accepts decoded pixels to re-encode or an encoded bitstream to embed
+    /// verbatim (see [`EncodeGainMap`](crate::gainmap::EncodeGainMap)).
+    /// Check [`EncodeOutput::gain_map_embedded()`](crate::encode::EncodeOutput::gain_map_embedded)
+    /// to learn whether it was written.
+    ///
+    /// # Note
+    ///
+    /// The default implementation is a no-op. Codecs that embed gain maps
+    /// report [`EncodeCapabilities::gain_map()`](crate::EncodeCapabilities::gain_map).
+    fn with_gain_map(self, _gain_map: crate::gainmap::EncodeGainMap) -> Self {
+        self
+    }
+
     /// Set animation canvas dimensions.
     ///
     /// For full-frame animation, this sets the expected frame dimensions.
@@ -9,7 +9,8 @@
 //! DECODE:
 //!                                  ┌→ Dec (implements Decode)
 //! DecoderConfig → DecodeJob<'a> ──┤→ StreamDec (implements StreamingDecode)
-//!                                  └→ AnimationFrameDec (implements AnimationFrameDecoder)
+//!                                  ├→ AnimationFrameDec (implements AnimationFrameDecoder)
+//!                                  └→ MultiPageDec (implements MultiPageDecoder)
 //! ```
 //!
 //! Encoding and decoding are both **type-erased**: encoders accept any pixel
@@ -29,10 +30,11 @@ mod encoder;
 mod encoding;
 mod unsupported;
 
-pub use decoder::{AnimationFrameDecoder, Decode, StreamingDecode};
+pub use decoder::{AnimationFrameDecoder, Decode, MultiPageDecoder, StreamingDecode};
 pub use decoding::{DecodeJob, DecoderConfig};
 pub use dyn_decoding::{
-    DynAnimationFrameDecoder, DynDecodeJob, DynDecoder, DynDecoderConfig, DynStreamingDecoder,
+    DynAnimationFrameDecoder, DynDecodeJob, DynDecoder, DynDecoderConfig, DynMultiPageDecoder,
+    DynStreamingDecoder,
 };
 pub use dyn_encoding::{DynAnimationFrameEncoder, DynEncodeJob, DynEncoder, DynEncoderConfig};
 pub use encoder::{AnimationFrameEncoder, Encoder};
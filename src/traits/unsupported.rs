@@ -7,11 +7,11 @@ use core::marker::PhantomData;
 
 use crate::output::{AnimationFrame, OwnedAnimationFrame};
 use crate::sink::SinkError;
-use crate::{ImageInfo, OutputInfo};
+use crate::{DecodeOutput, ImageInfo, OutputInfo};
 use enough::Stop;
-use zenpixels::PixelSlice;
+use zenpixels::{PixelDescriptor, PixelSlice};
 
-use super::decoder::{AnimationFrameDecoder, StreamingDecode};
+use super::decoder::{AnimationFrameDecoder, MultiPageDecoder, StreamingDecode};
 
 /// Stub type for codecs that don't support an operation.
 ///
@@ -23,6 +23,7 @@ use super::decoder::{AnimationFrameDecoder, StreamingDecode};
 ///     type Dec = MyDecoder<'a>;
 ///     type StreamDec = Unsupported<At<MyError>>;
 ///     type AnimationFrameDec = Unsupported<At<MyError>>;
+///     type MultiPageDec = Unsupported<At<MyError>>;
 ///     // ...
 ///
 ///     fn streaming_decoder(self, ..) -> Result<Unsupported<At<MyError>>, At<MyError>> {
@@ -32,6 +33,10 @@ use super::decoder::{AnimationFrameDecoder, StreamingDecode};
 ///     fn animation_frame_decoder(self, ..) -> Result<Unsupported<At<MyError>>, At<MyError>> {
 ///         Err(MyError::from(UnsupportedOperation::AnimationDecode).start_at())
 ///     }
+///
+///     fn multi_page_decoder(self, ..) -> Result<Unsupported<At<MyError>>, At<MyError>> {
+///         Err(MyError::from(UnsupportedOperation::MultiImageDecode).start_at())
+///     }
 /// }
 /// ```
 ///
@@ -84,3 +89,38 @@ impl<E: core::error::Error + Send + Sync + 'static> AnimationFrameDecoder for Un
         unreachable!("Unsupported: full frame decode stub should never be constructed")
     }
 }
+
+impl<E: core::error::Error + Send + Sync + 'static> MultiPageDecoder for Unsupported<E> {
+    type Error = E;
+
+    fn wrap_sink_error(_err: SinkError) -> E {
+        unreachable!("Unsupported: multi-page decode stub should never be constructed")
+    }
+
+    fn info(&self) -> &ImageInfo {
+        unreachable!("Unsupported: multi-page decode stub should never be constructed")
+    }
+
+    fn page_info(&mut self, _index: u32) -> Result<ImageInfo, E> {
+        unreachable!("Unsupported: multi-page decode stub should never be constructed")
+    }
+
+    fn decode_page(
+        &mut self,
+        _index: u32,
+        _preferred: &[PixelDescriptor],
+        _stop: Option<&dyn Stop>,
+    ) -> Result<DecodeOutput, E> {
+        unreachable!("Unsupported: multi-page decode stub should never be constructed")
+    }
+
+    fn decode_page_to_sink(
+        &mut self,
+        _index: u32,
+        _preferred: &[PixelDescriptor],
+        _stop: Option<&dyn Stop>,
+        _sink: &mut dyn crate::DecodeRowSink,
+    ) -> Result<OutputInfo, E> {
+        unreachable!("Unsupported: multi-page decode stub should never be constructed")
+    }
+}
@@ -0,0 +1,373 @@
+//! Decode → encode in one call.
+//!
+//! [`transcode()`] wires the steps every conversion service repeats: apply
+//! limits, policies, and cancellation to both jobs, negotiate a pixel format
+//! the encoder accepts, carry metadata across, and encode.
+//!
+//! When the decoder streams
+//! ([`DecodeCapabilities::streaming()`](crate::decode::DecodeCapabilities::streaming))
+//! and the encoder accepts rows
+//! ([`EncodeCapabilities::push_rows()`](crate::encode::EncodeCapabilities::push_rows)),
+//! batches pulled from
+//! [`into_streaming_decoder()`](crate::decode::DynDecodeJob::into_streaming_decoder)
+//! flow through an [`EncoderRowSink`] into
+//! [`DynEncoder::push_rows()`](crate::encode::DynEncoder::push_rows) without
+//! materializing the full image.
+//! Otherwise the image is decoded into a full buffer and encoded in one
+//! call.
+//!
+//! When both codecs handle gain maps, the decoder is asked to extract one
+//! and it is handed to the encoder, so SDR + gain map images keep their HDR
+//! rendition across formats.
+
+use alloc::borrow::Cow;
+use alloc::sync::Arc;
+
+use crate::decode::{DecodeOutput, DynDecoderConfig, EncoderRowSink};
+use crate::encode::{DynEncoderConfig, EncodeCapabilities, EncodeOutput, best_encode_format};
+use crate::gainmap::{DecodedGainMap, EncodeGainMap, GainMapSource};
+use crate::helpers::StripCoalescer;
+use crate::helpers::exif::rewrite_exif;
+use crate::metadata::IccSubstitution;
+use crate::traits::BoxedError;
+use crate::{
+    DecodePolicy, EncodePolicy, ImageFormat, Metadata, Orientation, ResourceLimits, StopToken,
+    UnsupportedOperation,
+};
+use zenpixels::PixelDescriptor;
+
+/// Decode `data` with `decoder` and re-encode it with `encoder`.
+///
+/// - `limits` and `stop` apply to both the decode and encode jobs.
+/// - The decoder is asked for the encoder's
+///   [`supported_descriptors()`](DynEncoderConfig::supported_descriptors),
+///   in order. The decoded pixels (the first strip, when streaming) must be
+///   layout-compatible with one of them per [`best_encode_format()`],
+///   otherwise [`UnsupportedOperation::PixelFormat`] is returned before
+///   anything reaches the encoder.
+/// - Source metadata (ICC, EXIF, XMP, CICP, HDR, orientation), as read by
+///   the decoder, is passed to the encode job; `encode_policy` decides what
+///   is actually embedded. EXIF is first rewritten with
+///   [`EncodePolicy::exif_rewrite()`]; when the decoder already rotated the
+///   pixels ([`OutputInfo::orientation_applied`](crate::decode::OutputInfo::orientation_applied)),
+///   its Orientation tag is also reset so viewers don't rotate twice.
+///   With [`EncodePolicy::resolve_validate_icc()`] the ICC profile is
+///   repaired or dropped ([`Metadata::with_sanitized_icc()`](crate::Metadata::with_sanitized_icc)).
+///   With [`EncodePolicy::resolve_canonicalize_icc()`] a well-known profile
+///   is replaced per [`Metadata::canonicalize_icc()`](crate::Metadata::canonicalize_icc),
+///   so encoders that signal CICP drop it, and the returned output reports
+///   the [`icc_substitution()`](EncodeOutput::icc_substitution).
+///   Encoders that signal color only through ICC get a profile synthesized
+///   from CICP ([`Metadata::with_icc_fallback()`](crate::Metadata::with_icc_fallback)).
+/// - When both codecs report gain map support, the decoder extracts the
+///   gain map and the encoder receives it via
+///   [`DynEncodeJob::set_gain_map()`](crate::encode::DynEncodeJob::set_gain_map).
+///   A bitstream in another format than the encoder's is decoded with
+///   `decoder` first. Images with a gain map take the full-buffer path.
+///
+/// ```rust,ignore
+/// let webp = zencodec::transcode(
+///     &JpegDecoderConfig::new(),
+///     &WebpEncoderConfig::new().with_generic_quality(80.0),
+///     &jpeg_bytes,
+///     ResourceLimits::none().with_max_pixels(100_000_000),
+///     DecodePolicy::strict(),
+///     EncodePolicy::strip_all(),
+///     stop,
+/// )?;
+/// ```
+pub fn transcode(
+    decoder: &dyn DynDecoderConfig,
+    encoder: &dyn DynEncoderConfig,
+    data: &[u8],
+    limits: ResourceLimits,
+    decode_policy: DecodePolicy,
+    encode_policy: EncodePolicy,
+    stop: StopToken,
+) -> Result<EncodeOutput, BoxedError> {
+    let supported = encoder.supported_descriptors();
+
+    let mut dec_job = decoder.dyn_job();
+    dec_job.set_stop(stop.clone());
+    dec_job.set_limits(limits);
+    dec_job.set_policy(decode_policy);
+
+    let mut enc_job = encoder.dyn_job();
+    enc_job.set_stop(stop.clone());
+    enc_job.set_limits(limits);
+    enc_job.set_policy(encode_policy);
+
+    let carry_gain_map = decoder.capabilities().gain_map() && encoder.capabilities().gain_map();
+    if carry_gain_map {
+        dec_job.set_extract_gain_map(true);
+    }
+
+    let out = dec_job.output_info(data)?;
+    let carry_metadata = |metadata| {
+        encode_metadata(
+            metadata,
+            &encode_policy,
+            out.orientation_applied,
+            encoder.capabilities(),
+        )
+    };
+
+    // Gain maps arrive as decode output extras, which streaming lacks.
+    if decoder.capabilities().streaming()
+        && encoder.capabilities().push_rows()
+        && (!carry_gain_map || dec_job.probe_full(data)?.gain_map.is_absent())
+    {
+        let mut stream = dec_job.into_streaming_decoder(Cow::Borrowed(data), supported)?;
+        let (metadata, substitution) = carry_metadata(stream.info().metadata());
+        enc_job.set_metadata(metadata);
+        let mut sink = EncoderRowSink::new_dyn(enc_job.into_encoder()?);
+        let mut strips = None;
+        while let Some((y, strip)) = stream.next_batch()? {
+            let strips = match &mut strips {
+                Some(strips) => strips,
+                None => {
+                    check_descriptor(strip.descriptor(), supported)?;
+                    let begun = StripCoalescer::begin(
+                        &mut sink,
+                        out.width,
+                        out.height,
+                        strip.descriptor(),
+                    )?;
+                    strips.insert(begun)
+                }
+            };
+            strips.push(&mut sink, y, &strip)?;
+        }
+        let strips = match strips {
+            Some(strips) => strips,
+            None => StripCoalescer::begin(&mut sink, out.width, out.height, out.native_format)?,
+        };
+        strips.finish(&mut sink)?;
+        return Ok(with_substitution(sink.finish_encode_dyn()?, substitution));
+    }
+
+    let mut output = dec_job
+        .into_decoder(Cow::Borrowed(data), supported)?
+        .decode()?;
+    check_descriptor(output.descriptor(), supported)?;
+    let (metadata, substitution) = carry_metadata(output.metadata());
+    enc_job.set_metadata(metadata);
+    if carry_gain_map {
+        let target = encoder.format();
+        if let Some(gain_map) =
+            take_gain_map(&mut output, decoder, target, limits, decode_policy, &stop)?
+        {
+            enc_job.set_gain_map(gain_map);
+        }
+    }
+    let encoded = enc_job.into_encoder()?.encode(output.pixels())?;
+    Ok(with_substitution(encoded, substitution))
+}
+
+/// Source metadata as the encode job should see it: EXIF rewritten per
+/// `policy`, orientation adjusted for what the decoder already applied, the
+/// ICC profile repaired or dropped when `policy` validates it, replaced by
+/// its compact equivalent when `policy` canonicalizes it, and an ICC
+/// profile synthesized for ICC-only encoders. Returns the substitution
+/// [`Metadata::canonicalize_icc()`] made, for the encode output.
+fn encode_metadata(
+    mut metadata: Metadata,
+    policy: &EncodePolicy,
+    applied: Orientation,
+    caps: &EncodeCapabilities,
+) -> (Metadata, Option<IccSubstitution>) {
+    let mut rewrite = policy.exif_rewrite();
+    if !applied.is_identity() {
+        rewrite = rewrite.with_reset_orientation(true);
+        metadata.orientation = applied.inverse().then(metadata.orientation);
+    }
+    if !rewrite.is_noop()
+        && let Some(exif) = &metadata.exif
+    {
+        // EXIF the rewriter can't parse is dropped rather than embedded
+        // with the tags the policy asked to remove.
+        metadata.exif = rewrite_exif(exif, &rewrite).map(Arc::from);
+    }
+    if policy.resolve_validate_icc(false) {
+        metadata = metadata.with_sanitized_icc();
+    }
+    let substitution = if policy.resolve_canonicalize_icc(false) {
+        metadata.canonicalize_icc(caps)
+    } else {
+        None
+    };
+    (metadata.with_icc_fallback(caps), substitution)
+}
+
+/// Report the ICC substitution [`encode_metadata()`] made, if any.
+fn with_substitution(output: EncodeOutput, substitution: Option<IccSubstitution>) -> EncodeOutput {
+    match substitution {
+        Some(substitution) => output.with_icc_substitution(substitution),
+        None => output,
+    }
+}
+
+/// Move the gain map out of `output`, decoding a bitstream the encoder
+/// can't embed verbatim with `decoder` so it can be re-encoded.
+fn take_gain_map(
+    output: &mut DecodeOutput,
+    decoder: &dyn DynDecoderConfig,
+    target: ImageFormat,
+    limits: ResourceLimits,
+    policy: DecodePolicy,
+    stop: &StopToken,
+) -> Result<Option<EncodeGainMap>, BoxedError> {
+    if let Some(decoded) = output.take_extras::<DecodedGainMap>() {
+        return Ok(Some(decoded.into()));
+    }
+    let Some(source) = output.take_extras::<GainMapSource>() else {
+        return Ok(None);
+    };
+    if source.format == target || source.depth > 0 || !decoder.formats().contains(&source.format) {
+        return Ok(Some(source.into()));
+    }
+    let mut job = decoder.dyn_job();
+    job.set_stop(stop.clone());
+    job.set_limits(limits);
+    job.set_policy(policy);
+    let pixels = job
+        .into_decoder(Cow::Owned(source.data), &[])?
+        .decode()?
+        .into_buffer();
+    Ok(Some(DecodedGainMap::new(pixels, source.metadata).into()))
+}
+
+fn check_descriptor(
+    descriptor: PixelDescriptor,
+    supported: &[PixelDescriptor],
+) -> Result<(), UnsupportedOperation> {
+    match best_encode_format(descriptor, supported) {
+        Some(_) => Ok(()),
+        None => Err(UnsupportedOperation::PixelFormat),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::exif::ExifReader;
+
+    /// IFD0 with Orientation = 6 (Rotate90).
+    const ROTATED_TIFF: [u8; 26] = [
+        b'I', b'I', 42, 0, 8, 0, 0, 0, // header, IFD0 at 8
+        1, 0, // 1 entry
+        0x12, 0x01, 3, 0, 1, 0, 0, 0, // Orientation, SHORT, count 1
+        6, 0, 0, 0, // Rotate90
+        0, 0, 0, 0, // no IFD1
+    ];
+
+    fn rotated() -> Metadata {
+        Metadata::none()
+            .with_exif(&ROTATED_TIFF[..])
+            .with_orientation(Orientation::Rotate90)
+    }
+
+    fn exif_orientation(metadata: &Metadata) -> Option<Orientation> {
+        ExifReader::new(metadata.exif.as_deref()?)?.orientation()
+    }
+
+    #[test]
+    fn metadata_passes_through_without_rewrite() {
+        let caps = EncodeCapabilities::new();
+        let (out, _) = encode_metadata(
+            rotated(),
+            &EncodePolicy::none(),
+            Orientation::Identity,
+            &caps,
+        );
+        assert_eq!(out.exif.as_deref(), Some(&ROTATED_TIFF[..]));
+        assert_eq!(out.orientation, Orientation::Rotate90);
+    }
+
+    #[test]
+    fn policy_resets_exif_orientation() {
+        let caps = EncodeCapabilities::new();
+        let policy = EncodePolicy::none().with_reset_orientation(true);
+        let (out, _) = encode_metadata(rotated(), &policy, Orientation::Identity, &caps);
+        assert_eq!(exif_orientation(&out), Some(Orientation::Identity));
+    }
+
+    #[test]
+    fn decoder_orientation_resets_exif() {
+        let caps = EncodeCapabilities::new();
+        let (out, _) = encode_metadata(
+            rotated(),
+            &EncodePolicy::none(),
+            Orientation::Rotate90,
+            &caps,
+        );
+        assert_eq!(exif_orientation(&out), Some(Orientation::Identity));
+        assert_eq!(out.orientation, Orientation::Identity);
+    }
+
+    #[test]
+    fn policy_canonicalizes_icc() {
+        let p3 = moxcms::ColorProfile::new_display_p3().encode().unwrap();
+        let caps = EncodeCapabilities::new().with_icc(true).with_cicp(true);
+        let canonicalize = EncodePolicy::none().with_canonicalize_icc(true);
+
+        let (out, substitution) = encode_metadata(
+            Metadata::none().with_icc(p3.clone()),
+            &canonicalize,
+            Orientation::Identity,
+            &caps,
+        );
+        assert!(out.icc_profile.is_none());
+        assert_eq!(out.cicp, Some(crate::Cicp::DISPLAY_P3));
+        assert_eq!(
+            substitution,
+            Some(IccSubstitution::Cicp {
+                cicp: crate::Cicp::DISPLAY_P3,
+                original_len: p3.len()
+            })
+        );
+
+        let (out, substitution) = encode_metadata(
+            Metadata::none().with_icc(p3.clone()),
+            &EncodePolicy::none(),
+            Orientation::Identity,
+            &caps,
+        );
+        assert_eq!(out.icc_profile.as_deref(), Some(p3.as_slice()));
+        assert_eq!(substitution, None);
+    }
+
+    #[test]
+    fn policy_sanitizes_icc() {
+        let caps = EncodeCapabilities::new().with_icc(true);
+        let icc = crate::helpers::icc::icc_from_cicp(crate::Cicp::DISPLAY_P3, None).unwrap();
+        let mut padded = icc.clone();
+        padded.extend_from_slice(&[0xAB; 7]);
+        let truncated = icc[..200].to_vec();
+        let validate = EncodePolicy::none().with_validate_icc(true);
+
+        let (out, _) = encode_metadata(
+            Metadata::none().with_icc(padded.clone()),
+            &validate,
+            Orientation::Identity,
+            &caps,
+        );
+        assert_eq!(out.icc_profile.as_deref(), Some(icc.as_slice()));
+        let (out, _) = encode_metadata(
+            Metadata::none().with_icc(truncated),
+            &validate,
+            Orientation::Identity,
+            &caps,
+        );
+        assert!(out.icc_profile.is_none());
+
+        // Without the policy the profile is passed through as is.
+        let (out, _) = encode_metadata(
+            Metadata::none().with_icc(padded.clone()),
+            &EncodePolicy::none(),
+            Orientation::Identity,
+            &caps,
+        );
+        assert_eq!(out.icc_profile.as_deref(), Some(padded.as_slice()));
+    }
+}
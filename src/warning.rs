@@ -0,0 +1,364 @@
+//! Structured, non-fatal decode diagnostics.
+//!
+//! A decoder that recovers from a problem records a [`DecodeWarning`] on
+//! [`ImageInfo::warnings`](crate::ImageInfo::warnings) instead of failing.
+//! Each warning carries a stable [`DecodeWarningCode`] for programmatic
+//! handling, a [`DecodeWarningSeverity`], an optional byte offset into the
+//! input, and a message for humans.
+//!
+//! [`DecodePolicy::escalate_warnings`](crate::decode::DecodePolicy::escalate_warnings)
+//! turns chosen codes into errors — a finer-grained
+//! [`strict`](crate::decode::DecodePolicy::strict). Codecs route each
+//! warning through [`DecodePolicy::escalate()`](crate::decode::DecodePolicy::escalate):
+//!
+//! ```rust,ignore
+//! let warning = DecodeWarning::new(DecodeWarningCode::ChecksumMismatch, "IDAT CRC mismatch")
+//!     .with_offset(chunk_start);
+//! info.warnings.push(policy.escalate(warning)?); // MyError: From<DecodeWarning>
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+
+use crate::CodecErrorKind;
+
+/// What a [`DecodeWarning`] is about.
+///
+/// Discriminants are stable and may be logged or stored; new codes are
+/// added at the end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(u8)]
+pub enum DecodeWarningCode {
+    /// Anything not covered by a more specific code.
+    Other = 0,
+    /// The input ended early; missing pixels were filled in.
+    Truncated = 1,
+    /// A checksum (CRC, Adler-32, ...) did not match and was ignored.
+    ChecksumMismatch = 2,
+    /// The input violates its specification in a way the decoder tolerated.
+    NonConformant = 3,
+    /// An embedded ICC profile was ignored or dropped.
+    IccIgnored = 4,
+    /// An embedded ICC profile was repaired.
+    IccRepaired = 5,
+    /// Malformed EXIF, XMP, or other metadata was skipped.
+    MetadataIgnored = 6,
+    /// Orientation sources disagree (e.g. EXIF orientation vs HEIF `irot`).
+    OrientationConflict = 7,
+    /// Metadata was found beyond the fast-path probe limit.
+    MetadataBeyondProbe = 8,
+}
+
+const _: () = {
+    assert!(DecodeWarningCode::Other as u8 == 0);
+    assert!(DecodeWarningCode::Truncated as u8 == 1);
+    assert!(DecodeWarningCode::ChecksumMismatch as u8 == 2);
+    assert!(DecodeWarningCode::NonConformant as u8 == 3);
+    assert!(DecodeWarningCode::IccIgnored as u8 == 4);
+    assert!(DecodeWarningCode::IccRepaired as u8 == 5);
+    assert!(DecodeWarningCode::MetadataIgnored as u8 == 6);
+    assert!(DecodeWarningCode::OrientationConflict as u8 == 7);
+    assert!(DecodeWarningCode::MetadataBeyondProbe as u8 == 8);
+};
+
+impl DecodeWarningCode {
+    /// Every code, in discriminant order.
+    pub const ALL: &'static [Self] = &[
+        Self::Other,
+        Self::Truncated,
+        Self::ChecksumMismatch,
+        Self::NonConformant,
+        Self::IccIgnored,
+        Self::IccRepaired,
+        Self::MetadataIgnored,
+        Self::OrientationConflict,
+        Self::MetadataBeyondProbe,
+    ];
+
+    /// Stable snake_case identifier, for logs and metrics.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Other => "other",
+            Self::Truncated => "truncated",
+            Self::ChecksumMismatch => "checksum_mismatch",
+            Self::NonConformant => "non_conformant",
+            Self::IccIgnored => "icc_ignored",
+            Self::IccRepaired => "icc_repaired",
+            Self::MetadataIgnored => "metadata_ignored",
+            Self::OrientationConflict => "orientation_conflict",
+            Self::MetadataBeyondProbe => "metadata_beyond_probe",
+        }
+    }
+
+    /// Severity used by [`DecodeWarning::new()`].
+    pub const fn default_severity(self) -> DecodeWarningSeverity {
+        match self {
+            Self::Truncated => DecodeWarningSeverity::DataLoss,
+            Self::ChecksumMismatch
+            | Self::NonConformant
+            | Self::IccIgnored
+            | Self::MetadataIgnored
+            | Self::OrientationConflict => DecodeWarningSeverity::Degraded,
+            Self::Other | Self::IccRepaired | Self::MetadataBeyondProbe => {
+                DecodeWarningSeverity::Info
+            }
+        }
+    }
+
+    /// How an escalated warning with this code classifies as an error.
+    pub const fn error_kind(self) -> CodecErrorKind {
+        match self {
+            Self::Truncated => CodecErrorKind::Truncated,
+            _ => CodecErrorKind::Corrupt,
+        }
+    }
+}
+
+impl core::fmt::Display for DecodeWarningCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How much a [`DecodeWarning`] affects the decoded result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum DecodeWarningSeverity {
+    /// Nothing lost; worth knowing.
+    Info,
+    /// Pixels are intact but may not be shown as the file intended
+    /// (color, orientation, metadata).
+    Degraded,
+    /// Some pixels are missing or invented.
+    DataLoss,
+}
+
+/// A non-fatal problem found while probing or decoding.
+///
+/// Also an error type: [`DecodePolicy::escalate()`](crate::decode::DecodePolicy::escalate)
+/// returns it as `Err` for escalated codes, and
+/// [`CodecErrorExt::kind()`](crate::CodecErrorExt::kind) classifies it by
+/// [`DecodeWarningCode::error_kind()`].
+///
+/// ```
+/// use zencodec::decode::{DecodeWarning, DecodeWarningCode, DecodeWarningSeverity};
+///
+/// let w = DecodeWarning::new(DecodeWarningCode::Truncated, "padded 12 rows with gray")
+///     .with_offset(48_213);
+/// assert_eq!(w.severity(), DecodeWarningSeverity::DataLoss);
+/// assert_eq!(w.to_string(), "truncated: padded 12 rows with gray (at byte 48213)");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DecodeWarning {
+    code: DecodeWarningCode,
+    severity: DecodeWarningSeverity,
+    offset: Option<u64>,
+    message: Cow<'static, str>,
+}
+
+impl DecodeWarning {
+    /// A warning with the code's default severity and no offset.
+    pub fn new(code: DecodeWarningCode, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            code,
+            severity: code.default_severity(),
+            offset: None,
+            message: message.into(),
+        }
+    }
+
+    /// Override the severity.
+    pub fn with_severity(mut self, severity: DecodeWarningSeverity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Byte offset in the input where the problem was found.
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// What the warning is about.
+    pub fn code(&self) -> DecodeWarningCode {
+        self.code
+    }
+
+    /// How much it affects the result.
+    pub fn severity(&self) -> DecodeWarningSeverity {
+        self.severity
+    }
+
+    /// Byte offset in the input, if known.
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+
+    /// Human-readable description.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// An unclassified warning, as codecs recorded them before codes existed.
+impl From<String> for DecodeWarning {
+    fn from(message: String) -> Self {
+        Self::new(DecodeWarningCode::Other, message)
+    }
+}
+
+/// An unclassified warning with a static message.
+impl From<&'static str> for DecodeWarning {
+    fn from(message: &'static str) -> Self {
+        Self::new(DecodeWarningCode::Other, message)
+    }
+}
+
+impl core::fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)?;
+        if let Some(offset) = self.offset {
+            write!(f, " (at byte {offset})")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for DecodeWarning {}
+
+/// A set of [`DecodeWarningCode`]s, for
+/// [`DecodePolicy::escalate_warnings`](crate::decode::DecodePolicy::escalate_warnings).
+///
+/// ```
+/// use zencodec::decode::{DecodeWarningCode, DecodeWarningSet};
+///
+/// let set = DecodeWarningSet::EMPTY
+///     .with(DecodeWarningCode::ChecksumMismatch)
+///     .with(DecodeWarningCode::Truncated);
+/// assert!(set.contains(DecodeWarningCode::Truncated));
+/// assert!(!set.contains(DecodeWarningCode::IccIgnored));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DecodeWarningSet(u32);
+
+// One bit per code; discriminants must stay below the set's width.
+const _: () = {
+    let mut i = 0;
+    while i < DecodeWarningCode::ALL.len() {
+        assert!((DecodeWarningCode::ALL[i] as u32) < u32::BITS);
+        i += 1;
+    }
+};
+
+impl DecodeWarningSet {
+    /// No codes.
+    pub const EMPTY: Self = Self(0);
+
+    /// Every code, including codes added in later versions (the set has
+    /// room for 32).
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Problems with the image data itself: [`Truncated`](DecodeWarningCode::Truncated),
+    /// [`ChecksumMismatch`](DecodeWarningCode::ChecksumMismatch), and
+    /// [`NonConformant`](DecodeWarningCode::NonConformant). Escalated by
+    /// [`DecodePolicy::strict()`](crate::decode::DecodePolicy::strict).
+    pub const STRICT: Self = Self::EMPTY
+        .with(DecodeWarningCode::Truncated)
+        .with(DecodeWarningCode::ChecksumMismatch)
+        .with(DecodeWarningCode::NonConformant);
+
+    const fn bit(code: DecodeWarningCode) -> u32 {
+        1 << code as u8
+    }
+
+    /// This set plus `code`.
+    pub const fn with(self, code: DecodeWarningCode) -> Self {
+        Self(self.0 | Self::bit(code))
+    }
+
+    /// This set minus `code`.
+    pub const fn without(self, code: DecodeWarningCode) -> Self {
+        Self(self.0 & !Self::bit(code))
+    }
+
+    /// Codes in either set.
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Whether `code` is in the set.
+    pub const fn contains(self, code: DecodeWarningCode) -> bool {
+        self.0 & Self::bit(code) != 0
+    }
+
+    /// Whether the set has no codes.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CodecErrorExt;
+    use alloc::string::ToString;
+
+    #[test]
+    fn codes_are_distinct_and_named() {
+        for (i, code) in DecodeWarningCode::ALL.iter().enumerate() {
+            assert_eq!(*code as usize, i);
+            assert!(!code.name().is_empty());
+            assert_eq!(code.to_string(), code.name());
+        }
+    }
+
+    #[test]
+    fn warning_builder_and_display() {
+        let w = DecodeWarning::new(DecodeWarningCode::IccIgnored, "unsupported LUT profile");
+        assert_eq!(w.severity(), DecodeWarningSeverity::Degraded);
+        assert_eq!(w.offset(), None);
+        assert_eq!(w.message(), "unsupported LUT profile");
+        assert_eq!(w.to_string(), "icc_ignored: unsupported LUT profile");
+
+        let w = w.with_severity(DecodeWarningSeverity::Info).with_offset(7);
+        assert_eq!(w.severity(), DecodeWarningSeverity::Info);
+        assert_eq!(w.offset(), Some(7));
+
+        let legacy: DecodeWarning = String::from("odd header").into();
+        assert_eq!(legacy.code(), DecodeWarningCode::Other);
+        assert_eq!(legacy.severity(), DecodeWarningSeverity::Info);
+    }
+
+    #[test]
+    fn severity_orders_by_impact() {
+        assert!(DecodeWarningSeverity::Info < DecodeWarningSeverity::Degraded);
+        assert!(DecodeWarningSeverity::Degraded < DecodeWarningSeverity::DataLoss);
+    }
+
+    #[test]
+    fn escalated_warning_classifies() {
+        let w = DecodeWarning::new(DecodeWarningCode::Truncated, "short");
+        assert_eq!(w.kind(), Some(CodecErrorKind::Truncated));
+        let w = DecodeWarning::new(DecodeWarningCode::ChecksumMismatch, "crc");
+        assert_eq!(w.kind(), Some(CodecErrorKind::Corrupt));
+    }
+
+    #[test]
+    fn warning_sets() {
+        assert!(DecodeWarningSet::EMPTY.is_empty());
+        assert_eq!(DecodeWarningSet::default(), DecodeWarningSet::EMPTY);
+        for &code in DecodeWarningCode::ALL {
+            assert!(DecodeWarningSet::ALL.contains(code));
+            assert!(!DecodeWarningSet::EMPTY.contains(code));
+            assert!(DecodeWarningSet::EMPTY.with(code).contains(code));
+            assert!(!DecodeWarningSet::ALL.without(code).contains(code));
+        }
+        assert!(DecodeWarningSet::STRICT.contains(DecodeWarningCode::NonConformant));
+        assert!(!DecodeWarningSet::STRICT.contains(DecodeWarningCode::IccRepaired));
+        let icc = DecodeWarningSet::EMPTY.with(DecodeWarningCode::IccIgnored);
+        let both = DecodeWarningSet::STRICT.union(icc);
+        assert!(both.contains(DecodeWarningCode::IccIgnored));
+        assert!(both.contains(DecodeWarningCode::Truncated));
+    }
+}
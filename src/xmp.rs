@@ -0,0 +1,757 @@
+//! Minimal XMP packet reader and hdrgm gain map serializer.
+//!
+//! [`XmpReader`](crate::xmp::XmpReader) answers the handful of questions codecs ask of an XMP
+//! packet — UltraHDR gain map parameters (Adobe `hdrgm:` namespace),
+//! `tiff:Orientation`, `xmp:CreateDate` — without an XML parser:
+//!
+//! - Namespace prefixes are resolved from `xmlns:` declarations, so
+//!   packets using a non-standard prefix still match.
+//! - Properties are found in either RDF form: as attributes of
+//!   `rdf:Description`, or as child elements, including `rdf:Seq` /
+//!   `rdf:Bag` / `rdf:Alt` item lists.
+//! - No recursion and no heap allocation; every query is a linear scan of
+//!   the packet. Values borrow from the input and XML entities are not
+//!   decoded.
+//!
+//! [`serialize_hdrgm_xmp`](crate::xmp::serialize_hdrgm_xmp) writes a complete `<?xpacket?>`-wrapped packet
+//! carrying [`GainMapParams`] in the hdrgm namespace.
+//!
+//! Reference: Adobe "Gain Map Specification" 1.0 (hdrgm namespace),
+//! XMP Specification Part 1 (ISO 16684-1) §7 (RDF serialization).
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use zenpixels::Orientation;
+
+use crate::gainmap::{GainMapChannel, GainMapParams, GainMapParseError};
+
+/// Adobe gain map namespace URI (`hdrgm:`).
+pub const HDRGM_NAMESPACE: &str = "http://ns.adobe.com/hdr-gain-map/1.0/";
+/// TIFF properties namespace URI (`tiff:`).
+pub const TIFF_NAMESPACE: &str = "http://ns.adobe.com/tiff/1.0/";
+/// XMP basic namespace URI (`xmp:`).
+pub const XMP_NAMESPACE: &str = "http://ns.adobe.com/xap/1.0/";
+/// RDF namespace URI (`rdf:`).
+pub const RDF_NAMESPACE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+/// Prefix of an XMP packet stored in a JPEG APP1 segment.
+const JPEG_APP1_XMP_PREFIX: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+/// Longest namespace prefix accepted when resolving `xmlns:` declarations.
+const MAX_PREFIX_LEN: usize = 64;
+
+/// Read-only view of an XMP packet.
+///
+/// ```
+/// use zencodec::xmp::XmpReader;
+/// use zenpixels::Orientation;
+///
+/// let xmp = br#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+///   <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+///     <rdf:Description rdf:about=""
+///         xmlns:tiff="http://ns.adobe.com/tiff/1.0/"
+///         tiff:Orientation="6"/>
+///   </rdf:RDF>
+/// </x:xmpmeta>"#;
+/// let reader = XmpReader::new(xmp).unwrap();
+/// assert_eq!(reader.orientation(), Some(Orientation::Rotate90));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct XmpReader<'a> {
+    text: &'a str,
+}
+
+impl<'a> XmpReader<'a> {
+    /// Wrap an XMP packet.
+    ///
+    /// Accepts the bare packet or a JPEG APP1 body starting with
+    /// `http://ns.adobe.com/xap/1.0/\0`. Returns `None` if the data is not
+    /// UTF-8.
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let data = data.strip_prefix(JPEG_APP1_XMP_PREFIX).unwrap_or(data);
+        let text = core::str::from_utf8(data).ok()?;
+        Some(Self { text })
+    }
+
+    /// The packet text.
+    pub fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    /// The prefix the packet binds to `namespace`, if declared.
+    pub fn prefix_for(&self, namespace: &str) -> Option<&'a str> {
+        let text = self.text;
+        let mut from = 0;
+        while let Some(found) = text[from..].find(namespace) {
+            let at = from + found;
+            from = at + namespace.len();
+            if let Some(prefix) = declared_prefix(&text[..at], &text[from..]) {
+                return Some(prefix);
+            }
+        }
+        None
+    }
+
+    /// The value of property `name` in `namespace`.
+    ///
+    /// Returns attribute values and simple element contents, trimmed. For
+    /// an item list (`rdf:Seq` etc.), returns the first item; use
+    /// [`property_items()`](Self::property_items) for all of them.
+    pub fn property(&self, namespace: &str, name: &str) -> Option<&'a str> {
+        self.property_items(namespace, name).next()
+    }
+
+    /// The values of property `name` in `namespace`: each `rdf:li` of an
+    /// item list, or the single value of a simple property.
+    pub fn property_items(&self, namespace: &str, name: &str) -> XmpItems<'a> {
+        let empty = XmpItems {
+            rest: "",
+            li: "",
+            single: None,
+        };
+        let Some(prefix) = self.prefix_for(namespace) else {
+            return empty;
+        };
+        let Some(value) = find_property(self.text, prefix, name) else {
+            return empty;
+        };
+        match value {
+            Value::Attribute(v) => XmpItems {
+                single: Some(v),
+                ..empty
+            },
+            Value::Element(content) => {
+                let rdf = self.prefix_for(RDF_NAMESPACE).unwrap_or("rdf");
+                XmpItems {
+                    rest: content,
+                    li: rdf,
+                    single: (!content.contains('<')).then_some(content.trim()),
+                }
+            }
+        }
+    }
+
+    /// `tiff:Orientation`, validated to 1..=8.
+    pub fn orientation(&self) -> Option<Orientation> {
+        let value: u8 = self.property(TIFF_NAMESPACE, "Orientation")?.parse().ok()?;
+        Orientation::from_exif(value)
+    }
+
+    /// `xmp:CreateDate` as written (ISO 8601, e.g. `2024-05-06T07:08:09+02:00`).
+    pub fn create_date(&self) -> Option<&'a str> {
+        self.property(XMP_NAMESPACE, "CreateDate")
+    }
+
+    /// Whether the packet declares hdrgm gain map metadata
+    /// (`hdrgm:Version` is present).
+    pub fn has_gain_map(&self) -> bool {
+        self.property(HDRGM_NAMESPACE, "Version").is_some()
+    }
+
+    /// Gain map parameters from the `hdrgm:` namespace.
+    ///
+    /// Returns `Ok(None)` if the packet has no `hdrgm:Version`. Optional
+    /// fields take their Adobe defaults (`GainMapMin` 0, `Gamma` 1, offsets
+    /// 1/64, `HDRCapacityMin` 0, `BaseRenditionIsHDR` false).
+    /// `GainMapMax` and `HDRCapacityMax` are required. Per-channel fields
+    /// may be a single value or an `rdf:Seq` of three.
+    ///
+    /// hdrgm gains and capacities are log2, like [`GainMapParams`]. With
+    /// `BaseRenditionIsHDR="True"` the SDR/HDR offsets and capacities map
+    /// to the alternate/base fields and
+    /// [`backward_direction`](GainMapParams::backward_direction) is set.
+    pub fn gain_map_params(&self) -> Result<Option<GainMapParams>, GainMapParseError> {
+        if !self.has_gain_map() {
+            return Ok(None);
+        }
+        let gain_min = self.channel_values("GainMapMin", 0.0)?;
+        let gain_max = self.channel_values("GainMapMax", f64::NAN)?;
+        let gamma = self.channel_values("Gamma", 1.0)?;
+        let offset_sdr = self.channel_values("OffsetSDR", 1.0 / 64.0)?;
+        let offset_hdr = self.channel_values("OffsetHDR", 1.0 / 64.0)?;
+        let capacity_min = self.scalar("HDRCapacityMin", 0.0)?;
+        let capacity_max = self.scalar("HDRCapacityMax", f64::NAN)?;
+        let base_is_hdr = match self.property(HDRGM_NAMESPACE, "BaseRenditionIsHDR") {
+            None => false,
+            Some(v) => parse_bool(v).ok_or(GainMapParseError::InvalidXmpValue {
+                field: "BaseRenditionIsHDR",
+            })?,
+        };
+        if gain_max[0].is_nan() {
+            return Err(GainMapParseError::MissingXmpField {
+                field: "GainMapMax",
+            });
+        }
+        if capacity_max.is_nan() {
+            return Err(GainMapParseError::MissingXmpField {
+                field: "HDRCapacityMax",
+            });
+        }
+
+        let (base_offset, alternate_offset) = if base_is_hdr {
+            (offset_hdr, offset_sdr)
+        } else {
+            (offset_sdr, offset_hdr)
+        };
+        let mut params = GainMapParams::default();
+        for (i, ch) in params.channels.iter_mut().enumerate() {
+            *ch = GainMapChannel {
+                min: gain_min[i],
+                max: gain_max[i],
+                gamma: gamma[i],
+                base_offset: base_offset[i],
+                alternate_offset: alternate_offset[i],
+            };
+        }
+        (params.base_hdr_headroom, params.alternate_hdr_headroom) = if base_is_hdr {
+            (capacity_max, capacity_min)
+        } else {
+            (capacity_min, capacity_max)
+        };
+        params.backward_direction = base_is_hdr;
+        params.validate()?;
+        Ok(Some(params))
+    }
+
+    fn scalar(&self, field: &'static str, default: f64) -> Result<f64, GainMapParseError> {
+        match self.property(HDRGM_NAMESPACE, field) {
+            None => Ok(default),
+            Some(v) => parse_f64(v, field),
+        }
+    }
+
+    /// A per-channel field: absent → `default` ×3, one value → ×3, or three.
+    fn channel_values(
+        &self,
+        field: &'static str,
+        default: f64,
+    ) -> Result<[f64; 3], GainMapParseError> {
+        let mut items = self.property_items(HDRGM_NAMESPACE, field);
+        let Some(first) = items.next() else {
+            return Ok([default; 3]);
+        };
+        let first = parse_f64(first, field)?;
+        let Some(second) = items.next() else {
+            return Ok([first; 3]);
+        };
+        let second = parse_f64(second, field)?;
+        let third = items
+            .next()
+            .ok_or(GainMapParseError::InvalidXmpValue { field })
+            .and_then(|v| parse_f64(v, field))?;
+        if items.next().is_some() {
+            return Err(GainMapParseError::InvalidXmpValue { field });
+        }
+        Ok([first, second, third])
+    }
+}
+
+/// Iterator over the values of one XMP property.
+/// See [`XmpReader::property_items`].
+#[derive(Clone, Debug)]
+pub struct XmpItems<'a> {
+    /// Unscanned element content.
+    rest: &'a str,
+    /// RDF prefix for `li` elements.
+    li: &'a str,
+    /// Value of a simple property, yielded once.
+    single: Option<&'a str>,
+}
+
+impl<'a> Iterator for XmpItems<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if let Some(v) = self.single.take() {
+            self.rest = "";
+            return Some(v);
+        }
+        let start = find_tag_start(self.rest, self.li, "li")?;
+        let after = &self.rest[start..];
+        let open_end = after.find('>')?;
+        if after[..open_end].ends_with('/') {
+            self.rest = &after[open_end + 1..];
+            return Some("");
+        }
+        let content = &after[open_end + 1..];
+        let close = find_close_tag(content, self.li, "li")?;
+        self.rest = &content[close..];
+        Some(content[..close].trim())
+    }
+}
+
+/// A property as found in the packet.
+enum Value<'a> {
+    /// `prefix:name="value"` on an element.
+    Attribute(&'a str),
+    /// Content of `<prefix:name>...</prefix:name>`.
+    Element(&'a str),
+}
+
+/// If `before` ends with `xmlns:PREFIX="` (or `'`) and `after` starts with
+/// the matching quote, return `PREFIX`.
+fn declared_prefix<'a>(before: &'a str, after: &str) -> Option<&'a str> {
+    let quote = before.chars().next_back()?;
+    if !matches!(quote, '"' | '\'') || !after.starts_with(quote) {
+        return None;
+    }
+    let before = before[..before.len() - 1].trim_end();
+    let before = before.strip_suffix('=')?.trim_end();
+    let name_start = before
+        .rfind(|c: char| c.is_whitespace() || c == '<')
+        .map_or(0, |i| i + 1);
+    let prefix = before[name_start..].strip_prefix("xmlns:")?;
+    (!prefix.is_empty() && prefix.len() <= MAX_PREFIX_LEN).then_some(prefix)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')
+}
+
+/// Find `prefix:name` as an attribute or element anywhere in `text`.
+fn find_property<'a>(text: &'a str, prefix: &str, name: &str) -> Option<Value<'a>> {
+    let mut from = 0;
+    while let Some((at, qname_len)) = find_qname(&text[from..], prefix, name) {
+        let start = from + at;
+        let end = start + qname_len;
+        from = end;
+        let before = text[..start].chars().next_back();
+        let after = &text[end..];
+        if before == Some('<') {
+            let open_end = after.find('>')?;
+            if after[..open_end].ends_with('/') {
+                return Some(Value::Element(""));
+            }
+            let content = &after[open_end + 1..];
+            let close = find_close_tag(content, prefix, name)?;
+            return Some(Value::Element(&content[..close]));
+        }
+        if before.is_some_and(char::is_whitespace) {
+            let rest = after.trim_start().strip_prefix('=')?.trim_start();
+            let quote = rest.chars().next()?;
+            if !matches!(quote, '"' | '\'') {
+                continue;
+            }
+            let rest = &rest[1..];
+            let close = rest.find(quote)?;
+            return Some(Value::Attribute(rest[..close].trim()));
+        }
+    }
+    None
+}
+
+/// Offset and length of the next `prefix:name` not followed by a name
+/// character.
+fn find_qname(text: &str, prefix: &str, name: &str) -> Option<(usize, usize)> {
+    if prefix.is_empty() {
+        return None;
+    }
+    let mut from = 0;
+    loop {
+        let at = from + text[from..].find(prefix)?;
+        let after_prefix = &text[at + prefix.len()..];
+        from = at + prefix.len();
+        let Some(rest) = after_prefix
+            .strip_prefix(':')
+            .and_then(|r| r.strip_prefix(name))
+        else {
+            continue;
+        };
+        if rest.chars().next().is_some_and(is_name_char) {
+            continue;
+        }
+        // Reject matches inside a longer prefix (`xhdrgm:` for `hdrgm:`).
+        if text[..at].chars().next_back().is_some_and(is_name_char) {
+            continue;
+        }
+        return Some((at, prefix.len() + 1 + name.len()));
+    }
+}
+
+/// Offset of the next `<prefix:name` start tag in `text`.
+fn find_tag_start(text: &str, prefix: &str, name: &str) -> Option<usize> {
+    let mut from = 0;
+    loop {
+        let (at, len) = find_qname(&text[from..], prefix, name)?;
+        let at = from + at;
+        from = at + len;
+        if at > 0 && text.as_bytes()[at - 1] == b'<' {
+            return Some(at);
+        }
+    }
+}
+
+/// Offset of the `</prefix:name>` closing tag in `content`.
+fn find_close_tag(content: &str, prefix: &str, name: &str) -> Option<usize> {
+    let mut from = 0;
+    loop {
+        let at = from + content[from..].find("</")?;
+        from = at + 2;
+        let rest = &content[from..];
+        if let Some(rest) = rest
+            .strip_prefix(prefix)
+            .and_then(|r| r.strip_prefix(':'))
+            .and_then(|r| r.strip_prefix(name))
+            && rest.trim_start().starts_with('>')
+        {
+            return Some(at);
+        }
+    }
+}
+
+fn parse_f64(value: &str, field: &'static str) -> Result<f64, GainMapParseError> {
+    match value.trim().parse::<f64>() {
+        Ok(v) if v.is_finite() => Ok(v),
+        _ => Err(GainMapParseError::InvalidXmpValue { field }),
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim() {
+        "True" | "true" => Some(true),
+        "False" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Serialize [`GainMapParams`] as a complete XMP packet in the Adobe hdrgm
+/// namespace (`hdrgm:Version="1.0"`).
+///
+/// Single-channel parameters are written as attributes; multichannel
+/// fields as `rdf:Seq` elements of three values. Round-trips through
+/// [`XmpReader::gain_map_params`].
+///
+/// ```
+/// use zencodec::gainmap::GainMapParams;
+/// use zencodec::xmp::{XmpReader, serialize_hdrgm_xmp};
+///
+/// let mut params = GainMapParams::default();
+/// params.channels[0].max = 2.0;
+/// params.channels[1].max = 2.0;
+/// params.channels[2].max = 2.0;
+/// params.alternate_hdr_headroom = 2.0;
+///
+/// let packet = serialize_hdrgm_xmp(&params);
+/// let reader = XmpReader::new(&packet).unwrap();
+/// assert_eq!(reader.gain_map_params().unwrap(), Some(params));
+/// ```
+pub fn serialize_hdrgm_xmp(params: &GainMapParams) -> Vec<u8> {
+    let base_is_hdr = params.backward_direction;
+    let (capacity_min, capacity_max) = if base_is_hdr {
+        (params.alternate_hdr_headroom, params.base_hdr_headroom)
+    } else {
+        (params.base_hdr_headroom, params.alternate_hdr_headroom)
+    };
+    let ch = &params.channels;
+    let base_offsets = ch.map(|c| c.base_offset);
+    let alternate_offsets = ch.map(|c| c.alternate_offset);
+    let (offset_sdr, offset_hdr) = if base_is_hdr {
+        (alternate_offsets, base_offsets)
+    } else {
+        (base_offsets, alternate_offsets)
+    };
+    let fields: [(&str, [f64; 3]); 5] = [
+        ("GainMapMin", ch.map(|c| c.min)),
+        ("GainMapMax", ch.map(|c| c.max)),
+        ("Gamma", ch.map(|c| c.gamma)),
+        ("OffsetSDR", offset_sdr),
+        ("OffsetHDR", offset_hdr),
+    ];
+    let single = params.is_single_channel();
+
+    let mut out = String::new();
+    // Writing to a String cannot fail.
+    let _ = write_packet(
+        &mut out,
+        &fields,
+        single,
+        capacity_min,
+        capacity_max,
+        base_is_hdr,
+    );
+    out.into_bytes()
+}
+
+fn write_packet(
+    out: &mut String,
+    fields: &[(&str, [f64; 3])],
+    single: bool,
+    capacity_min: f64,
+    capacity_max: f64,
+    base_is_hdr: bool,
+) -> core::fmt::Result {
+    out.push_str("<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n");
+    out.push_str("<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n");
+    writeln!(out, " <rdf:RDF xmlns:rdf=\"{RDF_NAMESPACE}\">")?;
+    out.push_str("  <rdf:Description rdf:about=\"\"\n");
+    writeln!(out, "    xmlns:hdrgm=\"{HDRGM_NAMESPACE}\"")?;
+    out.push_str("    hdrgm:Version=\"1.0\"\n");
+    if single {
+        for (name, values) in fields {
+            writeln!(out, "    hdrgm:{name}=\"{}\"", values[0])?;
+        }
+    }
+    writeln!(out, "    hdrgm:HDRCapacityMin=\"{capacity_min}\"")?;
+    writeln!(out, "    hdrgm:HDRCapacityMax=\"{capacity_max}\"")?;
+    let base = if base_is_hdr { "True" } else { "False" };
+    write!(out, "    hdrgm:BaseRenditionIsHDR=\"{base}\"")?;
+    if single {
+        out.push_str("/>\n");
+    } else {
+        out.push_str(">\n");
+        for (name, values) in fields {
+            writeln!(out, "   <hdrgm:{name}>")?;
+            out.push_str("    <rdf:Seq>\n");
+            for v in values {
+                writeln!(out, "     <rdf:li>{v}</rdf:li>")?;
+            }
+            out.push_str("    </rdf:Seq>\n");
+            writeln!(out, "   </hdrgm:{name}>")?;
+        }
+        out.push_str("  </rdf:Description>\n");
+    }
+    out.push_str(" </rdf:RDF>\n");
+    out.push_str("</x:xmpmeta>\n");
+    out.push_str("<?xpacket end=\"w\"?>");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ULTRAHDR_ATTRS: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/" x:xmptk="Adobe XMP Core 5.1.2">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:hdrgm="http://ns.adobe.com/hdr-gain-map/1.0/"
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmp:CreateDate="2024-05-06T07:08:09+02:00"
+    hdrgm:Version="1.0"
+    hdrgm:GainMapMin="-0.5"
+    hdrgm:GainMapMax="2.5"
+    hdrgm:Gamma="1"
+    hdrgm:OffsetSDR="0"
+    hdrgm:OffsetHDR="0"
+    hdrgm:HDRCapacityMin="0"
+    hdrgm:HDRCapacityMax="2.5"
+    hdrgm:BaseRenditionIsHDR="False"/>
+ </rdf:RDF>
+</x:xmpmeta>"#;
+
+    #[test]
+    fn attribute_form_gain_map() {
+        let reader = XmpReader::new(ULTRAHDR_ATTRS.as_bytes()).unwrap();
+        assert!(reader.has_gain_map());
+        let params = reader.gain_map_params().unwrap().unwrap();
+        assert!(params.is_single_channel());
+        assert_eq!(params.channels[0].min, -0.5);
+        assert_eq!(params.channels[0].max, 2.5);
+        assert_eq!(params.channels[0].base_offset, 0.0);
+        assert_eq!(params.base_hdr_headroom, 0.0);
+        assert_eq!(params.alternate_hdr_headroom, 2.5);
+        assert!(!params.backward_direction);
+        assert_eq!(reader.create_date(), Some("2024-05-06T07:08:09+02:00"));
+    }
+
+    #[test]
+    fn element_form_with_seq() {
+        let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description xmlns:hdrgm="http://ns.adobe.com/hdr-gain-map/1.0/">
+   <hdrgm:Version>1.0</hdrgm:Version>
+   <hdrgm:GainMapMax>
+    <rdf:Seq><rdf:li>1.0</rdf:li><rdf:li>2.0</rdf:li><rdf:li>3.0</rdf:li></rdf:Seq>
+   </hdrgm:GainMapMax>
+   <hdrgm:HDRCapacityMax>3.0</hdrgm:HDRCapacityMax>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>"#;
+        let params = XmpReader::new(xmp.as_bytes())
+            .unwrap()
+            .gain_map_params()
+            .unwrap()
+            .unwrap();
+        assert!(!params.is_single_channel());
+        assert_eq!(params.channels.map(|c| c.max), [1.0, 2.0, 3.0]);
+        // Defaults for absent fields.
+        assert_eq!(params.channels[2].min, 0.0);
+        assert_eq!(params.channels[2].gamma, 1.0);
+        assert_eq!(params.channels[2].alternate_offset, 1.0 / 64.0);
+    }
+
+    #[test]
+    fn custom_prefix_resolved() {
+        let xmp = r#"<rdf:Description xmlns:gm='http://ns.adobe.com/hdr-gain-map/1.0/'
+            gm:Version='1.0' gm:GainMapMax='1' gm:HDRCapacityMax='1'/>"#;
+        let reader = XmpReader::new(xmp.as_bytes()).unwrap();
+        assert_eq!(reader.prefix_for(HDRGM_NAMESPACE), Some("gm"));
+        assert_eq!(
+            reader.gain_map_params().unwrap().unwrap().channels[0].max,
+            1.0
+        );
+    }
+
+    #[test]
+    fn base_rendition_hdr_swaps_roles() {
+        let xmp = ULTRAHDR_ATTRS
+            .replace(r#"OffsetSDR="0""#, r#"OffsetSDR="0.25""#)
+            .replace(
+                r#"BaseRenditionIsHDR="False""#,
+                r#"BaseRenditionIsHDR="True""#,
+            );
+        let params = XmpReader::new(xmp.as_bytes())
+            .unwrap()
+            .gain_map_params()
+            .unwrap()
+            .unwrap();
+        assert!(params.backward_direction);
+        assert_eq!(params.base_hdr_headroom, 2.5);
+        assert_eq!(params.alternate_hdr_headroom, 0.0);
+        assert_eq!(params.channels[0].alternate_offset, 0.25);
+        assert_eq!(params.channels[0].base_offset, 0.0);
+    }
+
+    #[test]
+    fn missing_required_field() {
+        let xmp = ULTRAHDR_ATTRS.replace(r#"hdrgm:GainMapMax="2.5""#, "");
+        let err = XmpReader::new(xmp.as_bytes())
+            .unwrap()
+            .gain_map_params()
+            .unwrap_err();
+        assert_eq!(
+            err,
+            GainMapParseError::MissingXmpField {
+                field: "GainMapMax"
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_number_rejected() {
+        let xmp = ULTRAHDR_ATTRS.replace(r#"Gamma="1""#, r#"Gamma="one""#);
+        let err = XmpReader::new(xmp.as_bytes())
+            .unwrap()
+            .gain_map_params()
+            .unwrap_err();
+        assert_eq!(err, GainMapParseError::InvalidXmpValue { field: "Gamma" });
+    }
+
+    #[test]
+    fn invalid_params_rejected_by_validate() {
+        let xmp = ULTRAHDR_ATTRS.replace(r#"GainMapMin="-0.5""#, r#"GainMapMin="9""#);
+        let err = XmpReader::new(xmp.as_bytes())
+            .unwrap()
+            .gain_map_params()
+            .unwrap_err();
+        assert!(matches!(err, GainMapParseError::MinExceedsMax { .. }));
+    }
+
+    #[test]
+    fn two_item_seq_rejected() {
+        let xmp = r#"<rdf:Description xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+            xmlns:hdrgm="http://ns.adobe.com/hdr-gain-map/1.0/" hdrgm:Version="1.0"
+            hdrgm:HDRCapacityMax="1">
+            <hdrgm:GainMapMax><rdf:Seq><rdf:li>1</rdf:li><rdf:li>2</rdf:li></rdf:Seq></hdrgm:GainMapMax>
+            </rdf:Description>"#;
+        let err = XmpReader::new(xmp.as_bytes())
+            .unwrap()
+            .gain_map_params()
+            .unwrap_err();
+        assert_eq!(
+            err,
+            GainMapParseError::InvalidXmpValue {
+                field: "GainMapMax"
+            }
+        );
+    }
+
+    #[test]
+    fn no_gain_map() {
+        let xmp = r#"<rdf:Description xmlns:tiff="http://ns.adobe.com/tiff/1.0/">
+            <tiff:Orientation>8</tiff:Orientation></rdf:Description>"#;
+        let reader = XmpReader::new(xmp.as_bytes()).unwrap();
+        assert!(!reader.has_gain_map());
+        assert_eq!(reader.gain_map_params(), Ok(None));
+        assert_eq!(reader.orientation(), Some(Orientation::Rotate270));
+    }
+
+    #[test]
+    fn orientation_out_of_range() {
+        let xmp = r#"<rdf:Description xmlns:tiff="http://ns.adobe.com/tiff/1.0/"
+            tiff:Orientation="9"/>"#;
+        assert_eq!(XmpReader::new(xmp.as_bytes()).unwrap().orientation(), None);
+    }
+
+    #[test]
+    fn undeclared_namespace_ignored() {
+        // hdrgm: used without an xmlns declaration is not a property.
+        let xmp = r#"<rdf:Description hdrgm:Version="1.0" hdrgm:GainMapMax="1"/>"#;
+        let reader = XmpReader::new(xmp.as_bytes()).unwrap();
+        assert!(!reader.has_gain_map());
+    }
+
+    #[test]
+    fn longer_names_do_not_match() {
+        let xmp = r#"<rdf:Description xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+            xmp:CreateDateX="nope" xmp:CreateDate="2020-01-01"/>"#;
+        assert_eq!(
+            XmpReader::new(xmp.as_bytes()).unwrap().create_date(),
+            Some("2020-01-01")
+        );
+    }
+
+    #[test]
+    fn jpeg_app1_prefix_and_invalid_utf8() {
+        let mut app1 = JPEG_APP1_XMP_PREFIX.to_vec();
+        app1.extend_from_slice(ULTRAHDR_ATTRS.as_bytes());
+        assert!(XmpReader::new(&app1).unwrap().has_gain_map());
+        assert!(XmpReader::new(&[0xFF, 0xFE, 0x00]).is_none());
+    }
+
+    #[test]
+    fn truncated_packet_does_not_panic() {
+        for end in 0..ULTRAHDR_ATTRS.len() {
+            if let Some(reader) = XmpReader::new(&ULTRAHDR_ATTRS.as_bytes()[..end]) {
+                let _ = reader.gain_map_params();
+                let _ = reader.create_date();
+            }
+        }
+    }
+
+    #[test]
+    fn serialize_single_channel_roundtrip() {
+        let reader_params = XmpReader::new(ULTRAHDR_ATTRS.as_bytes())
+            .unwrap()
+            .gain_map_params()
+            .unwrap()
+            .unwrap();
+        let packet = serialize_hdrgm_xmp(&reader_params);
+        let text = core::str::from_utf8(&packet).unwrap();
+        assert!(text.starts_with("<?xpacket begin="));
+        assert!(text.ends_with("<?xpacket end=\"w\"?>"));
+        assert!(text.contains("hdrgm:Version=\"1.0\""));
+        let back = XmpReader::new(&packet).unwrap().gain_map_params().unwrap();
+        assert_eq!(back, Some(reader_params));
+    }
+
+    #[test]
+    fn serialize_multichannel_backward_roundtrip() {
+        let mut params = GainMapParams::default();
+        for (i, ch) in params.channels.iter_mut().enumerate() {
+            ch.min = -(i as f64) / 4.0;
+            ch.max = 1.0 + i as f64;
+            ch.gamma = 1.0 + i as f64 / 10.0;
+            ch.base_offset = 0.01 * i as f64;
+            ch.alternate_offset = 0.02;
+        }
+        params.base_hdr_headroom = 3.0;
+        params.alternate_hdr_headroom = 0.5;
+        params.backward_direction = true;
+
+        let packet = serialize_hdrgm_xmp(&params);
+        assert!(core::str::from_utf8(&packet).unwrap().contains("<rdf:Seq>"));
+        let back = XmpReader::new(&packet).unwrap().gain_map_params().unwrap();
+        assert_eq!(back, Some(params));
+    }
+}
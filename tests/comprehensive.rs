@@ -12,7 +12,7 @@ use mock_anim::{MockDecoderConfig, MockEncoderConfig};
 
 use zencodec::decode::{
     AnimationFrameDecoder, Decode, DecodeCapabilities, DecodeJob, DecodeOutput, DecodePolicy,
-    DecoderConfig, DynDecoderConfig, OutputInfo, StreamingDecode,
+    DecoderConfig, DynDecoderConfig, MultiPageDecoder, OutputInfo, StreamingDecode,
 };
 use zencodec::encode::{
     AnimationFrameEncoder, DynEncoderConfig, EncodeCapabilities, EncodeJob, EncodeOutput,
@@ -2158,3 +2158,650 @@ fn extensions_wrong_type_returns_none() {
     let ext = job.extensions().unwrap();
     assert!(ext.downcast_ref::<u32>().is_none());
 }
+
+// =========================================================================
+// Multi-page decode
+// =========================================================================
+
+#[test]
+fn multi_page_decode_by_index() {
+    let p0 = make_rgb8_buffer(3, 2);
+    let mut p1 = make_rgb8_buffer(3, 2);
+    p1.as_slice_mut().row_mut(0)[0] = 0xAB;
+    let data = encode_animation_from_slices(&[(&p0, 0), (&p1, 0)]);
+
+    assert!(<MockDecoderConfig as DecoderConfig>::capabilities().multi_image());
+    let mut dec = MockDecoderConfig
+        .job()
+        .multi_page_decoder(Cow::Borrowed(&data))
+        .unwrap();
+
+    assert_eq!(dec.page_count(), Some(2));
+    assert_eq!(dec.info().sequence.count(), Some(2));
+    assert!(dec.info().sequence.random_access());
+
+    // Random access: page 1 before page 0
+    let out1 = dec.decode_page(1, &[], None).unwrap();
+    assert_eq!(out1.pixels().row(0), p1.as_slice().row(0));
+    let out0 = dec.decode_page(0, &[], None).unwrap();
+    assert_eq!(out0.pixels().row(0), p0.as_slice().row(0));
+
+    let info = dec.page_info(1).unwrap();
+    assert_eq!((info.width, info.height), (3, 2));
+    assert!(dec.page_info(2).is_err());
+    assert!(dec.decode_page(2, &[], None).is_err());
+}
+
+#[test]
+fn multi_page_decode_to_sink_default() {
+    let page = make_rgba8_buffer(2, 3);
+    let data = encode_animation_from_slices(&[(&page, 0)]);
+    let mut dec = MockDecoderConfig
+        .job()
+        .multi_page_decoder(Cow::Borrowed(&data))
+        .unwrap();
+
+    struct VecSink(Vec<u8>);
+    impl zencodec::decode::DecodeRowSink for VecSink {
+        fn provide_next_buffer(
+            &mut self,
+            _y: u32,
+            height: u32,
+            width: u32,
+            descriptor: PixelDescriptor,
+        ) -> Result<zenpixels::PixelSliceMut<'_>, zencodec::decode::SinkError> {
+            let stride = width as usize * descriptor.bytes_per_pixel();
+            self.0.resize(height as usize * stride, 0);
+            Ok(
+                zenpixels::PixelSliceMut::new(&mut self.0, width, height, stride, descriptor)
+                    .unwrap(),
+            )
+        }
+    }
+
+    let mut sink = VecSink(Vec::new());
+    let info = dec.decode_page_to_sink(0, &[], None, &mut sink).unwrap();
+    assert_eq!((info.width, info.height), (2, 3));
+    assert_eq!(info.native_format, PixelDescriptor::RGBA8_SRGB);
+    assert_eq!(&sink.0[..8], page.as_slice().row(0));
+}
+
+#[test]
+fn multi_page_decode_stop_token() {
+    let data = encode_animation(&[(make_rgb8_buffer(2, 2), 0)]);
+    let mut dec = MockDecoderConfig
+        .job()
+        .multi_page_decoder(Cow::Borrowed(&data))
+        .unwrap();
+
+    struct AlwaysStop;
+    impl enough::Stop for AlwaysStop {
+        fn check(&self) -> Result<(), enough::StopReason> {
+            Err(enough::StopReason::Cancelled)
+        }
+    }
+
+    let err = dec.decode_page(0, &[], Some(&AlwaysStop)).unwrap_err();
+    assert!(matches!(err, mock_anim::MockError::Cancelled(_)));
+}
+
+#[test]
+fn dyn_multi_page_decoder() {
+    let data = encode_animation(&[(make_rgb8_buffer(2, 2), 0), (make_rgb8_buffer(2, 2), 0)]);
+
+    let dyn_config: &dyn DynDecoderConfig = &MockDecoderConfig;
+    let mut dec = dyn_config
+        .dyn_job()
+        .into_multi_page_decoder(Cow::Borrowed(&data))
+        .unwrap();
+    assert_eq!(dec.page_count(), Some(2));
+    let out = dec.decode_page(1, &[], None).unwrap();
+    assert_eq!(out.width(), 2);
+    assert!(dec.page_info(5).is_err());
+
+    // Concrete job convenience method
+    let mut dec = MockDecoderConfig
+        .job()
+        .dyn_multi_page_decoder(Cow::Borrowed(&data))
+        .unwrap();
+    assert!(dec.decode_page(0, &[], None).is_ok());
+    assert!(format!("{dec:?}").contains("DynMultiPageDecoder"));
+}
+
+// =========================================================================
+// Reader-based input sources
+// =========================================================================
+
+/// Sequential reader that returns at most 5 bytes per call.
+struct ChunkedReader {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl zencodec::decode::ByteSource for ChunkedReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, zencodec::decode::SourceError> {
+        let n = buf.len().min(5).min(self.data.len() - self.pos);
+        buf[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[test]
+fn decode_from_sequential_source() {
+    use zencodec::decode::DecodeSource;
+
+    let buf = make_rgb8_buffer(4, 3);
+    let data = encode_single_frame(&buf);
+    let reader = ChunkedReader {
+        data: data.clone(),
+        pos: 0,
+    };
+
+    let output = MockDecoderConfig
+        .job()
+        .decoder_from_source(DecodeSource::sequential(reader), &[])
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!(output.width(), 4);
+    assert_eq!(output.pixels().row(2), buf.as_slice().row(2));
+}
+
+#[test]
+fn dyn_decode_from_random_access_source() {
+    use zencodec::decode::DecodeSource;
+
+    let frames = [(make_rgb8_buffer(2, 2), 10), (make_rgb8_buffer(2, 2), 20)];
+    let data = encode_animation(&frames);
+    let dyn_config: &dyn DynDecoderConfig = &MockDecoderConfig;
+
+    let output = dyn_config
+        .dyn_job()
+        .into_decoder_from_source(DecodeSource::random_access(data.clone()), &[])
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!(output.width(), 2);
+
+    let anim = dyn_config
+        .dyn_job()
+        .into_animation_frame_decoder_from_source(DecodeSource::random_access(data.clone()), &[])
+        .unwrap();
+    assert_eq!(anim.frame_count(), Some(2));
+
+    let mut pages = dyn_config
+        .dyn_job()
+        .into_multi_page_decoder_from_source(DecodeSource::from(&data[..]))
+        .unwrap();
+    assert_eq!(pages.page_count(), Some(2));
+    assert!(pages.decode_page(1, &[], None).is_ok());
+}
+
+#[test]
+fn source_read_error_reaches_caller() {
+    use zencodec::decode::{ByteSource, DecodeSource, SourceError};
+
+    struct Failing;
+    impl ByteSource for Failing {
+        fn read(&mut self, _buf: &mut [u8]) -> Result<usize, SourceError> {
+            Err(Box::new(LimitExceeded::InputSize { actual: 9, max: 1 }))
+        }
+    }
+
+    let err = MockDecoderConfig
+        .job()
+        .decoder_from_source(DecodeSource::sequential(Failing), &[])
+        .unwrap_err();
+    assert!(err.limit_exceeded().is_some());
+
+    let dyn_config: &dyn DynDecoderConfig = &MockDecoderConfig;
+    let err = dyn_config
+        .dyn_job()
+        .into_streaming_decoder_from_source(DecodeSource::sequential(Failing), &[])
+        .unwrap_err();
+    assert!(err.find_cause::<LimitExceeded>().is_some());
+}
+
+#[test]
+fn source_buffering_honors_job_input_limit() {
+    use zencodec::decode::DecodeSource;
+
+    let data = encode_single_frame(&make_rgb8_buffer(16, 16));
+    let limits = ResourceLimits::none().with_max_input_bytes(64);
+
+    // A sequential reader has no length hint, so the limit trips mid-read.
+    let reader = ChunkedReader {
+        data: data.clone(),
+        pos: 0,
+    };
+    let err = MockDecoderConfig
+        .job()
+        .with_limits(limits)
+        .decoder_from_source(DecodeSource::sequential(reader), &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.limit_exceeded(),
+        Some(LimitExceeded::InputSize { max: 64, .. })
+    ));
+
+    // The same limit applies through dyn dispatch.
+    let dyn_config: &dyn DynDecoderConfig = &MockDecoderConfig;
+    let mut job = dyn_config.dyn_job();
+    job.set_limits(limits);
+    let err = job
+        .into_decoder_from_source(DecodeSource::random_access(data.clone()), &[])
+        .unwrap_err();
+    assert!(err.limit_exceeded().is_some());
+
+    // Within the limit, decoding succeeds.
+    let output = MockDecoderConfig
+        .job()
+        .with_limits(ResourceLimits::none().with_max_input_bytes(data.len() as u64))
+        .decoder_from_source(DecodeSource::from(data), &[])
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!(output.width(), 16);
+}
+
+// =========================================================================
+// Incremental probe
+// =========================================================================
+
+#[test]
+fn probe_incremental_grows_prefix() {
+    use zencodec::decode::ProbeProgress;
+
+    let data = encode_single_frame(&make_rgb8_buffer(5, 3));
+    let job = MockDecoderConfig.job();
+
+    let mut have = 2usize;
+    let info = loop {
+        match job.probe_incremental(&data[..have]).unwrap() {
+            ProbeProgress::Complete(info) => break info,
+            ProbeProgress::NeedMoreData { at_least, .. } => {
+                assert!(at_least as usize > have);
+                have = at_least as usize;
+            }
+            _ => unreachable!(),
+        }
+    };
+    assert_eq!((info.width, info.height), (5, 3));
+    assert!(have < data.len());
+
+    // Garbage is an error, not a request for more data
+    assert!(job.probe_incremental(b"XX").is_err());
+}
+
+#[test]
+fn dyn_probe_incremental() {
+    let data = encode_single_frame(&make_rgb8_buffer(2, 2));
+    let dyn_config: &dyn DynDecoderConfig = &MockDecoderConfig;
+    let job = dyn_config.dyn_job();
+
+    let partial = job.probe_incremental(&data[..6]).unwrap();
+    assert!(!partial.is_complete());
+    assert_eq!(partial.additional_bytes(6), 11);
+
+    let full = job.probe_incremental(&data).unwrap();
+    assert_eq!(full.info().unwrap().width, 2);
+}
+
+// =========================================================================
+// Transcode
+// =========================================================================
+
+fn transcode_mock(
+    data: &[u8],
+    stop: zencodec::StopToken,
+) -> Result<EncodeOutput, zencodec::decode::BoxedError> {
+    zencodec::transcode(
+        &MockDecoderConfig,
+        &MockEncoderConfig::new(),
+        data,
+        ResourceLimits::none(),
+        DecodePolicy::default(),
+        EncodePolicy::default(),
+        stop,
+    )
+}
+
+#[test]
+fn transcode_streams_rows_when_both_sides_support_it() {
+    assert!(<MockDecoderConfig as DecoderConfig>::capabilities().streaming());
+    assert!(<MockEncoderConfig as EncoderConfig>::capabilities().push_rows());
+
+    let buf = make_rgb8_buffer(5, 9);
+    let data = encode_single_frame(&buf);
+    let output = transcode_mock(&data, zencodec::StopToken::new(zencodec::Unstoppable)).unwrap();
+
+    let decoded = MockDecoderConfig
+        .job()
+        .decoder(Cow::Borrowed(output.data()), &[])
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (5, 9));
+    for y in 0..9 {
+        assert_eq!(decoded.pixels().row(y), buf.as_slice().row(y));
+    }
+}
+
+/// Mock encoder that only claims Gray8, so RGB strips can't be matched.
+struct GrayOnlyEncoder(MockEncoderConfig);
+
+impl DynEncoderConfig for GrayOnlyEncoder {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn format(&self) -> ImageFormat {
+        DynEncoderConfig::format(&self.0)
+    }
+
+    fn supported_descriptors(&self) -> &'static [PixelDescriptor] {
+        &[PixelDescriptor::GRAY8_SRGB]
+    }
+
+    fn capabilities(&self) -> &'static EncodeCapabilities {
+        DynEncoderConfig::capabilities(&self.0)
+    }
+
+    fn dyn_job(&self) -> Box<dyn zencodec::encode::DynEncodeJob + 'static> {
+        self.0.dyn_job()
+    }
+}
+
+#[test]
+fn transcode_streaming_checks_descriptor() {
+    let data = encode_single_frame(&make_rgb8_buffer(4, 4));
+    let err = zencodec::transcode(
+        &MockDecoderConfig,
+        &GrayOnlyEncoder(MockEncoderConfig::new()),
+        &data,
+        ResourceLimits::none(),
+        DecodePolicy::default(),
+        EncodePolicy::default(),
+        zencodec::StopToken::new(zencodec::Unstoppable),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.unsupported_operation(),
+        Some(&UnsupportedOperation::PixelFormat)
+    );
+}
+
+#[test]
+fn transcode_honors_stop_token() {
+    use almost_enough::Stopper;
+
+    let data = encode_single_frame(&make_rgb8_buffer(4, 4));
+    let err = transcode_mock(&data, zencodec::StopToken::new(Stopper::cancelled())).unwrap_err();
+    assert!(err.to_string().contains("cancel"), "{err}");
+}
+
+#[test]
+fn transcode_reports_decode_limits() {
+    let data = encode_single_frame(&make_rgb8_buffer(8, 8));
+    let err = zencodec::transcode(
+        &MockDecoderConfig,
+        &MockEncoderConfig::new(),
+        &data,
+        ResourceLimits::none().with_max_width(4),
+        DecodePolicy::default(),
+        EncodePolicy::default(),
+        zencodec::StopToken::new(zencodec::Unstoppable),
+    )
+    .unwrap_err();
+    assert!(err.limit_exceeded().is_some());
+}
+
+#[test]
+fn encoder_row_sink_connects_push_decoder_to_encoder() {
+    use zencodec::decode::EncoderRowSink;
+
+    let buf = make_rgb8_buffer(6, 7);
+    let data = encode_single_frame(&buf);
+
+    let encoder = MockEncoderConfig::new().job().encoder().unwrap();
+    let mut sink = EncoderRowSink::new(encoder);
+    assert_eq!(sink.strip_height(), 4);
+    MockDecoderConfig
+        .job()
+        .push_decoder(Cow::Borrowed(&data), &mut sink, &[])
+        .unwrap();
+    let output = sink.finish_encode().unwrap();
+    assert_eq!(output.data(), data.as_slice());
+}
+
+// =========================================================================
+// Streaming / push bridges
+// =========================================================================
+
+use mock_anim::MockError;
+use zencodec::decode::{BufferSink, DecodeRowSink, SinkError};
+use zencodec::helpers::{PushStreamingDecoder, stream_decode_to_sink};
+
+/// Records strip positions while assembling the image.
+#[derive(Default)]
+struct StripLog {
+    image: BufferSink,
+    strips: Vec<(u32, u32)>,
+}
+
+impl DecodeRowSink for StripLog {
+    fn begin(&mut self, w: u32, h: u32, desc: PixelDescriptor) -> Result<(), SinkError> {
+        self.image.begin(w, h, desc)
+    }
+
+    fn provide_next_buffer(
+        &mut self,
+        y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<zenpixels::PixelSliceMut<'_>, SinkError> {
+        self.strips.push((y, height));
+        self.image.provide_next_buffer(y, height, width, descriptor)
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.image.finish()
+    }
+}
+
+#[test]
+fn stream_decode_to_sink_forwards_each_batch() {
+    let buf = make_rgb8_buffer(4, 3);
+    let data = encode_single_frame(&buf);
+
+    let mut sink = StripLog::default();
+    let info = stream_decode_to_sink(
+        MockDecoderConfig.job(),
+        Cow::Borrowed(&data),
+        &mut sink,
+        &[],
+        MockError::Sink,
+    )
+    .unwrap();
+
+    assert_eq!((info.width, info.height), (4, 3));
+    assert_eq!(info.native_format, PixelDescriptor::RGB8_SRGB);
+    // The mock streams one row per batch.
+    assert_eq!(sink.strips, [(0, 1), (1, 1), (2, 1)]);
+    assert!(sink.image.is_finished());
+    let out = sink.image.into_buffer().unwrap();
+    assert_eq!(
+        out.copy_to_contiguous_bytes(),
+        buf.copy_to_contiguous_bytes()
+    );
+}
+
+#[test]
+fn stream_decode_to_sink_propagates_errors() {
+    let buf = make_rgb8_buffer(4, 3);
+    let mut data = encode_single_frame(&buf);
+    data.truncate(data.len() - 4);
+
+    let mut sink = StripLog::default();
+    let err = stream_decode_to_sink(
+        MockDecoderConfig.job(),
+        Cow::Borrowed(&data),
+        &mut sink,
+        &[],
+        MockError::Sink,
+    )
+    .unwrap_err();
+    assert!(matches!(err, MockError::InvalidData(_)));
+    assert!(!sink.image.is_finished());
+}
+
+#[test]
+fn push_streaming_decoder_yields_queued_strips() {
+    let buf = make_rgb8_buffer(4, 3);
+    let data = encode_single_frame(&buf);
+
+    let mut dec =
+        PushStreamingDecoder::new(MockDecoderConfig.job(), Cow::Borrowed(&data), &[]).unwrap();
+    assert_eq!((dec.info().width, dec.info().height), (4, 3));
+    // The mock's push decoder writes the whole image as one strip.
+    assert_eq!(dec.queued(), 1);
+
+    let (y, strip) = dec.next_batch().unwrap().unwrap();
+    assert_eq!((y, strip.width(), strip.rows()), (0, 4, 3));
+    for row in 0..3 {
+        assert_eq!(strip.row(row), buf.as_slice().row(row));
+    }
+    assert!(dec.next_batch().unwrap().is_none());
+    assert_eq!(dec.queued(), 0);
+}
+
+#[test]
+fn push_streaming_decoder_propagates_errors() {
+    let buf = make_rgb8_buffer(4, 3);
+    let mut data = encode_single_frame(&buf);
+    data.truncate(data.len() - 4);
+
+    let err =
+        PushStreamingDecoder::new(MockDecoderConfig.job(), Cow::Borrowed(&data), &[]).unwrap_err();
+    assert!(matches!(err, MockError::InvalidData(_)));
+}
+
+// =========================================================================
+// Error classification
+// =========================================================================
+
+use zencodec::CodecErrorKind;
+use zencodec::decode::{ByteSource, DecodeSource, SourceError};
+
+/// Status an HTTP front end would map a codec error to.
+fn http_status(err: &(dyn std::error::Error + 'static)) -> u16 {
+    match err.kind() {
+        Some(CodecErrorKind::Truncated | CodecErrorKind::Corrupt) => 400,
+        Some(CodecErrorKind::LimitExceeded) => 413,
+        Some(CodecErrorKind::Unsupported) => 415,
+        Some(CodecErrorKind::Cancelled | CodecErrorKind::TimedOut) => 499,
+        _ => 500,
+    }
+}
+
+#[test]
+fn kind_classifies_codec_errors() {
+    let mut data = encode_single_frame(&make_rgb8_buffer(4, 2));
+
+    let err = MockDecoderConfig
+        .job()
+        .with_limits(ResourceLimits::none().with_max_width(2))
+        .decoder(Cow::Borrowed(&data), &[])
+        .unwrap_err();
+    assert_eq!(http_status(&err), 413);
+
+    data.truncate(data.len() - 1);
+    let err = MockDecoderConfig
+        .job()
+        .decoder(Cow::Borrowed(&data), &[])
+        .unwrap()
+        .decode()
+        .unwrap_err();
+    assert_eq!(err.kind(), Some(CodecErrorKind::Corrupt));
+    assert_eq!(http_status(&err), 400);
+
+    // Classification survives dyn dispatch.
+    let dyn_config: &dyn DynDecoderConfig = &MockDecoderConfig;
+    let err = dyn_config
+        .dyn_job()
+        .into_decoder(Cow::Borrowed(&data), &[])
+        .unwrap()
+        .decode()
+        .unwrap_err();
+    assert_eq!(err.kind(), Some(CodecErrorKind::Corrupt));
+}
+
+#[test]
+fn kind_and_stop_reason_for_cancellation() {
+    let data = encode_animation(&[(make_rgb8_buffer(2, 2), 0)]);
+    let mut dec = MockDecoderConfig
+        .job()
+        .multi_page_decoder(Cow::Borrowed(&data))
+        .unwrap();
+
+    struct TimedOut;
+    impl enough::Stop for TimedOut {
+        fn check(&self) -> Result<(), enough::StopReason> {
+            Err(enough::StopReason::TimedOut)
+        }
+    }
+
+    let err = dec.decode_page(0, &[], Some(&TimedOut)).unwrap_err();
+    assert_eq!(err.kind(), Some(CodecErrorKind::TimedOut));
+    assert_eq!(err.stop_reason(), Some(enough::StopReason::TimedOut));
+    assert_eq!(http_status(&err), 499);
+}
+
+#[test]
+fn helpers_mark_sink_failures() {
+    let data = encode_single_frame(&make_rgb8_buffer(4, 2));
+
+    struct Rejecting;
+    impl DecodeRowSink for Rejecting {
+        fn begin(&mut self, _w: u32, _h: u32, _d: PixelDescriptor) -> Result<(), SinkError> {
+            Err("disk full".into())
+        }
+        fn provide_next_buffer(
+            &mut self,
+            _y: u32,
+            _h: u32,
+            _w: u32,
+            _d: PixelDescriptor,
+        ) -> Result<zenpixels::PixelSliceMut<'_>, SinkError> {
+            unreachable!()
+        }
+    }
+
+    let err = MockDecoderConfig
+        .job()
+        .push_decoder(Cow::Borrowed(&data), &mut Rejecting, &[])
+        .unwrap_err();
+    assert_eq!(err.kind(), Some(CodecErrorKind::Sink));
+    assert_eq!(err.stop_reason(), None);
+    assert_eq!(err.to_string(), "mock: sink: disk full");
+    assert_eq!(http_status(&err), 500);
+}
+
+#[test]
+fn helpers_mark_source_failures() {
+    struct Broken;
+    impl ByteSource for Broken {
+        fn read(&mut self, _buf: &mut [u8]) -> Result<usize, SourceError> {
+            Err("connection reset".into())
+        }
+    }
+
+    let err = MockDecoderConfig
+        .job()
+        .decoder_from_source(DecodeSource::sequential(Broken), &[])
+        .unwrap_err();
+    assert_eq!(err.kind(), Some(CodecErrorKind::Io));
+    assert_eq!(err.to_string(), "connection reset");
+}
@@ -0,0 +1,318 @@
+//! Integration tests for `FallbackDecoderConfig` retry classification and
+//! backend attribution.
+
+#[allow(dead_code)]
+mod mock_anim;
+mod pnm;
+mod stub;
+
+use std::borrow::Cow;
+
+use mock_anim::{MockDecoderConfig, MockEncoderConfig};
+use pnm::{PnmDecoderConfig, PnmEncoderConfig};
+use stub::{FailingDecoderConfig, Failure, StubError};
+
+use almost_enough::Stopper;
+use zencodec::decode::{
+    DecodeOutput, DecodeRowSink, DecodedBy, DynDecoderConfig, FailureKind, FallbackConfigError,
+    FallbackDecoderConfig, RetryPolicy, SinkError,
+};
+use zencodec::encode::{EncodeJob, Encoder, EncoderConfig};
+use zencodec::{
+    CodecErrorExt, CodecErrorKind, CodecRegistry, ImageFormat, ResourceLimits, StopToken,
+    UnsupportedOperation,
+};
+use zenpixels::{PixelBuffer, PixelDescriptor, PixelSliceMut};
+
+fn pnm_bytes() -> Vec<u8> {
+    let data = vec![
+        255, 0, 0, 0, 255, 0, //
+        0, 0, 255, 255, 255, 255,
+    ];
+    let pixels = PixelBuffer::from_vec(data, 2, 2, PixelDescriptor::RGB8_SRGB).unwrap();
+    PnmEncoderConfig::new()
+        .job()
+        .encoder()
+        .unwrap()
+        .encode(pixels.as_slice())
+        .unwrap()
+        .into_vec()
+}
+
+fn chain(primary: Failure) -> FallbackDecoderConfig {
+    FallbackDecoderConfig::new("stub", FailingDecoderConfig::new(primary))
+        .with_fallback("pnm", PnmDecoderConfig::new())
+        .unwrap()
+}
+
+fn decode(
+    config: &FallbackDecoderConfig,
+    data: &[u8],
+) -> Result<DecodeOutput, zencodec::decode::BoxedError> {
+    config
+        .dyn_job()
+        .into_decoder(Cow::Borrowed(data), &[])?
+        .decode()
+}
+
+#[test]
+fn primary_success_is_attributed() {
+    let config = FallbackDecoderConfig::new("pnm", PnmDecoderConfig::new())
+        .with_fallback("stub", FailingDecoderConfig::new(Failure::Corrupt))
+        .unwrap();
+    let output = decode(&config, &pnm_bytes()).unwrap();
+    let by = output.extras::<DecodedBy>().unwrap();
+    assert_eq!(by.index, 0);
+    assert_eq!(by.name, "pnm");
+    assert!(by.rejected.is_empty());
+}
+
+#[test]
+fn falls_back_on_unsupported() {
+    let config = chain(Failure::Unsupported(UnsupportedOperation::PixelFormat));
+    let output = decode(&config, &pnm_bytes()).unwrap();
+    assert_eq!(output.width(), 2);
+    let by = output.extras::<DecodedBy>().unwrap();
+    assert_eq!(by.index, 1);
+    assert_eq!(by.name, "pnm");
+    assert_eq!(by.rejected, vec![("stub", FailureKind::Unsupported)]);
+}
+
+#[test]
+fn falls_back_on_corrupt() {
+    let output = decode(&chain(Failure::Corrupt), &pnm_bytes()).unwrap();
+    let by = output.extras::<DecodedBy>().unwrap();
+    assert_eq!(by.rejected, vec![("stub", FailureKind::Corrupt)]);
+}
+
+#[test]
+fn limit_exceeded_not_retried_by_default() {
+    let err = decode(&chain(Failure::Limit), &pnm_bytes()).unwrap_err();
+    assert!(err.limit_exceeded().is_some());
+}
+
+#[test]
+fn limit_exceeded_retried_when_enabled() {
+    let config =
+        chain(Failure::Limit).with_retry_policy(RetryPolicy::new().with_limit_exceeded(true));
+    let output = decode(&config, &pnm_bytes()).unwrap();
+    let by = output.extras::<DecodedBy>().unwrap();
+    assert_eq!(by.rejected, vec![("stub", FailureKind::LimitExceeded)]);
+}
+
+#[test]
+fn retry_policy_none_returns_primary_error() {
+    let config = chain(Failure::Unsupported(UnsupportedOperation::PixelFormat))
+        .with_retry_policy(RetryPolicy::none());
+    let err = decode(&config, &pnm_bytes()).unwrap_err();
+    assert!(err.unsupported_operation().is_some());
+}
+
+#[test]
+fn cancellation_not_retried() {
+    let config = chain(Failure::Cancelled);
+    let mut job = config.dyn_job();
+    job.set_stop(StopToken::new(Stopper::cancelled()));
+    let data = pnm_bytes();
+    let err = job
+        .into_decoder(Cow::Borrowed(&data), &[])
+        .unwrap()
+        .decode()
+        .unwrap_err();
+    assert!(matches!(
+        err.find_cause::<StubError>(),
+        Some(StubError::Cancelled(_))
+    ));
+}
+
+#[test]
+fn io_failure_not_retried() {
+    let err = decode(&chain(Failure::Kind(CodecErrorKind::Io)), &pnm_bytes()).unwrap_err();
+    assert!(matches!(
+        err.find_cause::<StubError>(),
+        Some(StubError::Kind(_))
+    ));
+    assert_eq!(err.kind(), Some(CodecErrorKind::Io));
+}
+
+#[test]
+fn last_backend_error_is_returned() {
+    let config = FallbackDecoderConfig::new(
+        "a",
+        FailingDecoderConfig::new(Failure::Unsupported(UnsupportedOperation::PixelFormat)),
+    )
+    .with_fallback("b", FailingDecoderConfig::new(Failure::Corrupt))
+    .unwrap();
+    let err = decode(&config, &pnm_bytes()).unwrap_err();
+    assert!(matches!(
+        err.find_cause::<StubError>(),
+        Some(StubError::Corrupt)
+    ));
+}
+
+#[test]
+fn probe_falls_back() {
+    let info = chain(Failure::Corrupt)
+        .dyn_job()
+        .probe(&pnm_bytes())
+        .unwrap();
+    assert_eq!((info.width, info.height), (2, 2));
+}
+
+#[test]
+fn owned_input_reaches_every_backend() {
+    let config = chain(Failure::Corrupt);
+    let output = config
+        .dyn_job()
+        .into_decoder(Cow::Owned(pnm_bytes()), &[])
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!(output.extras::<DecodedBy>().unwrap().index, 1);
+}
+
+fn mock_bytes() -> Vec<u8> {
+    let pixels =
+        PixelBuffer::from_vec(vec![7; 2 * 3 * 3], 2, 3, PixelDescriptor::RGB8_SRGB).unwrap();
+    MockEncoderConfig::new()
+        .job()
+        .encoder()
+        .unwrap()
+        .encode(pixels.as_slice())
+        .unwrap()
+        .into_vec()
+}
+
+fn count_rows(config: &FallbackDecoderConfig, data: Vec<u8>) -> u32 {
+    let mut stream = config
+        .dyn_job()
+        .into_streaming_decoder(Cow::Owned(data), &[])
+        .unwrap();
+    let mut rows = 0;
+    while let Some((_, slice)) = stream.next_batch().unwrap() {
+        rows += slice.rows();
+    }
+    rows
+}
+
+#[test]
+fn owned_input_streams_from_winning_backend() {
+    let config = FallbackDecoderConfig::new("mock", MockDecoderConfig)
+        .with_fallback("stub", FailingDecoderConfig::new(Failure::Corrupt))
+        .unwrap();
+    assert_eq!(count_rows(&config, mock_bytes()), 3);
+}
+
+#[test]
+fn owned_input_streams_from_last_backend() {
+    let config = FallbackDecoderConfig::new("stub", FailingDecoderConfig::new(Failure::Corrupt))
+        .with_fallback("mock", MockDecoderConfig)
+        .unwrap();
+    assert_eq!(count_rows(&config, mock_bytes()), 3);
+}
+
+struct CollectSink {
+    buf: Vec<u8>,
+    begun: u32,
+}
+
+impl DecodeRowSink for CollectSink {
+    fn begin(&mut self, _w: u32, _h: u32, _d: PixelDescriptor) -> Result<(), SinkError> {
+        self.begun += 1;
+        Ok(())
+    }
+
+    fn provide_next_buffer(
+        &mut self,
+        _y: u32,
+        height: u32,
+        width: u32,
+        descriptor: PixelDescriptor,
+    ) -> Result<PixelSliceMut<'_>, SinkError> {
+        let stride = width as usize * descriptor.bytes_per_pixel();
+        self.buf.resize(height as usize * stride, 0);
+        Ok(PixelSliceMut::new(&mut self.buf, width, height, stride, descriptor).unwrap())
+    }
+}
+
+#[test]
+fn push_decode_falls_back_before_sink_is_touched() {
+    let mut sink = CollectSink {
+        buf: Vec::new(),
+        begun: 0,
+    };
+    let data = pnm_bytes();
+    let info = chain(Failure::Corrupt)
+        .dyn_job()
+        .push_decode(Cow::Borrowed(&data), &mut sink, &[])
+        .unwrap();
+    assert_eq!(info.width, 2);
+    assert_eq!(sink.begun, 1);
+}
+
+#[test]
+fn push_decode_does_not_fall_back_after_sink_is_touched() {
+    let config = FallbackDecoderConfig::new(
+        "stub",
+        FailingDecoderConfig::new(Failure::Corrupt).touching_sink(),
+    )
+    .with_fallback("pnm", PnmDecoderConfig::new())
+    .unwrap();
+    let mut sink = CollectSink {
+        buf: Vec::new(),
+        begun: 0,
+    };
+    let data = pnm_bytes();
+    let err = config
+        .dyn_job()
+        .push_decode(Cow::Borrowed(&data), &mut sink, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.find_cause::<StubError>(),
+        Some(StubError::Corrupt)
+    ));
+    assert_eq!(sink.begun, 1);
+}
+
+#[test]
+fn fallback_chain_registers_in_registry() {
+    let codecs = CodecRegistry::new().with_decoder(chain(Failure::Corrupt), 0);
+    let output = codecs
+        .decode_any(&pnm_bytes(), ResourceLimits::none())
+        .unwrap();
+    assert_eq!(output.extras::<DecodedBy>().unwrap().name, "pnm");
+}
+
+/// Wraps a config but reports a format no other test backend decodes.
+struct ForeignFormat(PnmDecoderConfig);
+
+impl DynDecoderConfig for ForeignFormat {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn formats(&self) -> &'static [ImageFormat] {
+        &[ImageFormat::Gif]
+    }
+
+    fn supported_descriptors(&self) -> &'static [PixelDescriptor] {
+        self.0.supported_descriptors()
+    }
+
+    fn capabilities(&self) -> &'static zencodec::decode::DecodeCapabilities {
+        self.0.capabilities()
+    }
+
+    fn dyn_job(&self) -> Box<dyn zencodec::decode::DynDecodeJob<'_> + '_> {
+        self.0.dyn_job()
+    }
+}
+
+#[test]
+fn fallback_sharing_no_format_is_rejected() {
+    let err = FallbackDecoderConfig::new("pnm", PnmDecoderConfig::new())
+        .with_fallback("gif", ForeignFormat(PnmDecoderConfig::new()))
+        .err()
+        .unwrap();
+    assert_eq!(err, FallbackConfigError::NoSharedFormat { name: "gif" });
+}
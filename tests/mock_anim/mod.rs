@@ -4,6 +4,7 @@
 //! - One-shot decode (Decode)
 //! - Streaming decode (StreamingDecode)
 //! - Full-frame animation decode (AnimationFrameDecoder)
+//! - Multi-page decode (MultiPageDecoder), treating each frame as a page
 //! - One-shot encode (Encoder) with push_rows + encode_from
 //! - Full-frame animation encode (AnimationFrameEncoder)
 //!
@@ -12,15 +13,15 @@
 use std::borrow::Cow;
 
 use zencodec::decode::{
-    AnimationFrameDecoder, DecodeCapabilities, DecodeJob, DecodeOutput, DecoderConfig, OutputInfo,
-    StreamingDecode,
+    AnimationFrameDecoder, DecodeCapabilities, DecodeJob, DecodeOutput, DecoderConfig,
+    MultiPageDecoder, OutputInfo, ProbeProgress, StreamingDecode,
 };
 use zencodec::encode::{
     AnimationFrameEncoder, EncodeCapabilities, EncodeJob, EncodeOutput, Encoder, EncoderConfig,
 };
 use zencodec::{
-    AnimationFrame, ImageFormat, ImageInfo, ImageSequence, Metadata, ResourceLimits,
-    UnsupportedOperation,
+    AnimationFrame, CodecErrorKind, ImageFormat, ImageInfo, ImageSequence, Metadata,
+    ResourceLimits, UnsupportedOperation,
 };
 
 use enough::{Stop, StopReason};
@@ -57,8 +58,9 @@ impl std::error::Error for MockError {
         match self {
             Self::Unsupported(e) => Some(e),
             Self::LimitExceeded(e) => Some(e),
+            Self::InvalidData(_) => Some(&CodecErrorKind::Corrupt),
+            Self::Cancelled(r) => Some(CodecErrorKind::for_stop(*r)),
             Self::Sink(e) => Some(e.as_ref()),
-            _ => None,
         }
     }
 }
@@ -146,6 +148,7 @@ pub struct MockDecoderConfig;
 static MOCK_DECODE_CAPS: DecodeCapabilities = DecodeCapabilities::new()
     .with_cheap_probe(true)
     .with_animation(true)
+    .with_multi_image(true)
     .with_streaming(true)
     .with_native_alpha(true)
     .with_stop(true);
@@ -195,6 +198,7 @@ impl<'a> DecodeJob<'a> for MockDecodeJob {
     type Dec = MockDec<'a>;
     type StreamDec = MockStreamDec<'a>;
     type AnimationFrameDec = MockAnimationFrameDec;
+    type MultiPageDec = MockMultiPageDec<'a>;
 
     fn with_stop(mut self, stop: zencodec::StopToken) -> Self {
         self.stop = Some(stop);
@@ -234,6 +238,10 @@ impl<'a> DecodeJob<'a> for MockDecodeJob {
         Some(&mut self.ext)
     }
 
+    fn limits(&self) -> &ResourceLimits {
+        &self.limits
+    }
+
     fn probe(&self, data: &[u8]) -> Result<ImageInfo, MockError> {
         let (w, h, fc, _bpp) = parse_mock_header(data)?;
         let sequence = if fc > 1 {
@@ -248,6 +256,19 @@ impl<'a> DecodeJob<'a> for MockDecodeJob {
         Ok(ImageInfo::new(w, h, ImageFormat::Pnm).with_sequence(sequence))
     }
 
+    fn probe_incremental(&self, data: &[u8]) -> Result<ProbeProgress, MockError> {
+        if data.len() < 4 {
+            if !b"MOCK".starts_with(data) {
+                return Err(MockError::InvalidData("bad mock header".into()));
+            }
+            return Ok(ProbeProgress::need(HEADER_SIZE as u64));
+        }
+        if data.len() < HEADER_SIZE {
+            return Ok(ProbeProgress::need(HEADER_SIZE as u64));
+        }
+        self.probe(data).map(ProbeProgress::Complete)
+    }
+
     fn output_info(&self, data: &[u8]) -> Result<OutputInfo, MockError> {
         let (w, h, _fc, bpp) = parse_mock_header(data)?;
         Ok(OutputInfo::full_decode(w, h, descriptor_for_bpp(bpp)))
@@ -282,6 +303,9 @@ impl<'a> DecodeJob<'a> for MockDecodeJob {
     ) -> Result<MockStreamDec<'a>, MockError> {
         let (w, h, _, bpp) = parse_mock_header(&data)?;
         self.limits.check_dimensions(w, h)?;
+        if let Some(ref stop) = self.stop {
+            stop.check()?;
+        }
         Ok(MockStreamDec {
             data,
             current_row: 0,
@@ -308,6 +332,23 @@ impl<'a> DecodeJob<'a> for MockDecodeJob {
             current_frame: self.start_frame.unwrap_or(0),
         })
     }
+
+    fn multi_page_decoder(self, data: Cow<'a, [u8]>) -> Result<MockMultiPageDec<'a>, MockError> {
+        let (w, h, fc, bpp) = parse_mock_header(&data)?;
+        self.limits.check_dimensions(w, h)?;
+        let info = ImageInfo::new(w, h, ImageFormat::Pnm).with_sequence(ImageSequence::Multi {
+            image_count: Some(fc),
+            random_access: true,
+        });
+        Ok(MockMultiPageDec {
+            data,
+            info,
+            width: w,
+            height: h,
+            page_count: fc,
+            bpp,
+        })
+    }
 }
 
 // --- One-shot decoder ---
@@ -465,6 +506,77 @@ impl AnimationFrameDecoder for MockAnimationFrameDec {
     }
 }
 
+// --- Multi-page decoder (each frame is an independent page) ---
+
+pub struct MockMultiPageDec<'a> {
+    data: Cow<'a, [u8]>,
+    info: ImageInfo,
+    width: u32,
+    height: u32,
+    page_count: u32,
+    bpp: u8,
+}
+
+impl MockMultiPageDec<'_> {
+    fn check_index(&self, index: u32) -> Result<(), MockError> {
+        if index >= self.page_count {
+            return Err(MockError::InvalidData(format!(
+                "page {index} out of range (count {})",
+                self.page_count
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl MultiPageDecoder for MockMultiPageDec<'_> {
+    type Error = MockError;
+
+    fn wrap_sink_error(err: SinkError) -> MockError {
+        MockError::Sink(err)
+    }
+
+    fn info(&self) -> &ImageInfo {
+        &self.info
+    }
+
+    fn page_count(&self) -> Option<u32> {
+        Some(self.page_count)
+    }
+
+    fn page_info(&mut self, index: u32) -> Result<ImageInfo, MockError> {
+        self.check_index(index)?;
+        Ok(ImageInfo::new(self.width, self.height, ImageFormat::Pnm).with_alpha(self.bpp == 4))
+    }
+
+    fn decode_page(
+        &mut self,
+        index: u32,
+        _preferred: &[PixelDescriptor],
+        stop: Option<&dyn Stop>,
+    ) -> Result<DecodeOutput, MockError> {
+        if let Some(s) = stop {
+            s.check()?;
+        }
+        self.check_index(index)?;
+        let frame_pixels = self.width as usize * self.height as usize * self.bpp as usize;
+        let pixel_start = HEADER_SIZE + index as usize * (4 + frame_pixels) + 4;
+        let pixel_end = pixel_start + frame_pixels;
+        if pixel_end > self.data.len() {
+            return Err(MockError::InvalidData("truncated page data".into()));
+        }
+        let buf = PixelBuffer::from_vec(
+            self.data[pixel_start..pixel_end].to_vec(),
+            self.width,
+            self.height,
+            descriptor_for_bpp(self.bpp),
+        )
+        .map_err(|e| MockError::InvalidData(format!("buffer: {e}")))?;
+        let info = self.page_info(index)?;
+        Ok(DecodeOutput::new(buf, info))
+    }
+}
+
 // =========================================================================
 // Extension types (for testing extensions() / extensions_mut())
 // =========================================================================
@@ -5,13 +5,20 @@
 //!
 //! Uses `thiserror` + `whereat::At<E>` for error derivation and location
 //! tracking, validating that error chains and traces survive dyn dispatch.
+//!
+//! Gain maps ride in a test-only trailer after the primary raster:
+//! `b"GMAP"`, a `u32 BE` metadata length, the ISO 21496-1
+//! [`Iso21496Format::JxlJhgm`] payload, then the gain map as a complete PNM
+//! file.
 
 use std::borrow::Cow;
 
 use zencodec::decode::{Decode, DecodeCapabilities, DecodeJob, DecoderConfig};
 use zencodec::encode::{EncodeCapabilities, EncodeJob, EncodeOutput, Encoder, EncoderConfig};
+use zencodec::gainmap::{EncodeGainMap, GainMapInfo, GainMapSource};
 use zencodec::{
-    ImageFormat, ImageInfo, Metadata, ResourceLimits, Unsupported, UnsupportedOperation,
+    GainMapPresence, ImageFormat, ImageInfo, Iso21496Format, Metadata, ResourceLimits, Unsupported,
+    UnsupportedOperation,
 };
 
 use enough::{Stop, StopReason};
@@ -62,17 +69,20 @@ pub struct PnmEncodeJob {
     limits: ResourceLimits,
     stop: Option<zencodec::StopToken>,
     metadata: Option<Metadata>,
+    gain_map: Option<EncodeGainMap>,
 }
 
 /// The actual PPM/PGM encoder.
 pub struct PnmEnc {
     #[allow(dead_code)]
     stop: Option<Box<dyn Fn() -> Result<(), StopReason> + Send>>,
+    gain_map: Option<EncodeGainMap>,
 }
 
 static PNM_ENCODE_CAPS: EncodeCapabilities = EncodeCapabilities::new()
     .with_lossless(true)
-    .with_native_gray(true);
+    .with_native_gray(true)
+    .with_gain_map(true);
 
 impl EncoderConfig for PnmEncoderConfig {
     type Error = At<PnmError>;
@@ -95,6 +105,7 @@ impl EncoderConfig for PnmEncoderConfig {
             limits: ResourceLimits::none(),
             stop: None,
             metadata: None,
+            gain_map: None,
         }
     }
 }
@@ -119,11 +130,19 @@ impl EncodeJob for PnmEncodeJob {
         self
     }
 
+    fn with_gain_map(mut self, gain_map: EncodeGainMap) -> Self {
+        self.gain_map = Some(gain_map);
+        self
+    }
+
     fn encoder(self) -> Result<PnmEnc, At<PnmError>> {
         let stop: Option<Box<dyn Fn() -> Result<(), StopReason> + Send>> = self
             .stop
             .map(|s| Box::new(move || s.check()) as Box<dyn Fn() -> Result<(), StopReason> + Send>);
-        Ok(PnmEnc { stop })
+        Ok(PnmEnc {
+            stop,
+            gain_map: self.gain_map,
+        })
     }
 
     fn animation_frame_encoder(self) -> Result<(), At<PnmError>> {
@@ -156,7 +175,7 @@ impl Encoder for PnmEnc {
             .start_at());
         }
 
-        if is_gray {
+        let mut out = if is_gray {
             // P5 (PGM)
             let header = format!("P5\n{w} {h}\n255\n");
             let mut out = Vec::with_capacity(header.len() + (w * h) as usize);
@@ -164,7 +183,7 @@ impl Encoder for PnmEnc {
             for y in 0..h {
                 out.extend_from_slice(pixels.row(y));
             }
-            Ok(EncodeOutput::new(out, ImageFormat::Pnm))
+            out
         } else {
             // P6 (PPM)
             let header = format!("P6\n{w} {h}\n255\n");
@@ -175,11 +194,42 @@ impl Encoder for PnmEnc {
                 let row = pixels.row(y);
                 out.extend_from_slice(&row[..row_bytes]);
             }
-            Ok(EncodeOutput::new(out, ImageFormat::Pnm))
-        }
+            out
+        };
+
+        let embedded = match self.gain_map {
+            Some(gain_map) => write_gain_map_trailer(&mut out, gain_map),
+            None => false,
+        };
+        Ok(EncodeOutput::new(out, ImageFormat::Pnm).with_gain_map_embedded(embedded))
     }
 }
 
+/// Append the gain map trailer. Returns whether the gain map was usable.
+fn write_gain_map_trailer(out: &mut Vec<u8>, gain_map: EncodeGainMap) -> bool {
+    let image = match &gain_map {
+        EncodeGainMap::Decoded(decoded) => {
+            let enc = PnmEnc {
+                stop: None,
+                gain_map: None,
+            };
+            match enc.encode(decoded.pixels.as_slice()) {
+                Ok(output) => output.into_vec(),
+                Err(_) => return false,
+            }
+        }
+        EncodeGainMap::Source(source) if source.format == ImageFormat::Pnm => source.data.clone(),
+        _ => return false,
+    };
+    let metadata =
+        zencodec::gainmap::serialize_iso21496_fmt(&gain_map.info().params, Iso21496Format::JxlJhgm);
+    out.extend_from_slice(b"GMAP");
+    out.extend_from_slice(&(metadata.len() as u32).to_be_bytes());
+    out.extend_from_slice(&metadata);
+    out.extend_from_slice(&image);
+    true
+}
+
 // =========================================================================
 // Decode: Config → Job → Decoder
 // =========================================================================
@@ -198,16 +248,19 @@ impl PnmDecoderConfig {
 pub struct PnmDecodeJob {
     limits: ResourceLimits,
     stop: Option<zencodec::StopToken>,
+    extract_gain_map: bool,
 }
 
 /// The actual PPM/PGM decoder (data bound at construction).
 pub struct PnmDec<'a> {
     data: Cow<'a, [u8]>,
+    extract_gain_map: bool,
 }
 
 static PNM_DECODE_CAPS: DecodeCapabilities = DecodeCapabilities::new()
     .with_cheap_probe(true)
-    .with_native_gray(true);
+    .with_native_gray(true)
+    .with_gain_map(true);
 
 impl DecoderConfig for PnmDecoderConfig {
     type Error = At<PnmError>;
@@ -229,6 +282,7 @@ impl DecoderConfig for PnmDecoderConfig {
         PnmDecodeJob {
             limits: ResourceLimits::none(),
             stop: None,
+            extract_gain_map: false,
         }
     }
 }
@@ -238,6 +292,7 @@ impl<'a> DecodeJob<'a> for PnmDecodeJob {
     type Dec = PnmDec<'a>;
     type StreamDec = Unsupported<At<PnmError>>;
     type AnimationFrameDec = Unsupported<At<PnmError>>;
+    type MultiPageDec = Unsupported<At<PnmError>>;
 
     fn with_stop(mut self, stop: zencodec::StopToken) -> Self {
         self.stop = Some(stop);
@@ -249,10 +304,22 @@ impl<'a> DecodeJob<'a> for PnmDecodeJob {
         self
     }
 
+    fn with_extract_gain_map(mut self, extract: bool) -> Self {
+        self.extract_gain_map = extract;
+        self
+    }
+
+    fn limits(&self) -> &ResourceLimits {
+        &self.limits
+    }
+
     fn probe(&self, data: &[u8]) -> Result<ImageInfo, At<PnmError>> {
         let (w, h, _is_gray) = parse_pnm_header(data).map_err(|e| e.start_at())?;
-        let info = ImageInfo::new(w, h, ImageFormat::Pnm);
-        Ok(info)
+        let gain_map = match gain_map_trailer(data).map_err(|e| e.start_at())? {
+            Some(source) => GainMapPresence::Available(Box::new(source.metadata)),
+            None => GainMapPresence::Absent,
+        };
+        Ok(ImageInfo::new(w, h, ImageFormat::Pnm).with_gain_map(gain_map))
     }
 
     fn output_info(&self, data: &[u8]) -> Result<OutputInfo, At<PnmError>> {
@@ -274,7 +341,10 @@ impl<'a> DecodeJob<'a> for PnmDecodeJob {
         self.limits
             .check_dimensions(w, h)
             .map_err(|e| PnmError::from(e).start_at())?;
-        Ok(PnmDec { data })
+        Ok(PnmDec {
+            data,
+            extract_gain_map: self.extract_gain_map,
+        })
     }
 
     fn push_decoder(
@@ -303,6 +373,13 @@ impl<'a> DecodeJob<'a> for PnmDecodeJob {
     ) -> Result<Unsupported<At<PnmError>>, At<PnmError>> {
         Err(PnmError::from(UnsupportedOperation::AnimationDecode).start_at())
     }
+
+    fn multi_page_decoder(
+        self,
+        _data: Cow<'a, [u8]>,
+    ) -> Result<Unsupported<At<PnmError>>, At<PnmError>> {
+        Err(PnmError::from(UnsupportedOperation::MultiImageDecode).start_at())
+    }
 }
 
 impl<'a> Decode for PnmDec<'a> {
@@ -313,30 +390,52 @@ impl<'a> Decode for PnmDec<'a> {
         let data_offset = find_data_offset(&self.data).map_err(|e| e.start_at())?;
         let pixel_data = &self.data[data_offset..];
 
-        if is_gray {
-            let expected = w as usize * h as usize;
-            if pixel_data.len() < expected {
-                return Err(PnmError::InvalidData("truncated pixel data".into()).start_at());
-            }
-            let desc = PixelDescriptor::GRAY8_SRGB;
-            let buf = PixelBuffer::from_vec(pixel_data[..expected].to_vec(), w, h, desc)
-                .map_err(|e| PnmError::InvalidData(format!("buffer error: {e}")).start_at())?;
-            let info = ImageInfo::new(w, h, ImageFormat::Pnm);
-            Ok(DecodeOutput::new(buf, info))
+        let (desc, expected) = if is_gray {
+            (PixelDescriptor::GRAY8_SRGB, w as usize * h as usize)
         } else {
-            let expected = w as usize * h as usize * 3;
-            if pixel_data.len() < expected {
-                return Err(PnmError::InvalidData("truncated pixel data".into()).start_at());
-            }
-            let desc = PixelDescriptor::RGB8_SRGB;
-            let buf = PixelBuffer::from_vec(pixel_data[..expected].to_vec(), w, h, desc)
-                .map_err(|e| PnmError::InvalidData(format!("buffer error: {e}")).start_at())?;
-            let info = ImageInfo::new(w, h, ImageFormat::Pnm);
-            Ok(DecodeOutput::new(buf, info))
+            (PixelDescriptor::RGB8_SRGB, w as usize * h as usize * 3)
+        };
+        if pixel_data.len() < expected {
+            return Err(PnmError::InvalidData("truncated pixel data".into()).start_at());
+        }
+        let buf = PixelBuffer::from_vec(pixel_data[..expected].to_vec(), w, h, desc)
+            .map_err(|e| PnmError::InvalidData(format!("buffer error: {e}")).start_at())?;
+        let info = ImageInfo::new(w, h, ImageFormat::Pnm);
+        let output = DecodeOutput::new(buf, info);
+        if !self.extract_gain_map {
+            return Ok(output);
+        }
+        match gain_map_trailer(&self.data).map_err(|e| e.start_at())? {
+            Some(source) => Ok(output.with_extras(source)),
+            None => Ok(output),
         }
     }
 }
 
+/// Parse the gain map trailer following the primary raster, if any.
+fn gain_map_trailer(data: &[u8]) -> Result<Option<GainMapSource>, PnmError> {
+    let (w, h, is_gray) = parse_pnm_header(data)?;
+    let channels = if is_gray { 1 } else { 3 };
+    let end = find_data_offset(data)? + w as usize * h as usize * channels;
+    let Some(trailer) = data.get(end..).filter(|t| t.starts_with(b"GMAP")) else {
+        return Ok(None);
+    };
+    let truncated = || PnmError::InvalidData("truncated gain map trailer".into());
+    let len = trailer.get(4..8).ok_or_else(truncated)?;
+    let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+    let metadata = trailer.get(8..8 + len).ok_or_else(truncated)?;
+    let params = zencodec::gainmap::parse_iso21496_fmt(metadata, Iso21496Format::JxlJhgm)
+        .map_err(|e| PnmError::InvalidData(e.to_string()))?;
+    let image = &trailer[8 + len..];
+    let (gw, gh, gray) = parse_pnm_header(image)?;
+    let info = GainMapInfo::new(params, gw, gh, if gray { 1 } else { 3 });
+    Ok(Some(GainMapSource::new(
+        image.to_vec(),
+        ImageFormat::Pnm,
+        info,
+    )))
+}
+
 // =========================================================================
 // PNM header parsing (P5/P6 only, simplified)
 // =========================================================================
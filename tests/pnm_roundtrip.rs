@@ -10,9 +10,12 @@ use std::borrow::Cow;
 
 use pnm::{PnmDecoderConfig, PnmEncoderConfig};
 
-use zencodec::decode::{Decode, DecodeJob, DecoderConfig, DynDecoderConfig};
+use zencodec::decode::{
+    BufferSink, ConvertingSink, Decode, DecodeJob, DecoderConfig, DynDecoder, DynDecoderConfig,
+    FinishingDecoder, OrientingSink, TeeSink,
+};
 use zencodec::encode::{DynEncoderConfig, EncodeJob, Encoder, EncoderConfig};
-use zencodec::{ImageFormat, ResourceLimits, UnsupportedOperation};
+use zencodec::{ImageFormat, Orientation, OrientationHint, ResourceLimits, UnsupportedOperation};
 use zenpixels::{PixelBuffer, PixelDescriptor, PixelSlice};
 
 // =========================================================================
@@ -349,6 +352,23 @@ fn decode_truncated() {
     assert!(result.is_err());
 }
 
+#[test]
+fn probe_incremental_default_delegates_to_probe() {
+    // PNM does not override probe_incremental: truncation is still an error,
+    // complete input yields Complete.
+    let job = PnmDecoderConfig::new().job();
+    assert!(job.probe_incremental(b"P6").is_err());
+
+    let encoded = PnmEncoderConfig::new()
+        .job()
+        .encoder()
+        .unwrap()
+        .encode(test_rgb8_pixels().as_slice())
+        .unwrap();
+    let progress = job.probe_incremental(encoded.data()).unwrap();
+    assert_eq!(progress.info().map(|i| (i.width, i.height)), Some((4, 2)));
+}
+
 #[test]
 fn unsupported_animation_encode() {
     let config = PnmEncoderConfig::new();
@@ -1320,3 +1340,402 @@ fn sink_early_abort() {
     assert!(aborted, "sink should have aborted");
     assert_eq!(sink.strips_seen, 2); // first succeeds, second aborts
 }
+
+// =========================================================================
+// Transcode (full-buffer path: PNM neither streams nor accepts rows)
+// =========================================================================
+
+#[test]
+fn transcode_full_buffer_roundtrip() {
+    let pixels = test_rgb8_pixels();
+    let encoded = PnmEncoderConfig::new()
+        .job()
+        .encoder()
+        .unwrap()
+        .encode(pixels.as_slice())
+        .unwrap();
+
+    let output = zencodec::transcode(
+        &PnmDecoderConfig::new(),
+        &PnmEncoderConfig::new(),
+        encoded.data(),
+        ResourceLimits::none(),
+        zencodec::decode::DecodePolicy::default(),
+        zencodec::encode::EncodePolicy::default(),
+        zencodec::StopToken::new(zencodec::Unstoppable),
+    )
+    .expect("transcode");
+
+    assert_eq!(output.format(), ImageFormat::Pnm);
+    assert_eq!(output.data(), encoded.data());
+}
+
+// =========================================================================
+// BufferedEncoder (PNM only supports whole-image encode)
+// =========================================================================
+
+#[test]
+fn buffered_encoder_push_rows_matches_encode() {
+    use zencodec::encode::BufferedEncoder;
+
+    let pixels = test_rgb8_pixels();
+    let expected = PnmEncoderConfig::new()
+        .job()
+        .encoder()
+        .unwrap()
+        .encode(pixels.as_slice())
+        .unwrap();
+
+    let config = PnmEncoderConfig::new();
+    let mut encoder = BufferedEncoder::for_capabilities(
+        config.job().encoder().unwrap(),
+        <PnmEncoderConfig as EncoderConfig>::capabilities(),
+        ResourceLimits::none(),
+    );
+    let slice = pixels.as_slice();
+    for y in 0..slice.rows() {
+        encoder.push_rows(slice.sub_rows(y, 1)).unwrap();
+    }
+    let output = encoder.finish().unwrap();
+    assert_eq!(output.data(), expected.data());
+}
+
+#[test]
+fn buffered_dyn_encoder_encode_from() {
+    use zencodec::encode::{BufferedEncoder, DynEncoder};
+
+    let pixels = test_rgb8_pixels();
+    let slice = pixels.as_slice();
+    let config = PnmEncoderConfig::new();
+    let dyn_config: &dyn DynEncoderConfig = &config;
+    let expected = dyn_config
+        .dyn_job()
+        .into_encoder()
+        .unwrap()
+        .encode(slice.clone())
+        .unwrap();
+
+    let encoder: Box<dyn DynEncoder> = Box::new(
+        BufferedEncoder::for_capabilities(
+            dyn_config.dyn_job().into_encoder().unwrap(),
+            dyn_config.capabilities(),
+            ResourceLimits::none(),
+        )
+        .with_pull_layout(slice.width(), slice.rows(), slice.descriptor()),
+    );
+    let output = encoder
+        .encode_from(&mut |y, mut buf| {
+            for r in 0..buf.rows() {
+                buf.row_mut(r).copy_from_slice(slice.row(y + r));
+            }
+            buf.rows() as usize
+        })
+        .unwrap();
+    assert_eq!(output.data(), expected.data());
+}
+
+#[test]
+fn buffered_dyn_encoder_respects_memory_limit() {
+    use zencodec::CodecErrorExt;
+    use zencodec::encode::{BufferedEncoder, DynEncoder};
+
+    let pixels = test_rgb8_pixels();
+    let config = PnmEncoderConfig::new();
+    let mut encoder: Box<dyn DynEncoder> = Box::new(BufferedEncoder::new(
+        (&config as &dyn DynEncoderConfig)
+            .dyn_job()
+            .into_encoder()
+            .unwrap(),
+        ResourceLimits::none().with_max_memory(16),
+    ));
+    let err = encoder.push_rows(pixels.as_slice()).unwrap_err();
+    assert!(err.limit_exceeded().is_some());
+}
+
+// =========================================================================
+// Gain map passthrough (PNM carries a test-only gain map trailer)
+// =========================================================================
+
+fn gain_map_params() -> zencodec::GainMapParams {
+    let mut params = zencodec::GainMapParams::default();
+    params.alternate_hdr_headroom = 2.0;
+    params
+}
+
+fn decoded_gain_map() -> zencodec::gainmap::DecodedGainMap {
+    let pixels = PixelBuffer::from_vec(vec![10, 20], 2, 1, PixelDescriptor::GRAY8_SRGB).unwrap();
+    let info = zencodec::GainMapInfo::new(gain_map_params(), 2, 1, 1);
+    zencodec::gainmap::DecodedGainMap::new(pixels, info)
+}
+
+/// PNM with an embedded gain map whose own header carries a comment, so a
+/// verbatim copy can be told apart from a re-encode.
+fn pnm_with_gain_map() -> Vec<u8> {
+    use zencodec::gainmap::{EncodeGainMap, GainMapSource};
+
+    let source = GainMapSource::new(
+        b"P5\n# source\n2 1\n255\n\x0a\x14".to_vec(),
+        ImageFormat::Pnm,
+        zencodec::GainMapInfo::new(gain_map_params(), 2, 1, 1),
+    );
+    let output = PnmEncoderConfig::new()
+        .job()
+        .with_gain_map(EncodeGainMap::from(source))
+        .encoder()
+        .unwrap()
+        .encode(test_rgb8_pixels().as_slice())
+        .unwrap();
+    assert!(output.gain_map_embedded());
+    output.into_vec()
+}
+
+fn extract_gain_map(data: &[u8]) -> zencodec::gainmap::GainMapSource {
+    let config = PnmDecoderConfig::new();
+    let mut job = config.dyn_job();
+    job.set_extract_gain_map(true);
+    let mut output = job
+        .into_decoder(Cow::Borrowed(data), &[])
+        .unwrap()
+        .decode()
+        .unwrap();
+    output
+        .take_extras::<zencodec::gainmap::GainMapSource>()
+        .expect("gain map extracted")
+}
+
+#[test]
+fn encode_job_embeds_decoded_gain_map() {
+    let output = PnmEncoderConfig::new()
+        .job()
+        .with_gain_map(decoded_gain_map().into())
+        .encoder()
+        .unwrap()
+        .encode(test_rgb8_pixels().as_slice())
+        .unwrap();
+    assert!(output.gain_map_embedded());
+
+    let info = PnmDecoderConfig::new()
+        .dyn_job()
+        .probe(output.data())
+        .unwrap();
+    assert_eq!(info.gain_map.info().unwrap().params, gain_map_params());
+
+    let source = extract_gain_map(output.data());
+    assert_eq!(source.data, b"P5\n2 1\n255\n\x0a\x14");
+    assert_eq!((source.metadata.width, source.metadata.height), (2, 1));
+}
+
+#[test]
+fn encode_output_reports_unembedded_gain_map() {
+    use zencodec::gainmap::{EncodeGainMap, GainMapSource};
+
+    let plain = PnmEncoderConfig::new()
+        .job()
+        .encoder()
+        .unwrap()
+        .encode(test_rgb8_pixels().as_slice())
+        .unwrap();
+    assert!(!plain.gain_map_embedded());
+
+    let foreign = GainMapSource::new(
+        vec![0xFF, 0xD8],
+        ImageFormat::Jpeg,
+        zencodec::GainMapInfo::new(gain_map_params(), 2, 1, 1),
+    );
+    let output = PnmEncoderConfig::new()
+        .job()
+        .with_gain_map(EncodeGainMap::from(foreign))
+        .encoder()
+        .unwrap()
+        .encode(test_rgb8_pixels().as_slice())
+        .unwrap();
+    assert!(!output.gain_map_embedded());
+    assert_eq!(output.data(), plain.data());
+}
+
+#[test]
+fn dyn_encode_job_sets_gain_map() {
+    let mut job = PnmEncoderConfig::new().dyn_job();
+    job.set_gain_map(decoded_gain_map().into());
+    let output = job
+        .into_encoder()
+        .unwrap()
+        .encode(test_rgb8_pixels().as_slice())
+        .unwrap();
+    assert!(output.gain_map_embedded());
+}
+
+fn transcode_with(encoder: &dyn DynEncoderConfig, data: &[u8]) -> zencodec::encode::EncodeOutput {
+    zencodec::transcode(
+        &PnmDecoderConfig::new(),
+        encoder,
+        data,
+        ResourceLimits::none(),
+        zencodec::decode::DecodePolicy::default(),
+        zencodec::encode::EncodePolicy::default(),
+        zencodec::StopToken::new(zencodec::Unstoppable),
+    )
+    .expect("transcode")
+}
+
+#[test]
+fn transcode_carries_gain_map_verbatim() {
+    let data = pnm_with_gain_map();
+    let output = transcode_with(&PnmEncoderConfig::new(), &data);
+    assert!(output.gain_map_embedded());
+    assert_eq!(output.data(), &data[..]);
+}
+
+/// PNM encoder that claims another format, standing in for a JPEG → AVIF
+/// transcode where the gain map bitstream can't be copied verbatim.
+struct OtherFormatEncoder(PnmEncoderConfig);
+
+impl DynEncoderConfig for OtherFormatEncoder {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn format(&self) -> ImageFormat {
+        ImageFormat::Avif
+    }
+
+    fn supported_descriptors(&self) -> &'static [PixelDescriptor] {
+        DynEncoderConfig::supported_descriptors(&self.0)
+    }
+
+    fn capabilities(&self) -> &'static zencodec::encode::EncodeCapabilities {
+        DynEncoderConfig::capabilities(&self.0)
+    }
+
+    fn dyn_job(&self) -> Box<dyn zencodec::encode::DynEncodeJob + 'static> {
+        self.0.dyn_job()
+    }
+}
+
+#[test]
+fn transcode_decodes_foreign_gain_map_for_reencode() {
+    let data = pnm_with_gain_map();
+    let output = transcode_with(&OtherFormatEncoder(PnmEncoderConfig::new()), &data);
+    assert!(output.gain_map_embedded());
+
+    let source = extract_gain_map(output.data());
+    // Re-encoded from decoded pixels: the source comment is gone.
+    assert_eq!(source.data, b"P5\n2 1\n255\n\x0a\x14");
+    assert_eq!(source.metadata.params, gain_map_params());
+}
+
+// =========================================================================
+// Finishing ignored orientation and crop hints
+// =========================================================================
+
+fn encoded_rgb8() -> Vec<u8> {
+    PnmEncoderConfig::new()
+        .job()
+        .encoder()
+        .unwrap()
+        .encode(test_rgb8_pixels().as_slice())
+        .unwrap()
+        .into_vec()
+}
+
+#[test]
+fn finishing_decoder_applies_ignored_hints() {
+    let encoded = encoded_rgb8();
+    let decoder = FinishingDecoder::from_job(
+        PnmDecoderConfig::new().job(),
+        Cow::Borrowed(&encoded),
+        &[],
+        OrientationHint::ExactTransform(Orientation::Rotate90),
+        Some([1, 0, 2, 2]),
+    )
+    .unwrap();
+    assert_eq!(decoder.remaining().orientation(), Orientation::Rotate90);
+
+    let output = decoder.decode().unwrap();
+    assert_eq!((output.width(), output.height()), (2, 2));
+    #[rustfmt::skip]
+    let expected = [
+        255, 255, 0,   0, 255, 0,   // yellow, green
+        0, 255, 255,   0, 0, 255,   // cyan, blue
+    ];
+    assert_eq!(output.into_buffer().copy_to_contiguous_bytes(), expected);
+}
+
+#[test]
+fn finishing_dyn_decoder_matches_generic() {
+    let encoded = encoded_rgb8();
+    let hint = OrientationHint::ExactTransform(Orientation::Transverse);
+    let crop = Some([0, 1, 3, 1]);
+
+    let generic = FinishingDecoder::from_job(
+        PnmDecoderConfig::new().job(),
+        Cow::Borrowed(&encoded),
+        &[],
+        hint,
+        crop,
+    )
+    .unwrap()
+    .decode()
+    .unwrap();
+
+    let config = PnmDecoderConfig::new();
+    let finishing =
+        FinishingDecoder::from_dyn_job(config.dyn_job(), Cow::Borrowed(&encoded), &[], hint, crop)
+            .unwrap();
+    let decoder: Box<dyn DynDecoder + '_> = Box::new(finishing);
+    let dynamic = decoder.decode().unwrap();
+
+    assert_eq!((dynamic.width(), dynamic.height()), (1, 3));
+    assert_eq!(
+        dynamic.into_buffer().copy_to_contiguous_bytes(),
+        generic.into_buffer().copy_to_contiguous_bytes()
+    );
+}
+
+// =========================================================================
+// Row sink adapters
+// =========================================================================
+
+#[test]
+fn row_sink_adapters_compose_with_push_decoder() {
+    let encoded = encoded_rgb8();
+    let mut plain = BufferSink::new();
+    let mut upright = BufferSink::new();
+    let oriented = OrientingSink::new(&mut upright, Orientation::Rotate90);
+    let converted = ConvertingSink::new(
+        oriented,
+        PixelDescriptor::RGBA8_SRGB,
+        |src: &PixelSlice<'_>, dst: &mut PixelSliceMut<'_>| -> Result<(), SinkError> {
+            for y in 0..src.rows() {
+                let (s, d) = (src.row(y), dst.row_mut(y));
+                for (rgb, rgba) in s.chunks_exact(3).zip(d.chunks_exact_mut(4)) {
+                    rgba[..3].copy_from_slice(rgb);
+                    rgba[3] = 255;
+                }
+            }
+            Ok(())
+        },
+    );
+    let mut sink = TeeSink::new(&mut plain, converted);
+    let info = PnmDecoderConfig::new()
+        .job()
+        .push_decoder(Cow::Borrowed(&encoded), &mut sink, &[])
+        .unwrap();
+    drop(sink);
+    assert_eq!((info.width, info.height), (4, 2));
+
+    let plain = plain.into_buffer().unwrap();
+    assert_eq!(
+        plain.copy_to_contiguous_bytes(),
+        test_rgb8_pixels().copy_to_contiguous_bytes()
+    );
+
+    let upright = upright.into_buffer().unwrap();
+    assert_eq!((upright.width(), upright.height()), (2, 4));
+    assert_eq!(upright.descriptor(), PixelDescriptor::RGBA8_SRGB);
+    // Rotate90: the first output row is the first source column, bottom up.
+    assert_eq!(
+        &upright.copy_to_contiguous_bytes()[..8],
+        &[0, 0, 0, 255, 255, 0, 0, 255]
+    );
+}
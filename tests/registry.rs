@@ -0,0 +1,211 @@
+//! Integration tests for `CodecRegistry` dispatch, priorities, and fallback.
+
+mod pnm;
+mod stub;
+
+use pnm::{PnmDecoderConfig, PnmEncoderConfig};
+use stub::{FailingDecoderConfig, Failure, StubError};
+
+use zencodec::encode::{EncodeJob, Encoder, EncoderConfig};
+use zencodec::{
+    CodecErrorExt, CodecErrorKind, CodecRegistry, ImageFormat, RegistryError, ResourceLimits,
+    UnsupportedOperation,
+};
+use zenpixels::{PixelBuffer, PixelDescriptor};
+
+fn pnm_bytes() -> Vec<u8> {
+    let data = vec![
+        255, 0, 0, 0, 255, 0, //
+        0, 0, 255, 255, 255, 255,
+    ];
+    let pixels = PixelBuffer::from_vec(data, 2, 2, PixelDescriptor::RGB8_SRGB).unwrap();
+    PnmEncoderConfig::new()
+        .job()
+        .encoder()
+        .unwrap()
+        .encode(pixels.as_slice())
+        .unwrap()
+        .into_vec()
+}
+
+fn stub_error<'e>(err: &'e (dyn std::error::Error + 'static)) -> Option<&'e StubError> {
+    err.find_cause::<StubError>()
+}
+
+#[test]
+fn registry_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CodecRegistry>();
+}
+
+#[test]
+fn decode_any_detects_and_dispatches() {
+    let codecs = CodecRegistry::new().with_decoder(PnmDecoderConfig::new(), 0);
+    let output = codecs
+        .decode_any(&pnm_bytes(), ResourceLimits::none())
+        .unwrap();
+    assert_eq!(output.width(), 2);
+    assert_eq!(output.height(), 2);
+}
+
+#[test]
+fn decode_any_unknown_format() {
+    let codecs = CodecRegistry::new().with_decoder(PnmDecoderConfig::new(), 0);
+    let err = codecs
+        .decode_any(b"not an image", ResourceLimits::none())
+        .unwrap_err();
+    assert_eq!(
+        err.find_cause::<RegistryError>(),
+        Some(&RegistryError::UnknownFormat)
+    );
+}
+
+#[test]
+fn decode_any_no_decoder() {
+    let codecs = CodecRegistry::new();
+    let err = codecs
+        .decode_any(&pnm_bytes(), ResourceLimits::none())
+        .unwrap_err();
+    assert_eq!(
+        err.find_cause::<RegistryError>(),
+        Some(&RegistryError::NoDecoder(ImageFormat::Pnm))
+    );
+}
+
+#[test]
+fn decode_any_falls_back_on_unsupported() {
+    let codecs = CodecRegistry::new()
+        .with_decoder(PnmDecoderConfig::new(), 0)
+        .with_decoder(
+            FailingDecoderConfig::new(Failure::Unsupported(UnsupportedOperation::PixelFormat)),
+            10,
+        );
+    let output = codecs
+        .decode_any(&pnm_bytes(), ResourceLimits::none())
+        .unwrap();
+    assert_eq!(output.width(), 2);
+}
+
+#[test]
+fn decode_any_falls_back_on_unsupported_kind() {
+    let codecs = CodecRegistry::new()
+        .with_decoder(PnmDecoderConfig::new(), 0)
+        .with_decoder(
+            FailingDecoderConfig::new(Failure::Kind(CodecErrorKind::Unsupported)),
+            10,
+        );
+    let output = codecs
+        .decode_any(&pnm_bytes(), ResourceLimits::none())
+        .unwrap();
+    assert_eq!(output.width(), 2);
+}
+
+#[test]
+fn decode_any_applies_limits() {
+    let codecs = CodecRegistry::new().with_decoder(PnmDecoderConfig::new(), 0);
+    let err = codecs
+        .decode_any(&pnm_bytes(), ResourceLimits::none().with_max_width(1))
+        .unwrap_err();
+    assert!(err.limit_exceeded().is_some());
+}
+
+#[test]
+fn decode_any_does_not_fall_back_on_corrupt_data() {
+    let codecs = CodecRegistry::new()
+        .with_decoder(PnmDecoderConfig::new(), 0)
+        .with_decoder(FailingDecoderConfig::new(Failure::Corrupt), 10);
+    let err = codecs
+        .decode_any(&pnm_bytes(), ResourceLimits::none())
+        .unwrap_err();
+    assert!(matches!(stub_error(&*err), Some(StubError::Corrupt)));
+}
+
+#[test]
+fn decode_any_returns_last_unsupported_error() {
+    let codecs = CodecRegistry::new()
+        .with_decoder(
+            FailingDecoderConfig::new(Failure::Unsupported(UnsupportedOperation::PixelFormat)),
+            10,
+        )
+        .with_decoder(
+            FailingDecoderConfig::new(Failure::Unsupported(UnsupportedOperation::DecodeInto)),
+            0,
+        );
+    let err = codecs
+        .decode_any(&pnm_bytes(), ResourceLimits::none())
+        .unwrap_err();
+    assert_eq!(
+        err.unsupported_operation(),
+        Some(&UnsupportedOperation::DecodeInto)
+    );
+}
+
+#[test]
+fn priority_order_with_registration_order_tiebreak() {
+    let mut codecs = CodecRegistry::new();
+    codecs
+        .register_decoder(Box::new(FailingDecoderConfig::new(Failure::Corrupt)), 0)
+        .register_decoder(Box::new(PnmDecoderConfig::new()), 5)
+        .register_decoder(Box::new(FailingDecoderConfig::new(Failure::Limit)), 0);
+
+    // Highest priority wins.
+    let first = codecs.decoder_for(ImageFormat::Pnm).unwrap();
+    assert!(first.capabilities().cheap_probe());
+    assert_eq!(codecs.decoders_for(ImageFormat::Pnm).count(), 3);
+
+    // Equal priority: registration order.
+    let err = codecs
+        .decoders_for(ImageFormat::Pnm)
+        .nth(1)
+        .unwrap()
+        .dyn_job()
+        .probe(&pnm_bytes())
+        .unwrap_err();
+    assert!(matches!(stub_error(&*err), Some(StubError::Corrupt)));
+}
+
+#[test]
+fn encoders_filtered_by_capabilities() {
+    let codecs = CodecRegistry::new().with_encoder(PnmEncoderConfig::new(), 0);
+
+    assert_eq!(codecs.encoders_for(ImageFormat::Pnm).count(), 1);
+    assert!(codecs.encoder_for(ImageFormat::Png).is_none());
+    assert_eq!(
+        codecs
+            .encoders_where(ImageFormat::Pnm, |caps| caps.lossless())
+            .count(),
+        1
+    );
+    assert_eq!(
+        codecs
+            .encoders_where(ImageFormat::Pnm, |caps| caps.lossy())
+            .count(),
+        0
+    );
+    assert_eq!(
+        codecs
+            .encoders_supporting(ImageFormat::Pnm, &[UnsupportedOperation::AnimationEncode])
+            .count(),
+        0
+    );
+    assert_eq!(codecs.encodable_formats(), vec![ImageFormat::Pnm]);
+}
+
+#[test]
+fn decodable_formats_deduplicated() {
+    let codecs = CodecRegistry::new()
+        .with_decoder(PnmDecoderConfig::new(), 0)
+        .with_decoder(FailingDecoderConfig::new(Failure::Corrupt), 1);
+    assert_eq!(codecs.decodable_formats(), vec![ImageFormat::Pnm]);
+}
+
+#[test]
+fn decode_any_does_not_fall_back_on_cancellation() {
+    let codecs = CodecRegistry::new()
+        .with_decoder(PnmDecoderConfig::new(), 0)
+        .with_decoder(FailingDecoderConfig::new(Failure::Cancelled), 10);
+    let err = codecs
+        .decode_any(&pnm_bytes(), ResourceLimits::none())
+        .unwrap_err();
+    assert!(matches!(stub_error(&*err), Some(StubError::Cancelled(_))));
+}
@@ -0,0 +1,220 @@
+//! Stub decoder that always fails with a configured error class.
+//!
+//! Used to exercise dispatch and fallback paths (registry, fallback chains)
+//! without a real codec that happens to reject a particular file.
+
+// Shared by several test binaries; each uses a subset.
+#![allow(dead_code)]
+
+use std::borrow::Cow;
+
+use zencodec::decode::{
+    Decode, DecodeCapabilities, DecodeJob, DecodeOutput, DecodeRowSink, DecoderConfig, OutputInfo,
+};
+use zencodec::{
+    ClassifiedError, CodecErrorKind, ImageFormat, ImageInfo, LimitExceeded, ResourceLimits,
+    Unsupported, UnsupportedOperation,
+};
+
+use enough::StopReason;
+use zenpixels::PixelDescriptor;
+
+/// Which error class the stub reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Failure {
+    Unsupported(UnsupportedOperation),
+    Corrupt,
+    Limit,
+    Cancelled,
+    /// A plain error tagged with a `CodecErrorKind` and nothing else.
+    Kind(CodecErrorKind),
+}
+
+#[derive(Debug)]
+pub enum StubError {
+    Unsupported(UnsupportedOperation),
+    Corrupt,
+    Limit(LimitExceeded),
+    Cancelled(StopReason),
+    Kind(ClassifiedError),
+}
+
+impl std::fmt::Display for StubError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported(e) => write!(f, "stub: unsupported: {e}"),
+            Self::Corrupt => write!(f, "stub: corrupt data"),
+            Self::Limit(e) => write!(f, "stub: limit: {e}"),
+            Self::Cancelled(r) => write!(f, "stub: cancelled: {r}"),
+            Self::Kind(e) => write!(f, "stub: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for StubError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Unsupported(e) => Some(e),
+            Self::Limit(e) => Some(e),
+            Self::Kind(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Failure {
+    fn error(self) -> StubError {
+        match self {
+            Self::Unsupported(op) => StubError::Unsupported(op),
+            Self::Corrupt => StubError::Corrupt,
+            Self::Limit => StubError::Limit(LimitExceeded::Pixels { actual: 2, max: 1 }),
+            Self::Cancelled => StubError::Cancelled(StopReason::Cancelled),
+            Self::Kind(kind) => StubError::Kind(ClassifiedError::new(kind, "tagged failure")),
+        }
+    }
+}
+
+static STUB_DECODE_CAPS: DecodeCapabilities = DecodeCapabilities::new();
+
+/// Decoder config that fails every operation with `failure`.
+#[derive(Clone, Copy, Debug)]
+pub struct FailingDecoderConfig {
+    pub failure: Failure,
+    /// Call `sink.begin()` in `push_decoder` before failing.
+    pub touch_sink: bool,
+}
+
+impl FailingDecoderConfig {
+    pub fn new(failure: Failure) -> Self {
+        Self {
+            failure,
+            touch_sink: false,
+        }
+    }
+
+    /// Fail in `push_decoder` only after the sink has seen `begin()`.
+    pub fn touching_sink(mut self) -> Self {
+        self.touch_sink = true;
+        self
+    }
+}
+
+impl DecoderConfig for FailingDecoderConfig {
+    type Error = StubError;
+    type Job<'a> = FailingDecodeJob;
+
+    fn formats() -> &'static [ImageFormat] {
+        &[ImageFormat::Pnm]
+    }
+
+    fn supported_descriptors() -> &'static [PixelDescriptor] {
+        &[PixelDescriptor::RGB8_SRGB]
+    }
+
+    fn capabilities() -> &'static DecodeCapabilities {
+        &STUB_DECODE_CAPS
+    }
+
+    fn job<'a>(self) -> Self::Job<'a> {
+        FailingDecodeJob {
+            failure: self.failure,
+            touch_sink: self.touch_sink,
+            limits: ResourceLimits::none(),
+        }
+    }
+}
+
+pub struct FailingDecodeJob {
+    failure: Failure,
+    touch_sink: bool,
+    limits: ResourceLimits,
+}
+
+impl<'a> DecodeJob<'a> for FailingDecodeJob {
+    type Error = StubError;
+    type Dec = FailingDec;
+    type StreamDec = Unsupported<StubError>;
+    type AnimationFrameDec = Unsupported<StubError>;
+    type MultiPageDec = Unsupported<StubError>;
+
+    fn with_stop(self, _stop: zencodec::StopToken) -> Self {
+        self
+    }
+
+    fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    fn limits(&self) -> &ResourceLimits {
+        &self.limits
+    }
+
+    fn probe(&self, _data: &[u8]) -> Result<ImageInfo, StubError> {
+        Err(self.failure.error())
+    }
+
+    fn output_info(&self, _data: &[u8]) -> Result<OutputInfo, StubError> {
+        Err(self.failure.error())
+    }
+
+    fn decoder(
+        self,
+        _data: Cow<'a, [u8]>,
+        _preferred: &[PixelDescriptor],
+    ) -> Result<FailingDec, StubError> {
+        Ok(FailingDec {
+            failure: self.failure,
+        })
+    }
+
+    fn push_decoder(
+        self,
+        _data: Cow<'a, [u8]>,
+        sink: &mut dyn DecodeRowSink,
+        _preferred: &[PixelDescriptor],
+    ) -> Result<OutputInfo, StubError> {
+        if self.touch_sink {
+            sink.begin(1, 1, PixelDescriptor::RGB8_SRGB)
+                .map_err(|_| StubError::Corrupt)?;
+        }
+        Err(self.failure.error())
+    }
+
+    fn streaming_decoder(
+        self,
+        _data: Cow<'a, [u8]>,
+        _preferred: &[PixelDescriptor],
+    ) -> Result<Unsupported<StubError>, StubError> {
+        Err(StubError::Unsupported(UnsupportedOperation::RowLevelDecode))
+    }
+
+    fn animation_frame_decoder(
+        self,
+        _data: Cow<'a, [u8]>,
+        _preferred: &[PixelDescriptor],
+    ) -> Result<Unsupported<StubError>, StubError> {
+        Err(StubError::Unsupported(
+            UnsupportedOperation::AnimationDecode,
+        ))
+    }
+
+    fn multi_page_decoder(self, _data: Cow<'a, [u8]>) -> Result<Unsupported<StubError>, StubError> {
+        Err(StubError::Unsupported(
+            UnsupportedOperation::MultiImageDecode,
+        ))
+    }
+}
+
+/// Fails in `decode()`, after the decoder was created successfully.
+pub struct FailingDec {
+    failure: Failure,
+}
+
+impl Decode for FailingDec {
+    type Error = StubError;
+
+    fn decode(self) -> Result<DecodeOutput, StubError> {
+        Err(self.failure.error())
+    }
+}